@@ -0,0 +1,181 @@
+use chrono::Utc;
+use reqwest::header::CONTENT_TYPE;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::{
+    FetchError, SearchOptions,
+    cache::{self, CacheError},
+    config,
+    engines::{EngineError, rand_client_builder},
+    search_engine_results_with,
+};
+
+const TICK: Duration = Duration::from_secs(60); // how often the scheduler looks for due queries
+
+pub use crate::cache::{AlertRow, StandingQueryRow};
+
+/// Registers `query` to be re-run every `interval`. The query is stored in
+/// plain text even with `hash_queries`, since it has to be searched again.
+/// Standing queries and their results are only kept in the SQLite cache, on
+/// other backends this and the rest of the module fail with
+/// [`CacheError::Unsupported`].
+pub async fn add(query: &str, interval: Duration) -> Result<i64, FetchError> {
+    let pool = cache::sqlite().await.map_err(FetchError::Cache)?;
+    cache::add_standing_query(pool, query, interval.as_secs() as i64)
+        .await
+        .map_err(|e| FetchError::Cache(e.into()))
+}
+
+pub async fn remove(id: i64) -> Result<(), FetchError> {
+    let pool = cache::sqlite().await.map_err(FetchError::Cache)?;
+    cache::delete_standing_query(pool, id)
+        .await
+        .map_err(|e| FetchError::Cache(e.into()))
+}
+
+pub async fn list() -> Result<Vec<StandingQueryRow>, FetchError> {
+    let pool = cache::sqlite().await.map_err(FetchError::Cache)?;
+    cache::get_standing_queries(pool)
+        .await
+        .map_err(|e| FetchError::Cache(e.into()))
+}
+
+/// Results standing queries found since the last call, oldest first.
+pub async fn new_results() -> Result<Vec<AlertRow>, FetchError> {
+    let alerts = pending_results().await?;
+    if let Some(last) = alerts.last() {
+        acknowledge(last.id).await?;
+    }
+    Ok(alerts)
+}
+
+/// Results standing queries found that weren't acknowledged yet, oldest
+/// first. Unlike [`new_results`] they are returned again until
+/// [`acknowledge`]d, so a failed delivery can be retried.
+pub async fn pending_results() -> Result<Vec<AlertRow>, FetchError> {
+    let pool = cache::sqlite().await.map_err(FetchError::Cache)?;
+    cache::get_new_alert_results(pool)
+        .await
+        .map_err(|e| FetchError::Cache(e.into()))
+}
+
+/// Marks the results up to and including `last_id` as delivered.
+pub async fn acknowledge(last_id: i64) -> Result<(), FetchError> {
+    let pool = cache::sqlite().await.map_err(FetchError::Cache)?;
+    cache::mark_alert_results_seen(pool, last_id)
+        .await
+        .map_err(|e| FetchError::Cache(e.into()))
+}
+
+/// Re-runs every standing query whose interval has passed, straight from the
+/// engines. A query's first run only records what's already out there, later
+/// runs record urls it hadn't returned before. Returns how many new results
+/// were found.
+pub async fn run_due() -> Result<usize, FetchError> {
+    let pool = cache::sqlite().await.map_err(FetchError::Cache)?;
+    let now = Utc::now().naive_utc();
+    let mut found = 0;
+
+    let standing = cache::get_standing_queries(pool)
+        .await
+        .map_err(|e| FetchError::Cache(e.into()))?;
+    for standing in standing {
+        let due = standing
+            .last_run
+            .is_none_or(|last| (now - last).num_seconds() >= standing.interval_secs);
+        if !due {
+            continue;
+        }
+
+        let options = SearchOptions {
+            fresh: true,
+            ..Default::default()
+        };
+        let response =
+            match search_engine_results_with(standing.query.clone(), vec![], options).await {
+                Ok(response) => response,
+                Err(e) => {
+                    // tried again on the next tick
+                    eprintln!("Standing query {} failed: {e:?}", standing.id);
+                    continue;
+                }
+            };
+
+        let baseline = standing.last_run.is_none();
+        for result in &response.results {
+            let new = cache::insert_alert_result(
+                pool,
+                standing.id,
+                &result.url,
+                &result.title,
+                now,
+                baseline,
+            )
+            .await
+            .map_err(|e| FetchError::Cache(e.into()))?;
+            if new && !baseline {
+                found += 1;
+            }
+        }
+
+        cache::set_standing_query_run(pool, standing.id, now)
+            .await
+            .map_err(|e| FetchError::Cache(e.into()))?;
+    }
+
+    Ok(found)
+}
+
+/// Runs due standing queries in the background. With a `webhook`, new
+/// results are POSTed to it as a JSON array instead of waiting for
+/// [`new_results`], through the configured proxy. They are only marked
+/// delivered once the webhook answers with a 2xx status, else they are sent
+/// again on the next tick.
+pub fn spawn_scheduler(webhook: Option<String>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+
+        loop {
+            interval.tick().await;
+
+            match run_due().await {
+                Ok(_) => {}
+                Err(FetchError::Cache(CacheError::Unsupported)) => {
+                    eprintln!("Standing queries need the SQLite cache, scheduler stopped");
+                    return;
+                }
+                Err(e) => eprintln!("Failed to run standing queries: {e:?}"),
+            }
+
+            if let Some(webhook) = &webhook
+                && let Err(e) = deliver(webhook).await
+            {
+                eprintln!("Failed to deliver alerts: {e:?}");
+            }
+        }
+    })
+}
+
+/// POSTs the pending results to `webhook`, acknowledging them on success.
+async fn deliver(webhook: &str) -> Result<(), FetchError> {
+    config::init().map_err(FetchError::Config)?;
+    let alerts = pending_results().await?;
+    let Some(last) = alerts.last() else {
+        return Ok(());
+    };
+
+    let body = serde_json::to_vec(&alerts).expect("alerts serialize");
+    rand_client_builder()
+        .and_then(|builder| builder.build())
+        .map_err(|e| FetchError::Engine(EngineError::ReqwestError(e)))?
+        .post(webhook)
+        .header(CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| FetchError::Engine(EngineError::ReqwestError(e)))?;
+
+    acknowledge(last.id).await
+}
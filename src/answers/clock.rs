@@ -0,0 +1,171 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use chrono_tz::{America, Asia, Europe, TZ_VARIANTS, Tz};
+
+use crate::{
+    FetchError,
+    answers::{Answer, AnswerContext, AnswerProvider},
+};
+
+/// Ways of asking for the time somewhere, the place follows them
+const PREFIXES: [&str; 7] = [
+    "what time is it in ",
+    "what's the time in ",
+    "what is the time in ",
+    "current time in ",
+    "local time in ",
+    "time in ",
+    "time at ",
+];
+/// Ways of asking for the time somewhere, the place comes before them
+const SUFFIXES: [&str; 3] = [" local time", " current time", " time"];
+
+/// Places people ask for that aren't the city a zone is named after
+const ALIASES: [(&str, Tz); 29] = [
+    ("new york city", America::New_York),
+    ("nyc", America::New_York),
+    ("washington", America::New_York),
+    ("boston", America::New_York),
+    ("miami", America::New_York),
+    ("atlanta", America::New_York),
+    ("dallas", America::Chicago),
+    ("houston", America::Chicago),
+    ("san francisco", America::Los_Angeles),
+    ("seattle", America::Los_Angeles),
+    ("san diego", America::Los_Angeles),
+    ("las vegas", America::Los_Angeles),
+    ("uk", Europe::London),
+    ("england", Europe::London),
+    ("germany", Europe::Berlin),
+    ("munich", Europe::Berlin),
+    ("france", Europe::Paris),
+    ("spain", Europe::Madrid),
+    ("barcelona", Europe::Madrid),
+    ("italy", Europe::Rome),
+    ("milan", Europe::Rome),
+    ("japan", Asia::Tokyo),
+    ("china", Asia::Shanghai),
+    ("beijing", Asia::Shanghai),
+    ("india", Asia::Kolkata),
+    ("delhi", Asia::Kolkata),
+    ("new delhi", Asia::Kolkata),
+    ("mumbai", Asia::Kolkata),
+    ("korea", Asia::Seoul),
+];
+
+/// The current time in a city or time zone, from the tz database bundled
+/// with the crate.
+#[derive(Debug, Clone)]
+pub struct WorldClock;
+
+#[async_trait]
+impl AnswerProvider for WorldClock {
+    fn name(&self) -> &'static str {
+        "World clock"
+    }
+
+    async fn answer(
+        &self,
+        query: &str,
+        _context: &AnswerContext,
+    ) -> Result<Vec<Answer>, FetchError> {
+        Ok(world_clock(query, Utc::now()).into_iter().collect())
+    }
+}
+
+fn world_clock(query: &str, now: DateTime<Utc>) -> Option<Answer> {
+    let query = query.trim().trim_end_matches('?').trim().to_lowercase();
+    let place = PREFIXES
+        .iter()
+        .find_map(|prefix| query.strip_prefix(prefix))
+        .or_else(|| {
+            SUFFIXES
+                .iter()
+                .find_map(|suffix| query.strip_suffix(suffix))
+        })?
+        .trim();
+    let zone = find_zone(place)?;
+    let local = now.with_timezone(&zone);
+
+    Some(Answer {
+        provider: "World clock".to_string(),
+        title: Some(format!("Time in {}", zone.name())),
+        text: format!(
+            "{} {} (UTC{})",
+            local.format("%H:%M, %A %-d %B %Y"),
+            local.format("%Z"),
+            local.format("%:z")
+        ),
+        ..Default::default()
+    })
+}
+
+/// Zone for a zone name like "europe/berlin", the city it is named after
+/// or one of the [`ALIASES`].
+fn find_zone(place: &str) -> Option<Tz> {
+    if place.is_empty() {
+        return None;
+    }
+    let city = place.replace(' ', "_");
+
+    TZ_VARIANTS
+        .iter()
+        .find(|zone| zone.name().eq_ignore_ascii_case(&city))
+        .or_else(|| {
+            // Etc/GMT+5 and friends have the sign backwards, leave them to
+            // their full names
+            TZ_VARIANTS.iter().find(|zone| {
+                !zone.name().starts_with("Etc/")
+                    && zone
+                        .name()
+                        .rsplit('/')
+                        .next()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(&city))
+            })
+        })
+        .copied()
+        .or_else(|| {
+            ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == place)
+                .map(|(_, zone)| *zone)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_zone, world_clock};
+    use chrono::{TimeZone, Utc};
+    use chrono_tz::{America, Asia, Tz};
+
+    #[test]
+    fn test_find_zone() {
+        assert_eq!(find_zone("tokyo"), Some(Asia::Tokyo));
+        assert_eq!(find_zone("new york"), Some(America::New_York));
+        assert_eq!(
+            find_zone("america/argentina/buenos_aires"),
+            Some(America::Argentina::Buenos_Aires)
+        );
+        assert_eq!(find_zone("utc"), Some(Tz::UTC));
+        assert_eq!(find_zone("san francisco"), Some(America::Los_Angeles));
+        assert_eq!(find_zone("atlantis"), None);
+    }
+
+    #[test]
+    fn test_world_clock() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 15, 12, 30, 0).unwrap();
+
+        let tokyo = world_clock("What time is it in Tokyo?", now).unwrap();
+        assert_eq!(tokyo.title.as_deref(), Some("Time in Asia/Tokyo"));
+        assert_eq!(tokyo.text, "21:30, Monday 15 January 2024 JST (UTC+09:00)");
+
+        let new_york = world_clock("nyc time", now).unwrap();
+        assert_eq!(
+            new_york.text,
+            "07:30, Monday 15 January 2024 EST (UTC-05:00)"
+        );
+
+        assert!(world_clock("time in atlantis", now).is_none());
+        assert!(world_clock("tokyo", now).is_none());
+    }
+}
@@ -0,0 +1,301 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    FetchError,
+    answers::{Answer, AnswerContext, AnswerProvider, Infobox, Link},
+    cache, config,
+    engines::{EngineError, new_rand_client, parse_json},
+};
+
+const API_URL: &str = "https://api.duckduckgo.com/";
+const BASE_URL: &str = "https://duckduckgo.com";
+/// Related topics kept per answer, disambiguations can list hundreds
+const MAX_RELATED: usize = 8;
+
+/// DuckDuckGo's Instant Answer API: topic abstracts, short answers,
+/// definitions and the destination of `!bang` queries.
+#[derive(Debug, Clone)]
+pub struct DuckDuckGoInstant;
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "PascalCase", default)]
+struct InstantResponse {
+    /// "A" article, "D" disambiguation, "C" category, "N" name, "E" exclusive
+    #[serde(rename = "Type")]
+    kind: String,
+    heading: String,
+    abstract_text: String,
+    #[serde(rename = "AbstractURL")]
+    abstract_url: String,
+    /// Kind of thing the abstract is about, e.g. "programming language"
+    entity: String,
+    /// Path on duckduckgo.com or an absolute url
+    image: String,
+    /// A string for most answer types, an object for the interactive ones
+    answer: serde_json::Value,
+    answer_type: String,
+    definition: String,
+    #[serde(rename = "DefinitionURL")]
+    definition_url: String,
+    /// Where a `!bang` query sends the user
+    redirect: String,
+    related_topics: Vec<RelatedTopic>,
+}
+
+/// A topic, or a named group of them on disambiguation pages
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RelatedTopic {
+    Topic {
+        #[serde(rename = "Text")]
+        text: String,
+        #[serde(rename = "FirstURL")]
+        first_url: String,
+    },
+    Group {
+        #[serde(rename = "Topics")]
+        topics: Vec<RelatedTopic>,
+    },
+}
+
+#[async_trait]
+impl AnswerProvider for DuckDuckGoInstant {
+    fn name(&self) -> &'static str {
+        "DuckDuckGo Instant Answers"
+    }
+
+    async fn answer(
+        &self,
+        query: &str,
+        context: &AnswerContext,
+    ) -> Result<Vec<Answer>, FetchError> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let name = self.name();
+        let persist = config::get().persist_cache && !context.private;
+        // the answers aren't cached, only the request is budgeted and timed
+        let body = cache::backend()
+            .await
+            .fetch_and_store(
+                name,
+                name,
+                persist,
+                true,
+                fetch(query),
+                async |_: &String| Ok(()),
+            )
+            .await?;
+
+        parse_response(&body).map_err(FetchError::Engine)
+    }
+}
+
+async fn fetch(query: &str) -> Result<String, EngineError> {
+    new_rand_client()
+        .map_err(EngineError::ReqwestError)?
+        .get(API_URL)
+        .query(&[
+            ("q", query),
+            ("format", "json"),
+            ("no_html", "1"),
+            ("no_redirect", "1"),
+        ])
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)?
+        .error_for_status()
+        .map_err(EngineError::ReqwestError)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)
+}
+
+fn parse_response(body: &str) -> Result<Vec<Answer>, EngineError> {
+    let response: InstantResponse = parse_json(body)?;
+    let provider = "DuckDuckGo".to_string();
+    let non_empty = |s: String| (!s.trim().is_empty()).then(|| s.trim().to_string());
+
+    // a bang answers with nothing but its destination
+    if let Some(redirect) = non_empty(response.redirect) {
+        return Ok(vec![Answer {
+            provider,
+            text: format!("Redirects to {redirect}"),
+            url: Some(redirect.clone()),
+            redirect: Some(redirect),
+            ..Default::default()
+        }]);
+    }
+
+    let heading = non_empty(response.heading);
+    let mut related = Vec::new();
+    flatten_topics(response.related_topics, &mut related);
+
+    let mut answers = Vec::new();
+    if let Some(text) = response
+        .answer
+        .as_str()
+        .and_then(|a| non_empty(a.to_string()))
+    {
+        answers.push(Answer {
+            provider: provider.clone(),
+            title: non_empty(response.answer_type),
+            text,
+            ..Default::default()
+        });
+    }
+    if let Some(text) = non_empty(response.abstract_text) {
+        let thumbnail = non_empty(response.image).map(|image| match image.starts_with('/') {
+            true => format!("{BASE_URL}{image}"),
+            false => image,
+        });
+        answers.push(Answer {
+            provider: provider.clone(),
+            title: heading.clone(),
+            text,
+            url: non_empty(response.abstract_url),
+            infobox: Some(Infobox {
+                description: non_empty(response.entity),
+                thumbnail,
+            }),
+            related: std::mem::take(&mut related),
+            ..Default::default()
+        });
+    }
+    if let Some(text) = non_empty(response.definition) {
+        answers.push(Answer {
+            provider: provider.clone(),
+            title: heading.clone(),
+            text,
+            url: non_empty(response.definition_url),
+            ..Default::default()
+        });
+    }
+    if response.kind == "D"
+        && !related.is_empty()
+        && let Some(heading) = heading
+    {
+        answers.push(Answer {
+            provider,
+            text: format!("{heading} may refer to:"),
+            title: Some(heading),
+            related,
+            ..Default::default()
+        });
+    }
+
+    Ok(answers)
+}
+
+/// Links of `topics` and of the topics in their groups, up to [`MAX_RELATED`].
+fn flatten_topics(topics: Vec<RelatedTopic>, links: &mut Vec<Link>) {
+    for topic in topics {
+        if links.len() >= MAX_RELATED {
+            return;
+        }
+        match topic {
+            RelatedTopic::Topic { text, first_url } => {
+                if !text.trim().is_empty() && !first_url.is_empty() {
+                    links.push(Link {
+                        text: text.trim().to_string(),
+                        url: first_url,
+                    });
+                }
+            }
+            RelatedTopic::Group { topics } => flatten_topics(topics, links),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_abstract() {
+        let answers = parse_response(
+            r#"{
+                "Type": "A", "Heading": "Rust (programming language)",
+                "AbstractText": "Rust is a general-purpose programming language.",
+                "AbstractSource": "Wikipedia",
+                "AbstractURL": "https://en.wikipedia.org/wiki/Rust_(programming_language)",
+                "Entity": "programming language", "Image": "/i/a6e0d4c3.png",
+                "Answer": "", "AnswerType": "", "Definition": "", "DefinitionURL": "",
+                "Redirect": "", "Infobox": "",
+                "RelatedTopics": [
+                    {"FirstURL": "https://duckduckgo.com/Cargo", "Icon": {"URL": ""},
+                     "Result": "<a href=\"https://duckduckgo.com/Cargo\">Cargo</a> Rust's package manager",
+                     "Text": "Cargo Rust's package manager"},
+                    {"Name": "See also", "Topics": [
+                        {"FirstURL": "https://duckduckgo.com/Mozilla", "Text": "Mozilla"}
+                    ]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(answers.len(), 1);
+        let rust = &answers[0];
+        assert_eq!(rust.title.as_deref(), Some("Rust (programming language)"));
+        assert_eq!(
+            rust.url.as_deref(),
+            Some("https://en.wikipedia.org/wiki/Rust_(programming_language)")
+        );
+        let infobox = rust.infobox.as_ref().unwrap();
+        assert_eq!(infobox.description.as_deref(), Some("programming language"));
+        assert_eq!(
+            infobox.thumbnail.as_deref(),
+            Some("https://duckduckgo.com/i/a6e0d4c3.png")
+        );
+        assert_eq!(rust.related.len(), 2);
+        assert_eq!(rust.related[0].text, "Cargo Rust's package manager");
+        assert_eq!(rust.related[1].url, "https://duckduckgo.com/Mozilla");
+    }
+
+    #[test]
+    fn test_parse_redirect() {
+        let answers = parse_response(
+            r#"{"Type": "E", "Heading": "", "AbstractText": "", "Answer": "",
+                "Redirect": "https://en.wikipedia.org/wiki/Special:Search?search=rust",
+                "RelatedTopics": []}"#,
+        )
+        .unwrap();
+
+        assert_eq!(answers.len(), 1);
+        assert_eq!(
+            answers[0].redirect.as_deref(),
+            Some("https://en.wikipedia.org/wiki/Special:Search?search=rust")
+        );
+    }
+
+    #[test]
+    fn test_parse_answer_and_disambiguation() {
+        let answers = parse_response(
+            r#"{"Type": "E", "Answer": "4", "AnswerType": "calc", "RelatedTopics": []}"#,
+        )
+        .unwrap();
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].text, "4");
+
+        // interactive answers come as objects and are skipped
+        let answers =
+            parse_response(r#"{"Type": "E", "Answer": {"from": "timer"}, "AnswerType": "timer"}"#)
+                .unwrap();
+        assert!(answers.is_empty());
+
+        let answers = parse_response(
+            r#"{"Type": "D", "Heading": "Mercury",
+                "RelatedTopics": [
+                    {"FirstURL": "https://duckduckgo.com/Mercury_(planet)", "Text": "Mercury (planet)"},
+                    {"FirstURL": "https://duckduckgo.com/Mercury_(element)", "Text": "Mercury (element)"}
+                ]}"#,
+        )
+        .unwrap();
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].text, "Mercury may refer to:");
+        assert_eq!(answers[0].related.len(), 2);
+    }
+}
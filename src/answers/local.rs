@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+
+use crate::{
+    FetchError,
+    answers::{Answer, AnswerContext, AnswerProvider},
+};
+
+/// Leading words of a question that don't change what is asked
+const QUESTION_PREFIXES: [&str; 6] = [
+    "what is ", "what's ", "whats ", "show me ", "show ", "check ",
+];
+
+/// Answers "what is my ip" with the address the request came from.
+#[derive(Debug, Clone)]
+pub struct MyIp;
+
+/// Answers "what is my user agent" with the browser's User-Agent header.
+#[derive(Debug, Clone)]
+pub struct MyUserAgent;
+
+#[async_trait]
+impl AnswerProvider for MyIp {
+    fn name(&self) -> &'static str {
+        "My IP"
+    }
+
+    async fn answer(
+        &self,
+        query: &str,
+        context: &AnswerContext,
+    ) -> Result<Vec<Answer>, FetchError> {
+        let asked = matches!(
+            subject(query).as_str(),
+            "my ip"
+                | "my ip address"
+                | "my public ip"
+                | "my public ip address"
+                | "my ipv4"
+                | "my ipv6"
+        );
+        Ok(context
+            .client_ip
+            .filter(|_| asked)
+            .map(|ip| Answer {
+                provider: self.name().to_string(),
+                title: Some("Your IP address".to_string()),
+                text: ip.to_string(),
+                ..Default::default()
+            })
+            .into_iter()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl AnswerProvider for MyUserAgent {
+    fn name(&self) -> &'static str {
+        "My user agent"
+    }
+
+    async fn answer(
+        &self,
+        query: &str,
+        context: &AnswerContext,
+    ) -> Result<Vec<Answer>, FetchError> {
+        let asked = matches!(
+            subject(query).as_str(),
+            "my user agent" | "my useragent" | "my ua" | "my browser user agent"
+        );
+        Ok(context
+            .user_agent
+            .as_deref()
+            .map(str::trim)
+            .filter(|user_agent| asked && !user_agent.is_empty())
+            .map(|user_agent| Answer {
+                provider: self.name().to_string(),
+                title: Some("Your user agent".to_string()),
+                text: user_agent.to_string(),
+                ..Default::default()
+            })
+            .into_iter()
+            .collect())
+    }
+}
+
+/// What the query asks about, lowercased and without the question around
+/// it, e.g. "my ip" for "What's my IP?".
+fn subject(query: &str) -> String {
+    let query = query
+        .to_lowercase()
+        .replace(['?', '!', '.'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut subject = query.as_str();
+    for prefix in QUESTION_PREFIXES {
+        if let Some(rest) = subject.strip_prefix(prefix) {
+            subject = rest;
+            break;
+        }
+    }
+    subject.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MyIp, MyUserAgent, subject};
+    use crate::answers::{AnswerContext, AnswerProvider};
+
+    #[test]
+    fn test_subject() {
+        assert_eq!(subject("What's my IP?"), "my ip");
+        assert_eq!(subject("  show me   my user agent "), "my user agent");
+        assert_eq!(subject("whats my ip address"), "my ip address");
+        assert_eq!(subject("ip address lookup"), "ip address lookup");
+    }
+
+    #[tokio::test]
+    async fn test_local_answers() {
+        let context = AnswerContext {
+            client_ip: Some("203.0.113.7".parse().unwrap()),
+            user_agent: Some("Mozilla/5.0 (X11; Linux x86_64)".to_string()),
+            ..Default::default()
+        };
+
+        let ip = MyIp.answer("what is my ip", &context).await.unwrap();
+        assert_eq!(ip.len(), 1);
+        assert_eq!(ip[0].text, "203.0.113.7");
+        assert!(
+            MyIp.answer("my ip camera", &context)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let user_agent = MyUserAgent
+            .answer("What's my user agent?", &context)
+            .await
+            .unwrap();
+        assert_eq!(user_agent[0].text, "Mozilla/5.0 (X11; Linux x86_64)");
+
+        // nothing to say without the request's details
+        let unknown = AnswerContext::default();
+        assert!(MyIp.answer("my ip", &unknown).await.unwrap().is_empty());
+        assert!(
+            MyUserAgent
+                .answer("my user agent", &unknown)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
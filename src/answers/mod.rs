@@ -0,0 +1,327 @@
+use async_trait::async_trait;
+use serde::Serialize;
+use std::{
+    net::IpAddr,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+use tokio::{task::JoinSet, time::timeout};
+
+use crate::{
+    FetchError, config,
+    engines::{new_circuit, with_circuit},
+};
+
+mod clock;
+mod duckduckgo;
+mod local;
+mod utility;
+mod wikipedia;
+
+pub use clock::WorldClock;
+pub use duckduckgo::DuckDuckGoInstant;
+pub use local::{MyIp, MyUserAgent};
+pub use utility::{Base64Codec, HashDigest, UuidGenerator};
+pub use wikipedia::WikipediaSummary;
+
+static PROVIDERS: RwLock<Vec<Arc<dyn AnswerProvider>>> = RwLock::new(Vec::new());
+
+/// An instant answer shown above the web results, e.g. a definition, a
+/// conversion or the user's own IP address.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Answer {
+    /// Name of the provider that gave it
+    pub provider: String,
+    /// Heading shown above the answer
+    pub title: Option<String>,
+    /// The answer itself, as plain text
+    pub text: String,
+    /// Page the answer was taken from
+    pub url: Option<String>,
+    /// Details of the one thing the answer is about, if it is about one
+    pub infobox: Option<Infobox>,
+    /// Topics related to the answer, e.g. the meanings of an ambiguous word
+    pub related: Vec<Link>,
+    /// Where the query asks to be sent instead of searching, e.g. the site
+    /// a `!bang` stands for
+    pub redirect: Option<String>,
+}
+
+/// Structured details of an answer about a single entity, e.g. the subject
+/// of an encyclopedia article.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Infobox {
+    /// Short description, e.g. "General-purpose programming language"
+    pub description: Option<String>,
+    /// Url of a picture of the entity
+    pub thumbnail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Link {
+    pub text: String,
+    pub url: String,
+}
+
+/// Details of the request a query came with, for answers about the person
+/// asking. A server fills it from the incoming request.
+#[derive(Debug, Clone, Default)]
+pub struct AnswerContext {
+    /// Address the search request came from
+    pub client_ip: Option<IpAddr>,
+    /// The User-Agent header of the search request
+    pub user_agent: Option<String>,
+    /// The search is private, providers must not cache what they fetch
+    pub private: bool,
+}
+
+/// Answers queries directly, alongside the web search. Providers are asked
+/// about every query and answer nothing for ones they don't handle.
+#[async_trait]
+pub trait AnswerProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Answers for `query`, empty when the provider has nothing to say.
+    async fn answer(&self, query: &str, context: &AnswerContext)
+    -> Result<Vec<Answer>, FetchError>;
+}
+
+/// Answer providers shipped with the crate, enabled through the config.
+#[derive(Debug, Clone)]
+pub enum AnswerProviders {
+    Wikipedia { lang: String },
+    DuckDuckGo,
+    MyIp,
+    MyUserAgent,
+    WorldClock,
+    HashDigest,
+    UuidGenerator,
+    Base64Codec,
+}
+
+impl AnswerProviders {
+    pub(crate) fn provider(&self) -> Arc<dyn AnswerProvider> {
+        match self {
+            AnswerProviders::Wikipedia { lang } => Arc::new(WikipediaSummary::new(lang)),
+            AnswerProviders::DuckDuckGo => Arc::new(DuckDuckGoInstant),
+            AnswerProviders::MyIp => Arc::new(MyIp),
+            AnswerProviders::MyUserAgent => Arc::new(MyUserAgent),
+            AnswerProviders::WorldClock => Arc::new(WorldClock),
+            AnswerProviders::HashDigest => Arc::new(HashDigest),
+            AnswerProviders::UuidGenerator => Arc::new(UuidGenerator),
+            AnswerProviders::Base64Codec => Arc::new(Base64Codec),
+        }
+    }
+
+    /// Whether the provider answers without any network access
+    pub fn is_local(&self) -> bool {
+        !matches!(
+            self,
+            AnswerProviders::Wikipedia { .. } | AnswerProviders::DuckDuckGo
+        )
+    }
+}
+
+impl FromStr for AnswerProviders {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((kind, arg)) = s.trim().split_once(':') {
+            return match kind.to_lowercase().as_str() {
+                "wikipedia" => {
+                    let lang = arg.trim().to_lowercase();
+                    match !lang.is_empty()
+                        && lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    {
+                        true => Ok(Self::Wikipedia { lang }),
+                        false => Err(format!("Invalid Wikipedia language: {arg}")),
+                    }
+                }
+                _ => Err(format!("Unknown answer provider: {s}")),
+            };
+        }
+
+        match s.trim().to_lowercase().as_str() {
+            "wikipedia" | "wiki" => Ok(Self::Wikipedia {
+                lang: "en".to_string(),
+            }),
+            "duckduckgo" | "ddg" => Ok(Self::DuckDuckGo),
+            "my-ip" | "myip" => Ok(Self::MyIp),
+            "my-user-agent" | "user-agent" => Ok(Self::MyUserAgent),
+            "world-clock" | "clock" => Ok(Self::WorldClock),
+            "hash" => Ok(Self::HashDigest),
+            "uuid" | "guid" => Ok(Self::UuidGenerator),
+            "base64" => Ok(Self::Base64Codec),
+            _ => Err(format!("Unknown answer provider: {s}")),
+        }
+    }
+}
+
+/// Adds a provider asked by [`search_answers`], after the configured ones
+/// and the ones registered before it.
+pub fn register_provider(provider: impl AnswerProvider + 'static) {
+    PROVIDERS.write().unwrap().push(Arc::new(provider));
+}
+
+/// Asks the configured and every registered provider about `query` at once.
+pub async fn search_answers(query: &str) -> Result<Vec<Answer>, FetchError> {
+    search_answers_with(query, &AnswerContext::default()).await
+}
+
+/// Same as [`search_answers`] for a request with known details, which the
+/// local providers like [`MyIp`] answer from.
+pub async fn search_answers_with(
+    query: &str,
+    context: &AnswerContext,
+) -> Result<Vec<Answer>, FetchError> {
+    let config = config::init().map_err(FetchError::Config)?;
+    let mut providers: Vec<Arc<dyn AnswerProvider>> = config
+        .answer_providers
+        .iter()
+        .map(AnswerProviders::provider)
+        .collect();
+    providers.extend(PROVIDERS.read().unwrap().iter().cloned());
+
+    Ok(ask(providers, query, context).await)
+}
+
+/// Answers from the configured providers that need no network access, like
+/// the asker's IP address, the time in a city or the hash of a string. A
+/// server can reply with these and skip the search altogether.
+pub async fn local_answers(
+    query: &str,
+    context: &AnswerContext,
+) -> Result<Vec<Answer>, FetchError> {
+    let config = config::init().map_err(FetchError::Config)?;
+    let providers = config
+        .answer_providers
+        .iter()
+        .filter(|provider| provider.is_local())
+        .map(AnswerProviders::provider)
+        .collect();
+
+    Ok(ask(providers, query, context).await)
+}
+
+/// Runs `providers` concurrently, each limited to the engine timeout.
+/// Answers keep the order of `providers`, and providers that fail or time
+/// out are left out.
+async fn ask(
+    providers: Vec<Arc<dyn AnswerProvider>>,
+    query: &str,
+    context: &AnswerContext,
+) -> Vec<Answer> {
+    if providers.is_empty() {
+        return Vec::new();
+    }
+
+    let timeout_duration = config::get().engine_timeout;
+    let circuit = new_circuit();
+    let mut set = JoinSet::new();
+
+    for (i, provider) in providers.into_iter().enumerate() {
+        let query = query.to_string();
+        let context = context.clone();
+        let fut = with_circuit(circuit.clone(), async move {
+            let answers = timeout(timeout_duration, provider.answer(&query, &context)).await;
+            (provider.name(), answers)
+        });
+        set.spawn(async move { (i, fut.await) });
+    }
+
+    let mut outcomes = set.join_all().await;
+    outcomes.sort_by_key(|(i, _)| *i);
+
+    let mut answers = Vec::new();
+    for (_, (name, outcome)) in outcomes {
+        match outcome {
+            Ok(Ok(mut provided)) => answers.append(&mut provided),
+            Ok(Err(e)) => eprintln!("Answer provider {name} failed: {:?}", e),
+            Err(_) => eprintln!("Answer provider {name} timed out"),
+        }
+    }
+    answers
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Answer, AnswerContext, AnswerProvider, AnswerProviders, ask};
+    use crate::{FetchError, engines::EngineError};
+    use async_trait::async_trait;
+    use std::sync::Arc;
+
+    /// Answers "ping" and nothing else
+    struct Ping;
+
+    #[async_trait]
+    impl AnswerProvider for Ping {
+        fn name(&self) -> &'static str {
+            "Ping"
+        }
+
+        async fn answer(
+            &self,
+            query: &str,
+            _context: &AnswerContext,
+        ) -> Result<Vec<Answer>, FetchError> {
+            if query != "ping" {
+                return Ok(Vec::new());
+            }
+            Ok(vec![Answer {
+                provider: self.name().to_string(),
+                text: "pong".to_string(),
+                ..Default::default()
+            }])
+        }
+    }
+
+    struct Broken;
+
+    #[async_trait]
+    impl AnswerProvider for Broken {
+        fn name(&self) -> &'static str {
+            "Broken"
+        }
+
+        async fn answer(
+            &self,
+            _query: &str,
+            _context: &AnswerContext,
+        ) -> Result<Vec<Answer>, FetchError> {
+            Err(FetchError::Engine(EngineError::Timeout))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ask() {
+        let providers: Vec<Arc<dyn AnswerProvider>> = vec![Arc::new(Broken), Arc::new(Ping)];
+
+        let context = AnswerContext::default();
+        let answers = ask(providers.clone(), "ping", &context).await;
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].provider, "Ping");
+        assert_eq!(answers[0].text, "pong");
+
+        assert!(ask(providers, "something else", &context).await.is_empty());
+    }
+
+    #[test]
+    fn test_answer_providers_from_str() {
+        assert!(matches!(
+            "wikipedia".parse(),
+            Ok(AnswerProviders::Wikipedia { lang }) if lang == "en"
+        ));
+        assert!(matches!(
+            "wikipedia:DE".parse(),
+            Ok(AnswerProviders::Wikipedia { lang }) if lang == "de"
+        ));
+        assert!(matches!("ddg".parse(), Ok(AnswerProviders::DuckDuckGo)));
+        assert!(matches!("My-IP".parse(), Ok(AnswerProviders::MyIp)));
+        assert!(matches!("clock".parse(), Ok(AnswerProviders::WorldClock)));
+        assert!(matches!("guid".parse(), Ok(AnswerProviders::UuidGenerator)));
+        assert!(!AnswerProviders::DuckDuckGo.is_local());
+        assert!(AnswerProviders::MyUserAgent.is_local());
+        assert!("wikipedia:".parse::<AnswerProviders>().is_err());
+        assert!("oracle".parse::<AnswerProviders>().is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::{
+    FetchError,
+    answers::{Answer, AnswerContext, AnswerProvider},
+};
+
+/// Digest of the rest of the query, for "md5 hello" or "sha256 of hello".
+#[derive(Debug, Clone)]
+pub struct HashDigest;
+
+/// A random version 4 UUID, for "uuid" or "generate a guid".
+#[derive(Debug, Clone)]
+pub struct UuidGenerator;
+
+/// Base64 of the rest of the query, for "base64 encode hello" or "base64
+/// decode aGVsbG8=".
+#[derive(Debug, Clone)]
+pub struct Base64Codec;
+
+#[async_trait]
+impl AnswerProvider for HashDigest {
+    fn name(&self) -> &'static str {
+        "Hash"
+    }
+
+    async fn answer(
+        &self,
+        query: &str,
+        _context: &AnswerContext,
+    ) -> Result<Vec<Answer>, FetchError> {
+        Ok(hash(query).into_iter().collect())
+    }
+}
+
+#[async_trait]
+impl AnswerProvider for UuidGenerator {
+    fn name(&self) -> &'static str {
+        "UUID"
+    }
+
+    async fn answer(
+        &self,
+        query: &str,
+        _context: &AnswerContext,
+    ) -> Result<Vec<Answer>, FetchError> {
+        let asked = matches!(
+            query.trim().to_lowercase().as_str(),
+            "uuid"
+                | "guid"
+                | "uuid v4"
+                | "uuid4"
+                | "random uuid"
+                | "new uuid"
+                | "generate uuid"
+                | "generate a uuid"
+                | "generate guid"
+                | "generate a guid"
+        );
+        if !asked {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![Answer {
+            provider: self.name().to_string(),
+            title: Some("Random UUID".to_string()),
+            text: uuid_v4(rand::random()),
+            ..Default::default()
+        }])
+    }
+}
+
+#[async_trait]
+impl AnswerProvider for Base64Codec {
+    fn name(&self) -> &'static str {
+        "Base64"
+    }
+
+    async fn answer(
+        &self,
+        query: &str,
+        _context: &AnswerContext,
+    ) -> Result<Vec<Answer>, FetchError> {
+        Ok(base64(query).into_iter().collect())
+    }
+}
+
+fn hash(query: &str) -> Option<Answer> {
+    let (algorithm, rest) = split_command(query)?;
+    let input = strip_word(rest, "hash")
+        .map(|rest| strip_word(rest, "of").unwrap_or(rest))
+        .or_else(|| strip_word(rest, "of"))
+        .unwrap_or(rest);
+    if input.is_empty() {
+        return None;
+    }
+
+    let (name, digest) = match algorithm.as_str() {
+        "md5" => ("MD5", format!("{:x}", md5::Md5::digest(input.as_bytes()))),
+        "sha1" => (
+            "SHA-1",
+            format!("{:x}", sha1::Sha1::digest(input.as_bytes())),
+        ),
+        "sha256" => ("SHA-256", format!("{:x}", Sha256::digest(input.as_bytes()))),
+        "sha512" => ("SHA-512", format!("{:x}", Sha512::digest(input.as_bytes()))),
+        _ => return None,
+    };
+
+    Some(Answer {
+        provider: "Hash".to_string(),
+        title: Some(format!("{name} of \"{input}\"")),
+        text: digest,
+        ..Default::default()
+    })
+}
+
+fn base64(query: &str) -> Option<Answer> {
+    let (command, rest) = split_command(query)?;
+    if command != "base64" {
+        return None;
+    }
+
+    let (title, text) = if let Some(input) = strip_word(rest, "decode") {
+        // binary data has no text to show
+        let decoded = String::from_utf8(STANDARD.decode(input.trim()).ok()?).ok()?;
+        (format!("Base64 decoded \"{input}\""), decoded)
+    } else {
+        let input = strip_word(rest, "encode").unwrap_or(rest);
+        if input.is_empty() {
+            return None;
+        }
+        (format!("Base64 of \"{input}\""), STANDARD.encode(input))
+    };
+
+    Some(Answer {
+        provider: "Base64".to_string(),
+        title: Some(title),
+        text,
+        ..Default::default()
+    })
+}
+
+/// The query's first word, lowercased, and the rest of it as written.
+fn split_command(query: &str) -> Option<(String, &str)> {
+    let (command, rest) = query.trim().split_once(char::is_whitespace)?;
+    Some((command.to_lowercase(), rest.trim()))
+}
+
+/// `text` after its first word if that word is `word`, ignoring case.
+fn strip_word<'a>(text: &'a str, word: &str) -> Option<&'a str> {
+    let (first, rest) = text.split_once(char::is_whitespace)?;
+    first.eq_ignore_ascii_case(word).then(|| rest.trim())
+}
+
+/// Formats random bytes as a version 4, variant 1 UUID.
+fn uuid_v4(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{base64, hash, uuid_v4};
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(
+            hash("md5 hello world").unwrap().text,
+            "5eb63bbbe01eeed093cb22bb8f5acdc3"
+        );
+        let sha256 = hash("SHA256 of Hello").unwrap();
+        assert_eq!(sha256.title.as_deref(), Some("SHA-256 of \"Hello\""));
+        assert_eq!(
+            sha256.text,
+            "185f8db32271fe25f561a6fc938b2e264306ec304eda518007d1764826381969"
+        );
+        assert_eq!(
+            hash("sha1 hash of abc").unwrap().text,
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert!(hash("md5").is_none());
+        assert!(hash("crc32 hello").is_none());
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64("base64 encode hello").unwrap().text, "aGVsbG8=");
+        assert_eq!(
+            base64("base64 Hello World").unwrap().text,
+            "SGVsbG8gV29ybGQ="
+        );
+        assert_eq!(base64("base64 decode aGVsbG8=").unwrap().text, "hello");
+        // not base64, and base64 of bytes that aren't text
+        assert!(base64("base64 decode not base64!").is_none());
+        assert!(base64("base64 decode //79").is_none());
+    }
+
+    #[test]
+    fn test_uuid_v4() {
+        let uuid = uuid_v4([0xff; 16]);
+        assert_eq!(uuid, "ffffffff-ffff-4fff-bfff-ffffffffffff");
+        assert_eq!(uuid_v4([0; 16]), "00000000-0000-4000-8000-000000000000");
+    }
+}
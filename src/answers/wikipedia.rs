@@ -0,0 +1,289 @@
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{
+    FetchError,
+    answers::{Answer, AnswerContext, AnswerProvider, Infobox},
+    cache::{self, WikiSummaryRow},
+    config,
+    engines::{EngineError, new_rand_client, parse_json},
+    vertical_key,
+};
+
+/// Longer queries are questions rather than the name of something
+const MAX_WORDS: usize = 5;
+
+/// An infobox for the Wikipedia article whose title matches the query, with
+/// the lead and thumbnail from the article's REST summary.
+#[derive(Debug, Clone)]
+pub struct WikipediaSummary {
+    lang: String,
+}
+
+impl WikipediaSummary {
+    /// `lang` is the edition's subdomain, e.g. `en` or `de`.
+    pub fn new(lang: &str) -> Self {
+        Self {
+            lang: lang.to_string(),
+        }
+    }
+
+    /// Summary of the matching article, `None` if no title matches.
+    async fn fetch(&self, query: &str) -> Result<Option<WikiSummaryRow>, EngineError> {
+        let client = new_rand_client().map_err(EngineError::ReqwestError)?;
+
+        let body = get_text(
+            client
+                .get(format!("https://{}.wikipedia.org/w/api.php", self.lang))
+                .query(&[
+                    ("action", "query"),
+                    ("format", "json"),
+                    ("formatversion", "2"),
+                    ("list", "search"),
+                    ("srsearch", query),
+                    // titles and redirects matching the query, not full text hits
+                    ("srwhat", "nearmatch"),
+                    ("srlimit", "1"),
+                    ("srprop", ""),
+                ]),
+        )
+        .await?;
+        let Some(title) = parse_search(&body)? else {
+            return Ok(None);
+        };
+
+        let mut url = Url::parse(&format!(
+            "https://{}.wikipedia.org/api/rest_v1/page/summary",
+            self.lang
+        ))
+        .map_err(|e| EngineError::ParseError(e.to_string()))?;
+        url.path_segments_mut()
+            .map_err(|_| EngineError::ParseError("Wikipedia url has no path".to_string()))?
+            .push(&title.replace(' ', "_"));
+
+        let body = get_text(client.get(url)).await?;
+        parse_summary(&body)
+    }
+}
+
+async fn get_text(request: reqwest::RequestBuilder) -> Result<String, EngineError> {
+    request
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)?
+        .error_for_status()
+        .map_err(EngineError::ReqwestError)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)
+}
+
+#[async_trait]
+impl AnswerProvider for WikipediaSummary {
+    fn name(&self) -> &'static str {
+        "Wikipedia summary"
+    }
+
+    async fn answer(
+        &self,
+        query: &str,
+        context: &AnswerContext,
+    ) -> Result<Vec<Answer>, FetchError> {
+        let query = query.trim();
+        let words = query.split_whitespace().count();
+        if words == 0 || words > MAX_WORDS {
+            return Ok(Vec::new());
+        }
+
+        let backend = cache::backend().await;
+        let config = config::get();
+        let persist = config.persist_cache && !context.private;
+        let name = self.name();
+
+        // a query without a matching article is cached without a summary
+        let key = vertical_key(
+            &query.to_lowercase(),
+            &format!("{}\u{1f}summary", self.lang),
+        );
+        if let Some(query_row) = backend
+            .get_query(name, &key, config.cache_ttl, persist)
+            .await
+            .map_err(FetchError::Cache)?
+        {
+            let summary: Vec<WikiSummaryRow> = backend
+                .rows(query_row.id)
+                .await
+                .map_err(FetchError::Cache)?;
+            return Ok(summary.into_iter().take(1).map(to_answer).collect());
+        }
+
+        let summary = backend
+            .fetch_and_store(
+                name,
+                name,
+                persist,
+                true,
+                self.fetch(query),
+                async |summary: &Option<WikiSummaryRow>| {
+                    let fetched_at = chrono::Utc::now().naive_utc();
+                    backend
+                        .store(name, &key, summary.iter().cloned().collect(), fetched_at)
+                        .await?;
+                    Ok(())
+                },
+            )
+            .await?;
+
+        Ok(summary.map(to_answer).into_iter().collect())
+    }
+}
+
+fn to_answer(summary: WikiSummaryRow) -> Answer {
+    Answer {
+        provider: "Wikipedia".to_string(),
+        title: Some(summary.title),
+        text: summary.extract,
+        url: Some(summary.url),
+        infobox: Some(Infobox {
+            description: summary.description,
+            thumbnail: summary.thumbnail,
+        }),
+        ..Default::default()
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    query: Option<SearchQuery>,
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    #[serde(default)]
+    search: Vec<SearchHit>,
+}
+
+#[derive(Deserialize)]
+struct SearchHit {
+    title: String,
+}
+
+fn parse_search(body: &str) -> Result<Option<String>, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    Ok(response
+        .query
+        .and_then(|query| query.search.into_iter().next())
+        .map(|hit| hit.title))
+}
+
+#[derive(Deserialize)]
+struct SummaryResponse {
+    /// "standard", or "disambiguation" for pages listing other articles
+    #[serde(rename = "type")]
+    kind: String,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    extract: String,
+    #[serde(default)]
+    thumbnail: Option<Thumbnail>,
+    content_urls: ContentUrls,
+}
+
+#[derive(Deserialize)]
+struct Thumbnail {
+    source: String,
+}
+
+#[derive(Deserialize)]
+struct ContentUrls {
+    desktop: PageUrls,
+}
+
+#[derive(Deserialize)]
+struct PageUrls {
+    page: String,
+}
+
+fn parse_summary(body: &str) -> Result<Option<WikiSummaryRow>, EngineError> {
+    let summary: SummaryResponse = parse_json(body)?;
+    if summary.kind != "standard" || summary.extract.trim().is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(WikiSummaryRow {
+        title: summary.title,
+        description: summary.description.filter(|d| !d.trim().is_empty()),
+        extract: summary.extract.trim().to_string(),
+        thumbnail: summary.thumbnail.map(|thumbnail| thumbnail.source),
+        url: summary.content_urls.desktop.page,
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_search, parse_summary};
+
+    #[test]
+    fn test_parse_search() {
+        assert_eq!(
+            parse_search(
+                r#"{"batchcomplete": true, "query": {"searchinfo": {"totalhits": 1},
+                    "search": [{"ns": 0, "title": "Rust (programming language)", "pageid": 29414838}]}}"#
+            )
+            .unwrap()
+            .as_deref(),
+            Some("Rust (programming language)")
+        );
+        assert_eq!(
+            parse_search(r#"{"batchcomplete": true, "query": {"search": []}}"#).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_summary() {
+        let summary = parse_summary(
+            r#"{
+                "type": "standard", "title": "Rust (programming language)",
+                "displaytitle": "<span>Rust (programming language)</span>",
+                "description": "General-purpose programming language",
+                "thumbnail": {"source": "https://upload.wikimedia.org/rust-logo.png", "width": 320, "height": 320},
+                "content_urls": {"desktop": {"page": "https://en.wikipedia.org/wiki/Rust_(programming_language)"},
+                                 "mobile": {"page": "https://en.m.wikipedia.org/wiki/Rust_(programming_language)"}},
+                "extract": "Rust is a general-purpose programming language. "
+            }"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(summary.title, "Rust (programming language)");
+        assert_eq!(
+            summary.description.as_deref(),
+            Some("General-purpose programming language")
+        );
+        assert_eq!(
+            summary.extract,
+            "Rust is a general-purpose programming language."
+        );
+        assert_eq!(
+            summary.thumbnail.as_deref(),
+            Some("https://upload.wikimedia.org/rust-logo.png")
+        );
+        assert_eq!(
+            summary.url,
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)"
+        );
+
+        let disambiguation = parse_summary(
+            r#"{"type": "disambiguation", "title": "Rust (disambiguation)",
+                "content_urls": {"desktop": {"page": "https://en.wikipedia.org/wiki/Rust_(disambiguation)"}},
+                "extract": "Rust may refer to:"}"#,
+        )
+        .unwrap();
+        assert!(disambiguation.is_none());
+    }
+}
@@ -0,0 +1,49 @@
+use chrono::{NaiveDateTime, Timelike, Utc};
+
+use crate::{
+    cache::{self, CacheError},
+    config,
+};
+
+/// Counts a request by the engine `id` against the budget configured for its
+/// `name`, or returns false without counting it if the budget is spent. Each
+/// edition or instance of an engine spends its own budget. Engines without a
+/// budget always pass and aren't tracked.
+///
+/// Private searches are counted too: the count per engine and hour tells
+/// nothing about what was searched, and skipping it would let them overrun
+/// the budget.
+pub(crate) async fn try_spend(name: &str, id: &str) -> Result<bool, CacheError> {
+    let Some(budget) = config::get().budgets.get(name) else {
+        return Ok(true);
+    };
+
+    let hour = current_hour(Utc::now().naive_utc());
+    cache::backend()
+        .await
+        .try_add_engine_usage(id, hour, budget.hourly, budget.daily)
+        .await
+}
+
+/// Start of the clock hour `time` is in.
+fn current_hour(time: NaiveDateTime) -> NaiveDateTime {
+    time.with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(time)
+}
+
+#[cfg(test)]
+mod test {
+    use super::current_hour;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_current_hour() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        assert_eq!(
+            current_hour(date.and_hms_milli_opt(13, 59, 12, 500).unwrap()),
+            date.and_hms_opt(13, 0, 0).unwrap()
+        );
+    }
+}
@@ -1,10 +1,23 @@
+use async_trait::async_trait;
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
 use serde::Serialize;
-use sqlx::{SqlitePool, prelude::FromRow};
-use std::env;
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool, prelude::FromRow};
+use std::{
+    env,
+    io::{Read, Write},
+    time::Duration,
+};
+use tokio::sync::OnceCell;
+
+use crate::{
+    FetchError, SearchEngines, budget, engines::EngineError, obfuscation, rewrite, selection,
+};
 
 const DEFAULT_SQLITE_DB_NAME: &str = "data/cache.db";
 const SQLITE_DB_ENV: &str = "CACHE_DB_PATH";
 
+static BACKEND: OnceCell<Box<dyn CacheBackend>> = OnceCell::const_new();
+
 pub async fn init() -> Result<SqlitePool, sqlx::Error> {
     let db_path = env::var(SQLITE_DB_ENV).unwrap_or_else(|_| DEFAULT_SQLITE_DB_NAME.to_string());
 
@@ -21,7 +34,430 @@ pub async fn init() -> Result<SqlitePool, sqlx::Error> {
     Ok(conn)
 }
 
-async fn create_search_cache(conn: &SqlitePool) -> Result<(), sqlx::Error> {
+/// Errors of a [`CacheBackend`].
+#[derive(Debug)]
+pub enum CacheError {
+    Sqlx(sqlx::Error),
+    /// Whatever another backend failed with
+    Backend(Box<dyn std::error::Error + Send + Sync>),
+    /// Only kept in SQLite, which the backend in use isn't
+    Unsupported,
+}
+
+impl From<sqlx::Error> for CacheError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Sqlx(e)
+    }
+}
+
+/// Storage for cached searches of every vertical, and for what searches
+/// keep track of: result rules, request budgets, engine stats and archived
+/// responses. [`SqliteCache`] is used unless another backend is set with
+/// [`set_backend`].
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// The cached search of `query` by `engine`, marked as recently used
+    /// when `touch`. Searches fetched more than `max_age` ago miss, and are
+    /// dropped when `touch`. Without it nothing is written, so private
+    /// searches leave no trace.
+    async fn get_query(
+        &self,
+        engine: &str,
+        query: &str,
+        max_age: Option<Duration>,
+        touch: bool,
+    ) -> Result<Option<QueryRow>, CacheError>;
+
+    /// What `query_id` lists of the kind with this [`CachedRow::LIST_TABLE`],
+    /// in order. `None` for a kind the backend doesn't know.
+    async fn get_rows(&self, query_id: i64, kind: &str) -> Result<Option<Rows>, CacheError>;
+
+    /// Adds `rows` after what `query` lists of their kind, or replaces them
+    /// for [`CachedRow::REPLACE`] kinds, creating the query if it isn't
+    /// cached yet. Returns its id.
+    async fn store_rows(
+        &self,
+        engine: &str,
+        query: &str,
+        rows: Rows,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<i64, CacheError>;
+
+    /// Replaces the results cached for `query_id` with a refetched first page.
+    async fn replace_results(
+        &self,
+        query_id: i64,
+        entries: &[ResultRow],
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<(), CacheError>;
+
+    /// Stores the paging state of the last page fetched for `query_id`.
+    async fn set_page_meta(
+        &self,
+        query_id: i64,
+        total_results: Option<i64>,
+        has_next_page: bool,
+        next_page: Option<&str>,
+    ) -> Result<(), CacheError>;
+
+    /// Archives the response a page of results for `query_id` was parsed
+    /// from. `page` is the token it was fetched with, `None` for the first
+    /// page.
+    async fn archive_response(
+        &self,
+        query_id: i64,
+        page: Option<&str>,
+        body: &str,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<(), CacheError>;
+
+    /// Drops archived responses fetched before `before`, returns how many.
+    async fn prune_archived_responses(
+        &self,
+        before: chrono::NaiveDateTime,
+    ) -> Result<u64, CacheError>;
+
+    /// Pins `target` to the top, or hides it when not `pinned`. Returns the
+    /// rule's id.
+    async fn add_result_rule(
+        &self,
+        target: &str,
+        pinned: bool,
+        query: Option<&str>,
+    ) -> Result<i64, CacheError>;
+
+    /// Every result rule, oldest first.
+    async fn get_result_rules(&self) -> Result<Vec<ResultRuleRow>, CacheError>;
+
+    async fn delete_result_rule(&self, id: i64) -> Result<(), CacheError>;
+
+    /// Requests by `engine` in the hours starting at or after `since`.
+    async fn get_engine_usage(
+        &self,
+        engine: &str,
+        since: chrono::NaiveDateTime,
+    ) -> Result<i64, CacheError>;
+
+    /// Counts one request by `engine` in the clock hour starting at `hour`,
+    /// unless it already made `hourly` requests in that hour or `daily` in
+    /// the 24 hours ending with it. The check and count are one atomic step,
+    /// returns whether the request was counted.
+    async fn try_add_engine_usage(
+        &self,
+        engine: &str,
+        hour: chrono::NaiveDateTime,
+        hourly: Option<u32>,
+        daily: Option<u32>,
+    ) -> Result<bool, CacheError>;
+
+    /// Records how a request to `engine` went, `outcome` being e.g. "ok" or
+    /// "timeout".
+    async fn record_engine_request(
+        &self,
+        engine: &str,
+        requested_at: chrono::NaiveDateTime,
+        latency_ms: i64,
+        outcome: &str,
+    ) -> Result<(), CacheError>;
+
+    /// Drops requests recorded before `before`.
+    async fn prune_engine_requests(&self, before: chrono::NaiveDateTime) -> Result<(), CacheError>;
+
+    /// Totals of the requests to `engine` recorded since `since`.
+    async fn get_engine_stats(
+        &self,
+        engine: &str,
+        since: chrono::NaiveDateTime,
+    ) -> Result<EngineStatsRow, CacheError>;
+
+    /// Drops the least recently used searches past the `max` most recent,
+    /// returns how many.
+    async fn evict(&self, max: usize) -> Result<u64, CacheError>;
+
+    /// Removes the matching searches, returns how many.
+    async fn purge(&self, purge: Purge) -> Result<u64, CacheError>;
+
+    async fn stats(&self) -> Result<CacheStats, CacheError>;
+
+    /// The database behind the backend, if it is SQLite. Alerts, screenshots,
+    /// thumbnail hashes and suggestions from past searches are only kept
+    /// there, and fail with [`CacheError::Unsupported`] on other backends.
+    fn sqlite(&self) -> Option<&SqlitePool> {
+        None
+    }
+}
+
+impl<'a> dyn CacheBackend + 'a {
+    /// [`CacheBackend::get_rows`] for items of type `T`.
+    pub async fn rows<T: CachedRow>(&self, query_id: i64) -> Result<Vec<T>, CacheError> {
+        let rows = self.get_rows(query_id, T::LIST_TABLE).await?;
+        Ok(rows.and_then(T::from_rows).unwrap_or_default())
+    }
+
+    /// [`CacheBackend::store_rows`] for items of type `T`.
+    pub async fn store<T: CachedRow>(
+        &self,
+        engine: &str,
+        query: &str,
+        rows: Vec<T>,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<i64, CacheError> {
+        self.store_rows(engine, query, T::into_rows(rows), fetched_at)
+            .await
+    }
+
+    /// One request to an engine: spends the budget of the engine `id`, which
+    /// is configured under its `name`, waits out the jitter unless `jitter`
+    /// is off, and times `fetch`. When `persist`, what was fetched is then
+    /// handed to `store` and the cache trimmed past
+    /// [`max_cached_queries`](crate::config::Config::max_cached_queries).
+    /// Fails with [`FetchError::BudgetExhausted`] without fetching once the
+    /// budget is spent.
+    pub(crate) async fn fetch_and_store<T>(
+        &self,
+        name: &str,
+        id: &str,
+        persist: bool,
+        jitter: bool,
+        fetch: impl Future<Output = Result<T, EngineError>>,
+        store: impl AsyncFnOnce(&T) -> Result<(), CacheError>,
+    ) -> Result<T, FetchError> {
+        if !budget::try_spend(name, id)
+            .await
+            .map_err(FetchError::Cache)?
+        {
+            return Err(FetchError::BudgetExhausted);
+        }
+        if jitter {
+            obfuscation::jitter().await;
+        }
+        let timer = selection::RequestTimer::start(id.to_string(), persist);
+        let fetched = fetch.await;
+        timer.finish(&fetched);
+        let fetched = fetched.map_err(FetchError::Engine)?;
+
+        if persist {
+            store(&fetched).await.map_err(FetchError::Cache)?;
+            evict_past_max(self).await.map_err(FetchError::Cache)?;
+        }
+        Ok(fetched)
+    }
+}
+
+/// Which cached searches [`CacheBackend::purge`] removes
+#[derive(Debug, Clone)]
+pub enum Purge {
+    /// Every engine's and vertical's search of this cache key
+    Query(String),
+    /// Everything from the engine with this name, in every edition or instance
+    Engine(String),
+    /// Searches fetched before this time
+    OlderThan(chrono::NaiveDateTime),
+    All,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    pub queries: u64,
+    pub results: u64,
+    pub images: u64,
+}
+
+/// Sets the cache backend. Can only be set once and before the first search,
+/// a second backend is handed back.
+pub fn set_backend(backend: impl CacheBackend + 'static) -> Result<(), Box<dyn CacheBackend>> {
+    BACKEND.set(Box::new(backend)).map_err(|e| match e {
+        tokio::sync::SetError::AlreadyInitializedError(backend)
+        | tokio::sync::SetError::InitializingError(backend) => backend,
+    })
+}
+
+/// The backend set with [`set_backend`], else the SQLite database.
+pub(crate) async fn backend() -> &'static dyn CacheBackend {
+    BACKEND
+        .get_or_init(|| async {
+            Box::new(SqliteCache::new(crate::get_db().await.clone())) as Box<dyn CacheBackend>
+        })
+        .await
+        .as_ref()
+}
+
+/// The database of the backend in use, [`CacheError::Unsupported`] unless it
+/// is SQLite.
+pub(crate) async fn sqlite() -> Result<&'static SqlitePool, CacheError> {
+    backend().await.sqlite().ok_or(CacheError::Unsupported)
+}
+
+/// The SQLite database at `CACHE_DB_PATH`.
+#[derive(Debug, Clone)]
+pub struct SqliteCache {
+    pool: SqlitePool,
+}
+
+impl SqliteCache {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for SqliteCache {
+    async fn get_query(
+        &self,
+        engine: &str,
+        query: &str,
+        max_age: Option<Duration>,
+        touch: bool,
+    ) -> Result<Option<QueryRow>, CacheError> {
+        let engine_id = match touch {
+            true => get_engine_id(&self.pool, engine).await?,
+            false => match find_engine_id(&self.pool, engine).await? {
+                Some(id) => id,
+                None => return Ok(None),
+            },
+        };
+        Ok(get_unexpired_query(&self.pool, query, engine_id, max_age, touch).await?)
+    }
+
+    async fn get_rows(&self, query_id: i64, kind: &str) -> Result<Option<Rows>, CacheError> {
+        Ok(self.load_rows(query_id, kind).await?)
+    }
+
+    async fn store_rows(
+        &self,
+        engine: &str,
+        query: &str,
+        rows: Rows,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<i64, CacheError> {
+        Ok(self.save_rows(engine, query, rows, fetched_at).await?)
+    }
+
+    async fn replace_results(
+        &self,
+        query_id: i64,
+        entries: &[ResultRow],
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<(), CacheError> {
+        replace_query_results(&self.pool, query_id, entries).await?;
+        Ok(set_query_fetched_at(&self.pool, query_id, fetched_at).await?)
+    }
+
+    async fn set_page_meta(
+        &self,
+        query_id: i64,
+        total_results: Option<i64>,
+        has_next_page: bool,
+        next_page: Option<&str>,
+    ) -> Result<(), CacheError> {
+        Ok(set_query_page_meta(
+            &self.pool,
+            query_id,
+            total_results,
+            has_next_page,
+            next_page,
+        )
+        .await?)
+    }
+
+    async fn archive_response(
+        &self,
+        query_id: i64,
+        page: Option<&str>,
+        body: &str,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<(), CacheError> {
+        Ok(archive_response(&self.pool, query_id, page, body, fetched_at).await?)
+    }
+
+    async fn prune_archived_responses(
+        &self,
+        before: chrono::NaiveDateTime,
+    ) -> Result<u64, CacheError> {
+        Ok(prune_archived_responses(&self.pool, before).await?)
+    }
+
+    async fn add_result_rule(
+        &self,
+        target: &str,
+        pinned: bool,
+        query: Option<&str>,
+    ) -> Result<i64, CacheError> {
+        Ok(add_result_rule(&self.pool, target, pinned, query).await?)
+    }
+
+    async fn get_result_rules(&self) -> Result<Vec<ResultRuleRow>, CacheError> {
+        Ok(get_result_rules(&self.pool).await?)
+    }
+
+    async fn delete_result_rule(&self, id: i64) -> Result<(), CacheError> {
+        Ok(delete_result_rule(&self.pool, id).await?)
+    }
+
+    async fn get_engine_usage(
+        &self,
+        engine: &str,
+        since: chrono::NaiveDateTime,
+    ) -> Result<i64, CacheError> {
+        Ok(get_engine_usage(&self.pool, engine, since).await?)
+    }
+
+    async fn try_add_engine_usage(
+        &self,
+        engine: &str,
+        hour: chrono::NaiveDateTime,
+        hourly: Option<u32>,
+        daily: Option<u32>,
+    ) -> Result<bool, CacheError> {
+        Ok(try_add_engine_usage(&self.pool, engine, hour, hourly, daily).await?)
+    }
+
+    async fn record_engine_request(
+        &self,
+        engine: &str,
+        requested_at: chrono::NaiveDateTime,
+        latency_ms: i64,
+        outcome: &str,
+    ) -> Result<(), CacheError> {
+        Ok(record_engine_request(&self.pool, engine, requested_at, latency_ms, outcome).await?)
+    }
+
+    async fn prune_engine_requests(&self, before: chrono::NaiveDateTime) -> Result<(), CacheError> {
+        Ok(prune_engine_requests(&self.pool, before).await?)
+    }
+
+    async fn get_engine_stats(
+        &self,
+        engine: &str,
+        since: chrono::NaiveDateTime,
+    ) -> Result<EngineStatsRow, CacheError> {
+        Ok(get_engine_stats(&self.pool, engine, since).await?)
+    }
+
+    async fn evict(&self, max: usize) -> Result<u64, CacheError> {
+        Ok(evict_queries(&self.pool, max).await?)
+    }
+
+    async fn purge(&self, purge: Purge) -> Result<u64, CacheError> {
+        let removed = match purge {
+            Purge::Query(query) => purge_query(&self.pool, &query).await?,
+            Purge::Engine(engine) => purge_engine(&self.pool, &engine).await?,
+            Purge::OlderThan(before) => purge_older_than(&self.pool, before).await?,
+            Purge::All => purge_all(&self.pool).await?,
+        };
+        Ok(removed)
+    }
+
+    async fn stats(&self) -> Result<CacheStats, CacheError> {
+        Ok(get_cache_stats(&self.pool).await?)
+    }
+
+    fn sqlite(&self) -> Option<&SqlitePool> {
+        Some(&self.pool)
+    }
+}
+
+pub(crate) async fn create_search_cache(conn: &SqlitePool) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
     -- Engines
@@ -67,411 +503,3610 @@ async fn create_search_cache(conn: &SqlitePool) -> Result<(), sqlx::Error> {
         image_index INTEGER NOT NULL,
         PRIMARY KEY (query_id, image_id)
     );
-        "#,
-    )
-    .execute(conn)
-    .await?;
-
-    Ok(())
-}
 
-pub async fn upsert_query_with_results(
-    pool: &SqlitePool,
-    engine: &str,
-    query: &str,
-    entries: Vec<ResultRow>,
-    fetched_at: chrono::NaiveDateTime,
-) -> Result<i64, sqlx::Error> {
-    let engine_id = get_engine_id(pool, engine).await?;
-    let query_row = get_query(pool, query, engine_id).await?;
+    -- News Results
+    CREATE TABLE IF NOT EXISTS news (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        source TEXT NOT NULL,
+        published_at TEXT
+    );
 
-    // start sql transaction
-    let tx = pool.begin().await?;
+    CREATE TABLE IF NOT EXISTS query_news (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        news_id INTEGER NOT NULL REFERENCES news(id),
+        news_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, news_id)
+    );
 
-    let query_id = if let Some(q) = query_row {
-        q.id
-    } else {
-        insert_query(pool, query, engine_id, fetched_at).await?
-    };
+    -- Video Results
+    CREATE TABLE IF NOT EXISTS videos (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        thumbnail TEXT,
+        duration TEXT,
+        channel TEXT
+    );
 
-    // Determine starting index for new results
-    let current_count: i64 =
-        sqlx::query_scalar("SELECT COUNT(*) FROM query_results WHERE query_id = ?")
-            .bind(query_id)
-            .fetch_one(pool)
-            .await?;
+    CREATE TABLE IF NOT EXISTS query_videos (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        video_id INTEGER NOT NULL REFERENCES videos(id),
+        video_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, video_id)
+    );
 
-    for (i, entry) in entries.iter().enumerate() {
-        let result_id = insert_result(pool, &entry.title, &entry.url, &entry.description).await?;
-        insert_query_result(pool, query_id, result_id, current_count + i as i64).await?;
-    }
+    -- Places from geocoders, keyed by their OpenStreetMap object
+    CREATE TABLE IF NOT EXISTS places (
+        id INTEGER PRIMARY KEY,
+        osm_url TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        lat REAL NOT NULL,
+        lon REAL NOT NULL,
+        address TEXT,
+        kind TEXT
+    );
 
-    tx.commit().await?;
+    CREATE TABLE IF NOT EXISTS query_places (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        place_id INTEGER NOT NULL REFERENCES places(id),
+        place_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, place_id)
+    );
 
-    Ok(query_id)
-}
+    -- Shopping Results
+    CREATE TABLE IF NOT EXISTS products (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        price TEXT, -- decimal as written by the engine, e.g. "12.50"
+        currency TEXT, -- ISO 4217 code
+        merchant TEXT,
+        image TEXT
+    );
 
-pub async fn upsert_query_with_images(
-    pool: &SqlitePool,
-    engine: &str,
-    query: &str,
-    entries: Vec<ImagesRow>,
-    fetched_at: chrono::NaiveDateTime,
-) -> Result<i64, sqlx::Error> {
-    let engine_id = get_engine_id(pool, engine).await?;
-    let query_row = get_query(pool, query, engine_id).await?;
+    CREATE TABLE IF NOT EXISTS query_products (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        product_id INTEGER NOT NULL REFERENCES products(id),
+        product_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, product_id)
+    );
 
-    // start sql transaction
-    let tx = pool.begin().await?;
+    -- Academic papers
+    CREATE TABLE IF NOT EXISTS papers (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        authors TEXT NOT NULL, -- JSON array of names
+        year INTEGER,
+        doi TEXT,
+        abstract_text TEXT,
+        kind TEXT
+    );
 
-    let query_id = if let Some(qi) = query_row {
-        qi.id
-    } else {
-        insert_query(pool, query, engine_id, fetched_at).await?
-    };
+    CREATE TABLE IF NOT EXISTS query_papers (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        paper_id INTEGER NOT NULL REFERENCES papers(id),
+        paper_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, paper_id)
+    );
 
-    // Determine starting index for new images
-    let current_count: i64 =
-        sqlx::query_scalar("SELECT COUNT(*) FROM query_images WHERE query_id = ?")
-            .bind(query_id)
-            .fetch_one(pool)
-            .await?;
+    -- Torrents from indexers
+    CREATE TABLE IF NOT EXISTS files (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        magnet TEXT,
+        download_url TEXT,
+        size INTEGER, -- bytes
+        seeders INTEGER,
+        leechers INTEGER,
+        published_at TEXT
+    );
 
-    for (i, entry) in entries.iter().enumerate() {
-        let image_id = insert_image(pool, &entry.title, &entry.url).await?;
-        insert_query_image(pool, query_id, image_id, current_count + i as i64).await?;
-    }
+    CREATE TABLE IF NOT EXISTS query_files (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        file_id INTEGER NOT NULL REFERENCES files(id),
+        file_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, file_id)
+    );
 
-    tx.commit().await?;
+    -- Fediverse posts and communities
+    CREATE TABLE IF NOT EXISTS social (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        kind TEXT NOT NULL, -- post or community
+        title TEXT,
+        content TEXT,
+        author TEXT,
+        community TEXT,
+        score INTEGER,
+        published_at TEXT
+    );
 
-    Ok(query_id)
-}
+    CREATE TABLE IF NOT EXISTS query_social (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        social_id INTEGER NOT NULL REFERENCES social(id),
+        social_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, social_id)
+    );
 
-#[derive(FromRow)]
-pub struct EngineRow {
-    pub id: i64,
-    pub name: String,
-}
+    -- Recipes, read from the pages of web results
+    CREATE TABLE IF NOT EXISTS recipes (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        image TEXT,
+        ingredients TEXT NOT NULL, -- JSON array
+        prep_time INTEGER, -- minutes
+        cook_time INTEGER,
+        total_time INTEGER,
+        yields TEXT,
+        rating REAL,
+        rating_count INTEGER
+    );
 
-pub async fn get_engine_id(pool: &SqlitePool, engine: &str) -> Result<i64, sqlx::Error> {
-    let row: Option<(i64,)> = sqlx::query_as("SELECT id FROM engines WHERE name = ?")
-        .bind(engine)
-        .fetch_optional(pool)
-        .await?;
+    CREATE TABLE IF NOT EXISTS query_recipes (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        recipe_id INTEGER NOT NULL REFERENCES recipes(id),
+        recipe_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, recipe_id)
+    );
 
-    if let Some((id,)) = row {
-        return Ok(id);
-    }
+    -- Word senses from dictionaries
+    CREATE TABLE IF NOT EXISTS definitions (
+        id INTEGER PRIMARY KEY,
+        word TEXT NOT NULL,
+        part_of_speech TEXT NOT NULL,
+        definition TEXT NOT NULL,
+        examples TEXT NOT NULL, -- JSON array
+        phonetic TEXT,
+        source_url TEXT,
+        UNIQUE (word, part_of_speech, definition)
+    );
 
-    let id = sqlx::query("INSERT INTO engines (name) VALUES (?)")
-        .bind(engine)
-        .execute(pool)
-        .await?
-        .last_insert_rowid();
+    CREATE TABLE IF NOT EXISTS query_definitions (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        definition_id INTEGER NOT NULL REFERENCES definitions(id),
+        definition_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, definition_id)
+    );
 
-    Ok(id)
-}
+    -- The latest price quote of a query, replaced whenever it is refetched
+    CREATE TABLE IF NOT EXISTS quotes (
+        query_id INTEGER PRIMARY KEY REFERENCES queries(id) ON DELETE CASCADE,
+        symbol TEXT NOT NULL,
+        name TEXT,
+        price REAL NOT NULL,
+        change REAL,
+        change_percent REAL,
+        currency TEXT, -- ISO 4217 code
+        market_time TEXT
+    );
 
-pub async fn insert_engine(pool: &SqlitePool, engine: &str) -> Result<i64, sqlx::Error> {
-    let id = sqlx::query("INSERT OR IGNORE INTO engines (name) VALUES (?)")
-        .bind(engine)
-        .execute(pool)
-        .await?
-        .last_insert_rowid();
+    -- Wikipedia article summaries answering a query
+    CREATE TABLE IF NOT EXISTS wiki_summaries (
+        query_id INTEGER PRIMARY KEY REFERENCES queries(id) ON DELETE CASCADE,
+        title TEXT NOT NULL,
+        description TEXT,
+        extract TEXT NOT NULL,
+        thumbnail TEXT,
+        url TEXT NOT NULL
+    );
 
-    Ok(id)
-}
+    -- Autocomplete suggestions from engines, kept briefly
+    CREATE TABLE IF NOT EXISTS query_suggestions (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        suggestion_index INTEGER NOT NULL,
+        suggestion TEXT NOT NULL,
+        PRIMARY KEY (query_id, suggestion_index)
+    );
 
-#[derive(FromRow)]
-pub struct QueryRow {
-    pub id: i64,
-    pub query: String,
-    pub engine_id: i64,
-    pub fetched_at: chrono::NaiveDateTime,
-}
+    -- Packages from language registries
+    CREATE TABLE IF NOT EXISTS packages (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        registry TEXT NOT NULL,
+        version TEXT,
+        description TEXT,
+        downloads INTEGER,
+        repository TEXT
+    );
 
-pub async fn get_query(
-    pool: &SqlitePool,
-    query: &str,
-    engine_id: i64,
-) -> Result<Option<QueryRow>, sqlx::Error> {
-    let row: Option<QueryRow> = sqlx::query_as(
-        r#"
-        SELECT id, query, engine_id, fetched_at
-        FROM queries
-        WHERE query = ? AND engine_id = ?
-        "#,
-    )
-    .bind(query)
-    .bind(engine_id)
-    .fetch_optional(pool)
-    .await?;
+    CREATE TABLE IF NOT EXISTS query_packages (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        package_id INTEGER NOT NULL REFERENCES packages(id),
+        package_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, package_id)
+    );
 
-    Ok(row)
-}
+    -- Apps from software catalogs
+    CREATE TABLE IF NOT EXISTS apps (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        name TEXT NOT NULL,
+        store TEXT NOT NULL,
+        app_id TEXT NOT NULL,
+        summary TEXT,
+        icon TEXT,
+        install_url TEXT
+    );
 
-pub async fn insert_query(
-    pool: &SqlitePool,
-    query: &str,
-    engine_id: i64,
-    fetched_at: chrono::NaiveDateTime,
-) -> Result<i64, sqlx::Error> {
-    let id = sqlx::query(
-        r#"
-        INSERT INTO queries (query, engine_id, fetched_at)
-        VALUES (?, ?, ?)
-        "#,
-    )
-    .bind(query)
-    .bind(engine_id)
-    .bind(fetched_at)
-    .execute(pool)
-    .await?
-    .last_insert_rowid();
+    CREATE TABLE IF NOT EXISTS query_apps (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        app_id INTEGER NOT NULL REFERENCES apps(id),
+        app_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, app_id)
+    );
+
+    -- Patents
+    CREATE TABLE IF NOT EXISTS patents (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        number TEXT NOT NULL,
+        title TEXT NOT NULL,
+        assignee TEXT,
+        inventor TEXT,
+        filing_date TEXT,
+        snippet TEXT,
+        pdf TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS query_patents (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        patent_id INTEGER NOT NULL REFERENCES patents(id),
+        patent_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, patent_id)
+    );
+
+    -- Albums, tracks and artists
+    CREATE TABLE IF NOT EXISTS music (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        artist TEXT,
+        kind TEXT NOT NULL,
+        year INTEGER,
+        cover TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS query_music (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        music_id INTEGER NOT NULL REFERENCES music(id),
+        music_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, music_id)
+    );
+
+    -- Podcasts
+    CREATE TABLE IF NOT EXISTS podcasts (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        feed_url TEXT NOT NULL,
+        author TEXT,
+        artwork TEXT,
+        description TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS query_podcasts (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        podcast_id INTEGER NOT NULL REFERENCES podcasts(id),
+        podcast_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, podcast_id)
+    );
+
+    -- Books
+    CREATE TABLE IF NOT EXISTS books (
+        id INTEGER PRIMARY KEY,
+        url TEXT NOT NULL UNIQUE,
+        title TEXT NOT NULL,
+        authors TEXT NOT NULL, -- JSON array of names
+        year INTEGER,
+        cover TEXT,
+        isbn TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS query_books (
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        book_id INTEGER NOT NULL REFERENCES books(id),
+        book_index INTEGER NOT NULL,
+        PRIMARY KEY (query_id, book_id)
+    );
+
+    -- User rules pinning or hiding results
+    CREATE TABLE IF NOT EXISTS result_rules (
+        id INTEGER PRIMARY KEY,
+        target TEXT NOT NULL, -- url, or domain including its subdomains
+        pinned BOOLEAN NOT NULL, -- hidden otherwise
+        query TEXT -- only searches containing this, NULL for all
+    );
+
+    -- Outcome of every engine request, for picking engines by recent performance
+    CREATE TABLE IF NOT EXISTS engine_requests (
+        id INTEGER PRIMARY KEY,
+        engine_id INTEGER NOT NULL REFERENCES engines(id),
+        requested_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        latency_ms INTEGER NOT NULL,
+        outcome TEXT NOT NULL -- ok, error, blocked or timeout
+    );
+    CREATE INDEX IF NOT EXISTS engine_requests_time
+        ON engine_requests (engine_id, requested_at);
+
+    -- Requests per engine and clock hour, for request budgets
+    CREATE TABLE IF NOT EXISTS engine_usage (
+        engine_id INTEGER NOT NULL REFERENCES engines(id),
+        hour DATETIME NOT NULL,
+        requests INTEGER NOT NULL,
+        PRIMARY KEY (engine_id, hour)
+    );
+
+    -- Queries re-run periodically to watch for new results
+    CREATE TABLE IF NOT EXISTS standing_queries (
+        id INTEGER PRIMARY KEY,
+        query TEXT NOT NULL,
+        interval_secs INTEGER NOT NULL,
+        last_run DATETIME
+    );
+
+    CREATE TABLE IF NOT EXISTS alert_results (
+        id INTEGER PRIMARY KEY,
+        standing_query_id INTEGER NOT NULL REFERENCES standing_queries(id) ON DELETE CASCADE,
+        url TEXT NOT NULL,
+        title TEXT NOT NULL,
+        found_at DATETIME NOT NULL,
+        seen BOOLEAN NOT NULL DEFAULT 0,
+        UNIQUE (standing_query_id, url)
+    );
+
+    -- Responses cached result pages were parsed from, for re-parsing
+    CREATE TABLE IF NOT EXISTS raw_responses (
+        id INTEGER PRIMARY KEY,
+        query_id INTEGER NOT NULL REFERENCES queries(id) ON DELETE CASCADE,
+        page TEXT, -- token the page was fetched with, NULL for the first
+        body BLOB NOT NULL, -- deflate compressed
+        fetched_at DATETIME NOT NULL
+    );
+
+    -- Page screenshot previews
+    CREATE TABLE IF NOT EXISTS screenshots (
+        url TEXT PRIMARY KEY,
+        image BLOB NOT NULL, -- jpeg
+        captured_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL
+    );
+        "#,
+    )
+    .execute(conn)
+    .await?;
+
+    // Columns added after the initial schema, so older databases pick them up
+    add_column(conn, "queries", "total_results", "INTEGER").await?;
+    add_column(conn, "queries", "next_page", "TEXT").await?;
+    // last cache hit, for evicting the least recently used queries
+    add_column(conn, "queries", "last_used", "DATETIME").await?;
+    add_column(conn, "results", "published_at", "TEXT").await?;
+    add_column(conn, "results", "language", "TEXT").await?;
+    add_column(
+        conn,
+        "results",
+        "extra_snippets",
+        "TEXT NOT NULL DEFAULT '[]'",
+    )
+    .await?;
+    add_column(conn, "images", "width", "INTEGER").await?;
+    add_column(conn, "images", "height", "INTEGER").await?;
+    add_column(conn, "images", "thumbnail", "TEXT").await?;
+    add_column(conn, "images", "source_url", "TEXT").await?;
+    add_column(conn, "images", "blurhash", "TEXT").await?;
+    add_column(conn, "images", "phash", "INTEGER").await?;
+    add_column(conn, "images", "license", "TEXT").await?;
+    add_column(conn, "images", "attribution", "TEXT").await?;
+    add_column(
+        conn,
+        "queries",
+        "has_next_page",
+        "BOOLEAN NOT NULL DEFAULT 0",
+    )
+    .await?;
+    add_column(conn, "papers", "kind", "TEXT").await?;
+
+    Ok(())
+}
+
+async fn add_column(
+    conn: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pragma_table_info(?) WHERE name = ?)")
+            .bind(table)
+            .bind(column)
+            .fetch_one(conn)
+            .await?;
+
+    if !exists {
+        sqlx::query(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))
+        .execute(conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Items a query lists in order, e.g. the stories of a news search. Each
+/// kind has its own table and a `LIST_TABLE` junction table keyed by
+/// `query_id`, `upsert_query_with_rows` takes care of the query itself.
+#[async_trait]
+pub trait CachedRow: RowKind + Clone + Send + Sync + 'static {
+    /// Table listing the items of each query, with a `query_id` column
+    const LIST_TABLE: &'static str;
+    /// Sources answering in a single page replace what the query listed,
+    /// others add a page to it
+    const REPLACE: bool = false;
+
+    /// Stores the item as the `index`th of the query
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error>;
+
+    /// The items of the query in order
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error>;
+}
+
+/// Moves a kind's items in and out of [`Rows`], implemented for every kind
+/// `cached_kinds!` declares.
+pub trait RowKind: Sized {
+    fn into_rows(rows: Vec<Self>) -> Rows;
+
+    /// The items, `None` if `rows` are of another kind
+    fn from_rows(rows: Rows) -> Option<Vec<Self>>;
+}
+
+/// Declares [`Rows`] with a variant for each kind of cached item, and how
+/// [`SqliteCache`] stores and loads each of them.
+macro_rules! cached_kinds {
+    ($($variant:ident($row:ty)),* $(,)?) => {
+        /// Items of one cached kind, how a [`CacheBackend`] is handed those of
+        /// any vertical.
+        #[derive(Debug, Clone)]
+        pub enum Rows {
+            $($variant(Vec<$row>),)*
+        }
+
+        impl Rows {
+            /// [`CachedRow::LIST_TABLE`] of the kind
+            pub fn kind(&self) -> &'static str {
+                match self {
+                    $(Rows::$variant(_) => <$row as CachedRow>::LIST_TABLE,)*
+                }
+            }
+
+            /// [`CachedRow::REPLACE`] of the kind
+            pub fn replaces(&self) -> bool {
+                match self {
+                    $(Rows::$variant(_) => <$row as CachedRow>::REPLACE,)*
+                }
+            }
+
+            pub fn len(&self) -> usize {
+                match self {
+                    $(Rows::$variant(rows) => rows.len(),)*
+                }
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len() == 0
+            }
+
+            /// Adds `other` after these items, or takes its place if it is of
+            /// another kind.
+            pub fn append(&mut self, other: Rows) {
+                match (self, other) {
+                    $((Rows::$variant(rows), Rows::$variant(other)) => rows.extend(other),)*
+                    (rows, other) => *rows = other,
+                }
+            }
+        }
+
+        $(
+            impl RowKind for $row {
+                fn into_rows(rows: Vec<Self>) -> Rows {
+                    Rows::$variant(rows)
+                }
+
+                fn from_rows(rows: Rows) -> Option<Vec<Self>> {
+                    match rows {
+                        Rows::$variant(rows) => Some(rows),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+
+        impl SqliteCache {
+            async fn load_rows(
+                &self,
+                query_id: i64,
+                kind: &str,
+            ) -> Result<Option<Rows>, sqlx::Error> {
+                $(
+                    if kind == <$row as CachedRow>::LIST_TABLE {
+                        let rows = <$row as CachedRow>::for_query(&self.pool, query_id).await?;
+                        return Ok(Some(Rows::$variant(rows)));
+                    }
+                )*
+                Ok(None)
+            }
+
+            async fn save_rows(
+                &self,
+                engine: &str,
+                query: &str,
+                rows: Rows,
+                fetched_at: chrono::NaiveDateTime,
+            ) -> Result<i64, sqlx::Error> {
+                match rows {
+                    $(Rows::$variant(rows) => {
+                        upsert_query_with_rows(&self.pool, engine, query, &rows, fetched_at).await
+                    })*
+                }
+            }
+        }
+    };
+}
+
+cached_kinds! {
+    Results(ResultRow),
+    Images(ImagesRow),
+    News(NewsRow),
+    Videos(VideoRow),
+    Products(ProductRow),
+    Papers(PaperRow),
+    Files(FileRow),
+    Social(SocialRow),
+    Recipes(RecipeRow),
+    Music(MusicRow),
+    Patents(PatentRow),
+    Apps(AppRow),
+    Packages(PackageRow),
+    Podcasts(PodcastRow),
+    Books(BookRow),
+    Definitions(DefinitionRow),
+    Places(PlaceRow),
+    Quotes(QuoteRow),
+    WikiSummaries(WikiSummaryRow),
+    Suggestions(String),
+}
+
+/// Adds `entries` after what the query already lists, creating the query if
+/// needed, or replaces them for [`CachedRow::REPLACE`] kinds, which also
+/// moves `fetched_at` along. Returns the query's id.
+pub async fn upsert_query_with_rows<T: CachedRow>(
+    pool: &SqlitePool,
+    engine: &str,
+    query: &str,
+    entries: &[T],
+    fetched_at: chrono::NaiveDateTime,
+) -> Result<i64, sqlx::Error> {
+    let engine_id = get_engine_id(pool, engine).await?;
+    let query_row = get_query(pool, query, engine_id).await?;
+
+    let mut tx = pool.begin().await?;
+
+    let query_id = match &query_row {
+        Some(q) => q.id,
+        None => insert_query(&mut *tx, query, engine_id, fetched_at).await?,
+    };
+
+    let start: i64 = if T::REPLACE {
+        sqlx::query("UPDATE queries SET fetched_at = ? WHERE id = ?")
+            .bind(fetched_at)
+            .bind(query_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!("DELETE FROM {} WHERE query_id = ?", T::LIST_TABLE))
+            .bind(query_id)
+            .execute(&mut *tx)
+            .await?;
+        0
+    } else {
+        sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM {} WHERE query_id = ?",
+            T::LIST_TABLE
+        ))
+        .bind(query_id)
+        .fetch_one(&mut *tx)
+        .await?
+    };
+
+    for (i, entry) in entries.iter().enumerate() {
+        entry.insert(&mut tx, query_id, start + i as i64).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(query_id)
+}
+
+#[async_trait]
+impl CachedRow for ResultRow {
+    const LIST_TABLE: &'static str = "query_results";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let result_id =
+            insert_result(&mut *conn, &self.title, &self.url, &self.description).await?;
+        set_result_meta(&mut *conn, result_id, self).await?;
+        insert_query_result(&mut *conn, query_id, result_id, index).await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_results_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for ImagesRow {
+    const LIST_TABLE: &'static str = "query_images";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let image_id = insert_image(&mut *conn, &self.title, &self.url).await?;
+        set_image_meta(&mut *conn, image_id, self).await?;
+        insert_query_image(&mut *conn, query_id, image_id, index).await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_images_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for NewsRow {
+    const LIST_TABLE: &'static str = "query_news";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        // keep the older date when a source stops showing one
+        let (news_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO news (url, title, source, published_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                source = excluded.source,
+                published_at = COALESCE(excluded.published_at, published_at)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(&self.source)
+        .bind(&self.published_at)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_news (query_id, news_id, news_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(news_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_news_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for VideoRow {
+    const LIST_TABLE: &'static str = "query_videos";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (video_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO videos (url, title, thumbnail, duration, channel)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                thumbnail = COALESCE(excluded.thumbnail, thumbnail),
+                duration = COALESCE(excluded.duration, duration),
+                channel = COALESCE(excluded.channel, channel)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(&self.thumbnail)
+        .bind(&self.duration)
+        .bind(&self.channel)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_videos (query_id, video_id, video_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(video_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_videos_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for ProductRow {
+    const LIST_TABLE: &'static str = "query_products";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        // prices change, so the latest one seen wins
+        let (product_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO products (url, title, price, currency, merchant, image)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                price = excluded.price,
+                currency = excluded.currency,
+                merchant = COALESCE(excluded.merchant, merchant),
+                image = COALESCE(excluded.image, image)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(&self.price)
+        .bind(&self.currency)
+        .bind(&self.merchant)
+        .bind(&self.image)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_products (query_id, product_id, product_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(product_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_products_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for PaperRow {
+    const LIST_TABLE: &'static str = "query_papers";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (paper_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO papers (url, title, authors, year, doi, abstract_text, kind)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                authors = excluded.authors,
+                year = COALESCE(excluded.year, year),
+                doi = COALESCE(excluded.doi, doi),
+                abstract_text = COALESCE(excluded.abstract_text, abstract_text),
+                kind = COALESCE(excluded.kind, kind)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(sqlx::types::Json(&self.authors))
+        .bind(self.year)
+        .bind(&self.doi)
+        .bind(&self.abstract_text)
+        .bind(&self.kind)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_papers (query_id, paper_id, paper_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(paper_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_papers_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for FileRow {
+    const LIST_TABLE: &'static str = "query_files";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        // swarm counts change, so the latest ones seen win
+        let (file_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO files (url, title, magnet, download_url, size, seeders, leechers, published_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                magnet = COALESCE(excluded.magnet, magnet),
+                download_url = COALESCE(excluded.download_url, download_url),
+                size = COALESCE(excluded.size, size),
+                seeders = excluded.seeders,
+                leechers = excluded.leechers,
+                published_at = COALESCE(excluded.published_at, published_at)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(&self.magnet)
+        .bind(&self.download_url)
+        .bind(self.size)
+        .bind(self.seeders)
+        .bind(self.leechers)
+        .bind(&self.published_at)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_files (query_id, file_id, file_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(file_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_files_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for SocialRow {
+    const LIST_TABLE: &'static str = "query_social";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (social_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO social (url, kind, title, content, author, community, score, published_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET kind = excluded.kind,
+                title = COALESCE(excluded.title, title),
+                content = COALESCE(excluded.content, content),
+                author = COALESCE(excluded.author, author),
+                community = COALESCE(excluded.community, community),
+                score = COALESCE(excluded.score, score),
+                published_at = COALESCE(excluded.published_at, published_at)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.kind)
+        .bind(&self.title)
+        .bind(&self.content)
+        .bind(&self.author)
+        .bind(&self.community)
+        .bind(self.score)
+        .bind(&self.published_at)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_social (query_id, social_id, social_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(social_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_social_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for RecipeRow {
+    const LIST_TABLE: &'static str = "query_recipes";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        // a page's recipe is replaced as a whole, it was read again
+        let (recipe_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO recipes (url, title, image, ingredients, prep_time, cook_time,
+                total_time, yields, rating, rating_count)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                image = excluded.image,
+                ingredients = excluded.ingredients,
+                prep_time = excluded.prep_time,
+                cook_time = excluded.cook_time,
+                total_time = excluded.total_time,
+                yields = excluded.yields,
+                rating = excluded.rating,
+                rating_count = excluded.rating_count
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(&self.image)
+        .bind(sqlx::types::Json(&self.ingredients))
+        .bind(self.prep_time)
+        .bind(self.cook_time)
+        .bind(self.total_time)
+        .bind(&self.yields)
+        .bind(self.rating)
+        .bind(self.rating_count)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_recipes (query_id, recipe_id, recipe_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(recipe_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_recipes_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for DefinitionRow {
+    const LIST_TABLE: &'static str = "query_definitions";
+    const REPLACE: bool = true;
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (definition_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO definitions (word, part_of_speech, definition, examples, phonetic,
+                source_url)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (word, part_of_speech, definition) DO UPDATE SET
+                examples = excluded.examples,
+                phonetic = COALESCE(excluded.phonetic, phonetic),
+                source_url = COALESCE(excluded.source_url, source_url)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.word)
+        .bind(&self.part_of_speech)
+        .bind(&self.definition)
+        .bind(sqlx::types::Json(&self.examples))
+        .bind(&self.phonetic)
+        .bind(&self.source_url)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_definitions (query_id, definition_id, definition_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(definition_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_definitions_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for QuoteRow {
+    const LIST_TABLE: &'static str = "quotes";
+    const REPLACE: bool = true;
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        _index: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO quotes (query_id, symbol, name, price, change, change_percent,
+                currency, market_time)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(query_id)
+        .bind(&self.symbol)
+        .bind(&self.name)
+        .bind(self.price)
+        .bind(self.change)
+        .bind(self.change_percent)
+        .bind(&self.currency)
+        .bind(&self.market_time)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        Ok(get_quote_for_query(pool, query_id)
+            .await?
+            .into_iter()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl CachedRow for WikiSummaryRow {
+    const LIST_TABLE: &'static str = "wiki_summaries";
+    const REPLACE: bool = true;
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        _index: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO wiki_summaries (query_id, title, description, extract,
+                thumbnail, url)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(query_id)
+        .bind(&self.title)
+        .bind(&self.description)
+        .bind(&self.extract)
+        .bind(&self.thumbnail)
+        .bind(&self.url)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        Ok(get_wiki_summary_for_query(pool, query_id)
+            .await?
+            .into_iter()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl CachedRow for String {
+    const LIST_TABLE: &'static str = "query_suggestions";
+    const REPLACE: bool = true;
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO query_suggestions (query_id, suggestion_index, suggestion) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(index)
+        .bind(self)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_suggestions_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for MusicRow {
+    const LIST_TABLE: &'static str = "query_music";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (music_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO music (url, title, artist, kind, year, cover)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                artist = COALESCE(excluded.artist, artist),
+                kind = excluded.kind,
+                year = COALESCE(excluded.year, year),
+                cover = COALESCE(excluded.cover, cover)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(&self.artist)
+        .bind(&self.kind)
+        .bind(self.year)
+        .bind(&self.cover)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_music (query_id, music_id, music_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(music_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_music_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for PatentRow {
+    const LIST_TABLE: &'static str = "query_patents";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (patent_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO patents (url, number, title, assignee, inventor, filing_date, snippet, pdf)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                assignee = COALESCE(excluded.assignee, assignee),
+                inventor = COALESCE(excluded.inventor, inventor),
+                filing_date = COALESCE(excluded.filing_date, filing_date),
+                snippet = COALESCE(excluded.snippet, snippet),
+                pdf = COALESCE(excluded.pdf, pdf)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.number)
+        .bind(&self.title)
+        .bind(&self.assignee)
+        .bind(&self.inventor)
+        .bind(&self.filing_date)
+        .bind(&self.snippet)
+        .bind(&self.pdf)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_patents (query_id, patent_id, patent_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(patent_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_patents_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for AppRow {
+    const LIST_TABLE: &'static str = "query_apps";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (app_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO apps (url, name, store, app_id, summary, icon, install_url)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET name = excluded.name,
+                summary = COALESCE(excluded.summary, summary),
+                icon = COALESCE(excluded.icon, icon),
+                install_url = COALESCE(excluded.install_url, install_url)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.name)
+        .bind(&self.store)
+        .bind(&self.app_id)
+        .bind(&self.summary)
+        .bind(&self.icon)
+        .bind(&self.install_url)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_apps (query_id, app_id, app_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(app_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_apps_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for PackageRow {
+    const LIST_TABLE: &'static str = "query_packages";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (package_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO packages (url, name, registry, version, description, downloads,
+                repository)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET name = excluded.name,
+                version = COALESCE(excluded.version, version),
+                description = COALESCE(excluded.description, description),
+                downloads = COALESCE(excluded.downloads, downloads),
+                repository = COALESCE(excluded.repository, repository)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.name)
+        .bind(&self.registry)
+        .bind(&self.version)
+        .bind(&self.description)
+        .bind(self.downloads)
+        .bind(&self.repository)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_packages (query_id, package_id, package_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(package_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_packages_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for PodcastRow {
+    const LIST_TABLE: &'static str = "query_podcasts";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (podcast_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO podcasts (url, title, feed_url, author, artwork, description)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                feed_url = excluded.feed_url,
+                author = COALESCE(excluded.author, author),
+                artwork = COALESCE(excluded.artwork, artwork),
+                description = COALESCE(excluded.description, description)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(&self.feed_url)
+        .bind(&self.author)
+        .bind(&self.artwork)
+        .bind(&self.description)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_podcasts (query_id, podcast_id, podcast_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(podcast_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_podcasts_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for BookRow {
+    const LIST_TABLE: &'static str = "query_books";
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (book_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO books (url, title, authors, year, cover, isbn)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                authors = excluded.authors,
+                year = COALESCE(excluded.year, year),
+                cover = COALESCE(excluded.cover, cover),
+                isbn = COALESCE(excluded.isbn, isbn)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.url)
+        .bind(&self.title)
+        .bind(sqlx::types::Json(&self.authors))
+        .bind(self.year)
+        .bind(&self.cover)
+        .bind(&self.isbn)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_books (query_id, book_id, book_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(book_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_books_for_query(pool, query_id).await
+    }
+}
+
+#[async_trait]
+impl CachedRow for PlaceRow {
+    const LIST_TABLE: &'static str = "query_places";
+    const REPLACE: bool = true;
+
+    async fn insert(
+        &self,
+        conn: &mut SqliteConnection,
+        query_id: i64,
+        index: i64,
+    ) -> Result<(), sqlx::Error> {
+        let (place_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO places (osm_url, name, lat, lon, address, kind)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (osm_url) DO UPDATE SET name = excluded.name,
+                lat = excluded.lat,
+                lon = excluded.lon,
+                address = COALESCE(excluded.address, address),
+                kind = COALESCE(excluded.kind, kind)
+            RETURNING id
+            "#,
+        )
+        .bind(&self.osm_url)
+        .bind(&self.name)
+        .bind(self.lat)
+        .bind(self.lon)
+        .bind(&self.address)
+        .bind(&self.kind)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_places (query_id, place_id, place_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(place_id)
+        .bind(index)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn for_query(pool: &SqlitePool, query_id: i64) -> Result<Vec<Self>, sqlx::Error> {
+        get_places_for_query(pool, query_id).await
+    }
+}
+
+pub async fn get_engine_id(pool: &SqlitePool, engine: &str) -> Result<i64, sqlx::Error> {
+    if let Some(id) = find_engine_id(pool, engine).await? {
+        return Ok(id);
+    }
+
+    let id = sqlx::query("INSERT INTO engines (name) VALUES (?)")
+        .bind(engine)
+        .execute(pool)
+        .await?
+        .last_insert_rowid();
+
+    Ok(id)
+}
+
+/// Id of `engine`, `None` if it was never stored. Unlike [`get_engine_id`]
+/// it writes nothing.
+pub async fn find_engine_id(pool: &SqlitePool, engine: &str) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar("SELECT id FROM engines WHERE name = ?")
+        .bind(engine)
+        .fetch_optional(pool)
+        .await
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct QueryRow {
+    pub id: i64,
+    pub query: String,
+    pub engine_id: i64,
+    pub fetched_at: chrono::NaiveDateTime,
+    pub total_results: Option<i64>,
+    pub has_next_page: bool,
+    /// Engine specific token for fetching the page after the cached results
+    pub next_page: Option<String>,
+}
+
+pub async fn get_query(
+    pool: &SqlitePool,
+    query: &str,
+    engine_id: i64,
+) -> Result<Option<QueryRow>, sqlx::Error> {
+    let row: Option<QueryRow> = sqlx::query_as(
+        r#"
+        SELECT id, query, engine_id, fetched_at, total_results, has_next_page, next_page
+        FROM queries
+        WHERE query = ? AND engine_id = ?
+        "#,
+    )
+    .bind(query)
+    .bind(engine_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+/// Same as [`get_query`], but a query fetched more than `max_age` ago counts
+/// as a miss. `None` keeps queries forever. With `touch` a hit marks the
+/// query as recently used and an expired one is deleted with everything
+/// cached for it, private searches pass `false` to leave no trace.
+pub async fn get_unexpired_query(
+    pool: &SqlitePool,
+    query: &str,
+    engine_id: i64,
+    max_age: Option<std::time::Duration>,
+    touch: bool,
+) -> Result<Option<QueryRow>, sqlx::Error> {
+    let Some(row) = get_query(pool, query, engine_id).await? else {
+        return Ok(None);
+    };
+    let max_age = max_age.and_then(|age| chrono::Duration::from_std(age).ok());
+
+    if max_age.is_some_and(|max_age| chrono::Utc::now().naive_utc() - row.fetched_at > max_age) {
+        if touch {
+            delete_query(pool, row.id).await?;
+        }
+        return Ok(None);
+    }
+    if touch {
+        touch_query(pool, row.id).await?;
+    }
+    Ok(Some(row))
+}
+
+async fn touch_query(pool: &SqlitePool, query_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE queries SET last_used = ? WHERE id = ?")
+        .bind(chrono::Utc::now().naive_utc())
+        .bind(query_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Applies [`max_cached_queries`](crate::config::Config::max_cached_queries).
+/// Called after storing each fetched page, not on every insert.
+pub(crate) async fn evict_past_max(backend: &dyn CacheBackend) -> Result<(), CacheError> {
+    if let Some(max) = crate::config::get().max_cached_queries {
+        backend.evict(max).await?;
+    }
+    Ok(())
+}
+
+/// Removes the least recently used queries past the `max` most recent ones,
+/// and the items only they listed. Returns how many queries were removed.
+pub async fn evict_queries(pool: &SqlitePool, max: usize) -> Result<u64, sqlx::Error> {
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM queries
+        WHERE id IN (
+            SELECT id
+            FROM queries
+            ORDER BY COALESCE(last_used, fetched_at) DESC, id DESC
+            LIMIT -1 OFFSET ?
+        )
+        "#,
+    )
+    .bind(max as i64)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    if removed > 0 {
+        delete_orphans(pool).await?;
+    }
+    Ok(removed)
+}
+
+/// Removes a cached query, its result lists go with it.
+pub async fn delete_query(pool: &SqlitePool, query_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM queries WHERE id = ?")
+        .bind(query_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Tables of cached items with the table listing them per query and its id
+/// column. Items are shared between queries, so they outlive a deleted query
+/// until [`delete_orphans`] finds nothing listing them anymore.
+const ITEM_TABLES: [(&str, &str, &str); 17] = [
+    ("results", "query_results", "result_id"),
+    ("images", "query_images", "image_id"),
+    ("news", "query_news", "news_id"),
+    ("videos", "query_videos", "video_id"),
+    ("places", "query_places", "place_id"),
+    ("products", "query_products", "product_id"),
+    ("papers", "query_papers", "paper_id"),
+    ("files", "query_files", "file_id"),
+    ("social", "query_social", "social_id"),
+    ("recipes", "query_recipes", "recipe_id"),
+    ("definitions", "query_definitions", "definition_id"),
+    ("packages", "query_packages", "package_id"),
+    ("apps", "query_apps", "app_id"),
+    ("patents", "query_patents", "patent_id"),
+    ("music", "query_music", "music_id"),
+    ("podcasts", "query_podcasts", "podcast_id"),
+    ("books", "query_books", "book_id"),
+];
+
+/// Removes cached items no query lists anymore, returns how many.
+pub async fn delete_orphans(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut removed = 0;
+    for (table, junction, column) in ITEM_TABLES {
+        removed += sqlx::query(&format!(
+            "DELETE FROM {table} WHERE id NOT IN (SELECT {column} FROM {junction})"
+        ))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+    tx.commit().await?;
+    Ok(removed)
+}
+
+/// Removes every cached search for `query` from every engine, the web search
+/// stored under it and the other verticals stored under `query` and a suffix.
+/// `query` is the cache key, verticals keep their suffix out of the hash so
+/// hashed keys match them too. Returns how many queries were removed.
+pub async fn purge_query(pool: &SqlitePool, query: &str) -> Result<u64, sqlx::Error> {
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM queries
+        WHERE query = ? OR query LIKE ? ESCAPE '\'
+        "#,
+    )
+    .bind(query)
+    .bind(format!("{}\u{1f}%", escape_like(query)))
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    delete_orphans(pool).await?;
+    Ok(removed)
+}
+
+/// Removes everything `engine` has cached, from every edition or instance of
+/// it, returns how many queries.
+pub async fn purge_engine(pool: &SqlitePool, engine: &str) -> Result<u64, sqlx::Error> {
+    let removed = sqlx::query(
+        r#"
+        DELETE FROM queries
+        WHERE engine_id IN (
+            SELECT id FROM engines WHERE name = ?1 OR substr(name, 1, length(?1) + 1) = ?1 || ':'
+        )
+        "#,
+    )
+    .bind(engine)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    delete_orphans(pool).await?;
+    Ok(removed)
+}
+
+/// Removes queries fetched before `before`, returns how many.
+pub async fn purge_older_than(
+    pool: &SqlitePool,
+    before: chrono::NaiveDateTime,
+) -> Result<u64, sqlx::Error> {
+    let removed = sqlx::query("DELETE FROM queries WHERE fetched_at < ?")
+        .bind(before)
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+    delete_orphans(pool).await?;
+    Ok(removed)
+}
+
+/// Empties the cache of searches and page screenshots, returns how many
+/// queries were removed. Result rules, budgets and alerts are kept.
+pub async fn purge_all(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let removed = sqlx::query("DELETE FROM queries")
+        .execute(pool)
+        .await?
+        .rows_affected();
+    sqlx::query("DELETE FROM screenshots").execute(pool).await?;
+
+    delete_orphans(pool).await?;
+    Ok(removed)
+}
+
+pub async fn get_cache_stats(pool: &SqlitePool) -> Result<CacheStats, sqlx::Error> {
+    let (queries, results, images): (i64, i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM queries),
+            (SELECT COUNT(*) FROM results),
+            (SELECT COUNT(*) FROM images)
+        "#,
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(CacheStats {
+        queries: queries as u64,
+        results: results as u64,
+        images: images as u64,
+    })
+}
+
+pub async fn insert_query(
+    executor: impl SqliteExecutor<'_>,
+    query: &str,
+    engine_id: i64,
+    fetched_at: chrono::NaiveDateTime,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        r#"
+        INSERT INTO queries (query, engine_id, fetched_at)
+        VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(query)
+    .bind(engine_id)
+    .bind(fetched_at)
+    .execute(executor)
+    .await?
+    .last_insert_rowid();
+
+    Ok(id)
+}
+
+/// Stores the result page metadata of the latest fetch for a query.
+pub async fn set_query_page_meta(
+    pool: &SqlitePool,
+    query_id: i64,
+    total_results: Option<i64>,
+    has_next_page: bool,
+    next_page: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE queries SET total_results = ?, has_next_page = ?, next_page = ? WHERE id = ?",
+    )
+    .bind(total_results)
+    .bind(has_next_page)
+    .bind(next_page)
+    .bind(query_id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Marks a query as fetched at `fetched_at`, after its results were replaced.
+pub async fn set_query_fetched_at(
+    pool: &SqlitePool,
+    query_id: i64,
+    fetched_at: chrono::NaiveDateTime,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE queries SET fetched_at = ? WHERE id = ?")
+        .bind(fetched_at)
+        .bind(query_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct ImagesRow {
+    pub url: String,
+    pub title: String,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub thumbnail: Option<String>,
+    /// Page the image was found on
+    pub source_url: Option<String>,
+    /// License as shown by the engine, e.g. "CC BY-SA 4.0"
+    pub license: Option<String>,
+    /// Author or credit line the license asks for
+    pub attribution: Option<String>,
+    /// Placeholder computed from the thumbnail after caching
+    pub blurhash: Option<String>,
+    /// 64 bit perceptual hash of the thumbnail, stored as its bit pattern
+    pub phash: Option<i64>,
+}
+
+pub async fn get_images_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<ImagesRow>, sqlx::Error> {
+    let rows: Vec<ImagesRow> = sqlx::query_as(
+        r#"
+        SELECT i.url, i.title, i.width, i.height, i.thumbnail, i.source_url, i.license,
+            i.attribution, i.blurhash, i.phash
+        FROM images i
+        INNER JOIN query_images ir ON i.id = ir.image_id
+        WHERE ir.query_id = ?
+        ORDER BY ir.image_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn insert_image(
+    executor: impl SqliteExecutor<'_>,
+    title: &str,
+    url: &str,
+) -> Result<i64, sqlx::Error> {
+    // a no-op update, so the id is returned for an image already stored
+    let (id,): (i64,) = sqlx::query_as(
+        r#"
+        INSERT INTO images (url, title) VALUES (?, ?)
+        ON CONFLICT (url) DO UPDATE SET url = excluded.url
+        RETURNING id
+        "#,
+    )
+    .bind(url)
+    .bind(title)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(id)
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct ResultRow {
+    pub url: String,
+    pub title: String,
+    pub description: String,
+    /// As shown by the engine, often relative e.g. "2 days ago"
+    pub published_at: Option<String>,
+    /// Language of the page as reported by the engine, e.g. "en"
+    pub language: Option<String>,
+    /// Further excerpts of the page besides the description
+    #[sqlx(json)]
+    pub extra_snippets: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct NewsRow {
+    pub url: String,
+    pub title: String,
+    pub source: String,
+    /// As shown by the engine, often relative e.g. "3 hours ago"
+    pub published_at: Option<String>,
+}
+
+pub async fn get_news_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<NewsRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT n.url, n.title, n.source, n.published_at
+        FROM news n
+        INNER JOIN query_news qn ON n.id = qn.news_id
+        WHERE qn.query_id = ?
+        ORDER BY qn.news_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct VideoRow {
+    pub url: String,
+    pub title: String,
+    pub thumbnail: Option<String>,
+    pub duration: Option<String>,
+    pub channel: Option<String>,
+}
+
+pub async fn get_videos_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<VideoRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT v.url, v.title, v.thumbnail, v.duration, v.channel
+        FROM videos v
+        INNER JOIN query_videos qv ON v.id = qv.video_id
+        WHERE qv.query_id = ?
+        ORDER BY qv.video_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct PlaceRow {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+    /// Full address as written by the geocoder
+    pub address: Option<String>,
+    /// The place's page on openstreetmap.org
+    pub osm_url: String,
+    /// e.g. "city", "restaurant" or "museum"
+    pub kind: Option<String>,
+}
+
+pub async fn get_places_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<PlaceRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT p.name, p.lat, p.lon, p.address, p.osm_url, p.kind
+        FROM places p
+        INNER JOIN query_places qp ON p.id = qp.place_id
+        WHERE qp.query_id = ?
+        ORDER BY qp.place_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct ProductRow {
+    pub url: String,
+    pub title: String,
+    /// Decimal as written by the engine, e.g. "12.50"
+    pub price: Option<String>,
+    /// ISO 4217 code of `price`
+    pub currency: Option<String>,
+    /// Shop or seller offering the product
+    pub merchant: Option<String>,
+    pub image: Option<String>,
+}
+
+pub async fn get_products_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<ProductRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT p.url, p.title, p.price, p.currency, p.merchant, p.image
+        FROM products p
+        INNER JOIN query_products qp ON p.id = qp.product_id
+        WHERE qp.query_id = ?
+        ORDER BY qp.product_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct PaperRow {
+    /// The paper's landing page, e.g. its arXiv abstract page
+    pub url: String,
+    pub title: String,
+    #[sqlx(json)]
+    pub authors: Vec<String>,
+    /// Year of publication
+    pub year: Option<i32>,
+    /// Bare DOI, e.g. "10.1145/3453483.3454035"
+    pub doi: Option<String>,
+    /// Plain text, with any markup stripped
+    pub abstract_text: Option<String>,
+    /// What sort of work it is when the source says, e.g. "preprint" or
+    /// "dataset"
+    pub kind: Option<String>,
+}
+
+pub async fn get_papers_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<PaperRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT p.url, p.title, p.authors, p.year, p.doi, p.abstract_text, p.kind
+        FROM papers p
+        INNER JOIN query_papers qp ON p.id = qp.paper_id
+        WHERE qp.query_id = ?
+        ORDER BY qp.paper_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct FileRow {
+    /// The torrent's page on its tracker, else its download url
+    pub url: String,
+    pub title: String,
+    pub magnet: Option<String>,
+    /// The .torrent file, for trackers that don't give out magnet links
+    pub download_url: Option<String>,
+    /// In bytes
+    pub size: Option<i64>,
+    pub seeders: Option<u32>,
+    pub leechers: Option<u32>,
+    pub published_at: Option<String>,
+}
+
+pub async fn get_files_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<FileRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT f.url, f.title, f.magnet, f.download_url, f.size, f.seeders, f.leechers,
+            f.published_at
+        FROM files f
+        INNER JOIN query_files qf ON f.id = qf.file_id
+        WHERE qf.query_id = ?
+        ORDER BY qf.file_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct SocialRow {
+    /// The post or community on its home instance
+    pub url: String,
+    /// `post` or `community`
+    pub kind: String,
+    /// Mastodon posts have no title
+    pub title: Option<String>,
+    /// Plain text of the post, or the community's description
+    pub content: Option<String>,
+    /// e.g. `@ferris@lemmy.ml`
+    pub author: Option<String>,
+    /// The Lemmy community a post is in, e.g. `!rust@lemmy.ml`
+    pub community: Option<String>,
+    /// Votes on Lemmy, favourites and boosts on Mastodon
+    pub score: Option<i64>,
+    pub published_at: Option<String>,
+}
+
+pub async fn get_social_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<SocialRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT s.url, s.kind, s.title, s.content, s.author, s.community, s.score, s.published_at
+        FROM social s
+        INNER JOIN query_social qs ON s.id = qs.social_id
+        WHERE qs.query_id = ?
+        ORDER BY qs.social_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct RecipeRow {
+    /// The page the recipe was read from
+    pub url: String,
+    pub title: String,
+    pub image: Option<String>,
+    #[sqlx(json)]
+    pub ingredients: Vec<String>,
+    /// Times in minutes
+    pub prep_time: Option<u32>,
+    pub cook_time: Option<u32>,
+    pub total_time: Option<u32>,
+    /// Servings or amount, as the page words it, e.g. "4 servings"
+    pub yields: Option<String>,
+    pub rating: Option<f64>,
+    pub rating_count: Option<u32>,
+}
+
+pub async fn get_recipes_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<RecipeRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT r.url, r.title, r.image, r.ingredients, r.prep_time, r.cook_time,
+            r.total_time, r.yields, r.rating, r.rating_count
+        FROM recipes r
+        INNER JOIN query_recipes qr ON r.id = qr.recipe_id
+        WHERE qr.query_id = ?
+        ORDER BY qr.recipe_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// One sense of a word.
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct DefinitionRow {
+    pub word: String,
+    /// e.g. "noun" or "verb"
+    pub part_of_speech: String,
+    pub definition: String,
+    /// Sentences using the word in this sense
+    #[sqlx(json)]
+    pub examples: Vec<String>,
+    /// Pronunciation in IPA, e.g. "/ˌsɛɹ.ənˈdɪp.ɪ.ti/"
+    pub phonetic: Option<String>,
+    /// The word's page on the dictionary's site
+    pub source_url: Option<String>,
+}
+
+pub async fn get_definitions_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<DefinitionRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT d.word, d.part_of_speech, d.definition, d.examples, d.phonetic, d.source_url
+        FROM definitions d
+        INNER JOIN query_definitions qd ON d.id = qd.definition_id
+        WHERE qd.query_id = ?
+        ORDER BY qd.definition_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// The price of a stock or crypto currency at one point in time.
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct QuoteRow {
+    /// e.g. `AAPL`, or `BTC-USD` for crypto pairs
+    pub symbol: String,
+    /// e.g. "Apple Inc." or "Bitcoin"
+    pub name: Option<String>,
+    pub price: f64,
+    /// Against the previous close for stocks, over 24 hours for crypto
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    /// ISO 4217 code of `price`
+    pub currency: Option<String>,
+    /// When the source last traded at `price`, RFC 3339
+    pub market_time: Option<String>,
+}
+
+pub async fn get_quote_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Option<QuoteRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT symbol, name, price, change, change_percent, currency, market_time
+        FROM quotes
+        WHERE query_id = ?
+        "#,
+    )
+    .bind(query_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_suggestions_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT suggestion
+        FROM query_suggestions
+        WHERE query_id = ?
+        ORDER BY suggestion_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// The lead of a Wikipedia article, from its REST summary.
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct WikiSummaryRow {
+    pub title: String,
+    /// Short description from Wikidata, e.g. "General-purpose programming
+    /// language"
+    pub description: Option<String>,
+    /// First paragraph of the article, as plain text
+    pub extract: String,
+    /// Url of the article's lead image, scaled down
+    pub thumbnail: Option<String>,
+    /// The article itself
+    pub url: String,
+}
+
+pub async fn get_wiki_summary_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Option<WikiSummaryRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT title, description, extract, thumbnail, url
+        FROM wiki_summaries
+        WHERE query_id = ?
+        "#,
+    )
+    .bind(query_id)
+    .fetch_optional(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct MusicRow {
+    /// The release's or artist's page
+    pub url: String,
+    pub title: String,
+    /// Credited artist, `None` when the row is the artist itself
+    pub artist: Option<String>,
+    /// e.g. "album", "single", "track" or "artist"
+    pub kind: String,
+    /// Year of first release
+    pub year: Option<i32>,
+    /// Url of the cover art, or the artist's picture
+    pub cover: Option<String>,
+}
+
+pub async fn get_music_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<MusicRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT m.url, m.title, m.artist, m.kind, m.year, m.cover
+        FROM music m
+        INNER JOIN query_music qm ON m.id = qm.music_id
+        WHERE qm.query_id = ?
+        ORDER BY qm.music_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct PatentRow {
+    /// The patent's page, e.g. on Google Patents
+    pub url: String,
+    /// Publication number with its country code and kind, e.g. "US9123456B2"
+    pub number: String,
+    pub title: String,
+    /// Current owner, usually a company
+    pub assignee: Option<String>,
+    /// First named inventor
+    pub inventor: Option<String>,
+    /// `YYYY-MM-DD`
+    pub filing_date: Option<String>,
+    /// Plain text excerpt matching the query
+    pub snippet: Option<String>,
+    /// Url of the full patent document
+    pub pdf: Option<String>,
+}
+
+pub async fn get_patents_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<PatentRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT p.url, p.number, p.title, p.assignee, p.inventor, p.filing_date, p.snippet, p.pdf
+        FROM patents p
+        INNER JOIN query_patents qp ON p.id = qp.patent_id
+        WHERE qp.query_id = ?
+        ORDER BY qp.patent_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct AppRow {
+    /// The app's page in its catalog
+    pub url: String,
+    pub name: String,
+    /// e.g. "F-Droid" or "Flathub"
+    pub store: String,
+    /// Package name or Flatpak id, e.g. `org.mozilla.firefox`
+    pub app_id: String,
+    pub summary: Option<String>,
+    /// Url of the app's icon
+    pub icon: Option<String>,
+    /// Link that hands the app to an installer, e.g. a `.flatpakref`
+    pub install_url: Option<String>,
+}
+
+pub async fn get_apps_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<AppRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT a.url, a.name, a.store, a.app_id, a.summary, a.icon, a.install_url
+        FROM apps a
+        INNER JOIN query_apps qa ON a.id = qa.app_id
+        WHERE qa.query_id = ?
+        ORDER BY qa.app_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct PackageRow {
+    /// The package's page on its registry
+    pub url: String,
+    pub name: String,
+    /// e.g. "crates.io", "npm" or "PyPI"
+    pub registry: String,
+    /// Latest release
+    pub version: Option<String>,
+    pub description: Option<String>,
+    /// All time downloads on crates.io, last month's on npm
+    pub downloads: Option<i64>,
+    /// Url of the source code
+    pub repository: Option<String>,
+}
+
+pub async fn get_packages_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<PackageRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT p.url, p.name, p.registry, p.version, p.description, p.downloads, p.repository
+        FROM packages p
+        INNER JOIN query_packages qp ON p.id = qp.package_id
+        WHERE qp.query_id = ?
+        ORDER BY qp.package_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct PodcastRow {
+    /// The show's page, e.g. on Apple Podcasts, else its feed
+    pub url: String,
+    pub title: String,
+    /// The RSS feed podcast apps subscribe to
+    pub feed_url: String,
+    pub author: Option<String>,
+    /// Url of the cover art
+    pub artwork: Option<String>,
+    pub description: Option<String>,
+}
+
+pub async fn get_podcasts_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<PodcastRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT p.url, p.title, p.feed_url, p.author, p.artwork, p.description
+        FROM podcasts p
+        INNER JOIN query_podcasts qp ON p.id = qp.podcast_id
+        WHERE qp.query_id = ?
+        ORDER BY qp.podcast_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct BookRow {
+    /// The book's page, e.g. its Open Library work
+    pub url: String,
+    pub title: String,
+    #[sqlx(json)]
+    pub authors: Vec<String>,
+    /// Year of first publication
+    pub year: Option<i32>,
+    /// Url of the cover image
+    pub cover: Option<String>,
+    /// ISBN-13 if the book has one, else ISBN-10
+    pub isbn: Option<String>,
+}
+
+pub async fn get_books_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<BookRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT b.url, b.title, b.authors, b.year, b.cover, b.isbn
+        FROM books b
+        INNER JOIN query_books qb ON b.id = qb.book_id
+        WHERE qb.query_id = ?
+        ORDER BY qb.book_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_results_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<ResultRow>, sqlx::Error> {
+    let rows: Vec<ResultRow> = sqlx::query_as(
+        r#"
+        SELECT r.url, r.title, r.description, r.published_at, r.language, r.extra_snippets
+        FROM results r
+        INNER JOIN query_results qr ON r.id = qr.result_id
+        WHERE qr.query_id = ?
+        ORDER BY qr.result_index ASC
+        "#,
+    )
+    .bind(query_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn insert_result(
+    executor: impl SqliteExecutor<'_>,
+    title: &str,
+    url: &str,
+    description: &str,
+) -> Result<i64, sqlx::Error> {
+    // a no-op update, so the id is returned for a result already stored
+    let (id,): (i64,) = sqlx::query_as(
+        r#"
+        INSERT INTO results (url, title, description) VALUES (?, ?, ?)
+        ON CONFLICT (url) DO UPDATE SET url = excluded.url
+        RETURNING id
+        "#,
+    )
+    .bind(url)
+    .bind(title)
+    .bind(description)
+    .fetch_one(executor)
+    .await?;
+
+    Ok(id)
+}
+
+pub async fn insert_query_result(
+    executor: impl SqliteExecutor<'_>,
+    query_id: i64,
+    result_id: i64,
+    result_index: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO query_results (query_id, result_id, result_index)
+        VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(query_id)
+    .bind(result_id)
+    .bind(result_index)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Stores the latest metadata seen for a result, keeping older values the
+/// engine didn't provide this time.
+pub async fn set_result_meta(
+    executor: impl SqliteExecutor<'_>,
+    result_id: i64,
+    result: &ResultRow,
+) -> Result<(), sqlx::Error> {
+    let extra_snippets =
+        (!result.extra_snippets.is_empty()).then_some(sqlx::types::Json(&result.extra_snippets));
+    sqlx::query(
+        r#"
+        UPDATE results SET
+            published_at = COALESCE(?, published_at),
+            language = COALESCE(?, language),
+            extra_snippets = COALESCE(?, extra_snippets)
+        WHERE id = ?
+        "#,
+    )
+    .bind(&result.published_at)
+    .bind(&result.language)
+    .bind(extra_snippets)
+    .bind(result_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Stores the latest metadata seen for an image, keeping older values the
+/// engine didn't provide this time.
+pub async fn set_image_meta(
+    executor: impl SqliteExecutor<'_>,
+    image_id: i64,
+    image: &ImagesRow,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE images SET
+            width = COALESCE(?, width),
+            height = COALESCE(?, height),
+            thumbnail = COALESCE(?, thumbnail),
+            source_url = COALESCE(?, source_url),
+            license = COALESCE(?, license),
+            attribution = COALESCE(?, attribution)
+        WHERE id = ?
+        "#,
+    )
+    .bind(image.width)
+    .bind(image.height)
+    .bind(&image.thumbnail)
+    .bind(&image.source_url)
+    .bind(&image.license)
+    .bind(&image.attribution)
+    .bind(image_id)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(any(test, feature = "blurhash"))]
+pub async fn set_image_blurhash(
+    pool: &SqlitePool,
+    url: &str,
+    blurhash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE images SET blurhash = ? WHERE url = ?")
+        .bind(blurhash)
+        .bind(url)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(any(test, feature = "phash"))]
+pub async fn set_image_phash(pool: &SqlitePool, url: &str, phash: u64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE images SET phash = ? WHERE url = ?")
+        .bind(phash as i64)
+        .bind(url)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[derive(sqlx::FromRow)]
+pub struct QueryImageRow {
+    pub query_id: i64,
+    pub image_id: i64,
+}
+
+pub async fn insert_query_image(
+    executor: impl SqliteExecutor<'_>,
+    query_id: i64,
+    image_id: i64,
+    image_index: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO query_images (query_id, image_id, image_index)
+        VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(query_id)
+    .bind(image_id)
+    .bind(image_index)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub async fn get_image_for_query(
+    pool: &SqlitePool,
+    query_id: i64,
+) -> Result<Vec<QueryImageRow>, sqlx::Error> {
+    sqlx::query_as("SELECT query_id, image_id FROM query_images WHERE query_id = ?")
+        .bind(query_id)
+        .fetch_all(pool)
+        .await
+}
+
+/// A user rule pinning a result to the top or hiding it.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
+pub struct ResultRuleRow {
+    pub id: i64,
+    /// A url (`https://...`) or a domain, which covers its subdomains too
+    pub target: String,
+    /// Pinned to the top, hidden when false
+    pub pinned: bool,
+    /// Only applies to searches containing this text, `None` for every search
+    pub query: Option<String>,
+}
+
+pub async fn add_result_rule(
+    pool: &SqlitePool,
+    target: &str,
+    pinned: bool,
+    query: Option<&str>,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query("INSERT INTO result_rules (target, pinned, query) VALUES (?, ?, ?)")
+        .bind(target)
+        .bind(pinned)
+        .bind(query)
+        .execute(pool)
+        .await?
+        .last_insert_rowid();
 
     Ok(id)
 }
 
-#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
-pub struct ImagesRow {
-    pub url: String,
-    pub title: String,
+pub async fn get_result_rules(pool: &SqlitePool) -> Result<Vec<ResultRuleRow>, sqlx::Error> {
+    sqlx::query_as("SELECT id, target, pinned, query FROM result_rules ORDER BY id ASC")
+        .fetch_all(pool)
+        .await
+}
+
+pub async fn delete_result_rule(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM result_rules WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(any(test, feature = "screenshot"))]
+pub async fn set_screenshot(pool: &SqlitePool, url: &str, jpeg: &[u8]) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT OR REPLACE INTO screenshots (url, image) VALUES (?, ?)")
+        .bind(url)
+        .bind(jpeg)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(any(test, feature = "screenshot"))]
+pub async fn get_screenshot(pool: &SqlitePool, url: &str) -> Result<Option<Vec<u8>>, sqlx::Error> {
+    sqlx::query_scalar("SELECT image FROM screenshots WHERE url = ?")
+        .bind(url)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn record_engine_request(
+    pool: &SqlitePool,
+    engine: &str,
+    requested_at: chrono::NaiveDateTime,
+    latency_ms: i64,
+    outcome: &str,
+) -> Result<(), sqlx::Error> {
+    let engine_id = get_engine_id(pool, engine).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO engine_requests (engine_id, requested_at, latency_ms, outcome)
+        VALUES (?, ?, ?, ?)
+        "#,
+    )
+    .bind(engine_id)
+    .bind(requested_at)
+    .bind(latency_ms)
+    .bind(outcome)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn prune_engine_requests(
+    pool: &SqlitePool,
+    before: chrono::NaiveDateTime,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM engine_requests WHERE requested_at < ?")
+        .bind(before)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Totals of an engine's requests over a time window.
+#[derive(Debug, Clone, Default, sqlx::FromRow, Serialize)]
+pub struct EngineStatsRow {
+    pub requests: i64,
+    pub successes: i64,
+    /// Mean latency of the successful requests
+    pub avg_latency_ms: Option<f64>,
+}
+
+pub async fn get_engine_stats(
+    pool: &SqlitePool,
+    engine: &str,
+    since: chrono::NaiveDateTime,
+) -> Result<EngineStatsRow, sqlx::Error> {
+    let engine_id = get_engine_id(pool, engine).await?;
+    sqlx::query_as(
+        r#"
+        SELECT
+            COUNT(*) AS requests,
+            COALESCE(SUM(outcome = 'ok'), 0) AS successes,
+            AVG(CASE WHEN outcome = 'ok' THEN latency_ms END) AS avg_latency_ms
+        FROM engine_requests
+        WHERE engine_id = ? AND requested_at >= ?
+        "#,
+    )
+    .bind(engine_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await
+}
+
+/// Counts one request by `engine` in the clock hour starting at `hour`,
+/// unless it already made `hourly` requests in that hour or `daily` in the 24
+/// hours ending with it. Returns whether the request was counted.
+pub async fn try_add_engine_usage(
+    pool: &SqlitePool,
+    engine: &str,
+    hour: chrono::NaiveDateTime,
+    hourly: Option<u32>,
+    daily: Option<u32>,
+) -> Result<bool, sqlx::Error> {
+    let day_start = hour - chrono::Duration::hours(23);
+    let mut tx = pool.begin().await?;
+
+    // writing first takes the write lock before the usage is read
+    sqlx::query("INSERT INTO engines (name) VALUES (?) ON CONFLICT (name) DO NOTHING")
+        .bind(engine)
+        .execute(&mut *tx)
+        .await?;
+    let counted = sqlx::query(
+        r#"
+        INSERT INTO engine_usage (engine_id, hour, requests)
+        SELECT e.id, ?, 1 FROM engines e
+        WHERE e.name = ?
+            AND (? IS NULL OR (
+                SELECT COALESCE(SUM(requests), 0) FROM engine_usage
+                WHERE engine_id = e.id AND hour >= ?
+            ) < ?)
+            AND (? IS NULL OR (
+                SELECT COALESCE(SUM(requests), 0) FROM engine_usage
+                WHERE engine_id = e.id AND hour >= ?
+            ) < ?)
+        ON CONFLICT (engine_id, hour) DO UPDATE SET requests = requests + 1
+        "#,
+    )
+    .bind(hour)
+    .bind(engine)
+    .bind(hourly)
+    .bind(hour)
+    .bind(hourly)
+    .bind(daily)
+    .bind(day_start)
+    .bind(daily)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    tx.commit().await?;
+    Ok(counted)
+}
+
+/// Requests by `engine` in the hours starting at or after `since`.
+pub async fn get_engine_usage(
+    pool: &SqlitePool,
+    engine: &str,
+    since: chrono::NaiveDateTime,
+) -> Result<i64, sqlx::Error> {
+    let Some(engine_id) = find_engine_id(pool, engine).await? else {
+        return Ok(0);
+    };
+    sqlx::query_scalar(
+        "SELECT COALESCE(SUM(requests), 0) FROM engine_usage WHERE engine_id = ? AND hour >= ?",
+    )
+    .bind(engine_id)
+    .bind(since)
+    .fetch_one(pool)
+    .await
+}
+
+fn compress(body: &str) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(body.as_bytes())
+        .and_then(|_| encoder.finish())
+        .expect("writing to a Vec can't fail")
+}
+
+fn decompress(body: &[u8]) -> Result<String, std::io::Error> {
+    let mut decompressed = String::new();
+    DeflateDecoder::new(body).read_to_string(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+/// Archives the response a page of results for `query_id` was parsed from.
+/// `page` is the token it was fetched with, `None` for the first page.
+pub async fn archive_response(
+    pool: &SqlitePool,
+    query_id: i64,
+    page: Option<&str>,
+    body: &str,
+    fetched_at: chrono::NaiveDateTime,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO raw_responses (query_id, page, body, fetched_at) VALUES (?, ?, ?, ?)")
+        .bind(query_id)
+        .bind(page)
+        .bind(compress(body))
+        .bind(fetched_at)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Drops archived responses fetched before `before`.
+pub async fn prune_archived_responses(
+    pool: &SqlitePool,
+    before: chrono::NaiveDateTime,
+) -> Result<u64, sqlx::Error> {
+    let res = sqlx::query("DELETE FROM raw_responses WHERE fetched_at < ?")
+        .bind(before)
+        .execute(pool)
+        .await?;
+
+    Ok(res.rows_affected())
 }
 
-pub async fn get_images_for_query(
+#[derive(Debug, Clone)]
+pub struct ArchivedResponseRow {
+    pub query_id: i64,
+    pub page: Option<String>,
+    pub body: String,
+}
+
+/// Archived responses of `engine`, grouped by query in the order they were
+/// fetched.
+pub async fn get_archived_responses(
     pool: &SqlitePool,
-    query_id: i64,
-) -> Result<Vec<ImagesRow>, sqlx::Error> {
-    let rows: Vec<ImagesRow> = sqlx::query_as(
+    engine: &str,
+) -> Result<Vec<ArchivedResponseRow>, sqlx::Error> {
+    let engine_id = get_engine_id(pool, engine).await?;
+    let rows: Vec<(i64, Option<String>, Vec<u8>)> = sqlx::query_as(
         r#"
-        SELECT i.url, i.title
-        FROM images i
-        INNER JOIN query_images ir ON i.id = ir.image_id
-        WHERE ir.query_id = ?
-        ORDER BY ir.image_index ASC
+        SELECT a.query_id, a.page, a.body
+        FROM raw_responses a
+        INNER JOIN queries q ON q.id = a.query_id
+        WHERE q.engine_id = ?
+        ORDER BY a.query_id, a.id
         "#,
     )
-    .bind(query_id)
+    .bind(engine_id)
     .fetch_all(pool)
     .await?;
 
-    Ok(rows)
+    rows.into_iter()
+        .map(|(query_id, page, body)| {
+            Ok(ArchivedResponseRow {
+                query_id,
+                page,
+                body: decompress(&body).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+            })
+        })
+        .collect()
 }
 
-pub async fn insert_image(pool: &SqlitePool, title: &str, url: &str) -> Result<i64, sqlx::Error> {
-    let res = sqlx::query("INSERT OR IGNORE INTO images (url, title) VALUES (?, ?)")
-        .bind(url)
-        .bind(title)
-        .execute(pool)
+/// Replaces the results cached for `query_id`, overwriting the title and
+/// description stored for urls already in the cache.
+pub async fn replace_query_results(
+    pool: &SqlitePool,
+    query_id: i64,
+    entries: &[ResultRow],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM query_results WHERE query_id = ?")
+        .bind(query_id)
+        .execute(&mut *tx)
         .await?;
 
-    if res.rows_affected() == 0 {
-        // Already exists - fetch id
-        let row: (i64,) = sqlx::query_as("SELECT id FROM images WHERE url = ?")
-            .bind(url)
-            .fetch_one(pool)
-            .await?;
-        Ok(row.0)
-    } else {
-        Ok(res.last_insert_rowid())
+    for (i, entry) in entries.iter().enumerate() {
+        let (result_id,): (i64,) = sqlx::query_as(
+            r#"
+            INSERT INTO results (url, title, description, published_at, language, extra_snippets)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT (url) DO UPDATE SET title = excluded.title,
+                description = excluded.description,
+                published_at = excluded.published_at,
+                language = excluded.language,
+                extra_snippets = excluded.extra_snippets
+            RETURNING id
+            "#,
+        )
+        .bind(&entry.url)
+        .bind(&entry.title)
+        .bind(&entry.description)
+        .bind(&entry.published_at)
+        .bind(&entry.language)
+        .bind(sqlx::types::Json(&entry.extra_snippets))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO query_results (query_id, result_id, result_index) VALUES (?, ?, ?)",
+        )
+        .bind(query_id)
+        .bind(result_id)
+        .bind(i as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Runs the current parser of `engine` over its archived responses and
+/// replaces the cached results of every query whose first page is archived.
+/// Queries whose responses no longer parse, or parse to nothing, keep their
+/// results. Returns how many queries were re-parsed.
+pub async fn reparse(pool: &SqlitePool, engine: SearchEngines) -> Result<usize, sqlx::Error> {
+    let archived = get_archived_responses(pool, &engine.id()).await?;
+    let mut reparsed = 0;
+
+    for pages in archived.chunk_by(|a, b| a.query_id == b.query_id) {
+        let query_id = pages[0].query_id;
+        // without the first page the results would start at the wrong place
+        if pages[0].page.is_some() {
+            continue;
+        }
+
+        let mut results = Vec::new();
+        for archived in pages {
+            match engine.parse_raw(&archived.body) {
+                Ok(mut page) => {
+                    rewrite::rewrite_page(&mut page);
+                    results.extend(page.results);
+                }
+                Err(e) => {
+                    eprintln!("Failed to re-parse query {query_id}: {e:?}");
+                    results.clear();
+                    break;
+                }
+            }
+        }
+
+        if !results.is_empty() {
+            replace_query_results(pool, query_id, &results).await?;
+            reparsed += 1;
+        }
     }
+
+    Ok(reparsed)
 }
 
 #[derive(Debug, Clone, sqlx::FromRow, Serialize)]
-pub struct ResultRow {
-    pub url: String,
-    pub title: String,
-    pub description: String,
+pub struct StandingQueryRow {
+    pub id: i64,
+    pub query: String,
+    pub interval_secs: i64,
+    /// `None` until the first run
+    pub last_run: Option<chrono::NaiveDateTime>,
 }
 
-pub async fn get_results_for_query(
+pub async fn add_standing_query(
     pool: &SqlitePool,
-    query_id: i64,
-) -> Result<Vec<ResultRow>, sqlx::Error> {
-    let rows: Vec<ResultRow> = sqlx::query_as(
-        r#"
-        SELECT r.url, r.title, r.description
-        FROM results r
-        INNER JOIN query_results qr ON r.id = qr.result_id
-        WHERE qr.query_id = ?
-        ORDER BY qr.result_index ASC
-        "#,
-    )
-    .bind(query_id)
-    .fetch_all(pool)
-    .await?;
+    query: &str,
+    interval_secs: i64,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query("INSERT INTO standing_queries (query, interval_secs) VALUES (?, ?)")
+        .bind(query)
+        .bind(interval_secs)
+        .execute(pool)
+        .await?
+        .last_insert_rowid();
 
-    Ok(rows)
+    Ok(id)
 }
 
-pub async fn insert_result(
-    pool: &SqlitePool,
-    title: &str,
-    url: &str,
-    description: &str,
-) -> Result<i64, sqlx::Error> {
-    let res =
-        sqlx::query("INSERT OR IGNORE INTO results (url, title, description) VALUES (?, ?, ?)")
-            .bind(url)
-            .bind(title)
-            .bind(description)
-            .execute(pool)
-            .await?;
+pub async fn delete_standing_query(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+    // alert_results rows go with it only when foreign keys are enforced
+    sqlx::query("DELETE FROM alert_results WHERE standing_query_id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    sqlx::query("DELETE FROM standing_queries WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
 
-    if res.rows_affected() == 0 {
-        // Already exists - fetch id
-        let row: (i64,) = sqlx::query_as("SELECT id FROM results WHERE url = ?")
-            .bind(url)
-            .fetch_one(pool)
-            .await?;
-        Ok(row.0)
-    } else {
-        Ok(res.last_insert_rowid())
-    }
+    Ok(())
 }
 
-#[derive(sqlx::FromRow)]
-pub struct QueryResultRow {
-    pub query_id: i64,
-    pub result_id: i64,
+pub async fn get_standing_queries(pool: &SqlitePool) -> Result<Vec<StandingQueryRow>, sqlx::Error> {
+    sqlx::query_as("SELECT id, query, interval_secs, last_run FROM standing_queries ORDER BY id")
+        .fetch_all(pool)
+        .await
 }
 
-pub async fn insert_query_result(
+pub async fn set_standing_query_run(
     pool: &SqlitePool,
-    query_id: i64,
-    result_id: i64,
-    result_index: i64,
+    id: i64,
+    ran_at: chrono::NaiveDateTime,
 ) -> Result<(), sqlx::Error> {
-    sqlx::query(
+    sqlx::query("UPDATE standing_queries SET last_run = ? WHERE id = ?")
+        .bind(ran_at)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Records a result of a standing query, returning whether it wasn't known yet.
+/// Results recorded as `seen` aren't reported as new.
+pub async fn insert_alert_result(
+    pool: &SqlitePool,
+    standing_query_id: i64,
+    url: &str,
+    title: &str,
+    found_at: chrono::NaiveDateTime,
+    seen: bool,
+) -> Result<bool, sqlx::Error> {
+    let res = sqlx::query(
         r#"
-        INSERT OR IGNORE INTO query_results (query_id, result_id, result_index)
-        VALUES (?, ?, ?)
+        INSERT OR IGNORE INTO alert_results (standing_query_id, url, title, found_at, seen)
+        VALUES (?, ?, ?, ?, ?)
         "#,
     )
-    .bind(query_id)
-    .bind(result_id)
-    .bind(result_index)
+    .bind(standing_query_id)
+    .bind(url)
+    .bind(title)
+    .bind(found_at)
+    .bind(seen)
     .execute(pool)
     .await?;
 
-    Ok(())
+    Ok(res.rows_affected() > 0)
 }
 
-pub async fn get_result_for_query(
-    pool: &SqlitePool,
-    query_id: i64,
-) -> Result<Vec<QueryResultRow>, sqlx::Error> {
-    sqlx::query_as("SELECT query_id, result_id FROM query_results WHERE query_id = ?")
-        .bind(query_id)
-        .fetch_all(pool)
-        .await
+/// A result a standing query hadn't returned before.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize)]
+pub struct AlertRow {
+    pub id: i64,
+    pub standing_query_id: i64,
+    pub query: String,
+    pub url: String,
+    pub title: String,
+    pub found_at: chrono::NaiveDateTime,
 }
 
-#[derive(sqlx::FromRow)]
-pub struct QueryImageRow {
-    pub query_id: i64,
-    pub image_id: i64,
+/// Unseen alert results, oldest first. They stay unseen until
+/// [`mark_alert_results_seen`].
+pub async fn get_new_alert_results(pool: &SqlitePool) -> Result<Vec<AlertRow>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        SELECT a.id, a.standing_query_id, s.query, a.url, a.title, a.found_at
+        FROM alert_results a
+        INNER JOIN standing_queries s ON s.id = a.standing_query_id
+        WHERE a.seen = 0
+        ORDER BY a.id ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
 }
 
-pub async fn insert_query_image(
+/// Marks the alert results up to and including `last_id` as seen.
+pub async fn mark_alert_results_seen(pool: &SqlitePool, last_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE alert_results SET seen = 1 WHERE seen = 0 AND id <= ?")
+        .bind(last_id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Escapes `%`, `_` and `\` for a `LIKE ... ESCAPE '\'` pattern.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Cached web queries starting with `prefix`, most searched first. Image
+/// searches are left out, they're stored with a suffix after a \u{1f}.
+pub async fn get_query_suggestions(
     pool: &SqlitePool,
-    query_id: i64,
-    image_id: i64,
-    image_index: i64,
-) -> Result<(), sqlx::Error> {
-    sqlx::query(
+    prefix: &str,
+    limit: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
         r#"
-        INSERT OR IGNORE INTO query_images (query_id, image_id, image_index)
-        VALUES (?, ?, ?)
+        SELECT query
+        FROM queries
+        WHERE query LIKE ? ESCAPE '\' AND instr(query, char(31)) = 0
+        GROUP BY query
+        ORDER BY COUNT(*) DESC, MAX(fetched_at) DESC
+        LIMIT ?
         "#,
     )
-    .bind(query_id)
-    .bind(image_id)
-    .bind(image_index)
-    .execute(pool)
-    .await?;
-
-    Ok(())
+    .bind(format!("{}%", escape_like(prefix)))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
 }
 
-pub async fn get_image_for_query(
+/// Cached result titles containing a word starting with `prefix`.
+pub async fn get_title_suggestions(
     pool: &SqlitePool,
-    query_id: i64,
-) -> Result<Vec<QueryImageRow>, sqlx::Error> {
-    sqlx::query_as("SELECT query_id, image_id FROM query_images WHERE query_id = ?")
-        .bind(query_id)
-        .fetch_all(pool)
-        .await
+    prefix: &str,
+    limit: i64,
+) -> Result<Vec<String>, sqlx::Error> {
+    let prefix = escape_like(prefix);
+    sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT title
+        FROM results
+        WHERE title LIKE ? ESCAPE '\' OR title LIKE ? ESCAPE '\'
+        LIMIT ?
+        "#,
+    )
+    .bind(format!("{prefix}%"))
+    .bind(format!("% {prefix}%"))
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// The most recently fetched distinct web queries.
+pub async fn get_recent_queries(pool: &SqlitePool, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT query
+        FROM queries
+        WHERE instr(query, char(31)) = 0
+        GROUP BY query
+        ORDER BY MAX(fetched_at) DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
 }
 
 #[cfg(test)]
 mod test {
     use crate::cache::{
-        ImagesRow, ResultRow, create_search_cache, get_engine_id, get_image_for_query,
-        get_images_for_query, get_results_for_query, insert_image, insert_query,
-        insert_query_image, upsert_query_with_images, upsert_query_with_results,
+        AppRow, BookRow, CacheBackend, CacheStats, DefinitionRow, FileRow, ImagesRow, MusicRow,
+        NewsRow, PackageRow, PaperRow, PatentRow, PlaceRow, PodcastRow, ProductRow, Purge,
+        QuoteRow, RecipeRow, ResultRow, Rows, SocialRow, SqliteCache, VideoRow, WikiSummaryRow,
+        add_result_rule, add_standing_query, archive_response, create_search_cache, delete_query,
+        delete_result_rule, delete_standing_query, evict_queries, get_archived_responses,
+        get_engine_id, get_engine_stats, get_engine_usage, get_image_for_query,
+        get_images_for_query, get_new_alert_results, get_query, get_query_suggestions,
+        get_recent_queries, get_result_rules, get_results_for_query, get_screenshot,
+        get_standing_queries, get_title_suggestions, get_unexpired_query, insert_alert_result,
+        insert_image, insert_query, insert_query_image, mark_alert_results_seen,
+        prune_archived_responses, prune_engine_requests, purge_all, purge_engine, purge_older_than,
+        purge_query, record_engine_request, reparse, set_image_blurhash, set_image_phash,
+        set_query_fetched_at, set_query_page_meta, set_screenshot, set_standing_query_run,
+        try_add_engine_usage, upsert_query_with_rows,
     };
     use chrono::Utc;
     use sqlx::SqlitePool;
 
-    async fn new_db() -> SqlitePool {
-        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+    async fn new_db() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+
+        create_search_cache(&pool).await.unwrap();
+
+        pool
+    }
+
+    fn sample_results() -> Vec<ResultRow> {
+        vec![
+            ResultRow {
+                url: "https://example.com".into(),
+                title: "Example 1".into(),
+                description: "First description".into(),
+                ..Default::default()
+            },
+            ResultRow {
+                url: "https://super.com".into(),
+                title: "Example 2".into(),
+                description: "Second description".into(),
+                ..Default::default()
+            },
+            ResultRow {
+                url: "https://mega.com".into(),
+                title: "Example 3".into(),
+                description: "Third description".into(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[sqlx::test]
+    async fn smoke_init_db() {
+        let _ = new_db().await;
+    }
+
+    #[sqlx::test]
+    async fn test_upsert_query_with_results() {
+        let pool = new_db().await;
+        let results = sample_results();
+
+        let query = "rust sqlite test";
+        let fetched_at = Utc::now().naive_utc();
+
+        // upsert the query and results
+        let query_id = upsert_query_with_rows(&pool, "Brave", query, &results, fetched_at)
+            .await
+            .expect("Failed to upsert query");
+
+        assert!(query_id > 0);
+
+        // retrieve results for this query
+        let fetched = get_results_for_query(&pool, query_id).await.unwrap();
+        assert_eq!(fetched.len(), results.len());
+
+        for (i, r) in results.iter().enumerate() {
+            assert_eq!(fetched[i].url, r.url);
+            assert_eq!(fetched[i].title, r.title);
+            assert_eq!(fetched[i].description, r.description);
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_get_unexpired_query() {
+        let pool = new_db().await;
+        let engine_id = get_engine_id(&pool, "Brave").await.unwrap();
+        let day = std::time::Duration::from_secs(24 * 60 * 60);
+
+        let fetched_at = Utc::now().naive_utc() - chrono::Duration::days(2);
+        let query_id =
+            upsert_query_with_rows(&pool, "Brave", "rust", &sample_results(), fetched_at)
+                .await
+                .unwrap();
+
+        // without a max age nothing expires
+        let kept = get_unexpired_query(&pool, "rust", engine_id, None, true)
+            .await
+            .unwrap();
+        assert_eq!(kept.unwrap().id, query_id);
+        let kept = get_unexpired_query(&pool, "rust", engine_id, Some(day * 3), true)
+            .await
+            .unwrap();
+        assert!(kept.is_some());
+
+        // past it the query and its results list are gone
+        let expired = get_unexpired_query(&pool, "rust", engine_id, Some(day), true)
+            .await
+            .unwrap();
+        assert!(expired.is_none());
+        assert!(get_query(&pool, "rust", engine_id).await.unwrap().is_none());
+        assert!(
+            get_results_for_query(&pool, query_id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let query_id = upsert_query_with_rows(
+            &pool,
+            "Brave",
+            "rust",
+            &sample_results(),
+            Utc::now().naive_utc(),
+        )
+        .await
+        .unwrap();
+        delete_query(&pool, query_id).await.unwrap();
+        assert!(get_query(&pool, "rust", engine_id).await.unwrap().is_none());
+    }
+
+    #[sqlx::test]
+    async fn test_evict_queries() {
+        let pool = new_db().await;
+        let engine_id = get_engine_id(&pool, "Brave").await.unwrap();
+        let now = Utc::now().naive_utc();
+
+        // oldest first, then "rust" is used again
+        for (i, query) in ["rust", "zig", "go"].into_iter().enumerate() {
+            let fetched_at = now - chrono::Duration::hours(3 - i as i64);
+            upsert_query_with_rows(&pool, "Brave", query, &sample_results(), fetched_at)
+                .await
+                .unwrap();
+        }
+        get_unexpired_query(&pool, "rust", engine_id, None, true)
+            .await
+            .unwrap();
+        // a private lookup doesn't count as a use
+        get_unexpired_query(&pool, "zig", engine_id, None, false)
+            .await
+            .unwrap();
 
-        create_search_cache(&pool).await.unwrap();
+        assert_eq!(evict_queries(&pool, 3).await.unwrap(), 0);
+        assert_eq!(evict_queries(&pool, 2).await.unwrap(), 1);
+        assert!(get_query(&pool, "zig", engine_id).await.unwrap().is_none());
+        assert!(get_query(&pool, "rust", engine_id).await.unwrap().is_some());
 
-        pool
+        // results go with the last query listing them
+        assert_eq!(evict_queries(&pool, 0).await.unwrap(), 2);
+        let results: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM results")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(results, 0);
     }
 
-    fn sample_results() -> Vec<ResultRow> {
-        vec![
-            ResultRow {
-                url: "https://example.com".into(),
-                title: "Example 1".into(),
-                description: "First description".into(),
-            },
-            ResultRow {
-                url: "https://super.com".into(),
-                title: "Example 2".into(),
-                description: "Second description".into(),
-            },
-            ResultRow {
-                url: "https://mega.com".into(),
-                title: "Example 3".into(),
-                description: "Third description".into(),
-            },
-        ]
+    #[sqlx::test]
+    async fn test_sqlite_backend() {
+        let sqlite = SqliteCache::new(new_db().await);
+        let backend: &dyn CacheBackend = &sqlite;
+        let now = Utc::now().naive_utc();
+
+        assert!(
+            backend
+                .get_query("Brave", "rust", None, true)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let query_id = backend
+            .store("Brave", "rust", sample_results(), now)
+            .await
+            .unwrap();
+        backend
+            .set_page_meta(query_id, Some(10), true, Some("offset=10"))
+            .await
+            .unwrap();
+        let images = vec![ImagesRow {
+            url: "https://example.com/rust.png".to_string(),
+            title: "Rust logo".to_string(),
+            ..Default::default()
+        }];
+        backend
+            .store("Brave", "rust\u{1f}images", images, now)
+            .await
+            .unwrap();
+
+        let query = backend
+            .get_query("Brave", "rust", None, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(query.id, query_id);
+        assert_eq!(query.next_page.as_deref(), Some("offset=10"));
+        assert_eq!(backend.rows::<ResultRow>(query_id).await.unwrap().len(), 3);
+        assert_eq!(
+            backend.stats().await.unwrap(),
+            CacheStats {
+                queries: 2,
+                results: 3,
+                images: 1
+            }
+        );
+
+        backend
+            .replace_results(query_id, &sample_results()[..1], now)
+            .await
+            .unwrap();
+        assert_eq!(backend.rows::<ResultRow>(query_id).await.unwrap().len(), 1);
+
+        assert_eq!(
+            backend
+                .purge(Purge::Query("rust".to_string()))
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(backend.stats().await.unwrap(), CacheStats::default());
     }
 
     #[sqlx::test]
-    async fn smoke_init_db() {
-        let _ = new_db().await;
+    async fn test_purge() {
+        let pool = new_db().await;
+        let now = Utc::now().naive_utc();
+        let cached = |query: &'static str, engine: &'static str| {
+            let pool = pool.clone();
+            async move {
+                let engine_id = get_engine_id(&pool, engine).await.unwrap();
+                get_query(&pool, query, engine_id).await.unwrap().is_some()
+            }
+        };
+        let result_count = || async {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM results")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        for (engine, query) in [
+            ("Brave", "rust"),
+            ("Google", "rust"),
+            ("Brave", "rust\u{1f}news"),
+            ("Brave", "rust_lang"),
+        ] {
+            upsert_query_with_rows(&pool, engine, query, &sample_results(), now)
+                .await
+                .unwrap();
+        }
+        upsert_query_with_rows(
+            &pool,
+            "Google",
+            "zig",
+            &[ResultRow {
+                url: "https://ziglang.org".to_string(),
+                title: "Zig".to_string(),
+                description: "General-purpose programming language".to_string(),
+                ..Default::default()
+            }],
+            now - chrono::Duration::days(2),
+        )
+        .await
+        .unwrap();
+        let results = result_count().await;
+
+        // every engine and vertical, `_` is not a wildcard
+        assert_eq!(purge_query(&pool, "rust").await.unwrap(), 3);
+        assert!(!cached("rust", "Google").await);
+        assert!(!cached("rust\u{1f}news", "Brave").await);
+        assert!(cached("rust_lang", "Brave").await);
+        // results still listed by another query are kept
+        assert_eq!(result_count().await, results);
+
+        assert_eq!(
+            purge_older_than(&pool, now - chrono::Duration::days(1))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(!cached("zig", "Google").await);
+        assert_eq!(result_count().await, results - 1);
+
+        assert_eq!(purge_engine(&pool, "Google").await.unwrap(), 0);
+        assert_eq!(purge_engine(&pool, "Brave").await.unwrap(), 1);
+        assert_eq!(result_count().await, 0);
+
+        // editions go along with their engine, a mere prefix of the name doesn't
+        for engine in ["Wikipedia:de", "Wikipediax"] {
+            upsert_query_with_rows(&pool, engine, "rust", &sample_results(), now)
+                .await
+                .unwrap();
+        }
+        assert_eq!(purge_engine(&pool, "Wikipedia").await.unwrap(), 1);
+        assert!(cached("rust", "Wikipediax").await);
+
+        upsert_query_with_rows(&pool, "Brave", "rust", &sample_results(), now)
+            .await
+            .unwrap();
+        assert_eq!(purge_all(&pool).await.unwrap(), 2);
+        assert!(!cached("rust", "Brave").await);
+        assert_eq!(result_count().await, 0);
     }
 
     #[sqlx::test]
-    async fn test_upsert_query_with_results() {
+    async fn test_query_page_meta() {
         let pool = new_db().await;
-        let results = sample_results();
+        // running the schema twice must not re-add columns
+        create_search_cache(&pool).await.unwrap();
 
-        let query = "rust sqlite test";
         let fetched_at = Utc::now().naive_utc();
-
-        // upsert the query and results
         let query_id =
-            upsert_query_with_results(&pool, "Brave", query, results.clone(), fetched_at)
+            upsert_query_with_rows(&pool, "Brave", "meta test", &sample_results(), fetched_at)
                 .await
-                .expect("Failed to upsert query");
+                .unwrap();
 
-        assert!(query_id > 0);
+        let engine_id = get_engine_id(&pool, "Brave").await.unwrap();
+        let row = get_query(&pool, "meta test", engine_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.total_results, None);
+        assert!(!row.has_next_page);
 
-        // retrieve results for this query
-        let fetched = get_results_for_query(&pool, query_id).await.unwrap();
-        assert_eq!(fetched.len(), results.len());
+        set_query_page_meta(&pool, query_id, Some(1200), true, Some("offset=1"))
+            .await
+            .unwrap();
 
-        for (i, r) in results.iter().enumerate() {
-            assert_eq!(fetched[i].url, r.url);
-            assert_eq!(fetched[i].title, r.title);
-            assert_eq!(fetched[i].description, r.description);
-        }
+        let row = get_query(&pool, "meta test", engine_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.total_results, Some(1200));
+        assert!(row.has_next_page);
+        assert_eq!(row.next_page.as_deref(), Some("offset=1"));
+
+        let refreshed_at = fetched_at + chrono::Duration::hours(1);
+        set_query_fetched_at(&pool, query_id, refreshed_at)
+            .await
+            .unwrap();
+        let row = get_query(&pool, "meta test", engine_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.fetched_at, refreshed_at);
     }
 
     #[sqlx::test]
@@ -483,16 +4118,14 @@ mod test {
         let fetched_at = Utc::now().naive_utc();
 
         // first insert
-        let first_id =
-            upsert_query_with_results(&pool, "Brave", query, results.clone(), fetched_at)
-                .await
-                .unwrap();
+        let first_id = upsert_query_with_rows(&pool, "Brave", query, &results, fetched_at)
+            .await
+            .unwrap();
 
         // second insert with same query/results
-        let second_id =
-            upsert_query_with_results(&pool, "Brave", query, results.clone(), fetched_at)
-                .await
-                .unwrap();
+        let second_id = upsert_query_with_rows(&pool, "Brave", query, &results, fetched_at)
+            .await
+            .unwrap();
 
         // should return same query_id
         assert_eq!(first_id, second_id);
@@ -510,11 +4143,13 @@ mod test {
                 url: "https://extra.com".into(),
                 title: "Extra 1".into(),
                 description: "Extra description".into(),
+                ..Default::default()
             },
             ResultRow {
                 url: "https://more.com".into(),
                 title: "Extra 2".into(),
                 description: "More description".into(),
+                ..Default::default()
             },
         ];
 
@@ -522,13 +4157,12 @@ mod test {
         let fetched_at = Utc::now().naive_utc();
 
         // Insert page 1
-        let query_id =
-            upsert_query_with_results(&pool, "DuckDuckGo", query, page1.clone(), fetched_at)
-                .await
-                .unwrap();
+        let query_id = upsert_query_with_rows(&pool, "DuckDuckGo", query, &page1, fetched_at)
+            .await
+            .unwrap();
 
         // Append page 2
-        upsert_query_with_results(&pool, "DuckDuckGo", query, page2.clone(), fetched_at)
+        upsert_query_with_rows(&pool, "DuckDuckGo", query, &page2, fetched_at)
             .await
             .unwrap();
 
@@ -622,17 +4256,19 @@ mod test {
             ImagesRow {
                 url: "https://a.com".into(),
                 title: "A".into(),
+                ..Default::default()
             },
             ImagesRow {
                 url: "https://b.com".into(),
                 title: "B".into(),
+                ..Default::default()
             },
         ];
 
         let query = "img-upsert";
         let fetched_at = chrono::Utc::now().naive_utc();
 
-        let query_id = upsert_query_with_images(&pool, "Brave", query, entries.clone(), fetched_at)
+        let query_id = upsert_query_with_rows(&pool, "Brave", query, &entries, fetched_at)
             .await
             .unwrap();
 
@@ -653,27 +4289,30 @@ mod test {
         let page1 = vec![ImagesRow {
             url: "https://a.com".into(),
             title: "A".into(),
+            ..Default::default()
         }];
 
         let page2 = vec![
             ImagesRow {
                 url: "https://b.com".into(),
                 title: "B".into(),
+                ..Default::default()
             },
             ImagesRow {
                 url: "https://c.com".into(),
                 title: "C".into(),
+                ..Default::default()
             },
         ];
 
         let query = "img-append-test";
         let fetched_at = chrono::Utc::now().naive_utc();
 
-        let id1 = upsert_query_with_images(&pool, "Brave", query, page1.clone(), fetched_at)
+        let id1 = upsert_query_with_rows(&pool, "Brave", query, &page1, fetched_at)
             .await
             .unwrap();
 
-        let id2 = upsert_query_with_images(&pool, "Brave", query, page2.clone(), fetched_at)
+        let id2 = upsert_query_with_rows(&pool, "Brave", query, &page2, fetched_at)
             .await
             .unwrap();
 
@@ -688,4 +4327,837 @@ mod test {
         assert_eq!(imgs[1].title, "B");
         assert_eq!(imgs[2].title, "C");
     }
+
+    /// Every kind but results and images, which have tests of their own,
+    /// stored twice under one query: a page, then `again` from a later page
+    /// or a refetch. `check` gets what the query lists afterwards.
+    #[sqlx::test]
+    async fn test_store_rows_of_every_kind() {
+        let pool = new_db().await;
+        let cache = SqliteCache::new(pool.clone());
+        let earlier = Utc::now().naive_utc() - chrono::Duration::minutes(5);
+        let now = Utc::now().naive_utc();
+
+        type Case = (Rows, Rows, fn(Rows));
+        let cases: Vec<Case> = vec![
+            (
+                Rows::News(vec![
+                    NewsRow {
+                        url: "https://news.com/a".to_string(),
+                        title: "A".to_string(),
+                        published_at: Some("2024-05-01T10:00:00+00:00".to_string()),
+                        ..Default::default()
+                    },
+                    NewsRow {
+                        url: "https://news.com/b".to_string(),
+                        title: "B".to_string(),
+                        ..Default::default()
+                    },
+                ]),
+                // a later page repeating a story without its date
+                Rows::News(vec![
+                    NewsRow {
+                        url: "https://news.com/c".to_string(),
+                        title: "C".to_string(),
+                        ..Default::default()
+                    },
+                    NewsRow {
+                        url: "https://news.com/a".to_string(),
+                        title: "A".to_string(),
+                        ..Default::default()
+                    },
+                ]),
+                |rows| {
+                    let Rows::News(news) = rows else {
+                        unreachable!()
+                    };
+                    let titles: Vec<_> = news.iter().map(|n| n.title.as_str()).collect();
+                    assert_eq!(titles, ["A", "B", "C"]);
+                    assert_eq!(
+                        news[0].published_at.as_deref(),
+                        Some("2024-05-01T10:00:00+00:00")
+                    );
+                },
+            ),
+            (
+                Rows::Videos(vec![VideoRow {
+                    url: "https://www.youtube.com/watch?v=abc".to_string(),
+                    title: "Rust in 100 seconds".to_string(),
+                    duration: Some("2:25".to_string()),
+                    channel: Some("Fireship".to_string()),
+                    ..Default::default()
+                }]),
+                // seen again without metadata, the stored values are kept
+                Rows::Videos(vec![VideoRow {
+                    url: "https://www.youtube.com/watch?v=abc".to_string(),
+                    title: "Rust in 100 Seconds".to_string(),
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Videos(videos) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(videos.len(), 1);
+                    assert_eq!(videos[0].title, "Rust in 100 Seconds");
+                    assert_eq!(videos[0].duration.as_deref(), Some("2:25"));
+                    assert_eq!(videos[0].channel.as_deref(), Some("Fireship"));
+                },
+            ),
+            (
+                Rows::Products(vec![ProductRow {
+                    url: "https://www.ebay.com/itm/1234".to_string(),
+                    title: "Ferris plush".to_string(),
+                    price: Some("19.99".to_string()),
+                    merchant: Some("crab_shop".to_string()),
+                    ..Default::default()
+                }]),
+                // seen again with a new price and no seller
+                Rows::Products(vec![ProductRow {
+                    url: "https://www.ebay.com/itm/1234".to_string(),
+                    title: "Ferris plush".to_string(),
+                    price: Some("14.99".to_string()),
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Products(products) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(products.len(), 1);
+                    assert_eq!(products[0].price.as_deref(), Some("14.99"));
+                    assert_eq!(products[0].merchant.as_deref(), Some("crab_shop"));
+                },
+            ),
+            (
+                Rows::Papers(vec![PaperRow {
+                    url: "https://arxiv.org/abs/2101.00001".to_string(),
+                    title: "Ownership types".to_string(),
+                    authors: vec!["Jane Doe".to_string(), "John Roe".to_string()],
+                    year: Some(2021),
+                    doi: Some("10.1000/xyz123".to_string()),
+                    ..Default::default()
+                }]),
+                Rows::Papers(vec![PaperRow {
+                    url: "https://arxiv.org/abs/2101.00001".to_string(),
+                    title: "Ownership Types".to_string(),
+                    authors: vec!["Jane Doe".to_string()],
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Papers(papers) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(papers.len(), 1);
+                    assert_eq!(papers[0].authors, ["Jane Doe"]);
+                    assert_eq!(papers[0].year, Some(2021));
+                    assert_eq!(papers[0].doi.as_deref(), Some("10.1000/xyz123"));
+                },
+            ),
+            (
+                Rows::Files(vec![FileRow {
+                    url: "https://tracker.example/details/1".to_string(),
+                    title: "debian-12.5.0-amd64-netinst.iso".to_string(),
+                    magnet: Some("magnet:?xt=urn:btih:abc".to_string()),
+                    size: Some(659_554_304),
+                    seeders: Some(120),
+                    ..Default::default()
+                }]),
+                // seen again later, with fewer seeders and no magnet
+                Rows::Files(vec![FileRow {
+                    url: "https://tracker.example/details/1".to_string(),
+                    title: "debian-12.5.0-amd64-netinst.iso".to_string(),
+                    seeders: Some(80),
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Files(files) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(files.len(), 1);
+                    assert_eq!(files[0].seeders, Some(80));
+                    assert_eq!(files[0].size, Some(659_554_304));
+                    assert!(files[0].magnet.is_some());
+                },
+            ),
+            (
+                Rows::Social(vec![SocialRow {
+                    url: "https://lemmy.ml/post/1".to_string(),
+                    kind: "post".to_string(),
+                    score: Some(42),
+                    community: Some("!rust@lemmy.ml".to_string()),
+                    ..Default::default()
+                }]),
+                Rows::Social(vec![SocialRow {
+                    url: "https://lemmy.ml/c/rust".to_string(),
+                    kind: "community".to_string(),
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Social(social) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(social.len(), 2);
+                    assert_eq!(social[0].score, Some(42));
+                    assert_eq!(social[0].community.as_deref(), Some("!rust@lemmy.ml"));
+                    assert_eq!(social[1].kind, "community");
+                },
+            ),
+            (
+                Rows::Recipes(vec![RecipeRow {
+                    url: "https://example.com/pancakes".to_string(),
+                    title: "Fluffy pancakes".to_string(),
+                    ingredients: vec!["1 ½ cups flour".to_string(), "2 eggs".to_string()],
+                    total_time: Some(30),
+                    rating: Some(4.8),
+                    ..Default::default()
+                }]),
+                Rows::Recipes(Vec::new()),
+                |rows| {
+                    let Rows::Recipes(recipes) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(recipes.len(), 1);
+                    assert_eq!(recipes[0].ingredients, ["1 ½ cups flour", "2 eggs"]);
+                    assert_eq!(recipes[0].total_time, Some(30));
+                    assert_eq!(recipes[0].rating, Some(4.8));
+                },
+            ),
+            (
+                Rows::Music(vec![MusicRow {
+                    url: "https://band.bandcamp.com/album/first-light".to_string(),
+                    title: "First Light".to_string(),
+                    artist: Some("The Band".to_string()),
+                    kind: "album".to_string(),
+                    year: Some(2020),
+                    ..Default::default()
+                }]),
+                Rows::Music(Vec::new()),
+                |rows| {
+                    let Rows::Music(music) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(music.len(), 1);
+                    assert_eq!(music[0].artist.as_deref(), Some("The Band"));
+                    assert_eq!(music[0].year, Some(2020));
+                },
+            ),
+            (
+                Rows::Patents(vec![PatentRow {
+                    url: "https://patents.google.com/patent/US9123456B2/en".to_string(),
+                    number: "US9123456B2".to_string(),
+                    title: "Memory safe compiler".to_string(),
+                    assignee: Some("Acme Corp".to_string()),
+                    ..Default::default()
+                }]),
+                Rows::Patents(Vec::new()),
+                |rows| {
+                    let Rows::Patents(patents) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(patents.len(), 1);
+                    assert_eq!(patents[0].number, "US9123456B2");
+                    assert_eq!(patents[0].assignee.as_deref(), Some("Acme Corp"));
+                },
+            ),
+            (
+                Rows::Apps(vec![AppRow {
+                    url: "https://flathub.org/apps/org.mozilla.firefox".to_string(),
+                    name: "Firefox".to_string(),
+                    store: "Flathub".to_string(),
+                    app_id: "org.mozilla.firefox".to_string(),
+                    ..Default::default()
+                }]),
+                Rows::Apps(Vec::new()),
+                |rows| {
+                    let Rows::Apps(apps) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(apps.len(), 1);
+                    assert_eq!(apps[0].app_id, "org.mozilla.firefox");
+                },
+            ),
+            (
+                Rows::Packages(vec![PackageRow {
+                    url: "https://crates.io/crates/serde".to_string(),
+                    name: "serde".to_string(),
+                    registry: "crates.io".to_string(),
+                    version: Some("1.0.210".to_string()),
+                    downloads: Some(400_000_000),
+                    ..Default::default()
+                }]),
+                // seen again on a later page without details
+                Rows::Packages(vec![PackageRow {
+                    url: "https://crates.io/crates/serde".to_string(),
+                    name: "serde".to_string(),
+                    registry: "crates.io".to_string(),
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Packages(packages) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(packages.len(), 1);
+                    assert_eq!(packages[0].version.as_deref(), Some("1.0.210"));
+                    assert_eq!(packages[0].downloads, Some(400_000_000));
+                },
+            ),
+            (
+                Rows::Podcasts(vec![PodcastRow {
+                    url: "https://newrustacean.com".to_string(),
+                    title: "New Rustacean".to_string(),
+                    feed_url: "https://newrustacean.com/feed.xml".to_string(),
+                    ..Default::default()
+                }]),
+                Rows::Podcasts(Vec::new()),
+                |rows| {
+                    let Rows::Podcasts(podcasts) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(podcasts.len(), 1);
+                    assert_eq!(podcasts[0].feed_url, "https://newrustacean.com/feed.xml");
+                },
+            ),
+            (
+                Rows::Books(vec![BookRow {
+                    url: "https://openlibrary.org/works/OL17930368W".to_string(),
+                    title: "The Rust Programming Language".to_string(),
+                    authors: vec!["Steve Klabnik".to_string(), "Carol Nichols".to_string()],
+                    cover: Some("https://covers.openlibrary.org/b/id/8546231-M.jpg".to_string()),
+                    ..Default::default()
+                }]),
+                // seen again without a cover
+                Rows::Books(vec![BookRow {
+                    url: "https://openlibrary.org/works/OL17930368W".to_string(),
+                    title: "The Rust Programming Language".to_string(),
+                    authors: vec!["Steve Klabnik".to_string(), "Carol Nichols".to_string()],
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Books(books) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(books.len(), 1);
+                    assert_eq!(books[0].authors, ["Steve Klabnik", "Carol Nichols"]);
+                    assert!(books[0].cover.is_some());
+                },
+            ),
+            (
+                Rows::Definitions(vec![
+                    DefinitionRow {
+                        word: "run".to_string(),
+                        part_of_speech: "verb".to_string(),
+                        definition: "To move swiftly on foot.".to_string(),
+                        examples: vec!["She runs every morning.".to_string()],
+                        ..Default::default()
+                    },
+                    DefinitionRow {
+                        word: "run".to_string(),
+                        part_of_speech: "noun".to_string(),
+                        definition: "An act of running.".to_string(),
+                        ..Default::default()
+                    },
+                ]),
+                // a refetch replaces the list instead of appending to it
+                Rows::Definitions(vec![DefinitionRow {
+                    word: "run".to_string(),
+                    part_of_speech: "verb".to_string(),
+                    definition: "To move swiftly on foot.".to_string(),
+                    phonetic: Some("/ɹʌn/".to_string()),
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Definitions(definitions) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(definitions.len(), 1);
+                    assert!(definitions[0].examples.is_empty());
+                    assert_eq!(definitions[0].phonetic.as_deref(), Some("/ɹʌn/"));
+                },
+            ),
+            (
+                Rows::Places(vec![
+                    PlaceRow {
+                        name: "Berlin".to_string(),
+                        osm_url: "https://www.openstreetmap.org/relation/62422".to_string(),
+                        ..Default::default()
+                    },
+                    PlaceRow {
+                        name: "Berlin Hbf".to_string(),
+                        osm_url: "https://www.openstreetmap.org/node/240109189".to_string(),
+                        ..Default::default()
+                    },
+                ]),
+                Rows::Places(vec![PlaceRow {
+                    name: "Berlin".to_string(),
+                    lat: 52.517,
+                    osm_url: "https://www.openstreetmap.org/relation/62422".to_string(),
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Places(places) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(places.len(), 1);
+                    assert_eq!(places[0].lat, 52.517);
+                },
+            ),
+            (
+                Rows::Quotes(vec![QuoteRow {
+                    symbol: "AAPL".to_string(),
+                    name: Some("Apple Inc.".to_string()),
+                    price: 189.5,
+                    ..Default::default()
+                }]),
+                Rows::Quotes(vec![QuoteRow {
+                    symbol: "AAPL".to_string(),
+                    name: Some("Apple Inc.".to_string()),
+                    price: 190.25,
+                    ..Default::default()
+                }]),
+                |rows| {
+                    let Rows::Quotes(quotes) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(quotes.len(), 1);
+                    assert_eq!(quotes[0].price, 190.25);
+                },
+            ),
+            (
+                Rows::WikiSummaries(vec![WikiSummaryRow {
+                    title: "Rust (programming language)".to_string(),
+                    extract: "Rust is a general-purpose programming language.".to_string(),
+                    url: "https://en.wikipedia.org/wiki/Rust_(programming_language)".to_string(),
+                    ..Default::default()
+                }]),
+                // a miss leaves the query without a summary
+                Rows::WikiSummaries(Vec::new()),
+                |rows| {
+                    let Rows::WikiSummaries(summaries) = rows else {
+                        unreachable!()
+                    };
+                    assert!(summaries.is_empty());
+                },
+            ),
+            (
+                Rows::Suggestions(vec!["rust lang".to_string(), "rust game".to_string()]),
+                Rows::Suggestions(vec!["rust book".to_string()]),
+                |rows| {
+                    let Rows::Suggestions(suggestions) = rows else {
+                        unreachable!()
+                    };
+                    assert_eq!(suggestions, ["rust book"]);
+                },
+            ),
+        ];
+
+        for (first, again, check) in cases {
+            let kind = first.kind();
+            let replaces = first.replaces();
+            let query_id = cache
+                .store_rows("Engine", kind, first, earlier)
+                .await
+                .unwrap();
+            let same_id = cache.store_rows("Engine", kind, again, now).await.unwrap();
+            assert_eq!(query_id, same_id, "{kind}");
+            check(cache.get_rows(query_id, kind).await.unwrap().unwrap());
+
+            // only a refetch moves the fetch time along
+            let engine_id = get_engine_id(&pool, "Engine").await.unwrap();
+            let query = get_query(&pool, kind, engine_id).await.unwrap().unwrap();
+            let fetched_at = if replaces { now } else { earlier };
+            assert_eq!(query.fetched_at, fetched_at, "{kind}");
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_image_meta() {
+        let pool = new_db().await;
+
+        let image = ImagesRow {
+            url: "https://a.com/a.png".into(),
+            title: "A".into(),
+            width: Some(1920),
+            height: Some(1080),
+            thumbnail: Some("https://thumbs.com/a.jpg".into()),
+            source_url: Some("https://a.com/gallery".into()),
+            license: Some("CC BY 4.0".into()),
+            attribution: Some("Jane Doe".into()),
+            blurhash: None,
+            phash: None,
+        };
+
+        let fetched_at = chrono::Utc::now().naive_utc();
+        let query_id = upsert_query_with_rows(&pool, "Brave", "meta", &[image], fetched_at)
+            .await
+            .unwrap();
+
+        // seen again without metadata, the stored values are kept
+        let bare = ImagesRow {
+            url: "https://a.com/a.png".into(),
+            title: "A".into(),
+            ..Default::default()
+        };
+        upsert_query_with_rows(&pool, "Brave", "meta", &[bare], fetched_at)
+            .await
+            .unwrap();
+
+        let imgs = get_images_for_query(&pool, query_id).await.unwrap();
+        assert_eq!(imgs[0].width, Some(1920));
+        assert_eq!(imgs[0].height, Some(1080));
+        assert_eq!(
+            imgs[0].thumbnail.as_deref(),
+            Some("https://thumbs.com/a.jpg")
+        );
+        assert_eq!(imgs[0].source_url.as_deref(), Some("https://a.com/gallery"));
+        assert_eq!(imgs[0].license.as_deref(), Some("CC BY 4.0"));
+        assert_eq!(imgs[0].attribution.as_deref(), Some("Jane Doe"));
+        assert_eq!(imgs[0].blurhash, None);
+
+        set_image_blurhash(&pool, "https://a.com/a.png", "LEHV6nWB2yk8")
+            .await
+            .unwrap();
+        let imgs = get_images_for_query(&pool, query_id).await.unwrap();
+        assert_eq!(imgs[0].blurhash.as_deref(), Some("LEHV6nWB2yk8"));
+
+        set_image_phash(&pool, "https://a.com/a.png", u64::MAX)
+            .await
+            .unwrap();
+        let imgs = get_images_for_query(&pool, query_id).await.unwrap();
+        assert_eq!(imgs[0].phash.map(|h| h as u64), Some(u64::MAX));
+    }
+
+    #[sqlx::test]
+    async fn test_result_meta() {
+        let pool = new_db().await;
+
+        let result = ResultRow {
+            url: "https://www.rust-lang.org/".into(),
+            title: "Rust".into(),
+            description: "A language empowering everyone.".into(),
+            published_at: Some("2 days ago".into()),
+            language: Some("en".into()),
+            extra_snippets: vec!["Install Rust".into()],
+        };
+
+        let fetched_at = chrono::Utc::now().naive_utc();
+        let query_id = upsert_query_with_rows(&pool, "Brave API", "meta", &[result], fetched_at)
+            .await
+            .unwrap();
+
+        // seen again without metadata, the stored values are kept
+        let bare = ResultRow {
+            url: "https://www.rust-lang.org/".into(),
+            title: "Rust".into(),
+            ..Default::default()
+        };
+        upsert_query_with_rows(&pool, "Brave", "meta", &[bare], fetched_at)
+            .await
+            .unwrap();
+
+        let results = get_results_for_query(&pool, query_id).await.unwrap();
+        assert_eq!(results[0].published_at.as_deref(), Some("2 days ago"));
+        assert_eq!(results[0].language.as_deref(), Some("en"));
+        assert_eq!(results[0].extra_snippets, ["Install Rust"]);
+    }
+
+    #[sqlx::test]
+    async fn test_result_rules() {
+        let pool = new_db().await;
+
+        let pin = add_result_rule(&pool, "docs.rs", true, Some("rust"))
+            .await
+            .unwrap();
+        add_result_rule(&pool, "https://spam.com/page", false, None)
+            .await
+            .unwrap();
+
+        let rules = get_result_rules(&pool).await.unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].target, "docs.rs");
+        assert!(rules[0].pinned);
+        assert_eq!(rules[0].query.as_deref(), Some("rust"));
+        assert!(!rules[1].pinned);
+        assert!(rules[1].query.is_none());
+
+        delete_result_rule(&pool, pin).await.unwrap();
+        let rules = get_result_rules(&pool).await.unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].target, "https://spam.com/page");
+    }
+
+    #[sqlx::test]
+    async fn test_screenshots() {
+        let pool = new_db().await;
+        let url = "https://example.com";
+
+        assert!(get_screenshot(&pool, url).await.unwrap().is_none());
+
+        set_screenshot(&pool, url, b"old").await.unwrap();
+        set_screenshot(&pool, url, b"new").await.unwrap();
+        assert_eq!(
+            get_screenshot(&pool, url).await.unwrap().as_deref(),
+            Some(&b"new"[..])
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_suggestions() {
+        let pool = new_db().await;
+        let fetched_at = Utc::now().naive_utc();
+
+        for (engine, query) in [
+            ("Brave", "rust traits"),
+            ("DuckDuckGo", "rust traits"),
+            ("Brave", "rust_lang"),
+            ("Brave", "rust\u{1f}images"),
+            ("Brave", "ruby"),
+        ] {
+            upsert_query_with_rows(&pool, engine, query, &sample_results(), fetched_at)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            get_query_suggestions(&pool, "RUST", 8).await.unwrap(),
+            ["rust traits", "rust_lang"]
+        );
+        // `_` is literal, not a wildcard
+        assert_eq!(
+            get_query_suggestions(&pool, "rust_", 8).await.unwrap(),
+            ["rust_lang"]
+        );
+        assert_eq!(get_recent_queries(&pool, 8).await.unwrap().len(), 3);
+        assert_eq!(
+            get_title_suggestions(&pool, "exa", 8).await.unwrap().len(),
+            3
+        );
+        assert!(
+            get_title_suggestions(&pool, "xample", 8)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_engine_stats() {
+        let pool = new_db().await;
+        let now = Utc::now().naive_utc();
+        let hour_ago = now - chrono::Duration::hours(1);
+        let day_ago = now - chrono::Duration::days(1);
+
+        for (at, latency, outcome) in [
+            (day_ago, 100, "ok"),
+            (now, 200, "ok"),
+            (now, 400, "ok"),
+            (now, 3000, "timeout"),
+            (now, 50, "blocked"),
+        ] {
+            record_engine_request(&pool, "Brave", at, latency, outcome)
+                .await
+                .unwrap();
+        }
+
+        let stats = get_engine_stats(&pool, "Brave", hour_ago).await.unwrap();
+        assert_eq!(stats.requests, 4);
+        assert_eq!(stats.successes, 2);
+        assert_eq!(stats.avg_latency_ms, Some(300.0));
+
+        prune_engine_requests(&pool, now).await.unwrap();
+        let stats = get_engine_stats(&pool, "Brave", day_ago).await.unwrap();
+        assert_eq!(stats.requests, 4);
+
+        let stats = get_engine_stats(&pool, "DuckDuckGo", day_ago)
+            .await
+            .unwrap();
+        assert_eq!(stats.requests, 0);
+        assert_eq!(stats.avg_latency_ms, None);
+    }
+
+    #[sqlx::test]
+    async fn test_alerts() {
+        let pool = new_db().await;
+        let now = Utc::now().naive_utc();
+
+        let id = add_standing_query(&pool, "rust release", 3600)
+            .await
+            .unwrap();
+        let standing = get_standing_queries(&pool).await.unwrap();
+        assert_eq!(standing.len(), 1);
+        assert_eq!(standing[0].interval_secs, 3600);
+        assert!(standing[0].last_run.is_none());
+
+        set_standing_query_run(&pool, id, now).await.unwrap();
+        assert_eq!(
+            get_standing_queries(&pool).await.unwrap()[0].last_run,
+            Some(now)
+        );
+
+        // the first run's results are the baseline
+        assert!(
+            insert_alert_result(&pool, id, "https://a.com", "A", now, true)
+                .await
+                .unwrap()
+        );
+        assert!(
+            insert_alert_result(&pool, id, "https://b.com", "B", now, false)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !insert_alert_result(&pool, id, "https://a.com", "A", now, false)
+                .await
+                .unwrap()
+        );
+
+        let alerts = get_new_alert_results(&pool).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].url, "https://b.com");
+        assert_eq!(alerts[0].query, "rust release");
+        // unseen until acknowledged, e.g. after a failed delivery
+        assert_eq!(get_new_alert_results(&pool).await.unwrap().len(), 1);
+        mark_alert_results_seen(&pool, alerts[0].id).await.unwrap();
+        assert!(get_new_alert_results(&pool).await.unwrap().is_empty());
+
+        delete_standing_query(&pool, id).await.unwrap();
+        assert!(get_standing_queries(&pool).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn test_engine_usage() {
+        let pool = new_db().await;
+        let hour = chrono::NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let next_hour = hour + chrono::Duration::hours(1);
+
+        for hour in [hour, next_hour, next_hour] {
+            assert!(
+                try_add_engine_usage(&pool, "Brave", hour, None, None)
+                    .await
+                    .unwrap()
+            );
+        }
+
+        assert_eq!(get_engine_usage(&pool, "Brave", hour).await.unwrap(), 3);
+        assert_eq!(
+            get_engine_usage(&pool, "Brave", next_hour).await.unwrap(),
+            2
+        );
+        assert_eq!(
+            get_engine_usage(&pool, "DuckDuckGo", hour).await.unwrap(),
+            0
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_engine_usage_limits() {
+        let pool = new_db().await;
+        let hour = chrono::NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+        let next_hour = hour + chrono::Duration::hours(1);
+
+        assert!(
+            try_add_engine_usage(&pool, "Brave", hour, Some(1), Some(2))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !try_add_engine_usage(&pool, "Brave", hour, Some(1), Some(2))
+                .await
+                .unwrap()
+        );
+        assert!(
+            try_add_engine_usage(&pool, "Brave", next_hour, Some(1), Some(2))
+                .await
+                .unwrap()
+        );
+        let later = next_hour + chrono::Duration::hours(1);
+        assert!(
+            !try_add_engine_usage(&pool, "Brave", later, Some(1), Some(2))
+                .await
+                .unwrap()
+        );
+        assert_eq!(get_engine_usage(&pool, "Brave", hour).await.unwrap(), 2);
+    }
+
+    #[sqlx::test]
+    async fn test_engine_usage_concurrent() {
+        let pool = new_db().await;
+        let hour = chrono::NaiveDate::from_ymd_opt(2025, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap();
+
+        let mut set = tokio::task::JoinSet::new();
+        for _ in 0..8 {
+            let pool = pool.clone();
+            set.spawn(async move {
+                try_add_engine_usage(&pool, "Brave", hour, Some(1), None)
+                    .await
+                    .unwrap()
+            });
+        }
+        let spent = set.join_all().await;
+
+        assert_eq!(spent.iter().filter(|&&counted| counted).count(), 1);
+        assert_eq!(get_engine_usage(&pool, "Brave", hour).await.unwrap(), 1);
+    }
+
+    #[sqlx::test]
+    async fn test_reparse() {
+        let pool = new_db().await;
+        let now = Utc::now().naive_utc();
+        let lite = r#"
+            <table>
+            <tr><td><a href="https://www.rust-lang.org/" class="result-link">Rust</a></td></tr>
+            <tr><td class="result-snippet">A language empowering everyone.</td></tr>
+            <tr><td><a href="https://doc.rust-lang.org/book/" class="result-link">The Book</a></td></tr>
+            </table>
+        "#;
+
+        // cached while the parser was missing descriptions
+        let broken = vec![ResultRow {
+            url: "https://www.rust-lang.org/".into(),
+            title: "Rust".into(),
+            description: String::new(),
+            ..Default::default()
+        }];
+        let query_id = upsert_query_with_rows(&pool, "DuckDuckGo", "rust", &broken, now)
+            .await
+            .unwrap();
+        archive_response(&pool, query_id, None, lite, now)
+            .await
+            .unwrap();
+
+        let archived = get_archived_responses(&pool, "DuckDuckGo").await.unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].body, lite);
+
+        assert_eq!(
+            reparse(&pool, crate::SearchEngines::DuckDuckGo)
+                .await
+                .unwrap(),
+            1
+        );
+        let results = get_results_for_query(&pool, query_id).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].description, "A language empowering everyone.");
+        assert_eq!(results[1].url, "https://doc.rust-lang.org/book/");
+
+        assert_eq!(
+            prune_archived_responses(&pool, now + chrono::Duration::seconds(1))
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(
+            get_archived_responses(&pool, "DuckDuckGo")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
 }
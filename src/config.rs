@@ -0,0 +1,1473 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    path::PathBuf,
+    str::FromStr,
+    sync::OnceLock,
+    time::Duration,
+};
+
+use crate::{
+    AppEngines, BookEngines, DictionaryEngines, FilesEngines, ImageEngines, MusicEngines,
+    NewsEngines, PackageEngines, PatentEngines, PlacesEngines, PodcastEngines, QuoteEngines,
+    ScholarEngines, SearchEngines, ShoppingEngines, SocialEngines, Vertical, VideoEngines,
+    answers::AnswerProviders,
+    engines::{
+        BingApi, BraveApi, Ebay, Flickr, FourGet, GoogleCse, Kagi, Lemmy, LibreY, Mastodon, Pexels,
+        PodcastIndex, SearxNG, Torznab, Unsplash, Whoogle,
+    },
+    suggest::SuggestEngines,
+};
+
+// Environment variables, applied on top of the config file and builder values
+pub const CONFIG_PATH_ENV: &str = "SEARCH_CONFIG";
+pub const PROFILE_ENV: &str = "SEARCH_PROFILE";
+pub const ENGINES_ENV: &str = "SEARCH_ENGINES";
+pub const IMAGE_ENGINES_ENV: &str = "SEARCH_IMAGE_ENGINES";
+pub const NEWS_ENGINES_ENV: &str = "SEARCH_NEWS_ENGINES";
+pub const VIDEO_ENGINES_ENV: &str = "SEARCH_VIDEO_ENGINES";
+pub const PLACES_ENGINES_ENV: &str = "SEARCH_PLACES_ENGINES";
+pub const SHOPPING_ENGINES_ENV: &str = "SEARCH_SHOPPING_ENGINES";
+pub const SCHOLAR_ENGINES_ENV: &str = "SEARCH_SCHOLAR_ENGINES";
+pub const FILES_ENGINES_ENV: &str = "SEARCH_FILES_ENGINES";
+pub const BOOK_ENGINES_ENV: &str = "SEARCH_BOOK_ENGINES";
+pub const PODCAST_ENGINES_ENV: &str = "SEARCH_PODCAST_ENGINES";
+pub const SOCIAL_ENGINES_ENV: &str = "SEARCH_SOCIAL_ENGINES";
+pub const DICTIONARY_ENGINES_ENV: &str = "SEARCH_DICTIONARY_ENGINES";
+pub const QUOTE_ENGINES_ENV: &str = "SEARCH_QUOTE_ENGINES";
+pub const PACKAGE_ENGINES_ENV: &str = "SEARCH_PACKAGE_ENGINES";
+pub const APP_ENGINES_ENV: &str = "SEARCH_APP_ENGINES";
+pub const PATENT_ENGINES_ENV: &str = "SEARCH_PATENT_ENGINES";
+pub const MUSIC_ENGINES_ENV: &str = "SEARCH_MUSIC_ENGINES";
+pub const ANSWER_PROVIDERS_ENV: &str = "SEARCH_ANSWER_PROVIDERS";
+pub const SUGGEST_ENGINES_ENV: &str = "SEARCH_SUGGEST_ENGINES";
+pub const ENGINE_TIMEOUT_ENV: &str = "SEARCH_ENGINE_TIMEOUT";
+pub const PROXY_ENV: &str = "SEARCH_PROXY";
+pub const CACHE_TTL_ENV: &str = "SEARCH_CACHE_TTL";
+pub const CACHE_MAX_QUERIES_ENV: &str = "SEARCH_CACHE_MAX_QUERIES";
+pub const CACHE_REFRESH_AFTER_ENV: &str = "SEARCH_CACHE_REFRESH_AFTER";
+pub const SAFE_SEARCH_ENV: &str = "SEARCH_SAFE_SEARCH";
+
+const DEFAULT_ENGINE_TIMEOUT: u64 = 3; // seconds
+const DEFAULT_ARCHIVE_RETENTION: u64 = 7 * 24 * 60 * 60; // seconds
+const TOR_PROXY: &str = "socks5h://127.0.0.1:9050";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Loads the global config from the config file and environment, or returns
+/// the one already set. Call it at startup so a malformed file or variable
+/// is reported there rather than during a search.
+pub fn init() -> Result<&'static Config, ConfigError> {
+    if let Some(config) = CONFIG.get() {
+        return Ok(config);
+    }
+    let config = ConfigBuilder::new().build()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// Sets the global config. Fails with the given config if one was already set or loaded.
+#[allow(clippy::result_large_err)] // hands the config back like `OnceLock::set`
+pub fn set(config: Config) -> Result<(), Config> {
+    CONFIG.set(config)
+}
+
+/// Returns the global config. Without [`init`] or [`set`] it is loaded on
+/// first use.
+///
+/// # Panics
+///
+/// If the config can't be loaded. Searching with the defaults instead would
+/// drop a configured proxy, so call [`init`] at startup to handle the error.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        ConfigBuilder::new()
+            .build()
+            .expect("Failed to load search config")
+    })
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    InvalidValue { key: String, value: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Engines used when a search is made with an empty engine list
+    pub engines: Vec<SearchEngines>,
+    /// How engines are picked from `engines` for such searches
+    pub engine_selection: EngineSelection,
+    pub image_engines: Vec<ImageEngines>,
+    pub news_engines: Vec<NewsEngines>,
+    pub video_engines: Vec<VideoEngines>,
+    pub places_engines: Vec<PlacesEngines>,
+    /// Empty unless configured, every shopping engine needs credentials
+    pub shopping_engines: Vec<ShoppingEngines>,
+    pub scholar_engines: Vec<ScholarEngines>,
+    /// Empty unless configured, torrent indexers are self-hosted
+    pub files_engines: Vec<FilesEngines>,
+    pub book_engines: Vec<BookEngines>,
+    pub podcast_engines: Vec<PodcastEngines>,
+    /// Empty unless configured, fediverse instances are the user's pick
+    pub social_engines: Vec<SocialEngines>,
+    pub dictionary_engines: Vec<DictionaryEngines>,
+    /// Tried in order, the first source listing a symbol answers
+    pub quote_engines: Vec<QuoteEngines>,
+    pub package_engines: Vec<PackageEngines>,
+    pub app_engines: Vec<AppEngines>,
+    pub patent_engines: Vec<PatentEngines>,
+    pub music_engines: Vec<MusicEngines>,
+    /// Instant answers shown above the web results
+    pub answer_providers: Vec<AnswerProviders>,
+    /// Autocomplete sources, none by default so keystrokes stay local
+    pub suggest_engines: Vec<SuggestEngines>,
+    pub engine_timeout: Duration,
+    /// Request budgets keyed by engine name, engines without one are unlimited
+    pub budgets: HashMap<&'static str, Budget>,
+    /// Proxy url all engine traffic is sent through (http, https or socks5)
+    pub proxy: Option<String>,
+    /// Give every search its own Tor circuit through random SOCKS5
+    /// credentials, so engines can't link searches by exit address
+    pub circuit_isolation: bool,
+    /// Random delay of up to this before each engine request, counted
+    /// against `engine_timeout`
+    pub max_jitter: Option<Duration>,
+    /// Chance, from 0 to 1, that a search also sends a decoy search to one
+    /// of its keyless public engines, counted against that engine's budget
+    pub decoy_rate: f64,
+    /// Queries decoy searches are picked from
+    pub decoy_queries: Vec<String>,
+    /// Max age of cached queries, `None` keeps them forever
+    pub cache_ttl: Option<Duration>,
+    /// Max number of cached queries, the least recently used ones are
+    /// evicted past it. `None` keeps any number, zero is refused
+    pub max_cached_queries: Option<usize>,
+    /// Age past which a cached web search is still served but refetched in
+    /// the background, `None` only refetches after `cache_ttl`
+    pub cache_refresh_after: Option<Duration>,
+    /// Write fetched results to the cache, when false the cache is only read
+    pub persist_cache: bool,
+    /// Store queries in the cache as SHA-256 hashes instead of plain text
+    pub hash_queries: bool,
+    /// Keep the compressed responses cached web results were parsed from, so
+    /// [`crate::reparse_archived`] can parse them again after a parser fix
+    pub archive_responses: bool,
+    /// Age after which archived responses are dropped
+    pub archive_retention: Duration,
+    /// Fetch the thumbnails of newly cached images to derive blurhashes and
+    /// perceptual hashes from them (`blurhash` and `phash` features)
+    pub thumbnail_cache: bool,
+    /// Top results of each search to take page screenshots of, in the
+    /// background (`screenshot` feature)
+    pub screenshots: usize,
+    pub ranking: Ranking,
+    /// Move results matching more of the query's words, their stems and
+    /// synonyms up after ranking
+    pub query_expansion: bool,
+    /// Groups of interchangeable words added to the built in ones
+    pub synonyms: Vec<Vec<String>>,
+    /// Default safe search level, can be overridden per search
+    pub safe_search: SafeSearch,
+    /// Image hosts dropped from image results unless safe search is off,
+    /// matched along with their subdomains
+    pub adult_domains: HashSet<String>,
+    /// Privacy frontends result urls are rewritten to
+    pub redirects: Redirects,
+    /// Applied in order to result urls after the redirects
+    pub rewrite_rules: Vec<RewriteRule>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            engines: vec![SearchEngines::Brave, SearchEngines::DuckDuckGo],
+            engine_selection: EngineSelection::All,
+            image_engines: vec![ImageEngines::Brave],
+            news_engines: vec![NewsEngines::DuckDuckGo],
+            video_engines: vec![VideoEngines::DuckDuckGo],
+            places_engines: vec![PlacesEngines::Nominatim],
+            shopping_engines: Vec::new(),
+            scholar_engines: vec![
+                ScholarEngines::SemanticScholar,
+                ScholarEngines::Arxiv,
+                ScholarEngines::Crossref,
+                ScholarEngines::Biorxiv,
+                ScholarEngines::Zenodo,
+            ],
+            files_engines: Vec::new(),
+            book_engines: vec![BookEngines::OpenLibrary],
+            podcast_engines: vec![PodcastEngines::Itunes],
+            social_engines: Vec::new(),
+            dictionary_engines: vec![DictionaryEngines::DictionaryApi],
+            quote_engines: vec![QuoteEngines::YahooFinance, QuoteEngines::CoinGecko],
+            package_engines: vec![
+                PackageEngines::CratesIo,
+                PackageEngines::Npm,
+                PackageEngines::Pypi,
+            ],
+            app_engines: vec![AppEngines::FDroid, AppEngines::Flathub],
+            patent_engines: vec![PatentEngines::GooglePatents],
+            music_engines: vec![MusicEngines::MusicBrainz, MusicEngines::Bandcamp],
+            answer_providers: vec![
+                AnswerProviders::MyIp,
+                AnswerProviders::MyUserAgent,
+                AnswerProviders::WorldClock,
+                AnswerProviders::HashDigest,
+                AnswerProviders::UuidGenerator,
+                AnswerProviders::Base64Codec,
+                AnswerProviders::Wikipedia {
+                    lang: "en".to_string(),
+                },
+            ],
+            suggest_engines: Vec::new(),
+            engine_timeout: Duration::from_secs(DEFAULT_ENGINE_TIMEOUT),
+            budgets: HashMap::new(),
+            proxy: None,
+            circuit_isolation: false,
+            max_jitter: None,
+            decoy_rate: 0.0,
+            decoy_queries: Vec::new(),
+            cache_ttl: None,
+            max_cached_queries: None,
+            cache_refresh_after: None,
+            persist_cache: true,
+            hash_queries: false,
+            archive_responses: false,
+            archive_retention: Duration::from_secs(DEFAULT_ARCHIVE_RETENTION),
+            thumbnail_cache: false,
+            screenshots: 0,
+            ranking: Ranking::Domain,
+            query_expansion: false,
+            synonyms: Vec::new(),
+            safe_search: SafeSearch::Moderate,
+            adult_domains: HashSet::new(),
+            redirects: Redirects::default(),
+            rewrite_rules: Vec::new(),
+        }
+    }
+}
+
+/// Privacy frontend instances, e.g. `https://yewtu.be`, that links to the
+/// sites they front are rewritten to. `None` leaves links to that site alone.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Redirects {
+    /// Invidious instance for youtube.com and youtu.be
+    pub invidious: Option<String>,
+    /// Redlib instance for reddit.com
+    pub redlib: Option<String>,
+    /// Nitter instance for twitter.com and x.com
+    pub nitter: Option<String>,
+    /// Scribe instance for medium.com
+    pub scribe: Option<String>,
+}
+
+/// Replaces the first match of `pattern` in a result url with `replacement`,
+/// which may refer to capture groups as `$1` or `$name`.
+#[derive(Debug, Clone)]
+pub struct RewriteRule {
+    pub pattern: Regex,
+    pub replacement: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RewriteRuleFile {
+    #[serde(rename = "match")]
+    pattern: String,
+    replace: String,
+}
+
+/// How merged web results are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ranking {
+    /// Results whose domain matches the query words come first
+    Domain,
+    /// Reciprocal rank fusion of each engine's result positions
+    Fusion,
+}
+
+impl FromStr for Ranking {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "domain" => Ok(Self::Domain),
+            "fusion" => Ok(Self::Fusion),
+            _ => Err(format!("Unknown ranking: {s}")),
+        }
+    }
+}
+
+/// Most requests an engine may make, once spent it's skipped until requests
+/// age out of the window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    /// Per clock hour
+    pub hourly: Option<u32>,
+    /// Per rolling 24 hours
+    pub daily: Option<u32>,
+}
+
+/// Which of the configured engines a search without explicit engines uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineSelection {
+    /// Every configured engine
+    All,
+    /// The configured engines that have recently been fast and reliable,
+    /// judged from their recorded requests
+    Auto,
+}
+
+impl FromStr for EngineSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "all" => Ok(Self::All),
+            "auto" => Ok(Self::Auto),
+            _ => Err(format!("Unknown engine selection: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafeSearch {
+    Off,
+    Moderate,
+    Strict,
+}
+
+impl SafeSearch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SafeSearch::Off => "off",
+            SafeSearch::Moderate => "moderate",
+            SafeSearch::Strict => "strict",
+        }
+    }
+}
+
+impl FromStr for SafeSearch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "off" => Ok(Self::Off),
+            "moderate" => Ok(Self::Moderate),
+            "strict" => Ok(Self::Strict),
+            _ => Err(format!("Unknown safe search level: {s}")),
+        }
+    }
+}
+
+/// Named presets applied on top of the defaults, before any other layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// All traffic through a local Tor proxy, nothing written to the cache, hashed cache keys
+    PrivacyMax,
+    /// Brave and DuckDuckGo only, both quick to answer, with a short timeout
+    /// and week long caching. `engine_selection = "auto"` on top still drops
+    /// whichever of the two has lately been slow or failing.
+    Fast,
+    /// Every engine with fusion ranking
+    Comprehensive,
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "privacy-max" => Ok(Self::PrivacyMax),
+            "fast" => Ok(Self::Fast),
+            "comprehensive" => Ok(Self::Comprehensive),
+            _ => Err(format!("Unknown profile: {s}")),
+        }
+    }
+}
+
+impl Profile {
+    fn apply(self, config: &mut Config) {
+        match self {
+            Profile::PrivacyMax => {
+                config.proxy = Some(TOR_PROXY.to_string());
+                config.circuit_isolation = true;
+                config.persist_cache = false;
+                config.hash_queries = true;
+                // Tor adds a fair bit of latency
+                config.engine_timeout = Duration::from_secs(10);
+            }
+            Profile::Fast => {
+                config.engines = vec![SearchEngines::Brave, SearchEngines::DuckDuckGo];
+                config.engine_timeout = Duration::from_secs(2);
+                config.cache_ttl = Some(Duration::from_secs(7 * 24 * 60 * 60));
+            }
+            Profile::Comprehensive => {
+                config.engines = vec![
+                    SearchEngines::Brave,
+                    SearchEngines::DuckDuckGo,
+                    SearchEngines::Startpage,
+                    SearchEngines::Qwant,
+                    SearchEngines::Google,
+                    SearchEngines::Yandex,
+                    SearchEngines::Wiby,
+                    SearchEngines::Presearch,
+                    SearchEngines::Yahoo,
+                    SearchEngines::Baidu,
+                    SearchEngines::Naver,
+                    SearchEngines::Wikipedia {
+                        lang: "en".to_string(),
+                    },
+                ];
+                config.image_engines = vec![
+                    ImageEngines::Brave,
+                    ImageEngines::DuckDuckGo,
+                    ImageEngines::Bing,
+                    ImageEngines::Qwant,
+                    ImageEngines::Openverse,
+                    ImageEngines::WikimediaCommons,
+                    ImageEngines::Google,
+                ];
+                config.places_engines = vec![PlacesEngines::Nominatim, PlacesEngines::Photon];
+                config.dictionary_engines = vec![
+                    DictionaryEngines::DictionaryApi,
+                    DictionaryEngines::Wiktionary,
+                ];
+                config.engine_timeout = Duration::from_secs(5);
+                config.ranking = Ranking::Fusion;
+            }
+        }
+    }
+}
+
+/// Config file layout, every key is optional.
+///
+/// ```toml
+/// profile = "fast"
+/// engines = ["brave", "duckduckgo"]
+/// engine_selection = "all" # or "auto"
+/// image_engines = ["brave"]
+/// news_engines = ["duckduckgo"]
+/// video_engines = ["duckduckgo"]
+/// places_engines = ["nominatim"]
+/// shopping_engines = ["ebay:<client id>:<client secret>"]
+/// scholar_engines = ["arxiv", "crossref"]
+/// files_engines = ["torznab:http://localhost:9696/1/api?apikey=<key>"]
+/// book_engines = ["openlibrary"]
+/// podcast_engines = ["itunes", "podcastindex:<api key>:<api secret>"]
+/// social_engines = ["lemmy:https://lemmy.ml", "mastodon:https://mastodon.social"]
+/// dictionary_engines = ["dictionaryapi", "wiktionary"]
+/// quote_engines = ["yahoo-finance", "coingecko"]
+/// package_engines = ["crates.io", "npm", "pypi"]
+/// app_engines = ["fdroid", "flathub"]
+/// patent_engines = ["google-patents"]
+/// music_engines = ["musicbrainz", "bandcamp"]
+/// answer_providers = ["my-ip", "world-clock", "wikipedia", "duckduckgo"]
+/// suggest_engines = ["duckduckgo", "brave"]
+/// engine_timeout = 3 # seconds
+/// proxy = "socks5h://127.0.0.1:9050"
+/// circuit_isolation = false
+/// max_jitter = 500 # milliseconds
+/// decoy_rate = 0.2
+/// decoy_queries_file = "decoys.txt" # one query per line, # comments
+/// cache_ttl = 86400 # seconds
+/// max_cached_queries = 10000
+/// cache_refresh_after = 3600 # seconds
+/// persist_cache = true
+/// hash_queries = false
+/// archive_responses = false
+/// archive_retention = 604800 # seconds
+/// thumbnail_cache = false
+/// screenshots = 0
+/// ranking = "domain"
+/// query_expansion = false
+/// synonyms_file = "synonyms.txt" # comma separated words per line, # comments
+/// safe_search = "moderate"
+/// adult_domains_file = "adult-domains.txt" # one domain per line, # comments
+///
+/// [redirects]
+/// invidious = "https://yewtu.be"
+/// redlib = "https://safereddit.com"
+///
+/// [budgets.brave]
+/// hourly = 60
+/// daily = 1000
+///
+/// [[rewrite]]
+/// match = '^https://(www\.)?reddit\.com/'
+/// replace = "https://old.reddit.com/"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    profile: Option<String>,
+    engines: Option<Vec<String>>,
+    engine_selection: Option<String>,
+    image_engines: Option<Vec<String>>,
+    news_engines: Option<Vec<String>>,
+    video_engines: Option<Vec<String>>,
+    places_engines: Option<Vec<String>>,
+    shopping_engines: Option<Vec<String>>,
+    scholar_engines: Option<Vec<String>>,
+    files_engines: Option<Vec<String>>,
+    book_engines: Option<Vec<String>>,
+    podcast_engines: Option<Vec<String>>,
+    social_engines: Option<Vec<String>>,
+    dictionary_engines: Option<Vec<String>>,
+    quote_engines: Option<Vec<String>>,
+    package_engines: Option<Vec<String>>,
+    app_engines: Option<Vec<String>>,
+    patent_engines: Option<Vec<String>>,
+    music_engines: Option<Vec<String>>,
+    answer_providers: Option<Vec<String>>,
+    suggest_engines: Option<Vec<String>>,
+    engine_timeout: Option<u64>,
+    proxy: Option<String>,
+    circuit_isolation: Option<bool>,
+    max_jitter: Option<u64>,
+    decoy_rate: Option<f64>,
+    decoy_queries_file: Option<PathBuf>,
+    cache_ttl: Option<u64>,
+    max_cached_queries: Option<usize>,
+    cache_refresh_after: Option<u64>,
+    persist_cache: Option<bool>,
+    hash_queries: Option<bool>,
+    archive_responses: Option<bool>,
+    archive_retention: Option<u64>,
+    thumbnail_cache: Option<bool>,
+    screenshots: Option<usize>,
+    ranking: Option<String>,
+    query_expansion: Option<bool>,
+    synonyms_file: Option<PathBuf>,
+    safe_search: Option<String>,
+    adult_domains_file: Option<PathBuf>,
+    redirects: Option<Redirects>,
+    budgets: Option<HashMap<String, Budget>>,
+    rewrite: Option<Vec<RewriteRuleFile>>,
+}
+
+/// Builds a [`Config`] from layered sources, where later layers win:
+///
+/// 1. defaults
+/// 2. profile (`SEARCH_PROFILE`, else the builder's, else the config file's)
+/// 3. config file (`file()` or `SEARCH_CONFIG`)
+/// 4. values set on the builder
+/// 5. environment variables (`SEARCH_ENGINES`, `SEARCH_IMAGE_ENGINES`,
+///    `SEARCH_NEWS_ENGINES`, `SEARCH_VIDEO_ENGINES`, `SEARCH_PLACES_ENGINES`,
+///    `SEARCH_SHOPPING_ENGINES`, `SEARCH_SCHOLAR_ENGINES`, `SEARCH_FILES_ENGINES`,
+///    `SEARCH_BOOK_ENGINES`, `SEARCH_PODCAST_ENGINES`, `SEARCH_SOCIAL_ENGINES`,
+///    `SEARCH_DICTIONARY_ENGINES`, `SEARCH_QUOTE_ENGINES`, `SEARCH_PACKAGE_ENGINES`,
+///    `SEARCH_APP_ENGINES`, `SEARCH_PATENT_ENGINES`, `SEARCH_MUSIC_ENGINES`,
+///    `SEARCH_ANSWER_PROVIDERS`, `SEARCH_SUGGEST_ENGINES`, `SEARCH_ENGINE_TIMEOUT`,
+///    `SEARCH_PROXY`, `SEARCH_CACHE_TTL`, `SEARCH_CACHE_MAX_QUERIES`,
+///    `SEARCH_CACHE_REFRESH_AFTER`, `SEARCH_SAFE_SEARCH`)
+#[derive(Debug, Default, Clone)]
+pub struct ConfigBuilder {
+    file: Option<PathBuf>,
+    profile: Option<Profile>,
+    engines: Option<Vec<SearchEngines>>,
+    image_engines: Option<Vec<ImageEngines>>,
+    news_engines: Option<Vec<NewsEngines>>,
+    video_engines: Option<Vec<VideoEngines>>,
+    places_engines: Option<Vec<PlacesEngines>>,
+    shopping_engines: Option<Vec<ShoppingEngines>>,
+    scholar_engines: Option<Vec<ScholarEngines>>,
+    files_engines: Option<Vec<FilesEngines>>,
+    book_engines: Option<Vec<BookEngines>>,
+    podcast_engines: Option<Vec<PodcastEngines>>,
+    social_engines: Option<Vec<SocialEngines>>,
+    dictionary_engines: Option<Vec<DictionaryEngines>>,
+    quote_engines: Option<Vec<QuoteEngines>>,
+    package_engines: Option<Vec<PackageEngines>>,
+    app_engines: Option<Vec<AppEngines>>,
+    patent_engines: Option<Vec<PatentEngines>>,
+    music_engines: Option<Vec<MusicEngines>>,
+    answer_providers: Option<Vec<AnswerProviders>>,
+    suggest_engines: Option<Vec<SuggestEngines>>,
+    engine_timeout: Option<Duration>,
+    proxy: Option<String>,
+    cache_ttl: Option<Duration>,
+    max_cached_queries: Option<usize>,
+    cache_refresh_after: Option<Duration>,
+    safe_search: Option<SafeSearch>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.file = Some(path.into());
+        self
+    }
+
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub fn engines(mut self, engines: Vec<SearchEngines>) -> Self {
+        self.engines = Some(engines);
+        self
+    }
+
+    pub fn image_engines(mut self, engines: Vec<ImageEngines>) -> Self {
+        self.image_engines = Some(engines);
+        self
+    }
+
+    pub fn news_engines(mut self, engines: Vec<NewsEngines>) -> Self {
+        self.news_engines = Some(engines);
+        self
+    }
+
+    pub fn video_engines(mut self, engines: Vec<VideoEngines>) -> Self {
+        self.video_engines = Some(engines);
+        self
+    }
+
+    pub fn places_engines(mut self, engines: Vec<PlacesEngines>) -> Self {
+        self.places_engines = Some(engines);
+        self
+    }
+
+    pub fn shopping_engines(mut self, engines: Vec<ShoppingEngines>) -> Self {
+        self.shopping_engines = Some(engines);
+        self
+    }
+
+    pub fn scholar_engines(mut self, engines: Vec<ScholarEngines>) -> Self {
+        self.scholar_engines = Some(engines);
+        self
+    }
+
+    pub fn files_engines(mut self, engines: Vec<FilesEngines>) -> Self {
+        self.files_engines = Some(engines);
+        self
+    }
+
+    pub fn book_engines(mut self, engines: Vec<BookEngines>) -> Self {
+        self.book_engines = Some(engines);
+        self
+    }
+
+    pub fn podcast_engines(mut self, engines: Vec<PodcastEngines>) -> Self {
+        self.podcast_engines = Some(engines);
+        self
+    }
+
+    pub fn social_engines(mut self, engines: Vec<SocialEngines>) -> Self {
+        self.social_engines = Some(engines);
+        self
+    }
+
+    pub fn dictionary_engines(mut self, engines: Vec<DictionaryEngines>) -> Self {
+        self.dictionary_engines = Some(engines);
+        self
+    }
+
+    pub fn quote_engines(mut self, engines: Vec<QuoteEngines>) -> Self {
+        self.quote_engines = Some(engines);
+        self
+    }
+
+    pub fn package_engines(mut self, engines: Vec<PackageEngines>) -> Self {
+        self.package_engines = Some(engines);
+        self
+    }
+
+    pub fn app_engines(mut self, engines: Vec<AppEngines>) -> Self {
+        self.app_engines = Some(engines);
+        self
+    }
+
+    pub fn patent_engines(mut self, engines: Vec<PatentEngines>) -> Self {
+        self.patent_engines = Some(engines);
+        self
+    }
+
+    pub fn music_engines(mut self, engines: Vec<MusicEngines>) -> Self {
+        self.music_engines = Some(engines);
+        self
+    }
+
+    pub fn answer_providers(mut self, providers: Vec<AnswerProviders>) -> Self {
+        self.answer_providers = Some(providers);
+        self
+    }
+
+    pub fn suggest_engines(mut self, engines: Vec<SuggestEngines>) -> Self {
+        self.suggest_engines = Some(engines);
+        self
+    }
+
+    pub fn engine_timeout(mut self, timeout: Duration) -> Self {
+        self.engine_timeout = Some(timeout);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    pub fn max_cached_queries(mut self, max: usize) -> Self {
+        self.max_cached_queries = Some(max);
+        self
+    }
+
+    pub fn cache_refresh_after(mut self, age: Duration) -> Self {
+        self.cache_refresh_after = Some(age);
+        self
+    }
+
+    pub fn safe_search(mut self, safe_search: SafeSearch) -> Self {
+        self.safe_search = Some(safe_search);
+        self
+    }
+
+    pub fn build(self) -> Result<Config, ConfigError> {
+        self.build_with_env(|key| env::var(key).ok())
+    }
+
+    fn build_with_env(self, var: impl Fn(&str) -> Option<String>) -> Result<Config, ConfigError> {
+        let mut config = Config::default();
+
+        let file = match self
+            .file
+            .clone()
+            .or_else(|| var(CONFIG_PATH_ENV).map(PathBuf::from))
+        {
+            Some(path) => {
+                let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+                toml::from_str(&contents).map_err(ConfigError::Toml)?
+            }
+            None => ConfigFile::default(),
+        };
+
+        let profile = match (var(PROFILE_ENV), &file.profile) {
+            (Some(profile), _) => Some(parse_value(PROFILE_ENV, &profile)?),
+            (None, Some(profile)) if self.profile.is_none() => {
+                Some(parse_value("profile", profile)?)
+            }
+            _ => self.profile,
+        };
+        if let Some(profile) = profile {
+            profile.apply(&mut config);
+        }
+
+        config.apply_file(file)?;
+        self.apply(&mut config);
+        config.apply_env(var)?;
+
+        // a cache that can hold nothing would drop each search as it's stored
+        if config.max_cached_queries == Some(0) {
+            return Err(ConfigError::InvalidValue {
+                key: "max_cached_queries".to_string(),
+                value: "0".to_string(),
+            });
+        }
+
+        Ok(config)
+    }
+
+    fn apply(self, config: &mut Config) {
+        if let Some(engines) = self.engines {
+            config.engines = engines;
+        }
+        if let Some(engines) = self.image_engines {
+            config.image_engines = engines;
+        }
+        if let Some(engines) = self.news_engines {
+            config.news_engines = engines;
+        }
+        if let Some(engines) = self.video_engines {
+            config.video_engines = engines;
+        }
+        if let Some(engines) = self.places_engines {
+            config.places_engines = engines;
+        }
+        if let Some(engines) = self.shopping_engines {
+            config.shopping_engines = engines;
+        }
+        if let Some(engines) = self.scholar_engines {
+            config.scholar_engines = engines;
+        }
+        if let Some(engines) = self.files_engines {
+            config.files_engines = engines;
+        }
+        if let Some(engines) = self.book_engines {
+            config.book_engines = engines;
+        }
+        if let Some(engines) = self.podcast_engines {
+            config.podcast_engines = engines;
+        }
+        if let Some(engines) = self.social_engines {
+            config.social_engines = engines;
+        }
+        if let Some(engines) = self.dictionary_engines {
+            config.dictionary_engines = engines;
+        }
+        if let Some(engines) = self.quote_engines {
+            config.quote_engines = engines;
+        }
+        if let Some(engines) = self.package_engines {
+            config.package_engines = engines;
+        }
+        if let Some(engines) = self.app_engines {
+            config.app_engines = engines;
+        }
+        if let Some(engines) = self.patent_engines {
+            config.patent_engines = engines;
+        }
+        if let Some(engines) = self.music_engines {
+            config.music_engines = engines;
+        }
+        if let Some(providers) = self.answer_providers {
+            config.answer_providers = providers;
+        }
+        if let Some(engines) = self.suggest_engines {
+            config.suggest_engines = engines;
+        }
+        if let Some(timeout) = self.engine_timeout {
+            config.engine_timeout = timeout;
+        }
+        if let Some(proxy) = self.proxy {
+            config.proxy = Some(proxy);
+        }
+        if let Some(ttl) = self.cache_ttl {
+            config.cache_ttl = Some(ttl);
+        }
+        if let Some(max) = self.max_cached_queries {
+            config.max_cached_queries = Some(max);
+        }
+        if let Some(age) = self.cache_refresh_after {
+            config.cache_refresh_after = Some(age);
+        }
+        if let Some(safe_search) = self.safe_search {
+            config.safe_search = safe_search;
+        }
+    }
+}
+
+impl Config {
+    fn apply_file(&mut self, file: ConfigFile) -> Result<(), ConfigError> {
+        if let Some(engines) = file.engines {
+            self.engines = parse_list("engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(selection) = file.engine_selection {
+            self.engine_selection = parse_value("engine_selection", &selection)?;
+        }
+        if let Some(engines) = file.image_engines {
+            self.image_engines = parse_list("image_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.news_engines {
+            self.news_engines = parse_list("news_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.video_engines {
+            self.video_engines = parse_list("video_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.places_engines {
+            self.places_engines = parse_list("places_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.shopping_engines {
+            self.shopping_engines =
+                parse_list("shopping_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.scholar_engines {
+            self.scholar_engines =
+                parse_list("scholar_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.files_engines {
+            self.files_engines = parse_list("files_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.book_engines {
+            self.book_engines = parse_list("book_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.podcast_engines {
+            self.podcast_engines =
+                parse_list("podcast_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.social_engines {
+            self.social_engines = parse_list("social_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.dictionary_engines {
+            self.dictionary_engines =
+                parse_list("dictionary_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.quote_engines {
+            self.quote_engines = parse_list("quote_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.package_engines {
+            self.package_engines =
+                parse_list("package_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.app_engines {
+            self.app_engines = parse_list("app_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.patent_engines {
+            self.patent_engines = parse_list("patent_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.music_engines {
+            self.music_engines = parse_list("music_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(providers) = file.answer_providers {
+            self.answer_providers =
+                parse_list("answer_providers", providers.iter().map(String::as_str))?;
+        }
+        if let Some(engines) = file.suggest_engines {
+            self.suggest_engines =
+                parse_list("suggest_engines", engines.iter().map(String::as_str))?;
+        }
+        if let Some(secs) = file.engine_timeout {
+            self.engine_timeout = Duration::from_secs(secs);
+        }
+        if let Some(proxy) = file.proxy {
+            self.proxy = Some(proxy);
+        }
+        if let Some(isolation) = file.circuit_isolation {
+            self.circuit_isolation = isolation;
+        }
+        if let Some(millis) = file.max_jitter {
+            self.max_jitter = Some(Duration::from_millis(millis));
+        }
+        if let Some(rate) = file.decoy_rate {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(ConfigError::InvalidValue {
+                    key: "decoy_rate".to_string(),
+                    value: rate.to_string(),
+                });
+            }
+            self.decoy_rate = rate;
+        }
+        if let Some(path) = file.decoy_queries_file {
+            let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+            self.decoy_queries = parse_lines(&contents).map(str::to_string).collect();
+        }
+        if let Some(secs) = file.cache_ttl {
+            self.cache_ttl = Some(Duration::from_secs(secs));
+        }
+        if let Some(max) = file.max_cached_queries {
+            self.max_cached_queries = Some(max);
+        }
+        if let Some(secs) = file.cache_refresh_after {
+            self.cache_refresh_after = Some(Duration::from_secs(secs));
+        }
+        if let Some(persist) = file.persist_cache {
+            self.persist_cache = persist;
+        }
+        if let Some(hash) = file.hash_queries {
+            self.hash_queries = hash;
+        }
+        if let Some(archive) = file.archive_responses {
+            self.archive_responses = archive;
+        }
+        if let Some(secs) = file.archive_retention {
+            self.archive_retention = Duration::from_secs(secs);
+        }
+        if let Some(thumbnails) = file.thumbnail_cache {
+            self.thumbnail_cache = thumbnails;
+        }
+        if let Some(count) = file.screenshots {
+            self.screenshots = count;
+        }
+        if let Some(ranking) = file.ranking {
+            self.ranking = parse_value("ranking", &ranking)?;
+        }
+        if let Some(expansion) = file.query_expansion {
+            self.query_expansion = expansion;
+        }
+        if let Some(path) = file.synonyms_file {
+            let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+            self.synonyms = parse_synonyms(&contents);
+        }
+        if let Some(safe_search) = file.safe_search {
+            self.safe_search = parse_value("safe_search", &safe_search)?;
+        }
+        if let Some(path) = file.adult_domains_file {
+            let contents = fs::read_to_string(path).map_err(ConfigError::Io)?;
+            self.adult_domains = parse_domains(&contents);
+        }
+        if let Some(redirects) = file.redirects {
+            self.redirects = redirects;
+        }
+        if let Some(budgets) = file.budgets {
+            self.budgets = budgets
+                .into_iter()
+                .map(|(engine, budget)| Ok((engine_name(&engine)?, budget)))
+                .collect::<Result<_, ConfigError>>()?;
+        }
+        if let Some(rules) = file.rewrite {
+            self.rewrite_rules = rules
+                .into_iter()
+                .map(|rule| {
+                    let pattern =
+                        Regex::new(&rule.pattern).map_err(|_| ConfigError::InvalidValue {
+                            key: "rewrite.match".to_string(),
+                            value: rule.pattern.clone(),
+                        })?;
+                    Ok(RewriteRule {
+                        pattern,
+                        replacement: rule.replace,
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        if let Some(engines) = var(ENGINES_ENV) {
+            self.engines = parse_list(ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(IMAGE_ENGINES_ENV) {
+            self.image_engines = parse_list(IMAGE_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(NEWS_ENGINES_ENV) {
+            self.news_engines = parse_list(NEWS_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(VIDEO_ENGINES_ENV) {
+            self.video_engines = parse_list(VIDEO_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(PLACES_ENGINES_ENV) {
+            self.places_engines = parse_list(PLACES_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(SHOPPING_ENGINES_ENV) {
+            self.shopping_engines = parse_list(SHOPPING_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(SCHOLAR_ENGINES_ENV) {
+            self.scholar_engines = parse_list(SCHOLAR_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(FILES_ENGINES_ENV) {
+            self.files_engines = parse_list(FILES_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(BOOK_ENGINES_ENV) {
+            self.book_engines = parse_list(BOOK_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(PODCAST_ENGINES_ENV) {
+            self.podcast_engines = parse_list(PODCAST_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(SOCIAL_ENGINES_ENV) {
+            self.social_engines = parse_list(SOCIAL_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(DICTIONARY_ENGINES_ENV) {
+            self.dictionary_engines = parse_list(DICTIONARY_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(QUOTE_ENGINES_ENV) {
+            self.quote_engines = parse_list(QUOTE_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(PACKAGE_ENGINES_ENV) {
+            self.package_engines = parse_list(PACKAGE_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(APP_ENGINES_ENV) {
+            self.app_engines = parse_list(APP_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(PATENT_ENGINES_ENV) {
+            self.patent_engines = parse_list(PATENT_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(engines) = var(MUSIC_ENGINES_ENV) {
+            self.music_engines = parse_list(MUSIC_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(providers) = var(ANSWER_PROVIDERS_ENV) {
+            self.answer_providers = parse_list(ANSWER_PROVIDERS_ENV, providers.split(','))?;
+        }
+        if let Some(engines) = var(SUGGEST_ENGINES_ENV) {
+            self.suggest_engines = parse_list(SUGGEST_ENGINES_ENV, engines.split(','))?;
+        }
+        if let Some(secs) = var(ENGINE_TIMEOUT_ENV) {
+            self.engine_timeout = Duration::from_secs(parse_value(ENGINE_TIMEOUT_ENV, &secs)?);
+        }
+        if let Some(proxy) = var(PROXY_ENV) {
+            // an empty value disables a proxy set by an earlier layer
+            self.proxy = Some(proxy).filter(|p| !p.is_empty());
+        }
+        if let Some(secs) = var(CACHE_TTL_ENV) {
+            self.cache_ttl = Some(Duration::from_secs(parse_value(CACHE_TTL_ENV, &secs)?));
+        }
+        if let Some(max) = var(CACHE_MAX_QUERIES_ENV) {
+            self.max_cached_queries = Some(parse_value(CACHE_MAX_QUERIES_ENV, &max)?);
+        }
+        if let Some(secs) = var(CACHE_REFRESH_AFTER_ENV) {
+            let secs = parse_value(CACHE_REFRESH_AFTER_ENV, &secs)?;
+            self.cache_refresh_after = Some(Duration::from_secs(secs));
+        }
+        if let Some(safe_search) = var(SAFE_SEARCH_ENV) {
+            self.safe_search = parse_value(SAFE_SEARCH_ENV, &safe_search)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|_| ConfigError::InvalidValue {
+        key: key.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn parse_list<'a, T: FromStr>(
+    key: &str,
+    values: impl Iterator<Item = &'a str>,
+) -> Result<Vec<T>, ConfigError> {
+    values
+        .filter(|v| !v.trim().is_empty())
+        .map(|v| parse_value(key, v))
+        .collect()
+}
+
+/// Engines that need a key or instance url to be listed, by the kind they're
+/// listed with and the name they're budgeted under.
+const KEYED_ENGINES: [(&str, &str); 17] = [
+    ("searxng", SearxNG::NAME),
+    ("whoogle", Whoogle::NAME),
+    ("librey", LibreY::NAME),
+    ("librex", LibreY::NAME),
+    ("4get", FourGet::NAME),
+    ("brave-api", BraveApi::NAME),
+    ("kagi", Kagi::NAME),
+    ("bing-api", BingApi::NAME),
+    ("google-cse", GoogleCse::NAME),
+    ("flickr", Flickr::NAME),
+    ("unsplash", Unsplash::NAME),
+    ("pexels", Pexels::NAME),
+    ("podcastindex", PodcastIndex::NAME),
+    ("lemmy", Lemmy::NAME),
+    ("mastodon", Mastodon::NAME),
+    ("torznab", Torznab::NAME),
+    ("ebay", Ebay::NAME),
+];
+
+/// Name an engine is budgeted under in [`Config::budgets`]. Takes any engine
+/// as listed in the config, the kind of one needing a key or instance url
+/// (e.g. `brave-api`), or an engine or answer provider's name (e.g.
+/// `DuckDuckGo Instant Answers`).
+fn engine_name(engine: &str) -> Result<&'static str, ConfigError> {
+    let listed = listed_name::<SearchEngines>(engine, SearchEngines::name)
+        .or_else(|| listed_name::<ImageEngines>(engine, ImageEngines::name))
+        .or_else(|| listed_name::<SuggestEngines>(engine, SuggestEngines::name))
+        .or_else(|| vertical_name::<NewsEngines>(engine))
+        .or_else(|| vertical_name::<VideoEngines>(engine))
+        .or_else(|| vertical_name::<PlacesEngines>(engine))
+        .or_else(|| vertical_name::<ScholarEngines>(engine))
+        .or_else(|| vertical_name::<BookEngines>(engine))
+        .or_else(|| vertical_name::<PodcastEngines>(engine))
+        .or_else(|| vertical_name::<DictionaryEngines>(engine))
+        .or_else(|| vertical_name::<QuoteEngines>(engine))
+        .or_else(|| vertical_name::<PackageEngines>(engine))
+        .or_else(|| vertical_name::<AppEngines>(engine))
+        .or_else(|| vertical_name::<PatentEngines>(engine))
+        .or_else(|| vertical_name::<MusicEngines>(engine));
+    if let Some(name) = listed {
+        return Ok(name);
+    }
+
+    let key = engine.trim().to_lowercase();
+    let answer_providers = [
+        AnswerProviders::DuckDuckGo,
+        AnswerProviders::Wikipedia {
+            lang: "en".to_string(),
+        },
+    ]
+    .map(|provider| provider.provider().name());
+    KEYED_ENGINES
+        .iter()
+        .find(|(kind, name)| *kind == key || name.to_lowercase() == key)
+        .map(|(_, name)| *name)
+        .or_else(|| {
+            answer_providers
+                .into_iter()
+                .find(|name| name.to_lowercase() == key)
+        })
+        .ok_or_else(|| ConfigError::InvalidValue {
+            key: "budgets".to_string(),
+            value: engine.to_string(),
+        })
+}
+
+fn listed_name<T: FromStr>(engine: &str, name: fn(&T) -> &'static str) -> Option<&'static str> {
+    engine.trim().parse().ok().map(|engine| name(&engine))
+}
+
+fn vertical_name<V: Vertical + FromStr>(engine: &str) -> Option<&'static str> {
+    listed_name::<V>(engine, V::name)
+}
+
+/// Lines of a list file, blank lines and `#` comments skipped.
+fn parse_lines(contents: &str) -> impl Iterator<Item = &str> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+}
+
+/// Domain list with one domain per line.
+fn parse_domains(contents: &str) -> HashSet<String> {
+    parse_lines(contents)
+        .map(|domain| domain.trim_start_matches("*.").to_lowercase())
+        .collect()
+}
+
+/// Synonym groups, one comma separated group per line.
+fn parse_synonyms(contents: &str) -> Vec<Vec<String>> {
+    parse_lines(contents)
+        .map(|line| {
+            line.split(',')
+                .map(|word| word.trim().to_lowercase())
+                .filter(|word| !word.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        ConfigBuilder, ConfigError, ConfigFile, EngineSelection, Profile, Ranking, SafeSearch,
+        engine_name, parse_domains, parse_lines, parse_synonyms,
+    };
+    use crate::SearchEngines;
+    use std::{collections::HashMap, time::Duration};
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let config = ConfigBuilder::new().build_with_env(env(&[])).unwrap();
+
+        assert_eq!(config.engines.len(), 2);
+        assert_eq!(config.engine_timeout, Duration::from_secs(3));
+        assert!(config.proxy.is_none());
+        assert!(config.cache_ttl.is_none());
+    }
+
+    #[test]
+    fn test_env_overrides_builder() {
+        let config = ConfigBuilder::new()
+            .engines(vec![SearchEngines::Brave])
+            .engine_timeout(Duration::from_secs(10))
+            .proxy("http://localhost:8080")
+            .build_with_env(env(&[
+                ("SEARCH_ENGINES", "duckduckgo"),
+                ("SEARCH_PROXY", ""),
+                ("SEARCH_CACHE_TTL", "60"),
+                ("SEARCH_CACHE_MAX_QUERIES", "500"),
+            ]))
+            .unwrap();
+
+        assert!(matches!(config.engines[..], [SearchEngines::DuckDuckGo]));
+        assert_eq!(config.engine_timeout, Duration::from_secs(10));
+        assert!(config.proxy.is_none());
+        assert_eq!(config.cache_ttl, Some(Duration::from_secs(60)));
+        assert_eq!(config.max_cached_queries, Some(500));
+
+        let zero = ConfigBuilder::new()
+            .build_with_env(env(&[("SEARCH_CACHE_MAX_QUERIES", "0")]))
+            .unwrap_err();
+        assert!(matches!(zero, ConfigError::InvalidValue { .. }));
+    }
+
+    #[test]
+    fn test_file_layer() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            engines = ["brave"]
+            engine_timeout = 5
+            cache_ttl = 3600
+            cache_refresh_after = 600
+
+            [redirects]
+            invidious = "https://yewtu.be"
+
+            [budgets.ddg]
+            daily = 500
+            "#,
+        )
+        .unwrap();
+
+        let mut config = ConfigBuilder::new().build_with_env(env(&[])).unwrap();
+        config.apply_file(file).unwrap();
+        ConfigBuilder::new()
+            .engine_timeout(Duration::from_secs(7))
+            .apply(&mut config);
+
+        assert!(matches!(config.engines[..], [SearchEngines::Brave]));
+        assert_eq!(config.engine_timeout, Duration::from_secs(7));
+        assert_eq!(config.cache_ttl, Some(Duration::from_secs(3600)));
+        assert_eq!(config.cache_refresh_after, Some(Duration::from_secs(600)));
+        assert_eq!(
+            config.redirects.invidious.as_deref(),
+            Some("https://yewtu.be")
+        );
+        assert!(config.redirects.redlib.is_none());
+        assert_eq!(config.budgets["DuckDuckGo"].daily, Some(500));
+        assert_eq!(config.budgets["DuckDuckGo"].hourly, None);
+    }
+
+    #[test]
+    fn test_profile_layers() {
+        let config = ConfigBuilder::new()
+            .profile(Profile::PrivacyMax)
+            .engine_timeout(Duration::from_secs(20))
+            .build_with_env(env(&[]))
+            .unwrap();
+
+        assert_eq!(config.proxy.as_deref(), Some("socks5h://127.0.0.1:9050"));
+        assert!(!config.persist_cache);
+        assert!(config.hash_queries);
+        assert!(config.circuit_isolation);
+        assert_eq!(config.engine_timeout, Duration::from_secs(20));
+
+        // the environment profile replaces the builder's
+        let config = ConfigBuilder::new()
+            .profile(Profile::PrivacyMax)
+            .build_with_env(env(&[("SEARCH_PROFILE", "comprehensive")]))
+            .unwrap();
+
+        assert!(config.proxy.is_none());
+        assert!(config.persist_cache);
+        assert_eq!(config.ranking, Ranking::Fusion);
+
+        // the fast pair is searched as is unless auto selection is asked for
+        let config = ConfigBuilder::new()
+            .build_with_env(env(&[("SEARCH_PROFILE", "fast")]))
+            .unwrap();
+
+        assert!(matches!(
+            config.engines[..],
+            [SearchEngines::Brave, SearchEngines::DuckDuckGo]
+        ));
+        assert_eq!(config.engine_selection, EngineSelection::All);
+    }
+
+    #[test]
+    fn test_safe_search() {
+        let config = ConfigBuilder::new().build_with_env(env(&[])).unwrap();
+        assert_eq!(config.safe_search, SafeSearch::Moderate);
+
+        let config = ConfigBuilder::new()
+            .safe_search(SafeSearch::Off)
+            .build_with_env(env(&[("SEARCH_SAFE_SEARCH", "Strict")]))
+            .unwrap();
+        assert_eq!(config.safe_search, SafeSearch::Strict);
+
+        let domains = parse_domains("# adult hosts\nexample.xxx\n\n*.Example.com # wildcard\n");
+        assert_eq!(domains.len(), 2);
+        assert!(domains.contains("example.xxx"));
+        assert!(domains.contains("example.com"));
+    }
+
+    #[test]
+    fn test_obfuscation() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            max_jitter = 250
+            decoy_rate = 0.5
+            "#,
+        )
+        .unwrap();
+
+        let mut config = ConfigBuilder::new().build_with_env(env(&[])).unwrap();
+        assert!(config.max_jitter.is_none());
+        assert_eq!(config.decoy_rate, 0.0);
+
+        config.apply_file(file).unwrap();
+        assert_eq!(config.max_jitter, Some(Duration::from_millis(250)));
+        assert_eq!(config.decoy_rate, 0.5);
+
+        let file: ConfigFile = toml::from_str("decoy_rate = 2.0").unwrap();
+        assert!(matches!(
+            config.apply_file(file),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+
+        let queries: Vec<&str> =
+            parse_lines("weather tomorrow\n# comment\n\nrust traits # docs\n").collect();
+        assert_eq!(queries, ["weather tomorrow", "rust traits"]);
+    }
+
+    #[test]
+    fn test_rewrite_rules() {
+        let file: ConfigFile = toml::from_str(
+            r#"
+            [[rewrite]]
+            match = '^https://m\.'
+            replace = "https://"
+
+            [[rewrite]]
+            match = '^https://(www\.)?reddit\.com/'
+            replace = "https://old.reddit.com/"
+            "#,
+        )
+        .unwrap();
+
+        let mut config = ConfigBuilder::new().build_with_env(env(&[])).unwrap();
+        config.apply_file(file).unwrap();
+        assert_eq!(config.rewrite_rules.len(), 2);
+        assert_eq!(
+            config.rewrite_rules[1].replacement,
+            "https://old.reddit.com/"
+        );
+
+        let file: ConfigFile = toml::from_str("[[rewrite]]\nmatch = '('\nreplace = ''").unwrap();
+        assert!(matches!(
+            config.apply_file(file),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_synonyms() {
+        let groups = parse_synonyms("# groups\nRust, rustlang\nlonely\nfix,repair, mend # verbs\n");
+        assert_eq!(
+            groups,
+            [vec!["rust", "rustlang"], vec!["fix", "repair", "mend"]]
+        );
+    }
+
+    #[test]
+    fn test_budget_names() {
+        assert_eq!(engine_name("ddg").unwrap(), "DuckDuckGo");
+        assert_eq!(engine_name("brave-api").unwrap(), "Brave API");
+        assert_eq!(engine_name("ebay").unwrap(), "eBay");
+        assert_eq!(engine_name("Google CSE").unwrap(), "Google CSE");
+        assert_eq!(engine_name("openlibrary").unwrap(), "Open Library");
+        assert_eq!(
+            engine_name("duckduckgo instant answers").unwrap(),
+            "DuckDuckGo Instant Answers"
+        );
+        assert!(matches!(
+            engine_name("altavista"),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_env() {
+        let err = ConfigBuilder::new()
+            .build_with_env(env(&[("SEARCH_ENGINES", "brave,altavista")]))
+            .unwrap_err();
+
+        assert!(matches!(err, ConfigError::InvalidValue { .. }));
+    }
+}
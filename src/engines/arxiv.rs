@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
+use std::sync::LazyLock;
+
+use crate::{
+    cache::PaperRow,
+    engines::{EngineError, EngineInfo, PaperPage, VerticalEngine, new_rand_client},
+};
+
+const API_URL: &str = "https://export.arxiv.org/api/query";
+const MAX_RESULTS: u32 = 20;
+
+/// Preprints on arXiv, through its Atom search API.
+#[derive(Clone)]
+pub struct Arxiv;
+
+impl EngineInfo for Arxiv {
+    fn name(&self) -> &'static str {
+        "arXiv"
+    }
+}
+
+#[async_trait]
+impl VerticalEngine<PaperRow> for Arxiv {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<PaperPage, EngineError> {
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(API_URL)
+            .query(&[
+                ("search_query", format!("all:{query}").as_str()),
+                ("start", page.unwrap_or("0")),
+                ("max_results", &MAX_RESULTS.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body)
+    }
+}
+
+/// The feed is XML, which the HTML parser reads well enough: element names
+/// are lowercased and namespace prefixes stay part of them.
+fn parse_response(body: &str) -> Result<PaperPage, EngineError> {
+    static ENTRY: LazyLock<Selector> = LazyLock::new(|| Selector::parse("entry").unwrap());
+    static ID: LazyLock<Selector> = LazyLock::new(|| Selector::parse("id").unwrap());
+    static TITLE: LazyLock<Selector> = LazyLock::new(|| Selector::parse("title").unwrap());
+    static SUMMARY: LazyLock<Selector> = LazyLock::new(|| Selector::parse("summary").unwrap());
+    static PUBLISHED: LazyLock<Selector> = LazyLock::new(|| Selector::parse("published").unwrap());
+    static AUTHOR: LazyLock<Selector> = LazyLock::new(|| Selector::parse("author name").unwrap());
+    static DOI: LazyLock<Selector> = LazyLock::new(|| Selector::parse(r"arxiv\:doi").unwrap());
+    static TOTAL: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r"opensearch\:totalresults").unwrap());
+    static START: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r"opensearch\:startindex").unwrap());
+
+    let feed = Html::parse_document(body);
+    let number =
+        |selector: &Selector| -> Option<u32> { text(feed.select(selector).next()?)?.parse().ok() };
+    let total = number(&TOTAL)
+        .ok_or_else(|| EngineError::ParseError("No total results in arXiv feed".to_string()))?;
+    let start = number(&START).unwrap_or(0);
+
+    let papers: Vec<PaperRow> = feed
+        .select(&ENTRY)
+        .filter_map(|entry| {
+            let first = |selector: &Selector| text(entry.select(selector).next()?);
+            let url = first(&ID)?;
+            // errors, e.g. a malformed query, come back as an entry too
+            if !url.starts_with("http://arxiv.org/abs/")
+                && !url.starts_with("https://arxiv.org/abs/")
+            {
+                return None;
+            }
+
+            Some(PaperRow {
+                url: url.replacen("http://", "https://", 1),
+                title: first(&TITLE)?,
+                authors: entry.select(&AUTHOR).filter_map(text).collect(),
+                year: first(&PUBLISHED).and_then(|date| date.get(..4)?.parse().ok()),
+                doi: first(&DOI),
+                abstract_text: first(&SUMMARY),
+                kind: Some("preprint".to_string()),
+            })
+        })
+        .collect();
+
+    let next = start + MAX_RESULTS;
+    Ok(PaperPage {
+        next_page: (next < total && !papers.is_empty()).then(|| next.to_string()),
+        rows: papers,
+    })
+}
+
+/// An element's text with the feed's line wrapping undone.
+fn text(element: ElementRef) -> Option<String> {
+    let text = element.text().collect::<String>();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <link href="http://arxiv.org/api/query?search_query%3Dall%3Arust" rel="self" type="application/atom+xml"/>
+  <title type="html">ArXiv Query: search_query=all:rust&amp;id_list=&amp;start=0&amp;max_results=20</title>
+  <id>http://arxiv.org/api/cHxbiOdZaP56ODnBPIenZhzg5f8</id>
+  <updated>2024-05-01T00:00:00-04:00</updated>
+  <opensearch:totalResults xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">57</opensearch:totalResults>
+  <opensearch:startIndex xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">0</opensearch:startIndex>
+  <opensearch:itemsPerPage xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">20</opensearch:itemsPerPage>
+  <entry>
+    <id>http://arxiv.org/abs/1903.00982v3</id>
+    <updated>2019-07-10T12:00:00Z</updated>
+    <published>2019-03-03T20:48:56Z</published>
+    <title>Stacked Borrows: An Aliasing Model
+  for Rust</title>
+    <summary>  Type systems are useful not just for the safety guarantees they provide,
+but also for helping compilers generate more efficient code.
+</summary>
+    <author>
+      <name>Ralf Jung</name>
+    </author>
+    <author>
+      <name>Hoang-Hai Dang</name>
+    </author>
+    <arxiv:doi xmlns:arxiv="http://arxiv.org/schemas/atom">10.1145/3371109</arxiv:doi>
+    <link title="doi" href="http://dx.doi.org/10.1145/3371109" rel="related"/>
+    <link href="http://arxiv.org/abs/1903.00982v3" rel="alternate" type="text/html"/>
+    <link title="pdf" href="http://arxiv.org/pdf/1903.00982v3" rel="related" type="application/pdf"/>
+    <category xmlns:arxiv="http://arxiv.org/schemas/atom" term="cs.PL" scheme="http://arxiv.org/schemas/atom"/>
+  </entry>
+  <entry>
+    <id>http://arxiv.org/abs/2206.05503v1</id>
+    <published>2022-06-11T09:00:00Z</published>
+    <title>Learning and Programming Challenges of Rust</title>
+    <summary>Rust is a young systems programming language.</summary>
+    <author><name>Shuofei Zhu</name></author>
+  </entry>
+</feed>"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let borrows = &page.rows[0];
+        assert_eq!(borrows.url, "https://arxiv.org/abs/1903.00982v3");
+        assert_eq!(borrows.title, "Stacked Borrows: An Aliasing Model for Rust");
+        assert_eq!(borrows.authors, ["Ralf Jung", "Hoang-Hai Dang"]);
+        assert_eq!(borrows.year, Some(2019));
+        assert_eq!(borrows.doi.as_deref(), Some("10.1145/3371109"));
+        assert!(
+            borrows
+                .abstract_text
+                .as_deref()
+                .unwrap()
+                .starts_with("Type systems are useful not just")
+        );
+        assert_eq!(page.rows[1].doi, None);
+        assert_eq!(page.next_page.as_deref(), Some("20"));
+    }
+
+    #[test]
+    fn test_parse_error_entry() {
+        let page = parse_response(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <opensearch:totalResults xmlns:opensearch="http://a9.com/-/spec/opensearch/1.1/">1</opensearch:totalResults>
+  <entry>
+    <id>http://arxiv.org/api/errors#incorrect_id_format_for_1234</id>
+    <title>Error</title>
+    <summary>incorrect id format for 1234</summary>
+  </entry>
+</feed>"#,
+        )
+        .unwrap();
+
+        assert!(page.rows.is_empty());
+        assert_eq!(page.next_page, None);
+    }
+}
@@ -0,0 +1,264 @@
+use async_trait::async_trait;
+use encoding_rs::{Encoding, UTF_8};
+use regex::Regex;
+use reqwest::{
+    Client, Url,
+    header::{CONTENT_TYPE, LOCATION},
+    redirect::Policy,
+};
+use scraper::{Html, Selector};
+use std::{collections::HashMap, sync::LazyLock};
+use tokio::task::JoinSet;
+
+use crate::engines::{
+    EngineError, EngineInfo, SearchEngine, SerpPage, SerpSchema, new_rand_client,
+    parse_page_blocking, rand_client_builder,
+};
+
+const SEARCH_URL: &str = "https://www.baidu.com/s";
+
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    // ads are marked with data-tuiguang and carry no numeric id
+    SerpSchema::new(
+        "#content_left > div.c-container[id]:not([data-tuiguang])",
+        "h3",
+        "h3 a",
+    )
+    .container("#content_left")
+    .description(".c-abstract, [class^='content-right'], .c-span-last .c-color-text")
+    .next_page("#page a.n")
+});
+
+#[derive(Clone)]
+pub struct Baidu;
+
+impl EngineInfo for Baidu {
+    fn name(&self) -> &'static str {
+        "Baidu"
+    }
+}
+
+#[async_trait]
+impl SearchEngine for Baidu {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let resp = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[("wd", query), ("pn", page.unwrap_or("0")), ("ie", "utf-8")])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        // suspected bots are sent to a wappass.baidu.com verification page
+        let url = resp.url();
+        if url.host_str() != Some("www.baidu.com") || url.path().contains("captcha") {
+            return Err(EngineError::Blocked("Baidu served a captcha".to_string()));
+        }
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = resp.bytes().await.map_err(EngineError::ReqwestError)?;
+        let html = decode_html(&bytes, content_type.as_deref());
+
+        let mut page = parse_page_blocking(html, parse_response).await?;
+        resolve_links(&mut page).await?;
+        Ok(page)
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_response(body)
+    }
+}
+
+/// Decodes a page with the charset from its `Content-Type`, else from its
+/// `<meta>` tags. Baidu mostly serves UTF-8, but some of its pages and
+/// mirrors still use GBK.
+fn decode_html(bytes: &[u8], content_type: Option<&str>) -> String {
+    static META_CHARSET: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"(?i)<meta[^>]+charset=["']?([\w-]+)"#).unwrap());
+
+    let from_header = content_type
+        .and_then(|ct| {
+            ct.split(';')
+                .find_map(|p| p.trim().strip_prefix("charset="))
+        })
+        .map(|label| label.trim_matches('"').to_string());
+    let from_meta = || {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(2048)]);
+        META_CHARSET.captures(&head).map(|c| c[1].to_string())
+    };
+
+    let encoding = from_header
+        .or_else(from_meta)
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .unwrap_or(UTF_8);
+    let (html, _, _) = encoding.decode(bytes);
+    html.into_owned()
+}
+
+pub fn parse_response(html: &str) -> Result<SerpPage, EngineError> {
+    static CONTAINER: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("#content_left > div.c-container[mu]").unwrap());
+    static LINK: LazyLock<Selector> = LazyLock::new(|| Selector::parse("h3 a").unwrap());
+
+    let html = Html::parse_document(html);
+    let mut page = SEARCH_SCHEMA.parse_document(&html)?;
+
+    // result links are baidu.com/link redirects, most containers also
+    // carry the real url in their `mu` attribute
+    let real_urls: HashMap<&str, &str> = html
+        .select(&CONTAINER)
+        .filter_map(|c| {
+            let link = c.select(&LINK).next()?.value().attr("href")?;
+            Some((link, c.value().attr("mu")?))
+        })
+        .filter(|(_, mu)| mu.starts_with("http"))
+        .collect();
+    for result in &mut page.results {
+        if let Some(url) = real_urls.get(result.url.as_str()) {
+            result.url = url.to_string();
+        }
+    }
+    // only the offset is needed to fetch the next page
+    page.next_page = page.next_page.as_deref().and_then(pn_param);
+
+    Ok(page)
+}
+
+/// Resolves the result links no `mu` url was found for, by reading where
+/// they redirect to. Links that can't be resolved are kept as they are.
+async fn resolve_links(page: &mut SerpPage) -> Result<(), EngineError> {
+    let client = rand_client_builder()
+        .and_then(|b| b.redirect(Policy::none()).build())
+        .map_err(EngineError::ReqwestError)?;
+
+    let mut set = JoinSet::new();
+    for (i, result) in page.results.iter().enumerate() {
+        if is_redirect_link(&result.url) {
+            let client = client.clone();
+            let link = result.url.clone();
+            set.spawn(async move { (i, resolve_link(&client, &link).await) });
+        }
+    }
+    for (i, url) in set.join_all().await {
+        if let Some(url) = url {
+            page.results[i].url = url;
+        }
+    }
+
+    Ok(())
+}
+
+fn is_redirect_link(url: &str) -> bool {
+    Url::parse(url).is_ok_and(|u| {
+        u.host_str().is_some_and(|h| h.ends_with("baidu.com")) && u.path() == "/link"
+    })
+}
+
+/// Baidu answers a link either with a redirect or with a page that sets
+/// `window.location` to the target.
+async fn resolve_link(client: &Client, link: &str) -> Option<String> {
+    let resp = client.get(link).send().await.ok()?;
+    if let Some(location) = resp.headers().get(LOCATION) {
+        return location.to_str().ok().map(str::to_string);
+    }
+    location_replace(&resp.text().await.ok()?)
+}
+
+fn location_replace(html: &str) -> Option<String> {
+    static LOCATION_REPLACE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"(?:location\.replace\(|URL=)["']([^"']+)["']"#).unwrap());
+
+    LOCATION_REPLACE
+        .captures(html)
+        .map(|c| c[1].to_string())
+        .filter(|url| url.starts_with("http"))
+}
+
+/// `pn` query parameter of the next page link.
+fn pn_param(href: &str) -> Option<String> {
+    let url = Url::parse(SEARCH_URL).ok()?.join(href).ok()?;
+    url.query_pairs()
+        .find(|(k, _)| k == "pn")
+        .map(|(_, v)| v.into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_html, is_redirect_link, location_replace, parse_response};
+
+    #[test]
+    fn test_decode_html() {
+        // "百度" in GBK
+        let gbk = b"<html><head><meta charset=\"gbk\"></head><body>\xb0\xd9\xb6\xc8</body></html>";
+        assert!(decode_html(gbk, None).contains("百度"));
+        assert!(
+            decode_html(b"\xb0\xd9\xb6\xc8", Some("text/html; charset=GB2312")).contains("百度")
+        );
+        assert_eq!(
+            decode_html("百度".as_bytes(), Some("text/html;charset=utf-8")),
+            "百度"
+        );
+        assert_eq!(decode_html("百度".as_bytes(), None), "百度");
+    }
+
+    #[test]
+    fn test_location_replace() {
+        assert_eq!(
+            location_replace(
+                r#"<script>window.location.replace("https://www.rust-lang.org/")</script>"#
+            )
+            .as_deref(),
+            Some("https://www.rust-lang.org/")
+        );
+        assert_eq!(
+            location_replace(
+                r#"<meta http-equiv="refresh" content="0;URL='https://doc.rust-lang.org/'">"#
+            )
+            .as_deref(),
+            Some("https://doc.rust-lang.org/")
+        );
+        assert_eq!(location_replace("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let html = r#"
+            <div id="content_left">
+              <div class="result c-container" id="1" mu="https://www.rust-lang.org/">
+                <h3 class="t"><a href="http://www.baidu.com/link?url=abc">Rust 程序设计语言</a></h3>
+                <div class="c-abstract">一门赋予每个人构建可靠且高效软件能力的语言。</div>
+              </div>
+              <div class="result c-container" id="2">
+                <h3 class="t"><a href="http://www.baidu.com/link?url=def">Rust 文档</a></h3>
+              </div>
+              <div class="c-container" id="3" data-tuiguang="1">
+                <h3><a href="http://www.baidu.com/baidu.php?url=ad">广告</a></h3>
+              </div>
+            </div>
+            <div id="page">
+              <a class="n" href="/s?wd=rust&amp;pn=0">&lt;上一页</a>
+              <a class="n" href="/s?wd=rust&amp;pn=20">下一页&gt;</a>
+            </div>
+        "#;
+
+        let page = parse_response(html).unwrap();
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(page.results[0].title, "Rust 程序设计语言");
+        assert_eq!(
+            page.results[0].description,
+            "一门赋予每个人构建可靠且高效软件能力的语言。"
+        );
+        // left for resolve_links
+        assert!(is_redirect_link(&page.results[1].url));
+        assert!(!is_redirect_link(&page.results[0].url));
+        assert_eq!(page.next_page.as_deref(), Some("20"));
+    }
+}
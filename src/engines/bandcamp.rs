@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
+use std::sync::LazyLock;
+
+use crate::{
+    cache::MusicRow,
+    engines::{EngineError, EngineInfo, MusicPage, VerticalEngine, new_rand_client},
+};
+
+const SEARCH_URL: &str = "https://bandcamp.com/search";
+
+static RESULT: LazyLock<Selector> = LazyLock::new(|| Selector::parse("li.searchresult").unwrap());
+static ITEM_TYPE: LazyLock<Selector> = LazyLock::new(|| Selector::parse(".itemtype").unwrap());
+static HEADING: LazyLock<Selector> = LazyLock::new(|| Selector::parse(".heading a").unwrap());
+static SUBHEAD: LazyLock<Selector> = LazyLock::new(|| Selector::parse(".subhead").unwrap());
+static RELEASED: LazyLock<Selector> = LazyLock::new(|| Selector::parse(".released").unwrap());
+static ITEM_URL: LazyLock<Selector> = LazyLock::new(|| Selector::parse(".itemurl a").unwrap());
+static ART: LazyLock<Selector> = LazyLock::new(|| Selector::parse(".art img").unwrap());
+static NEXT: LazyLock<Selector> = LazyLock::new(|| Selector::parse("a.next").unwrap());
+
+/// Albums, tracks and artists on Bandcamp, from its search page. Labels and
+/// fan accounts in the results are skipped.
+#[derive(Clone)]
+pub struct Bandcamp;
+
+impl EngineInfo for Bandcamp {
+    fn name(&self) -> &'static str {
+        "Bandcamp"
+    }
+}
+
+#[async_trait]
+impl VerticalEngine<MusicRow> for Bandcamp {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<MusicPage, EngineError> {
+        // Bandcamp pages count from 1
+        let page: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+
+        let html = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[("q", query), ("page", &page.to_string())])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        Ok(parse_response(&html, page))
+    }
+}
+
+fn parse_response(html: &str, page: u32) -> MusicPage {
+    let document = Html::parse_document(html);
+
+    let items = document
+        .select(&RESULT)
+        .filter_map(|result| {
+            let kind = text(result, &ITEM_TYPE)?.to_lowercase();
+            if !matches!(kind.as_str(), "album" | "track" | "artist") {
+                return None;
+            }
+
+            let heading = result.select(&HEADING).next()?;
+            // the heading links carry search tracking parameters
+            let url = text(result, &ITEM_URL).or_else(|| {
+                let href = heading.value().attr("href")?;
+                Some(href.split('?').next()?.to_string())
+            })?;
+            // "by Artist" for albums, "from Album by Artist" for tracks and
+            // the location for artists
+            let artist = match kind.as_str() {
+                "artist" => None,
+                _ => text(result, &SUBHEAD)
+                    .and_then(|subhead| Some(subhead.rsplit_once("by ")?.1.trim().to_string())),
+            };
+
+            Some(MusicRow {
+                url,
+                title: normalize(heading.text().collect())?,
+                artist: artist.filter(|artist| !artist.is_empty()),
+                year: text(result, &RELEASED).and_then(|released| {
+                    released.get(released.len().checked_sub(4)?..)?.parse().ok()
+                }),
+                cover: result
+                    .select(&ART)
+                    .next()
+                    .and_then(|img| img.value().attr("src"))
+                    .map(str::to_string),
+                kind,
+            })
+        })
+        .collect();
+
+    MusicPage {
+        rows: items,
+        next_page: document
+            .select(&NEXT)
+            .next()
+            .map(|_| (page + 1).to_string()),
+    }
+}
+
+/// Text of the first element matching `selector` inside `parent`.
+fn text(parent: ElementRef, selector: &Selector) -> Option<String> {
+    normalize(parent.select(selector).next()?.text().collect())
+}
+
+fn normalize(text: String) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"<html><body><ul class="result-items">
+                <li class="searchresult data-search">
+                    <a class="artcont" href="https://band.bandcamp.com/album/first-light?from=search&amp;search_item_id=1">
+                        <div class="art"><img src="https://f4.bcbits.com/img/a0123456789_7.jpg"></div>
+                    </a>
+                    <div class="result-info">
+                        <div class="itemtype">
+                            ALBUM
+                        </div>
+                        <div class="heading">
+                            <a href="https://band.bandcamp.com/album/first-light?from=search&amp;search_item_id=1">
+                                First Light
+                            </a>
+                        </div>
+                        <div class="subhead">
+                            by The Band
+                        </div>
+                        <div class="released">
+                            released March 3, 2020
+                        </div>
+                        <div class="itemurl"><a href="https://band.bandcamp.com/album/first-light?from=search">https://band.bandcamp.com/album/first-light</a></div>
+                    </div>
+                </li>
+                <li class="searchresult data-search">
+                    <div class="result-info">
+                        <div class="itemtype">TRACK</div>
+                        <div class="heading"><a href="https://band.bandcamp.com/track/dawn?from=search">Dawn</a></div>
+                        <div class="subhead">from First Light by The Band</div>
+                    </div>
+                </li>
+                <li class="searchresult data-search">
+                    <div class="result-info">
+                        <div class="itemtype">ARTIST</div>
+                        <div class="heading"><a href="https://band.bandcamp.com?from=search">The Band</a></div>
+                        <div class="subhead">Berlin, Germany</div>
+                    </div>
+                </li>
+                <li class="searchresult data-search">
+                    <div class="result-info">
+                        <div class="itemtype">LABEL</div>
+                        <div class="heading"><a href="https://label.bandcamp.com?from=search">A Label</a></div>
+                    </div>
+                </li>
+            </ul>
+            <div class="pager"><a class="next" href="?page=2&amp;q=the+band">next</a></div>
+            </body></html>"#,
+            1,
+        );
+
+        assert_eq!(page.rows.len(), 3);
+        let album = &page.rows[0];
+        assert_eq!(album.url, "https://band.bandcamp.com/album/first-light");
+        assert_eq!(album.title, "First Light");
+        assert_eq!(album.artist.as_deref(), Some("The Band"));
+        assert_eq!(album.kind, "album");
+        assert_eq!(album.year, Some(2020));
+        assert_eq!(
+            album.cover.as_deref(),
+            Some("https://f4.bcbits.com/img/a0123456789_7.jpg")
+        );
+
+        let track = &page.rows[1];
+        assert_eq!(track.url, "https://band.bandcamp.com/track/dawn");
+        assert_eq!(track.artist.as_deref(), Some("The Band"));
+        assert_eq!(track.year, None);
+
+        let artist = &page.rows[2];
+        assert_eq!(artist.kind, "artist");
+        assert_eq!(artist.artist, None);
+        assert_eq!(page.next_page.as_deref(), Some("2"));
+    }
+}
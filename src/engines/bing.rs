@@ -0,0 +1,195 @@
+use async_trait::async_trait;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+use crate::{
+    cache::ImagesRow,
+    config::SafeSearch,
+    engines::{
+        EngineError, EngineInfo, ImageEngine, ImageFormat, ImageLicense, ImageOptions, ImagePage,
+        new_rand_client, parse_blocking, parse_json,
+        schema::{parse_dimensions, select_text},
+    },
+};
+
+const IMAGES_URL: &str = "https://www.bing.com/images/async";
+const COUNT: u32 = 35;
+
+/// Bing, only used for images so far.
+#[derive(Clone)]
+pub struct Bing;
+
+impl EngineInfo for Bing {
+    fn name(&self) -> &'static str {
+        "Bing"
+    }
+}
+
+/// The `m` attribute of each result link.
+#[derive(Deserialize)]
+struct ImageMeta {
+    /// Full size image
+    murl: String,
+    /// Thumbnail served by Bing
+    #[serde(default)]
+    turl: Option<String>,
+    /// Page the image was found on
+    #[serde(default)]
+    purl: Option<String>,
+    #[serde(default)]
+    t: String,
+}
+
+#[async_trait]
+impl ImageEngine for Bing {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        let first: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+        let safe_search = options.safe_search.unwrap_or(SafeSearch::Moderate);
+
+        let html = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(IMAGES_URL)
+            .query(&[
+                ("q", query),
+                ("first", &first.to_string()),
+                ("count", &COUNT.to_string()),
+                ("qft", &image_filters(options)),
+                ("adlt", safe_search.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        let mut images = parse_blocking(html, parse_image_response).await?;
+        // Bing filtered by license already, results don't say which
+        if let Some(license) = options.license {
+            for image in &mut images {
+                image.license = Some(license.label().to_string());
+            }
+        }
+
+        Ok(ImagePage {
+            next_page: (!images.is_empty()).then(|| (first + COUNT).to_string()),
+            images,
+            ..Default::default()
+        })
+    }
+}
+
+/// The `qft` parameter, `+filterui:` filters concatenated.
+fn image_filters(options: &ImageOptions) -> String {
+    let mut filters = String::new();
+    if let Some(size) = options.size {
+        filters.push_str(&format!("+filterui:imagesize-{}", size.as_str()));
+    }
+    if let Some(aspect) = options.aspect {
+        filters.push_str(&format!("+filterui:aspect-{}", aspect.as_str()));
+    }
+    if let Some(license) = options.license {
+        filters.push_str(match license {
+            ImageLicense::CreativeCommons => "+filterui:licenseType-Any",
+            ImageLicense::PublicDomain => "+filterui:license-L1",
+        });
+    }
+    match options.format {
+        Some(ImageFormat::Gif) => filters.push_str("+filterui:photo-animatedgif"),
+        Some(ImageFormat::Png) => filters.push_str("+filterui:photo-transparent"),
+        _ => {}
+    }
+    filters
+}
+
+pub fn parse_image_response(html: &str) -> Result<Vec<ImagesRow>, EngineError> {
+    static RESULT: LazyLock<Selector> = LazyLock::new(|| Selector::parse("div.imgpt").unwrap());
+    static LINK: LazyLock<Selector> = LazyLock::new(|| Selector::parse("a.iusc[m]").unwrap());
+    static INFO: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(".img_info .nowrap").unwrap());
+
+    let html = Html::parse_document(html);
+    let images = html
+        .select(&RESULT)
+        .filter_map(|result| {
+            let m = result.select(&LINK).next()?.value().attr("m")?;
+            let meta: ImageMeta = parse_json(m).ok()?;
+            let (width, height) = select_text(result, &INFO)
+                .and_then(|info| parse_dimensions(&info))
+                .unzip();
+
+            Some(ImagesRow {
+                url: meta.murl,
+                title: meta.t,
+                width,
+                height,
+                thumbnail: meta.turl,
+                source_url: meta.purl,
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    Ok(images)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{image_filters, parse_image_response};
+    use crate::engines::{ImageLicense, ImageOptions, ImageSize};
+
+    #[test]
+    fn test_parse_image_response() {
+        let html = r##"
+            <ul class="dgControl_list">
+              <li><div class="iuscp"><div class="imgpt">
+                <a class="iusc" m='{"purl":"https://www.rust-lang.org/","murl":"https://www.rust-lang.org/logos/rust-logo.png","turl":"https://tse1.mm.bing.net/th?id=OIP.abc","t":"Rust logo","desc":""}' href="/images/search?view=detailV2"></a>
+                <div class="img_info hon"><span class="nowrap">1024 x 1024 · png</span></div>
+              </div></div></li>
+              <li><div class="iuscp"><div class="imgpt">
+                <a class="iusc" m="not json" href="#"></a>
+              </div></div></li>
+            </ul>
+        "##;
+
+        let images = parse_image_response(html).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(
+            images[0].url,
+            "https://www.rust-lang.org/logos/rust-logo.png"
+        );
+        assert_eq!(images[0].title, "Rust logo");
+        assert_eq!(
+            images[0].thumbnail.as_deref(),
+            Some("https://tse1.mm.bing.net/th?id=OIP.abc")
+        );
+        assert_eq!(
+            images[0].source_url.as_deref(),
+            Some("https://www.rust-lang.org/")
+        );
+        assert_eq!(
+            (images[0].width, images[0].height),
+            (Some(1024), Some(1024))
+        );
+    }
+
+    #[test]
+    fn test_image_filters() {
+        assert_eq!(image_filters(&ImageOptions::default()), "");
+        let options = ImageOptions {
+            size: Some(ImageSize::Large),
+            license: Some(ImageLicense::PublicDomain),
+            ..Default::default()
+        };
+        assert_eq!(
+            image_filters(&options),
+            "+filterui:imagesize-large+filterui:license-L1"
+        );
+    }
+}
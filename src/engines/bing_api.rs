@@ -0,0 +1,146 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineInfo, JsonEngine},
+};
+
+const API_URL: &str = "https://api.bing.microsoft.com/v7.0/search";
+const COUNT: u32 = 10;
+
+/// Bing Web Search API, for an Azure subscription key. Unlike the scrapers
+/// it isn't challenged, only limited by the subscription's quota.
+#[derive(Debug, Clone)]
+pub struct BingApi {
+    api_key: String,
+}
+
+impl BingApi {
+    pub const NAME: &'static str = "Bing API";
+
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+impl EngineInfo for BingApi {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+/// Errors come back as an `ErrorResponse` without `webPages`.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BingApiResponse {
+    #[serde(default)]
+    web_pages: Option<BingWebPages>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BingWebPages {
+    #[serde(default)]
+    total_estimated_matches: u64,
+    #[serde(default)]
+    value: Vec<BingWebPage>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BingWebPage {
+    name: String,
+    url: String,
+    #[serde(default)]
+    snippet: String,
+    #[serde(default)]
+    date_published: Option<String>,
+    #[serde(default)]
+    language: Option<String>,
+}
+
+impl JsonEngine for BingApi {
+    type Response = BingApiResponse;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        client
+            .get(API_URL)
+            .header("Ocp-Apim-Subscription-Key", &self.api_key)
+            .query(&[
+                ("q", query),
+                ("count", &COUNT.to_string()),
+                ("offset", page.unwrap_or("0")),
+                ("responseFilter", "Webpages"),
+                ("textDecorations", "false"),
+            ])
+    }
+
+    fn next_page(&self, response: &BingApiResponse, page: Option<&str>) -> Option<String> {
+        let web = response.web_pages.as_ref()?;
+        let offset: u64 = page.and_then(|p| p.parse().ok()).unwrap_or(0) + COUNT as u64;
+        (!web.value.is_empty() && offset < web.total_estimated_matches).then(|| offset.to_string())
+    }
+
+    fn results(&self, response: BingApiResponse) -> Vec<ResultRow> {
+        response
+            .web_pages
+            .map(|web| web.value)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|page| ResultRow {
+                url: page.url,
+                title: page.name,
+                description: page.snippet,
+                published_at: page.date_published,
+                language: page.language,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BingApi, BingApiResponse};
+    use crate::engines::{JsonEngine, parse_json};
+
+    #[test]
+    fn test_results() {
+        let bing = BingApi::new("key");
+        let response: BingApiResponse = parse_json(
+            r#"{
+                "_type": "SearchResponse",
+                "webPages": {
+                    "totalEstimatedMatches": 25,
+                    "value": [
+                        {"name": "Rust", "url": "https://www.rust-lang.org/", "snippet": "A language empowering everyone.",
+                         "language": "en", "dateLastCrawled": "2024-05-01T00:00:00.0000000Z"},
+                        {"name": "The Book", "url": "https://doc.rust-lang.org/book/", "datePublished": "2023-02-09T00:00:00.0000000"}
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(bing.next_page(&response, Some("10")).as_deref(), Some("20"));
+        assert_eq!(bing.next_page(&response, Some("20")), None);
+        let results = bing.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Rust");
+        assert_eq!(results[0].language.as_deref(), Some("en"));
+        assert_eq!(
+            results[1].published_at.as_deref(),
+            Some("2023-02-09T00:00:00.0000000")
+        );
+
+        let error: BingApiResponse = parse_json(
+            r#"{"_type": "ErrorResponse", "errors": [{"code": "InvalidAuthorization", "message": "Access denied"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(bing.next_page(&error, None), None);
+        assert!(bing.results(error).is_empty());
+    }
+}
@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+use scraper::Html;
+use serde::Deserialize;
+
+use crate::{
+    cache::PaperRow,
+    engines::{EngineError, EngineInfo, PaperPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://www.ebi.ac.uk/europepmc/webservices/rest/search";
+const PAGE_SIZE: usize = 20;
+/// Cursor of the first page, later ones use the cursor returned with it
+const FIRST_CURSOR: &str = "*";
+
+/// Life science preprints on bioRxiv. bioRxiv's own API can only list
+/// preprints by date, so searches go through Europe PMC, which indexes every
+/// bioRxiv preprint with its abstract.
+#[derive(Clone)]
+pub struct Biorxiv;
+
+impl EngineInfo for Biorxiv {
+    fn name(&self) -> &'static str {
+        "bioRxiv"
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    /// Stays the same once the results run out
+    #[serde(default)]
+    next_cursor_mark: Option<String>,
+    result_list: ResultList,
+}
+
+#[derive(Deserialize)]
+struct ResultList {
+    #[serde(default)]
+    result: Vec<Preprint>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Preprint {
+    #[serde(default)]
+    doi: Option<String>,
+    /// May hold inline markup, e.g. `<i>`
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    author_list: Option<AuthorList>,
+    #[serde(default)]
+    pub_year: Option<String>,
+    /// HTML, often split into `<h4>` headed sections
+    #[serde(default)]
+    abstract_text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AuthorList {
+    #[serde(default)]
+    author: Vec<Author>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Author {
+    #[serde(default)]
+    first_name: Option<String>,
+    #[serde(default)]
+    last_name: Option<String>,
+    /// Consortia only have a full name
+    #[serde(default)]
+    full_name: Option<String>,
+}
+
+impl Author {
+    fn full_name(self) -> Option<String> {
+        match (self.first_name, self.last_name) {
+            (Some(first), Some(last)) => Some(format!("{first} {last}")),
+            (first, last) => last.or(first).or(self.full_name),
+        }
+    }
+}
+
+#[async_trait]
+impl VerticalEngine<PaperRow> for Biorxiv {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<PaperPage, EngineError> {
+        let cursor = page.unwrap_or(FIRST_CURSOR);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                (
+                    "query",
+                    format!(r#"({query}) AND SRC:PPR AND PUBLISHER:"bioRxiv""#).as_str(),
+                ),
+                ("resultType", "core"),
+                ("format", "json"),
+                ("pageSize", &PAGE_SIZE.to_string()),
+                ("cursorMark", cursor),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, cursor)
+    }
+}
+
+fn parse_response(body: &str, cursor: &str) -> Result<PaperPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    let full_page = response.result_list.result.len() >= PAGE_SIZE;
+
+    let papers = response
+        .result_list
+        .result
+        .into_iter()
+        .filter_map(|preprint| {
+            // bioRxiv DOIs all resolve to the latest version of the preprint
+            let doi = preprint.doi?;
+            Some(PaperRow {
+                url: format!("https://www.biorxiv.org/content/{doi}"),
+                title: html_text(&preprint.title?)?,
+                authors: preprint
+                    .author_list
+                    .map(|list| list.author)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(Author::full_name)
+                    .collect(),
+                year: preprint.pub_year.and_then(|year| year.parse().ok()),
+                doi: Some(doi),
+                abstract_text: preprint.abstract_text.and_then(|a| html_text(&a)),
+                kind: Some("preprint".to_string()),
+            })
+        })
+        .collect();
+
+    let next_page = response
+        .next_cursor_mark
+        .filter(|next| full_page && next != cursor);
+    Ok(PaperPage {
+        rows: papers,
+        next_page,
+    })
+}
+
+/// Text of an HTML fragment, with its whitespace collapsed and section
+/// headings kept apart from the text around them.
+fn html_text(html: &str) -> Option<String> {
+    let html = html.replace("<h4>", " <h4>").replace("</h4>", "</h4> ");
+    let text: String = Html::parse_fragment(&html).root_element().text().collect();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let mut results = String::new();
+        for i in 0..20 {
+            results.push_str(&format!(
+                r#"{{"id": "PPR{i}", "source": "PPR", "doi": "10.1101/2024.01.{i:02}.000000",
+                    "title": "Preprint {i}", "pubYear": "2024"}},"#
+            ));
+        }
+        let body = format!(
+            r#"{{
+                "version": "6.9", "hitCount": 45, "nextCursorMark": "AoIIP4AAACgxMjM0",
+                "request": {{"queryString": "crispr", "resultType": "core", "cursorMark": "*", "pageSize": 20}},
+                "resultList": {{"result": [{results}
+                    {{"id": "PPR999", "source": "PPR", "doi": "10.1101/2023.05.04.539400",
+                     "title": "Base editing in <i>Drosophila</i>.",
+                     "authorList": {{"author": [
+                        {{"fullName": "Doe J", "firstName": "Jane", "lastName": "Doe"}},
+                        {{"fullName": "Fly Consortium"}}
+                     ]}},
+                     "pubYear": "2023",
+                     "abstractText": "<h4>Background</h4>CRISPR base editors\n work <i>in vivo</i>."}},
+                    {{"id": "PPR1000", "source": "PPR", "title": "No DOI"}}
+                ]}}
+            }}"#
+        );
+        let page = parse_response(&body, "*").unwrap();
+
+        assert_eq!(page.rows.len(), 21);
+        let base = &page.rows[20];
+        assert_eq!(
+            base.url,
+            "https://www.biorxiv.org/content/10.1101/2023.05.04.539400"
+        );
+        assert_eq!(base.title, "Base editing in Drosophila.");
+        assert_eq!(base.authors, ["Jane Doe", "Fly Consortium"]);
+        assert_eq!(base.year, Some(2023));
+        assert_eq!(
+            base.abstract_text.as_deref(),
+            Some("Background CRISPR base editors work in vivo.")
+        );
+        assert_eq!(base.kind.as_deref(), Some("preprint"));
+        assert_eq!(page.next_page.as_deref(), Some("AoIIP4AAACgxMjM0"));
+
+        // the cursor comes back unchanged after the last page
+        let last = parse_response(
+            r#"{"hitCount": 45, "nextCursorMark": "AoIIP4AAACgxMjM0", "resultList": {"result": []}}"#,
+            "AoIIP4AAACgxMjM0",
+        )
+        .unwrap();
+        assert_eq!(last.next_page, None);
+    }
+}
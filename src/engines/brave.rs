@@ -1,11 +1,35 @@
 use crate::{
-    cache::{ImagesRow, ResultRow},
+    cache::{NewsRow, VideoRow},
     engines::{
-        EngineError, EngineInfo, ImageEngine, SearchEngine, new_rand_client, parse_images,
-        parse_search,
+        EngineError, EngineInfo, ImageEngine, ImageOptions, ImagePage, SearchEngine, SerpPage,
+        SerpSchema, SuggestEngine, capitalize, new_rand_client, parse_blocking,
+        parse_opensearch_suggestions, parse_page_blocking,
+        schema::{select_attr, select_text},
     },
 };
 use async_trait::async_trait;
+use reqwest::Url;
+use scraper::{Html, Selector};
+use std::sync::LazyLock;
+
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new(
+        "#results > .snippet[data-pos]:not(.standalone)",
+        ".title",
+        "a",
+    )
+    .container("#results")
+    .description(".generic-snippet, .video-snippet > .snippet-description")
+    .next_page("#pagination a[href*='offset=']")
+});
+
+static IMAGE_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new(".image-result", ".image-metadata-title", "img")
+        .href_attr("src")
+        .thumbnail("img", "src")
+        .source_url("a.image-metadata-url, a[href^='http']")
+        .dimensions(".image-metadata-dims")
+});
 
 #[derive(Clone)]
 pub struct Brave;
@@ -18,59 +42,309 @@ impl EngineInfo for Brave {
 
 #[async_trait]
 impl SearchEngine for Brave {
-    async fn search_results(&self, query: &str) -> Result<Vec<ResultRow>, EngineError> {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let url = search_url(query, page)?;
+
         let resp = new_rand_client()
             .map_err(EngineError::ReqwestError)?
-            .get(format!("https://search.brave.com/search?q={}", query))
+            .get(url)
             .send()
             .await
             .map_err(EngineError::ReqwestError)?;
 
-        parse_search_response(&resp.text().await.map_err(EngineError::ReqwestError)?)
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        parse_page_blocking(html, parse_search_response).await
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_search_response(body)
     }
 }
 
-pub fn parse_search_response(html: &str) -> Result<Vec<ResultRow>, EngineError> {
-    Ok(parse_search(
-        html,
-        "#results > .snippet[data-pos]:not(.standalone)",
-        ".title",
-        "a",
-        ".generic-snippet, .video-snippet > .snippet-description",
-    ))
+/// Web search for `query` from the page at `offset`, percent-encoded so `&`
+/// and `#` stay in the query.
+fn search_url(query: &str, offset: Option<&str>) -> Result<Url, EngineError> {
+    let mut params = vec![("q", query)];
+    params.extend(offset.map(|offset| ("offset", offset)));
+    Url::parse_with_params("https://search.brave.com/search", &params)
+        .map_err(|e| EngineError::ParseError(e.to_string()))
+}
+
+pub fn parse_search_response(html: &str) -> Result<SerpPage, EngineError> {
+    let html = Html::parse_document(html);
+
+    let mut page = SEARCH_SCHEMA.parse_document(&html)?;
+    // only the offset is needed to fetch the next page
+    page.next_page = page.next_page.as_deref().and_then(offset_param);
+    page.news = parse_news_cards(&html);
+    page.videos = parse_video_cards(&html);
+
+    Ok(page)
+}
+
+struct CardSelectors {
+    card: Selector,
+    link: Selector,
+    title: Selector,
+    source: Selector,
+    age: Selector,
+    thumbnail: Selector,
+    duration: Selector,
+}
+
+static NEWS_CARDS: LazyLock<CardSelectors> = LazyLock::new(|| CardSelectors::new("#news-carousel"));
+static VIDEO_CARDS: LazyLock<CardSelectors> =
+    LazyLock::new(|| CardSelectors::new("#video-carousel"));
+
+impl CardSelectors {
+    fn new(carousel: &str) -> Self {
+        let parse = |s: &str| Selector::parse(s).unwrap();
+        Self {
+            card: parse(&format!("{carousel} .card")),
+            link: parse("a[href]"),
+            title: parse(".title"),
+            source: parse(".netloc, .creator, .channel"),
+            age: parse(".age, time"),
+            thumbnail: parse("img"),
+            duration: parse(".duration"),
+        }
+    }
+}
+
+/// `offset` query parameter of a pagination link.
+fn offset_param(href: &str) -> Option<String> {
+    let url = Url::parse("https://search.brave.com")
+        .ok()?
+        .join(href)
+        .ok()?;
+    url.query_pairs()
+        .find(|(k, _)| k == "offset")
+        .map(|(_, v)| v.into_owned())
+}
+
+fn parse_news_cards(html: &Html) -> Vec<NewsRow> {
+    html.select(&NEWS_CARDS.card)
+        .filter_map(|card| {
+            Some(NewsRow {
+                url: select_attr(card, &NEWS_CARDS.link, "href")?,
+                title: select_text(card, &NEWS_CARDS.title)?.trim().to_string(),
+                source: select_text(card, &NEWS_CARDS.source)
+                    .map(|s| s.trim().to_string())
+                    .unwrap_or_default(),
+                published_at: select_text(card, &NEWS_CARDS.age).map(|a| a.trim().to_string()),
+            })
+        })
+        .collect()
+}
+
+fn parse_video_cards(html: &Html) -> Vec<VideoRow> {
+    html.select(&VIDEO_CARDS.card)
+        .filter_map(|card| {
+            Some(VideoRow {
+                url: select_attr(card, &VIDEO_CARDS.link, "href")?,
+                title: select_text(card, &VIDEO_CARDS.title)?.trim().to_string(),
+                thumbnail: select_attr(card, &VIDEO_CARDS.thumbnail, "src"),
+                duration: select_text(card, &VIDEO_CARDS.duration).map(|d| d.trim().to_string()),
+                channel: select_text(card, &VIDEO_CARDS.source).map(|c| c.trim().to_string()),
+            })
+        })
+        .collect()
 }
 
 #[async_trait]
 impl ImageEngine for Brave {
-    async fn search_images(&self, query: &str) -> Result<Vec<ImagesRow>, EngineError> {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        // image pages have no pagination links, the offset just counts pages
+        let offset: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+
+        let url = image_url(query, options, offset)?;
+
         let resp = new_rand_client()
             .map_err(EngineError::ReqwestError)?
-            .get(format!("https://search.brave.com/images?q={}", query))
+            .get(url)
             .send()
             .await
             .map_err(EngineError::ReqwestError)?;
 
-        parse_image_response(&resp.text().await.map_err(EngineError::ReqwestError)?)
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        let mut page = parse_blocking(html, parse_image_response).await?;
+        page.next_page = (!page.images.is_empty()).then(|| (offset + 1).to_string());
+
+        Ok(page)
+    }
+}
+
+/// Image search for `query` with the filters Brave supports, percent-encoded
+/// so a `&` or `#` in the query can't drop or override them.
+fn image_url(query: &str, options: &ImageOptions, offset: u32) -> Result<Url, EngineError> {
+    let mut params = vec![("q", query.to_string())];
+    if offset > 0 {
+        params.push(("offset", offset.to_string()));
+    }
+    if let Some(size) = options.size {
+        params.push(("size", capitalize(size.as_str())));
     }
+    if let Some(aspect) = options.aspect {
+        params.push(("layout", capitalize(aspect.as_str())));
+    }
+    if let Some(safe_search) = options.safe_search {
+        params.push(("safesearch", safe_search.as_str().to_string()));
+    }
+    // no license or format params, results carry no license so a license filter drops them all
+
+    Url::parse_with_params("https://search.brave.com/images", &params)
+        .map_err(|e| EngineError::ParseError(e.to_string()))
+}
+
+pub fn parse_image_response(html: &str) -> Result<ImagePage, EngineError> {
+    IMAGE_SCHEMA.parse_images(html)
 }
 
-pub fn parse_image_response(html: &str) -> Result<Vec<ImagesRow>, EngineError> {
-    Ok(parse_images(
-        html,
-        ".image-result",
-        ".image-metadata-title",
-        "img",
-    ))
+#[async_trait]
+impl SuggestEngine for Brave {
+    async fn suggest(&self, query: &str) -> Result<Vec<String>, EngineError> {
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get("https://search.brave.com/api/suggest")
+            .query(&[("q", query), ("rich", "false")])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_opensearch_suggestions(&body)
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use super::{image_url, offset_param, parse_search_response, search_url};
+    use crate::{
+        config::SafeSearch,
+        engines::{AspectRatio, ImageOptions, ImageSize},
+    };
+    use reqwest::Url;
+
+    /// Value of each parameter named `name` in `url`
+    fn params(url: &Url, name: &str) -> Vec<String> {
+        url.query_pairs()
+            .filter(|(k, _)| k == name)
+            .map(|(_, v)| v.into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            search_url("c# & rust", Some("2")).unwrap().as_str(),
+            "https://search.brave.com/search?q=c%23+%26+rust&offset=2"
+        );
+    }
+
+    #[test]
+    fn test_embedded_cards() {
+        let html = r#"
+            <div id="results">
+                <div class="snippet" data-pos="1">
+                    <a href="https://www.rust-lang.org"><div class="title">Rust</div></a>
+                    <div class="generic-snippet">A language</div>
+                </div>
+                <div id="news-carousel">
+                    <div class="card">
+                        <a href="https://news.com/rust-release"><div class="title">Rust 2.0 released</div></a>
+                        <span class="netloc">news.com</span><span class="age">3 hours ago</span>
+                    </div>
+                    <div class="card"><span class="title">No link</span></div>
+                </div>
+                <div id="video-carousel">
+                    <div class="card">
+                        <a href="https://video.com/watch?v=1"><img src="https://video.com/1.jpg"></a>
+                        <div class="title">Learn Rust</div>
+                        <span class="duration">12:03</span><span class="creator">Rustacean</span>
+                    </div>
+                </div>
+            </div>
+        "#;
+
+        let page = parse_search_response(html).unwrap();
+
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.news.len(), 1);
+        assert_eq!(page.news[0].url, "https://news.com/rust-release");
+        assert_eq!(page.news[0].source, "news.com");
+        assert_eq!(page.news[0].published_at.as_deref(), Some("3 hours ago"));
+        assert_eq!(page.videos.len(), 1);
+        assert_eq!(page.videos[0].title, "Learn Rust");
+        assert_eq!(page.videos[0].duration.as_deref(), Some("12:03"));
+        assert_eq!(page.videos[0].channel.as_deref(), Some("Rustacean"));
+        assert_eq!(
+            page.videos[0].thumbnail.as_deref(),
+            Some("https://video.com/1.jpg")
+        );
+    }
+
+    #[test]
+    fn test_image_url_keeps_safe_search() {
+        let options = ImageOptions {
+            safe_search: Some(SafeSearch::Strict),
+            ..Default::default()
+        };
+
+        for query in ["c# tutorial", "cats&safesearch=off"] {
+            let url = image_url(query, &options, 0).unwrap();
+            assert_eq!(url.fragment(), None);
+            assert_eq!(params(&url, "q"), [query]);
+            assert_eq!(params(&url, "safesearch"), ["strict"]);
+        }
+    }
+
+    #[test]
+    fn test_image_url_keeps_filters() {
+        let options = ImageOptions {
+            size: Some(ImageSize::Large),
+            aspect: Some(AspectRatio::Wide),
+            ..Default::default()
+        };
+
+        let url = image_url("c# logo", &options, 2).unwrap();
+        assert_eq!(params(&url, "q"), ["c# logo"]);
+        assert_eq!(params(&url, "offset"), ["2"]);
+        assert_eq!(params(&url, "size"), ["Large"]);
+        assert_eq!(params(&url, "layout"), ["Wide"]);
+    }
+
+    #[test]
+    fn test_offset_param() {
+        assert_eq!(
+            offset_param("/search?q=rust&offset=2&spellcheck=0").as_deref(),
+            Some("2")
+        );
+        assert_eq!(offset_param("/search?q=rust"), None);
+    }
+
     #[ignore]
     #[tokio::test]
     async fn test_brave_search_live() {
         use super::{Brave, SearchEngine};
         let brave = Brave;
-        let results = brave.search_results("rust async").await.unwrap();
+        let results = brave
+            .search_results("rust async", None)
+            .await
+            .unwrap()
+            .results;
         assert!(!results.is_empty());
 
         println!("Results: ");
@@ -84,7 +358,11 @@ mod test {
     async fn test_brave_images_live() {
         use super::{Brave, ImageEngine};
         let brave = Brave;
-        let images = brave.search_images("rust async").await.unwrap();
+        let images = brave
+            .search_images("rust async", &Default::default(), None)
+            .await
+            .unwrap()
+            .images;
         assert!(!images.is_empty());
 
         println!("Images: ");
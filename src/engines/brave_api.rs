@@ -0,0 +1,152 @@
+use regex::Regex;
+use reqwest::{Client, RequestBuilder, header::ACCEPT};
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineInfo, JsonEngine},
+};
+
+const API_URL: &str = "https://api.search.brave.com/res/v1/web/search";
+const MAX_OFFSET: u32 = 9; // the api pages at most this far
+
+/// Brave's official Search API, which needs a subscription token but isn't
+/// rate limited or challenged like the scraped result page.
+#[derive(Debug, Clone)]
+pub struct BraveApi {
+    api_key: String,
+}
+
+impl BraveApi {
+    pub const NAME: &'static str = "Brave API";
+
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+impl EngineInfo for BraveApi {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BraveApiResponse {
+    #[serde(default)]
+    query: Option<BraveApiQuery>,
+    #[serde(default)]
+    web: Option<BraveApiWeb>,
+}
+
+#[derive(Deserialize)]
+struct BraveApiQuery {
+    #[serde(default)]
+    more_results_available: bool,
+}
+
+#[derive(Deserialize)]
+struct BraveApiWeb {
+    #[serde(default)]
+    results: Vec<BraveApiHit>,
+}
+
+#[derive(Deserialize)]
+struct BraveApiHit {
+    url: String,
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    age: Option<String>,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    extra_snippets: Vec<String>,
+}
+
+impl JsonEngine for BraveApi {
+    type Response = BraveApiResponse;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        client
+            .get(API_URL)
+            .header("X-Subscription-Token", &self.api_key)
+            .header(ACCEPT, "application/json")
+            .query(&[("q", query), ("offset", page.unwrap_or("0"))])
+    }
+
+    fn next_page(&self, response: &BraveApiResponse, page: Option<&str>) -> Option<String> {
+        let more = response
+            .query
+            .as_ref()
+            .is_some_and(|q| q.more_results_available);
+        let offset: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0) + 1;
+        (more && offset <= MAX_OFFSET).then(|| offset.to_string())
+    }
+
+    fn results(&self, response: BraveApiResponse) -> Vec<ResultRow> {
+        response
+            .web
+            .map(|web| web.results)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|hit| ResultRow {
+                url: hit.url,
+                title: strip_tags(&hit.title),
+                description: strip_tags(&hit.description),
+                published_at: hit.age,
+                language: hit.language,
+                extra_snippets: hit.extra_snippets.iter().map(|s| strip_tags(s)).collect(),
+            })
+            .collect()
+    }
+}
+
+/// Matched query terms come back wrapped in `<strong>`.
+fn strip_tags(text: &str) -> String {
+    static TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"</?[a-zA-Z][^>]*>").unwrap());
+
+    TAG.replace_all(text, "").into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BraveApi, BraveApiResponse};
+    use crate::engines::{JsonEngine, parse_json};
+
+    #[test]
+    fn test_results() {
+        let brave = BraveApi::new("key");
+        let response: BraveApiResponse = parse_json(
+            r#"{
+                "type": "search",
+                "query": {"original": "rust", "more_results_available": true},
+                "web": {"type": "search", "results": [
+                    {"title": "<strong>Rust</strong> Programming Language", "url": "https://www.rust-lang.org/",
+                     "description": "A language empowering everyone.", "age": "2 days ago", "language": "en",
+                     "extra_snippets": ["Install <strong>Rust</strong>", "Learn Rust"]},
+                    {"title": "The Book", "url": "https://doc.rust-lang.org/book/"}
+                ]}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(brave.next_page(&response, Some("1")).as_deref(), Some("2"));
+        assert_eq!(brave.next_page(&response, Some("9")), None);
+        let results = brave.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Rust Programming Language");
+        assert_eq!(results[0].published_at.as_deref(), Some("2 days ago"));
+        assert_eq!(results[0].language.as_deref(), Some("en"));
+        assert_eq!(results[0].extra_snippets, ["Install Rust", "Learn Rust"]);
+        assert!(results[1].extra_snippets.is_empty());
+
+        let empty: BraveApiResponse = parse_json(r#"{"type": "search"}"#).unwrap();
+        assert_eq!(brave.next_page(&empty, None), None);
+        assert!(brave.results(empty).is_empty());
+    }
+}
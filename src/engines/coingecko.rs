@@ -0,0 +1,166 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{
+    cache::QuoteRow,
+    engines::{EngineError, EngineInfo, VerticalEngine, VerticalPage, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://api.coingecko.com/api/v3/search";
+const PRICE_URL: &str = "https://api.coingecko.com/api/v3/simple/price";
+
+/// Crypto pairs such as `BTC-USD`, through CoinGecko's keyless public API.
+/// Coins are looked up by ticker first, as prices are keyed by CoinGecko's
+/// own coin ids. Stock tickers aren't listed.
+#[derive(Clone)]
+pub struct CoinGecko;
+
+impl EngineInfo for CoinGecko {
+    fn name(&self) -> &'static str {
+        "CoinGecko"
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    coins: Vec<Coin>,
+}
+
+#[derive(Deserialize)]
+struct Coin {
+    id: String,
+    name: String,
+    symbol: String,
+}
+
+/// Prices by coin id, then by lowercase currency code, with `<currency>_24h_change`
+/// and `last_updated_at` alongside
+type PriceResponse = HashMap<String, HashMap<String, f64>>;
+
+#[async_trait]
+impl VerticalEngine<QuoteRow> for CoinGecko {
+    async fn search_page(
+        &self,
+        symbol: &str,
+        _page: Option<&str>,
+    ) -> Result<VerticalPage<QuoteRow>, EngineError> {
+        let Some((ticker, currency)) = symbol.split_once('-') else {
+            return Ok(VerticalPage::default());
+        };
+        let client = new_rand_client().map_err(EngineError::ReqwestError)?;
+
+        let body = client
+            .get(SEARCH_URL)
+            .query(&[("query", ticker)])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+        let Some(coin) = find_coin(&body, ticker)? else {
+            return Ok(VerticalPage::default());
+        };
+
+        let currency = currency.to_lowercase();
+        let body = client
+            .get(PRICE_URL)
+            .query(&[
+                ("ids", coin.id.as_str()),
+                ("vs_currencies", &currency),
+                ("include_24hr_change", "true"),
+                ("include_last_updated_at", "true"),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_price(&body, coin, &currency).map(|quote| Vec::from_iter(quote).into())
+    }
+}
+
+/// The coin with `ticker` as its symbol. Search results are ordered by
+/// market cap, so the best known of several coins sharing a ticker wins.
+fn find_coin(body: &str, ticker: &str) -> Result<Option<Coin>, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    Ok(response
+        .coins
+        .into_iter()
+        .find(|coin| coin.symbol.eq_ignore_ascii_case(ticker)))
+}
+
+fn parse_price(body: &str, coin: Coin, currency: &str) -> Result<Option<QuoteRow>, EngineError> {
+    let mut response: PriceResponse = parse_json(body)?;
+    let Some(prices) = response.remove(&coin.id) else {
+        return Ok(None);
+    };
+    // unknown currencies leave the coin's object empty
+    let Some(&price) = prices.get(currency) else {
+        return Ok(None);
+    };
+
+    let change_percent = prices.get(&format!("{currency}_24h_change")).copied();
+    // the price 24 hours ago, from the relative change
+    let change = change_percent.map(|percent| price - price / (1.0 + percent / 100.0));
+
+    Ok(Some(QuoteRow {
+        symbol: format!("{}-{}", coin.symbol.to_uppercase(), currency.to_uppercase()),
+        name: Some(coin.name),
+        price,
+        change,
+        change_percent,
+        currency: Some(currency.to_uppercase()),
+        market_time: prices
+            .get("last_updated_at")
+            .and_then(|time| chrono::DateTime::from_timestamp(*time as i64, 0))
+            .map(|time| time.to_rfc3339()),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_coin, parse_price};
+
+    #[test]
+    fn test_parse_price() {
+        let coin = find_coin(
+            r#"{"coins": [
+                {"id": "bitcoin", "name": "Bitcoin", "api_symbol": "bitcoin", "symbol": "BTC", "market_cap_rank": 1},
+                {"id": "bitcoin-cash", "name": "Bitcoin Cash", "api_symbol": "bitcoin-cash", "symbol": "BCH", "market_cap_rank": 20}
+            ], "exchanges": [], "categories": [], "nfts": []}"#,
+            "btc",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(coin.id, "bitcoin");
+
+        let quote = parse_price(
+            r#"{"bitcoin": {"eur": 50000.0, "eur_24h_change": 25.0, "last_updated_at": 1700000000}}"#,
+            coin,
+            "eur",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(quote.symbol, "BTC-EUR");
+        assert_eq!(quote.name.as_deref(), Some("Bitcoin"));
+        assert_eq!(quote.price, 50000.0);
+        assert_eq!(quote.change, Some(10000.0));
+        assert_eq!(quote.currency.as_deref(), Some("EUR"));
+        assert_eq!(
+            quote.market_time.as_deref(),
+            Some("2023-11-14T22:13:20+00:00")
+        );
+
+        let unknown = find_coin(r#"{"coins": []}"#, "zzz").unwrap();
+        assert!(unknown.is_none());
+    }
+}
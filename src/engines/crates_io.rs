@@ -0,0 +1,143 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    cache::PackageRow,
+    engines::{EngineError, EngineInfo, PackagePage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://crates.io/api/v1/crates";
+const PER_PAGE: u32 = 20;
+
+/// Rust crates, through the crates.io API.
+#[derive(Clone)]
+pub struct CratesIo;
+
+impl EngineInfo for CratesIo {
+    fn name(&self) -> &'static str {
+        "crates.io"
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    crates: Vec<Crate>,
+    meta: Meta,
+}
+
+#[derive(Deserialize)]
+struct Crate {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    downloads: Option<i64>,
+    /// Missing when every release is a pre-release
+    #[serde(default)]
+    max_stable_version: Option<String>,
+    #[serde(default)]
+    max_version: Option<String>,
+    #[serde(default)]
+    repository: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Meta {
+    /// Query string of the following page, `null` on the last one
+    #[serde(default)]
+    next_page: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<PackageRow> for CratesIo {
+    async fn search_page(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<PackagePage, EngineError> {
+        // crates.io pages count from 1
+        let page: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("q", query),
+                ("page", &page.to_string()),
+                ("per_page", &PER_PAGE.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, page)
+    }
+}
+
+fn parse_response(body: &str, page: u32) -> Result<PackagePage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+
+    let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+    let packages = response
+        .crates
+        .into_iter()
+        .map(|krate| PackageRow {
+            url: format!("https://crates.io/crates/{}", krate.name),
+            registry: "crates.io".to_string(),
+            version: krate.max_stable_version.or(krate.max_version),
+            description: non_empty(krate.description).map(|d| d.trim().to_string()),
+            downloads: krate.downloads,
+            repository: non_empty(krate.repository),
+            name: krate.name,
+        })
+        .collect();
+
+    Ok(PackagePage {
+        rows: packages,
+        next_page: response.meta.next_page.map(|_| (page + 1).to_string()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "crates": [
+                    {"id": "serde", "name": "serde", "description": "A generic serialization/deserialization framework\n",
+                     "downloads": 400000000, "recent_downloads": 50000000, "max_version": "1.0.210",
+                     "max_stable_version": "1.0.210", "repository": "https://github.com/serde-rs/serde"},
+                    {"id": "serde-pre", "name": "serde-pre", "description": null, "downloads": 12,
+                     "max_version": "0.1.0-alpha.1", "max_stable_version": null, "repository": ""}
+                ],
+                "meta": {"total": 2, "next_page": "?q=serde&page=2&per_page=20", "prev_page": null}
+            }"#,
+            1,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let serde = &page.rows[0];
+        assert_eq!(serde.url, "https://crates.io/crates/serde");
+        assert_eq!(serde.version.as_deref(), Some("1.0.210"));
+        assert_eq!(
+            serde.description.as_deref(),
+            Some("A generic serialization/deserialization framework")
+        );
+        assert_eq!(serde.downloads, Some(400_000_000));
+
+        let pre = &page.rows[1];
+        assert_eq!(pre.version.as_deref(), Some("0.1.0-alpha.1"));
+        assert_eq!(pre.repository, None);
+        assert_eq!(page.next_page.as_deref(), Some("2"));
+    }
+}
@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+use crate::{
+    cache::PaperRow,
+    engines::{EngineError, EngineInfo, PaperPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const WORKS_URL: &str = "https://api.crossref.org/works";
+const SELECT: &str = "DOI,URL,title,author,issued,abstract";
+const ROWS: u32 = 20;
+/// Deeper offsets are refused, cursors would be needed past it
+const MAX_OFFSET: u32 = 10_000;
+
+/// Crossref's registry of DOI metadata, covering most published journal
+/// articles and conference papers.
+#[derive(Clone)]
+pub struct Crossref;
+
+impl EngineInfo for Crossref {
+    fn name(&self) -> &'static str {
+        "Crossref"
+    }
+}
+
+#[derive(Deserialize)]
+struct WorksResponse {
+    status: String,
+    message: WorksMessage,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct WorksMessage {
+    #[serde(default)]
+    total_results: u32,
+    #[serde(default)]
+    items: Vec<Work>,
+}
+
+#[derive(Deserialize)]
+struct Work {
+    #[serde(rename = "DOI")]
+    doi: String,
+    /// The doi.org link
+    #[serde(rename = "URL")]
+    url: String,
+    /// Usually a single title
+    #[serde(default)]
+    title: Vec<String>,
+    #[serde(default)]
+    author: Vec<Author>,
+    #[serde(default)]
+    issued: Option<Issued>,
+    /// JATS XML, e.g. `<jats:p>...</jats:p>`
+    #[serde(default, rename = "abstract")]
+    abstract_text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Author {
+    #[serde(default)]
+    given: Option<String>,
+    #[serde(default)]
+    family: Option<String>,
+    /// Organizations have a name instead
+    #[serde(default)]
+    name: Option<String>,
+}
+
+impl Author {
+    fn full_name(self) -> Option<String> {
+        match (self.given, self.family) {
+            (Some(given), Some(family)) => Some(format!("{given} {family}")),
+            (given, family) => family.or(given).or(self.name),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Issued {
+    /// `[[year, month, day]]` with month and day optional, `[[null]]` when unknown
+    #[serde(rename = "date-parts")]
+    date_parts: Vec<Vec<Option<i32>>>,
+}
+
+#[async_trait]
+impl VerticalEngine<PaperRow> for Crossref {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<PaperPage, EngineError> {
+        let offset: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(WORKS_URL)
+            .query(&[
+                ("query", query),
+                ("select", SELECT),
+                ("rows", &ROWS.to_string()),
+                ("offset", &offset.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, offset)
+    }
+}
+
+fn parse_response(body: &str, offset: u32) -> Result<PaperPage, EngineError> {
+    let response: WorksResponse = parse_json(body)?;
+    if response.status != "ok" {
+        return Err(EngineError::ParseError(format!(
+            "Crossref returned status {}",
+            response.status
+        )));
+    }
+
+    let next = offset + ROWS;
+    let next_page =
+        (next < response.message.total_results && next <= MAX_OFFSET).then(|| next.to_string());
+
+    let papers = response
+        .message
+        .items
+        .into_iter()
+        .filter_map(|work| {
+            Some(PaperRow {
+                url: work.url,
+                title: work.title.into_iter().next()?,
+                authors: work
+                    .author
+                    .into_iter()
+                    .filter_map(Author::full_name)
+                    .collect(),
+                year: work
+                    .issued
+                    .and_then(|issued| *issued.date_parts.first()?.first()?),
+                doi: Some(work.doi),
+                abstract_text: work.abstract_text.and_then(|a| jats_text(&a)),
+                kind: None,
+            })
+        })
+        .collect();
+
+    Ok(PaperPage {
+        rows: papers,
+        next_page,
+    })
+}
+
+/// Text of a JATS abstract, without its markup and the "Abstract" heading
+/// some publishers start it with.
+fn jats_text(jats: &str) -> Option<String> {
+    static PARAGRAPH: LazyLock<Selector> = LazyLock::new(|| Selector::parse(r"jats\:p").unwrap());
+
+    let fragment = Html::parse_fragment(jats);
+    let paragraphs: Vec<String> = fragment
+        .select(&PARAGRAPH)
+        .map(|p| p.text().collect())
+        .collect();
+    let text = match paragraphs.is_empty() {
+        // plain text, or markup other than JATS
+        true => fragment.root_element().text().collect(),
+        false => paragraphs.join(" "),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{jats_text, parse_response};
+
+    #[test]
+    fn test_jats_text() {
+        assert_eq!(
+            jats_text(
+                "<jats:title>Abstract</jats:title><jats:p>Rust is\n  <jats:italic>safe</jats:italic>.</jats:p><jats:p>And fast.</jats:p>"
+            )
+            .as_deref(),
+            Some("Rust is safe. And fast.")
+        );
+        assert_eq!(jats_text("Plain text").as_deref(), Some("Plain text"));
+        assert_eq!(jats_text("<jats:p> </jats:p>"), None);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "status": "ok", "message-type": "work-list", "message-version": "1.0.0",
+                "message": {"total-results": 45, "items-per-page": 20, "items": [
+                    {"DOI": "10.1145/3158154", "URL": "https://doi.org/10.1145/3158154",
+                     "title": ["RustBelt: securing the foundations of the Rust programming language"],
+                     "author": [{"given": "Ralf", "family": "Jung", "sequence": "first"},
+                                {"name": "The Rust Project", "sequence": "additional"}],
+                     "issued": {"date-parts": [[2017, 12, 27]]},
+                     "abstract": "<jats:p>Rust is a new systems programming language.</jats:p>"},
+                    {"DOI": "10.1000/untitled", "URL": "https://doi.org/10.1000/untitled", "title": []},
+                    {"DOI": "10.1000/undated", "URL": "https://doi.org/10.1000/undated",
+                     "title": ["Undated"], "issued": {"date-parts": [[null]]}}
+                ]}
+            }"#,
+            20,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let rustbelt = &page.rows[0];
+        assert_eq!(rustbelt.url, "https://doi.org/10.1145/3158154");
+        assert_eq!(rustbelt.authors, ["Ralf Jung", "The Rust Project"]);
+        assert_eq!(rustbelt.year, Some(2017));
+        assert_eq!(rustbelt.doi.as_deref(), Some("10.1145/3158154"));
+        assert_eq!(
+            rustbelt.abstract_text.as_deref(),
+            Some("Rust is a new systems programming language.")
+        );
+        assert_eq!(page.rows[1].year, None);
+        // 40 of 45 results seen
+        assert_eq!(page.next_page.as_deref(), Some("40"));
+    }
+}
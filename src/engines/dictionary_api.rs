@@ -0,0 +1,159 @@
+use async_trait::async_trait;
+use reqwest::{StatusCode, Url};
+use serde::Deserialize;
+
+use crate::{
+    cache::DefinitionRow,
+    engines::{EngineError, EngineInfo, VerticalEngine, VerticalPage, new_rand_client, parse_json},
+};
+
+const ENTRIES_URL: &str = "https://api.dictionaryapi.dev/api/v2/entries/en/";
+
+/// English words in the keyless Free Dictionary API at dictionaryapi.dev,
+/// which serves Wiktionary's entries with an example for most senses.
+#[derive(Clone)]
+pub struct DictionaryApi;
+
+impl EngineInfo for DictionaryApi {
+    fn name(&self) -> &'static str {
+        "Free Dictionary"
+    }
+}
+
+/// One per etymology, e.g. "bass" the fish and "bass" the sound
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    word: String,
+    #[serde(default)]
+    phonetic: Option<String>,
+    #[serde(default)]
+    phonetics: Vec<Phonetic>,
+    #[serde(default)]
+    meanings: Vec<Meaning>,
+    #[serde(default)]
+    source_urls: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Phonetic {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Meaning {
+    part_of_speech: String,
+    #[serde(default)]
+    definitions: Vec<Sense>,
+}
+
+#[derive(Deserialize)]
+struct Sense {
+    definition: String,
+    #[serde(default)]
+    example: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<DefinitionRow> for DictionaryApi {
+    async fn search_page(
+        &self,
+        word: &str,
+        _page: Option<&str>,
+    ) -> Result<VerticalPage<DefinitionRow>, EngineError> {
+        let mut url = Url::parse(ENTRIES_URL).unwrap();
+        url.path_segments_mut().unwrap().pop_if_empty().push(word);
+
+        let resp = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(url)
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        // unknown words are a 404 with a "No Definitions Found" body
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(VerticalPage::default());
+        }
+        let body = resp
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body).map(VerticalPage::from)
+    }
+}
+
+fn parse_response(body: &str) -> Result<Vec<DefinitionRow>, EngineError> {
+    let entries: Vec<Entry> = parse_json(body)?;
+
+    let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+    let mut rows = Vec::new();
+    for entry in entries {
+        let phonetic = non_empty(entry.phonetic).or_else(|| {
+            entry
+                .phonetics
+                .into_iter()
+                .find_map(|phonetic| non_empty(phonetic.text))
+        });
+        let source_url = entry.source_urls.into_iter().next();
+
+        for meaning in entry.meanings {
+            for sense in meaning.definitions {
+                rows.push(DefinitionRow {
+                    word: entry.word.clone(),
+                    part_of_speech: meaning.part_of_speech.clone(),
+                    definition: sense.definition.trim().to_string(),
+                    examples: non_empty(sense.example).into_iter().collect(),
+                    phonetic: phonetic.clone(),
+                    source_url: source_url.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let rows = parse_response(
+            r#"[
+                {"word": "hello", "phonetics": [{"audio": ""}, {"text": "/həˈləʊ/", "audio": ""}],
+                 "meanings": [
+                    {"partOfSpeech": "noun", "definitions": [
+                        {"definition": "\"Hello!\" or an equivalent greeting.", "synonyms": [], "antonyms": []}
+                    ]},
+                    {"partOfSpeech": "verb", "definitions": [
+                        {"definition": "To greet with \"hello\".", "example": "I helloed him across the street.",
+                         "synonyms": [], "antonyms": []}
+                    ]}
+                 ],
+                 "license": {"name": "CC BY-SA 3.0", "url": "https://creativecommons.org/licenses/by-sa/3.0"},
+                 "sourceUrls": ["https://en.wiktionary.org/wiki/hello"]}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].word, "hello");
+        assert_eq!(rows[0].part_of_speech, "noun");
+        assert!(rows[0].examples.is_empty());
+        // the first phonetic with a text stands in for a missing `phonetic`
+        assert_eq!(rows[0].phonetic.as_deref(), Some("/həˈləʊ/"));
+        assert_eq!(
+            rows[0].source_url.as_deref(),
+            Some("https://en.wiktionary.org/wiki/hello")
+        );
+        assert_eq!(rows[1].part_of_speech, "verb");
+        assert_eq!(rows[1].examples, ["I helloed him across the street."]);
+    }
+}
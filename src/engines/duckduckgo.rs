@@ -1,11 +1,35 @@
 use async_trait::async_trait;
+use chrono::DateTime;
 use percent_encoding::percent_decode;
-use reqwest::Url;
+use reqwest::{StatusCode, Url};
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use std::sync::LazyLock;
 
-use crate::engines::{
-    EngineError, EngineInfo, SearchEngine, cache::ResultRow, new_rand_client, parse_search,
+use crate::{
+    cache::{ImagesRow, NewsRow, ResultRow, VideoRow},
+    config::{self, SafeSearch},
+    engines::{
+        EngineError, EngineInfo, ImageEngine, ImageFormat, ImageLicense, ImageOptions, ImagePage,
+        NewsPage, SearchEngine, SerpPage, SerpSchema, SuggestEngine, VerticalEngine, VideoPage,
+        capitalize, new_rand_client, parse_json, parse_page_blocking, vqd,
+    },
 };
 
+const HTML_URL: &str = "https://html.duckduckgo.com/html/";
+const LITE_URL: &str = "https://lite.duckduckgo.com/lite/";
+const IMAGES_URL: &str = "https://duckduckgo.com/i.js";
+const NEWS_URL: &str = "https://duckduckgo.com/news.js";
+const VIDEOS_URL: &str = "https://duckduckgo.com/v.js";
+const SUGGEST_URL: &str = "https://duckduckgo.com/ac/";
+
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new(".serp__results .result", ".result__a", ".result__a")
+        .container(".serp__results")
+        .description(".result__snippet")
+        .next_page(".nav-link input[type=submit][value=Next]")
+});
+
 #[derive(Clone)]
 pub struct DuckDuckGo;
 
@@ -17,35 +41,188 @@ impl EngineInfo for DuckDuckGo {
 
 #[async_trait]
 impl SearchEngine for DuckDuckGo {
-    async fn search_results(&self, query: &str) -> Result<Vec<ResultRow>, EngineError> {
-        let resp = new_rand_client()
-            .map_err(EngineError::ReqwestError)?
-            .get(format!("https://html.duckduckgo.com/html?q={}", query))
-            .send()
-            .await
-            .map_err(EngineError::ReqwestError)?;
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        if let Some(page) = page {
+            return next_page(page).await;
+        }
+
+        let html = fetch(search_url(HTML_URL, query)?).await?;
+        if !is_anomaly(&html) {
+            return parse_page_blocking(html, parse_response).await;
+        }
+
+        // The lite frontend is often still served when the html one wants a captcha
+        let html = fetch(search_url(LITE_URL, query)?).await?;
+        if is_anomaly(&html) {
+            return Err(EngineError::Blocked(
+                "DuckDuckGo served an anomaly page".to_string(),
+            ));
+        }
 
-        parse_response(&resp.text().await.map_err(EngineError::ReqwestError)?)
+        parse_page_blocking(html, parse_lite_response).await
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        if is_lite(body) {
+            parse_lite_response(body)
+        } else {
+            parse_response(body)
+        }
     }
 }
 
-pub fn parse_response(html: &str) -> Result<Vec<ResultRow>, EngineError> {
-    let results = parse_search(
-        html,
-        ".serp__results .result",
-        ".result__a",
-        ".result__a",
-        ".result__snippet",
-    )
-    .into_iter()
-    .filter(|r| !is_sponsored(&r.url))
-    .map(|mut r| {
-        r.url = extract_ddg_url(&r.url).unwrap();
-        r
-    })
-    .collect();
+/// `base` searching for `query`, percent-encoded so `&` and `#` stay in it.
+fn search_url(base: &str, query: &str) -> Result<Url, EngineError> {
+    Url::parse_with_params(base, &[("q", query)])
+        .map_err(|e| EngineError::ParseError(e.to_string()))
+}
+
+async fn fetch(url: Url) -> Result<String, EngineError> {
+    new_rand_client()
+        .map_err(EngineError::ReqwestError)?
+        .get(url)
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)
+}
+
+/// Follows a token from [`parse_next_form`] by resubmitting its form.
+async fn next_page(token: &str) -> Result<SerpPage, EngineError> {
+    let (action, form) = token.split_once('?').unwrap_or((token, ""));
+
+    let html = new_rand_client()
+        .map_err(EngineError::ReqwestError)?
+        .post(action)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(form.to_string())
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)?;
+
+    if is_anomaly(&html) {
+        return Err(EngineError::Blocked(
+            "DuckDuckGo served an anomaly page".to_string(),
+        ));
+    }
+
+    if action.starts_with(LITE_URL) {
+        parse_page_blocking(html, parse_lite_response).await
+    } else {
+        parse_page_blocking(html, parse_response).await
+    }
+}
+
+/// DuckDuckGo has no pagination links, the next page is a form posting the
+/// offset (`s`, `dc`, ...) as hidden inputs. The token is the form's absolute
+/// action with those inputs urlencoded as its query.
+fn parse_next_form(html: &Html, base: &str) -> Option<String> {
+    static FORM: LazyLock<Selector> = LazyLock::new(|| Selector::parse("form").unwrap());
+    static SUBMIT: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("input[type=submit]").unwrap());
+    static HIDDEN: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("input[type=hidden][name]").unwrap());
+
+    let form = html.select(&FORM).find(|form| {
+        form.select(&SUBMIT).any(|s| {
+            s.value()
+                .attr("value")
+                .is_some_and(|v| v.starts_with("Next"))
+        })
+    })?;
+
+    let mut url = Url::parse(base)
+        .ok()?
+        .join(form.value().attr("action").unwrap_or_default())
+        .ok()?;
+    url.set_query(None);
+    url.query_pairs_mut()
+        .extend_pairs(form.select(&HIDDEN).map(|input| {
+            (
+                input.value().attr("name").unwrap_or_default(),
+                input.value().attr("value").unwrap_or_default(),
+            )
+        }));
+
+    Some(url.into())
+}
+
+/// DuckDuckGo's bot challenge page
+fn is_anomaly(html: &str) -> bool {
+    html.contains("anomaly-modal") || html.contains("/anomaly.js")
+}
+
+/// Whether a page came from the lite frontend rather than the html one.
+fn is_lite(html: &str) -> bool {
+    html.contains("result-link") && !html.contains("serp__results")
+}
+
+pub fn parse_response(html: &str) -> Result<SerpPage, EngineError> {
+    let html = Html::parse_document(html);
+
+    let mut page = SEARCH_SCHEMA.parse_document(&html)?;
+    page.next_page = parse_next_form(&html, HTML_URL);
+    page.results = page
+        .results
+        .into_iter()
+        .filter(|r| !is_sponsored(&r.url))
+        .filter_map(|mut r| {
+            r.url = extract_ddg_url(&r.url)?;
+            Some(r)
+        })
+        .collect();
+
+    Ok(page)
+}
 
-    Ok(results)
+/// Parses lite.duckduckgo.com, where each result is spread over several
+/// table rows so it can't be described by a [`SerpSchema`].
+pub fn parse_lite_response(html: &str) -> Result<SerpPage, EngineError> {
+    static LITE_SELECTOR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("a.result-link, td.result-snippet").unwrap());
+
+    let html = Html::parse_document(html);
+    let mut results: Vec<ResultRow> = Vec::new();
+
+    // links and snippets come back in document order, a snippet belongs to the link before it
+    for element in html.select(&LITE_SELECTOR) {
+        let text = element.text().collect::<String>().trim().to_string();
+
+        if element.value().name() == "a" {
+            results.push(ResultRow {
+                url: element.value().attr("href").unwrap_or_default().to_string(),
+                title: text,
+                description: String::new(),
+                ..Default::default()
+            });
+        } else if let Some(last) = results.last_mut() {
+            last.description = text;
+        }
+    }
+
+    let results: Vec<ResultRow> = results
+        .into_iter()
+        .filter_map(|mut r| {
+            r.url = extract_ddg_url(&r.url)?;
+            Some(r)
+        })
+        .filter(|r| !r.url.is_empty() && !is_sponsored(&r.url))
+        .collect();
+
+    let mut page = SerpPage::from(results);
+    page.next_page = parse_next_form(&html, LITE_URL);
+    page.has_next_page = page.next_page.is_some();
+
+    Ok(page)
 }
 
 fn extract_ddg_url(ddg_href: &str) -> Option<String> {
@@ -68,14 +245,571 @@ fn is_sponsored(href: &str) -> bool {
         || href.contains("duckduckgo.com/?uddg=")
 }
 
+#[derive(Deserialize)]
+struct ImagesResponse {
+    #[serde(default)]
+    results: Vec<ImageHit>,
+    /// Relative url of the next page, absent on the last one
+    #[serde(default)]
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ImageHit {
+    image: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    thumbnail: Option<String>,
+    /// Page the image was found on
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+#[async_trait]
+impl ImageEngine for DuckDuckGo {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        let vqd = vqd::vqd_token(query).await?;
+        let safe_search = match options.safe_search {
+            Some(SafeSearch::Off) => "-1",
+            _ => "1",
+        };
+
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(IMAGES_URL)
+            .query(&[
+                ("l", "wt-wt"),
+                ("o", "json"),
+                ("q", query),
+                ("vqd", vqd.as_str()),
+                ("f", image_filters(options).as_str()),
+                ("p", safe_search),
+            ]);
+        if let Some(offset) = page {
+            request = request.query(&[("s", offset)]);
+        }
+
+        let resp = request.send().await.map_err(EngineError::ReqwestError)?;
+        if resp.status() == StatusCode::FORBIDDEN {
+            // the token expired or was issued for another query
+            vqd::invalidate(query);
+            return Err(EngineError::Blocked(
+                "DuckDuckGo rejected the vqd token".to_string(),
+            ));
+        }
+        let body = resp.text().await.map_err(EngineError::ReqwestError)?;
+
+        let mut page = parse_image_response(&body)?;
+        // DuckDuckGo filtered by license already, results don't say which
+        if let Some(license) = options.license {
+            for image in &mut page.images {
+                image.license = Some(license.label().to_string());
+            }
+        }
+        Ok(page)
+    }
+}
+
+/// The `f` parameter, comma separated `key:value` filters.
+fn image_filters(options: &ImageOptions) -> String {
+    let size = options.size.map(|s| capitalize(s.as_str()));
+    let layout = options.aspect.map(|a| capitalize(a.as_str()));
+    let kind = match options.format {
+        Some(ImageFormat::Gif) => "gif",
+        Some(ImageFormat::Png) => "transparent",
+        _ => "",
+    };
+    let license = match options.license {
+        Some(ImageLicense::CreativeCommons) => "Share",
+        Some(ImageLicense::PublicDomain) => "Public",
+        None => "",
+    };
+
+    format!(
+        "size:{},type:{kind},layout:{},color:,license:{license}",
+        size.unwrap_or_default(),
+        layout.unwrap_or_default()
+    )
+}
+
+fn parse_image_response(body: &str) -> Result<ImagePage, EngineError> {
+    let response: ImagesResponse = parse_json(body)?;
+    let next_page = response.next.as_deref().and_then(s_param);
+
+    Ok(ImagePage {
+        images: response
+            .results
+            .into_iter()
+            .map(|hit| ImagesRow {
+                url: hit.image,
+                title: hit.title,
+                width: hit.width,
+                height: hit.height,
+                thumbnail: hit.thumbnail,
+                source_url: hit.url,
+                ..Default::default()
+            })
+            .collect(),
+        next_page,
+        ..Default::default()
+    })
+}
+
+#[derive(Deserialize)]
+struct NewsResponse {
+    #[serde(default)]
+    results: Vec<NewsHit>,
+    /// Relative url of the next page, absent on the last one
+    #[serde(default)]
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NewsHit {
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    source: String,
+    /// Unix timestamp
+    #[serde(default)]
+    date: Option<i64>,
+}
+
+#[async_trait]
+impl VerticalEngine<NewsRow> for DuckDuckGo {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<NewsPage, EngineError> {
+        let vqd = vqd::vqd_token(query).await?;
+        let safe_search = match config::get().safe_search {
+            SafeSearch::Off => "-1",
+            _ => "1",
+        };
+
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(NEWS_URL)
+            .query(&[
+                ("l", "wt-wt"),
+                ("o", "json"),
+                ("noamp", "1"),
+                ("q", query),
+                ("vqd", vqd.as_str()),
+                ("p", safe_search),
+            ]);
+        if let Some(offset) = page {
+            request = request.query(&[("s", offset)]);
+        }
+
+        let resp = request.send().await.map_err(EngineError::ReqwestError)?;
+        if resp.status() == StatusCode::FORBIDDEN {
+            vqd::invalidate(query);
+            return Err(EngineError::Blocked(
+                "DuckDuckGo rejected the vqd token".to_string(),
+            ));
+        }
+        let body = resp.text().await.map_err(EngineError::ReqwestError)?;
+
+        parse_news_response(&body)
+    }
+}
+
+fn parse_news_response(body: &str) -> Result<NewsPage, EngineError> {
+    let response: NewsResponse = parse_json(body)?;
+
+    Ok(NewsPage {
+        rows: response
+            .results
+            .into_iter()
+            .map(|hit| NewsRow {
+                url: hit.url,
+                title: hit.title,
+                source: hit.source,
+                published_at: hit
+                    .date
+                    .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                    .map(|date| date.to_rfc3339()),
+            })
+            .collect(),
+        next_page: response.next.as_deref().and_then(s_param),
+    })
+}
+
+#[derive(Deserialize)]
+struct VideosResponse {
+    #[serde(default)]
+    results: Vec<VideoHit>,
+    /// Relative url of the next page, absent on the last one
+    #[serde(default)]
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VideoHit {
+    /// Url of the video page
+    content: String,
+    #[serde(default)]
+    title: String,
+    /// e.g. "4:13", empty for live streams
+    #[serde(default)]
+    duration: String,
+    #[serde(default)]
+    images: Option<VideoImages>,
+    #[serde(default)]
+    uploader: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VideoImages {
+    #[serde(default)]
+    medium: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<VideoRow> for DuckDuckGo {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<VideoPage, EngineError> {
+        let vqd = vqd::vqd_token(query).await?;
+        let safe_search = match config::get().safe_search {
+            SafeSearch::Off => "-1",
+            _ => "1",
+        };
+
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(VIDEOS_URL)
+            .query(&[
+                ("l", "wt-wt"),
+                ("o", "json"),
+                ("q", query),
+                ("vqd", vqd.as_str()),
+                ("f", ",,,"),
+                ("p", safe_search),
+            ]);
+        if let Some(offset) = page {
+            request = request.query(&[("s", offset)]);
+        }
+
+        let resp = request.send().await.map_err(EngineError::ReqwestError)?;
+        if resp.status() == StatusCode::FORBIDDEN {
+            vqd::invalidate(query);
+            return Err(EngineError::Blocked(
+                "DuckDuckGo rejected the vqd token".to_string(),
+            ));
+        }
+        let body = resp.text().await.map_err(EngineError::ReqwestError)?;
+
+        parse_video_response(&body)
+    }
+}
+
+fn parse_video_response(body: &str) -> Result<VideoPage, EngineError> {
+    let response: VideosResponse = parse_json(body)?;
+
+    Ok(VideoPage {
+        rows: response
+            .results
+            .into_iter()
+            .map(|hit| VideoRow {
+                url: hit.content,
+                title: hit.title,
+                thumbnail: hit.images.and_then(|i| i.medium),
+                duration: Some(hit.duration).filter(|d| !d.is_empty()),
+                channel: hit.uploader.filter(|u| !u.is_empty()),
+            })
+            .collect(),
+        next_page: response.next.as_deref().and_then(s_param),
+    })
+}
+
+/// `s` offset of a `next` url, all that's needed to fetch the next page.
+fn s_param(next: &str) -> Option<String> {
+    let url = Url::parse("https://duckduckgo.com/")
+        .ok()?
+        .join(next)
+        .ok()?;
+    url.query_pairs()
+        .find(|(k, _)| k == "s")
+        .map(|(_, v)| v.into_owned())
+}
+
+#[derive(Deserialize)]
+struct Completion {
+    phrase: String,
+}
+
+#[async_trait]
+impl SuggestEngine for DuckDuckGo {
+    async fn suggest(&self, query: &str) -> Result<Vec<String>, EngineError> {
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SUGGEST_URL)
+            .query(&[("q", query), ("kl", "wt-wt")])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_suggest_response(&body)
+    }
+}
+
+fn parse_suggest_response(body: &str) -> Result<Vec<String>, EngineError> {
+    let completions: Vec<Completion> = parse_json(body)?;
+    Ok(completions
+        .into_iter()
+        .map(|completion| completion.phrase)
+        .filter(|phrase| !phrase.trim().is_empty())
+        .collect())
+}
+
 #[cfg(test)]
 mod test {
+    use super::{
+        HTML_URL, image_filters, is_anomaly, is_lite, parse_image_response, parse_lite_response,
+        parse_news_response, parse_next_form, parse_response, parse_suggest_response,
+        parse_video_response, search_url,
+    };
+    use crate::engines::{AspectRatio, ImageLicense, ImageOptions, ImageSize};
+    use scraper::Html;
+
+    #[test]
+    fn test_search_url() {
+        assert_eq!(
+            search_url(HTML_URL, "c# & rust").unwrap().as_str(),
+            "https://html.duckduckgo.com/html/?q=c%23+%26+rust"
+        );
+    }
+
+    #[test]
+    fn test_parse_response_skips_malformed_redirects() {
+        let html = r#"
+            <div class="serp__results">
+            <div class="result"><a class="result__a" href="/l/?uddg=%25FF%25FE&amp;rut=abc">Broken</a><a class="result__snippet">Not utf-8.</a></div>
+            <div class="result"><a class="result__a" href="/l/?uddg=https%3A%2F%2Fwww.rust-lang.org%2F&amp;rut=def">Rust Programming Language</a><a class="result__snippet">A language empowering everyone.</a></div>
+            </div>
+        "#;
+
+        let page = parse_response(html).unwrap();
+
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(page.results[0].title, "Rust Programming Language");
+    }
+
+    #[test]
+    fn test_parse_lite_response() {
+        let html = r#"
+            <table>
+            <tr><td>1.&nbsp;</td><td><a rel="nofollow" href="//duckduckgo.com/l/?uddg=https%3A%2F%2Fwww.rust-lang.org%2F&amp;rut=abc" class="result-link">Rust Programming Language</a></td></tr>
+            <tr><td></td><td class="result-snippet">A language empowering everyone.</td></tr>
+            <tr><td></td><td><span class="link-text">www.rust-lang.org</span></td></tr>
+            <tr><td>2.&nbsp;</td><td><a rel="nofollow" href="https://doc.rust-lang.org/book/" class="result-link">The Book</a></td></tr>
+            <tr><td></td><td class="result-snippet">Learn Rust.</td></tr>
+            <tr><td>3.&nbsp;</td><td><a rel="nofollow" href="https://duckduckgo.com/y.js?ad_domain=x" class="result-link">Ad</a></td></tr>
+            </table>
+        "#;
+
+        let page = parse_lite_response(html).unwrap();
+
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(page.results[0].title, "Rust Programming Language");
+        assert_eq!(
+            page.results[0].description,
+            "A language empowering everyone."
+        );
+        assert_eq!(page.results[1].url, "https://doc.rust-lang.org/book/");
+        assert_eq!(page.results[1].description, "Learn Rust.");
+    }
+
+    #[test]
+    fn test_parse_next_form() {
+        let html = Html::parse_document(
+            r#"
+            <div class="nav-link">
+              <form action="/html/" method="post">
+                <input type="submit" class="btn" value="Previous">
+                <input type="hidden" name="s" value="0">
+              </form>
+            </div>
+            <div class="nav-link">
+              <form action="/html/" method="post">
+                <input type="submit" class="btn" value="Next">
+                <input type="hidden" name="q" value="rust async">
+                <input type="hidden" name="s" value="10">
+                <input type="hidden" name="dc" value="11">
+              </form>
+            </div>
+            "#,
+        );
+
+        assert_eq!(
+            parse_next_form(&html, HTML_URL).as_deref(),
+            Some("https://html.duckduckgo.com/html/?q=rust+async&s=10&dc=11")
+        );
+        assert_eq!(
+            parse_next_form(&Html::parse_document("<form></form>"), HTML_URL),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_anomaly() {
+        assert!(is_anomaly(r#"<div class="anomaly-modal__title">"#));
+        assert!(!is_anomaly(r#"<div class="serp__results">"#));
+    }
+
+    #[test]
+    fn test_parse_image_response() {
+        let page = parse_image_response(
+            r#"{
+                "query": "rust", "queryEncoded": "rust", "ads": null, "vqd": {"rust": "4-123"},
+                "next": "i.js?q=rust&o=json&p=1&s=100&u=bing&f=,,,,,&l=wt-wt",
+                "results": [
+                    {"title": "Rust logo", "image": "https://www.rust-lang.org/logos/rust-logo.png",
+                     "thumbnail": "https://tse1.mm.bing.net/th?id=OIP.abc", "url": "https://www.rust-lang.org/",
+                     "height": 1024, "width": 1024, "source": "Bing"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.images.len(), 1);
+        assert_eq!(
+            page.images[0].url,
+            "https://www.rust-lang.org/logos/rust-logo.png"
+        );
+        assert_eq!(
+            page.images[0].source_url.as_deref(),
+            Some("https://www.rust-lang.org/")
+        );
+        assert_eq!(page.images[0].width, Some(1024));
+        assert_eq!(page.next_page.as_deref(), Some("100"));
+
+        let last = parse_image_response(r#"{"results": []}"#).unwrap();
+        assert_eq!(last.next_page, None);
+    }
+
+    #[test]
+    fn test_parse_news_response() {
+        let page = parse_news_response(
+            r#"{
+                "query": "rust", "queryEncoded": "rust", "ads": null, "vqd": {"rust": "4-123"},
+                "next": "news.js?q=rust&noamp=1&l=wt-wt&o=json&s=30&p=1",
+                "results": [
+                    {"date": 1700000000, "excerpt": "The <b>Rust</b> team is happy to announce...",
+                     "image": "https://news.com/rust.jpg", "relative_time": "2 hours ago",
+                     "source": "Rust Blog", "title": "Announcing Rust 1.74", "url": "https://blog.rust-lang.org/2023/11/16/Rust-1.74.0.html"},
+                    {"excerpt": "", "source": "", "title": "Undated", "url": "https://news.com/undated"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        assert_eq!(page.rows[0].title, "Announcing Rust 1.74");
+        assert_eq!(page.rows[0].source, "Rust Blog");
+        assert_eq!(
+            page.rows[0].published_at.as_deref(),
+            Some("2023-11-14T22:13:20+00:00")
+        );
+        assert_eq!(page.rows[1].published_at, None);
+        assert_eq!(page.next_page.as_deref(), Some("30"));
+    }
+
+    #[test]
+    fn test_parse_video_response() {
+        let page = parse_video_response(
+            r#"{
+                "query": "rust", "queryEncoded": "rust", "ads": null, "vqd": {"rust": "4-123"},
+                "next": "v.js?q=rust&f=,,,&p=1&l=wt-wt&o=json&s=60",
+                "results": [
+                    {"content": "https://www.youtube.com/watch?v=5C_HPTJg5ek", "description": "Rust is a memory-safe...",
+                     "duration": "2:25", "embed_html": "", "embed_url": "https://www.youtube.com/embed/5C_HPTJg5ek",
+                     "images": {"large": "https://tse1.mm.bing.net/th?id=l", "medium": "https://tse1.mm.bing.net/th?id=m",
+                                "motion": "", "small": "https://tse1.mm.bing.net/th?id=s"},
+                     "provider": "Bing", "published": "2021-09-08T15:00:12.0000000", "publisher": "YouTube",
+                     "statistics": {"viewCount": 2000000}, "title": "Rust in 100 Seconds", "uploader": "Fireship"},
+                    {"content": "https://vimeo.com/1", "duration": "", "title": "Live", "uploader": ""}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        assert_eq!(
+            page.rows[0].url,
+            "https://www.youtube.com/watch?v=5C_HPTJg5ek"
+        );
+        assert_eq!(
+            page.rows[0].thumbnail.as_deref(),
+            Some("https://tse1.mm.bing.net/th?id=m")
+        );
+        assert_eq!(page.rows[0].duration.as_deref(), Some("2:25"));
+        assert_eq!(page.rows[0].channel.as_deref(), Some("Fireship"));
+        assert_eq!(page.rows[1].duration, None);
+        assert_eq!(page.rows[1].channel, None);
+        assert_eq!(page.next_page.as_deref(), Some("60"));
+    }
+
+    #[test]
+    fn test_parse_suggest_response() {
+        let suggestions = parse_suggest_response(
+            r#"[{"phrase": "rust lang"}, {"phrase": "rust game"}, {"phrase": ""}]"#,
+        )
+        .unwrap();
+        assert_eq!(suggestions, ["rust lang", "rust game"]);
+    }
+
+    #[test]
+    fn test_image_filters() {
+        assert_eq!(
+            image_filters(&ImageOptions::default()),
+            "size:,type:,layout:,color:,license:"
+        );
+        let options = ImageOptions {
+            size: Some(ImageSize::Wallpaper),
+            aspect: Some(AspectRatio::Wide),
+            license: Some(ImageLicense::PublicDomain),
+            ..Default::default()
+        };
+        assert_eq!(
+            image_filters(&options),
+            "size:Wallpaper,type:,layout:Wide,color:,license:Public"
+        );
+    }
+
+    #[test]
+    fn test_is_lite() {
+        assert!(is_lite(r#"<a href="/l/" class="result-link">Rust</a>"#));
+        assert!(!is_lite(
+            r#"<div class="serp__results"><a class="result__a">Rust</a></div>"#
+        ));
+    }
+
     #[ignore]
     #[tokio::test]
     async fn test_duckduckgo_live() {
         use super::{DuckDuckGo, SearchEngine};
         let ddg = DuckDuckGo;
-        let results = ddg.search_results("rust async").await.unwrap();
+        let results = ddg
+            .search_results("rust async", None)
+            .await
+            .unwrap()
+            .results;
         assert!(!results.is_empty());
 
         println!("Results: ");
@@ -0,0 +1,260 @@
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cache::ProductRow,
+    engines::{EngineError, EngineInfo, ProductPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const TOKEN_URL: &str = "https://api.ebay.com/identity/v1/oauth2/token";
+const SEARCH_URL: &str = "https://api.ebay.com/buy/browse/v1/item_summary/search";
+const PUBLIC_SCOPE: &str = "https://api.ebay.com/oauth/api_scope";
+const MARKETPLACE: &str = "EBAY_US";
+const LIMIT: u32 = 50;
+/// Tokens are refreshed a bit before eBay expires them
+const TOKEN_MARGIN: Duration = Duration::from_secs(60);
+
+/// Application tokens keyed by client id, with when they expire.
+static TOKENS: LazyLock<Mutex<HashMap<String, (String, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// eBay listings, through the Browse API with the client credentials of an
+/// eBay developer app. Application tokens are minted from them as needed.
+#[derive(Debug, Clone)]
+pub struct Ebay {
+    client_id: String,
+    client_secret: String,
+}
+
+impl Ebay {
+    pub const NAME: &'static str = "eBay";
+
+    pub fn new(client_id: &str, client_secret: &str) -> Self {
+        Self {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+        }
+    }
+
+    /// Returns a cached application token, minting a new one when missing or
+    /// about to expire.
+    async fn token(&self) -> Result<String, EngineError> {
+        if let Some((token, expires)) = TOKENS.lock().unwrap().get(&self.client_id)
+            && Instant::now() < *expires
+        {
+            return Ok(token.clone());
+        }
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .post(TOKEN_URL)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&[
+                ("grant_type", "client_credentials"),
+                ("scope", PUBLIC_SCOPE),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        let response: TokenResponse = parse_json(&body)?;
+        let lifetime = Duration::from_secs(response.expires_in).saturating_sub(TOKEN_MARGIN);
+        TOKENS.lock().unwrap().insert(
+            self.client_id.clone(),
+            (response.access_token.clone(), Instant::now() + lifetime),
+        );
+
+        Ok(response.access_token)
+    }
+}
+
+impl EngineInfo for Ebay {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// Seconds, usually two hours
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    offset: u32,
+    /// Url of the next page, missing on the last one
+    #[serde(default)]
+    next: Option<String>,
+    #[serde(default, rename = "itemSummaries")]
+    items: Vec<ItemSummary>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemSummary {
+    title: String,
+    item_web_url: String,
+    /// Buy it now price
+    #[serde(default)]
+    price: Option<Amount>,
+    /// Auctions without a buy it now price only have a bid
+    #[serde(default)]
+    current_bid_price: Option<Amount>,
+    #[serde(default)]
+    image: Option<Image>,
+    #[serde(default)]
+    seller: Option<Seller>,
+}
+
+#[derive(Deserialize)]
+struct Amount {
+    value: String,
+    currency: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Image {
+    image_url: String,
+}
+
+#[derive(Deserialize)]
+struct Seller {
+    username: String,
+}
+
+#[async_trait]
+impl VerticalEngine<ProductRow> for Ebay {
+    async fn search_page(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<ProductPage, EngineError> {
+        let token = self.token().await?;
+
+        let resp = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .bearer_auth(token)
+            .header("X-EBAY-C-MARKETPLACE-ID", MARKETPLACE)
+            .query(&[
+                ("q", query),
+                ("limit", &LIMIT.to_string()),
+                ("offset", page.unwrap_or("0")),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        match resp.status() {
+            // the app's daily call limit
+            StatusCode::TOO_MANY_REQUESTS => {
+                return Err(EngineError::QuotaExceeded(
+                    "eBay rate limited the app".to_string(),
+                ));
+            }
+            // revoked or expired early, mint a new token next time
+            StatusCode::UNAUTHORIZED => {
+                TOKENS.lock().unwrap().remove(&self.client_id);
+            }
+            _ => {}
+        }
+        let body = resp
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body)
+    }
+}
+
+fn parse_response(body: &str) -> Result<ProductPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    let next_page = response
+        .next
+        .is_some()
+        .then(|| (response.offset + LIMIT).to_string());
+
+    let products = response
+        .items
+        .into_iter()
+        .map(|item| {
+            let price = item.price.or(item.current_bid_price);
+            ProductRow {
+                url: item.item_web_url,
+                title: item.title,
+                currency: price.as_ref().map(|p| p.currency.clone()),
+                price: price.map(|p| p.value),
+                merchant: item.seller.map(|s| s.username),
+                image: item.image.map(|i| i.image_url),
+            }
+        })
+        .collect();
+
+    Ok(ProductPage {
+        rows: products,
+        next_page,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "href": "https://api.ebay.com/buy/browse/v1/item_summary/search?q=ferris&limit=50&offset=0",
+                "total": 212, "limit": 50, "offset": 0,
+                "next": "https://api.ebay.com/buy/browse/v1/item_summary/search?q=ferris&limit=50&offset=50",
+                "itemSummaries": [
+                    {"itemId": "v1|1234|0", "title": "Ferris the crab plush",
+                     "image": {"imageUrl": "https://i.ebayimg.com/images/g/abc/s-l225.jpg"},
+                     "price": {"value": "19.99", "currency": "USD"},
+                     "itemWebUrl": "https://www.ebay.com/itm/1234",
+                     "seller": {"username": "crab_shop", "feedbackPercentage": "99.8", "feedbackScore": 1024},
+                     "condition": "New", "buyingOptions": ["FIXED_PRICE"]},
+                    {"itemId": "v1|5678|0", "title": "Rust programming book",
+                     "currentBidPrice": {"value": "5.50", "currency": "USD"},
+                     "itemWebUrl": "https://www.ebay.com/itm/5678", "buyingOptions": ["AUCTION"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let plush = &page.rows[0];
+        assert_eq!(plush.url, "https://www.ebay.com/itm/1234");
+        assert_eq!(plush.price.as_deref(), Some("19.99"));
+        assert_eq!(plush.currency.as_deref(), Some("USD"));
+        assert_eq!(plush.merchant.as_deref(), Some("crab_shop"));
+        assert_eq!(
+            plush.image.as_deref(),
+            Some("https://i.ebayimg.com/images/g/abc/s-l225.jpg")
+        );
+        assert_eq!(page.rows[1].price.as_deref(), Some("5.50"));
+        assert_eq!(page.rows[1].merchant, None);
+        assert_eq!(page.next_page.as_deref(), Some("50"));
+
+        let last = parse_response(r#"{"total": 0, "limit": 50, "offset": 0}"#).unwrap();
+        assert!(last.rows.is_empty());
+        assert_eq!(last.next_page, None);
+    }
+}
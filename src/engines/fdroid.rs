@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::{
+    cache::AppRow,
+    engines::{AppPage, EngineError, EngineInfo, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://search.f-droid.org/api/search_apps";
+
+/// Free and open source Android apps in the main F-Droid repository,
+/// through the API behind search.f-droid.org. It answers with a single page.
+#[derive(Clone)]
+pub struct FDroid;
+
+impl EngineInfo for FDroid {
+    fn name(&self) -> &'static str {
+        "F-Droid"
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    apps: Vec<App>,
+}
+
+#[derive(Deserialize)]
+struct App {
+    name: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    icon: Option<String>,
+    /// The app's page, ending in its package name
+    url: String,
+}
+
+#[async_trait]
+impl VerticalEngine<AppRow> for FDroid {
+    async fn search_page(&self, query: &str, _page: Option<&str>) -> Result<AppPage, EngineError> {
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[("q", query), ("lang", "en")])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body)
+    }
+}
+
+fn parse_response(body: &str) -> Result<AppPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+
+    let apps = response
+        .apps
+        .into_iter()
+        .filter_map(|app| {
+            let package = Url::parse(&app.url)
+                .ok()?
+                .path_segments()?
+                .rfind(|segment| !segment.is_empty())?
+                .to_string();
+            Some(AppRow {
+                // opens the app in the F-Droid client on Android
+                install_url: Some(format!("market://details?id={package}")),
+                app_id: package,
+                url: app.url,
+                name: app.name,
+                store: "F-Droid".to_string(),
+                summary: app.summary.filter(|s| !s.trim().is_empty()),
+                icon: app.icon.filter(|icon| !icon.is_empty()),
+            })
+        })
+        .collect();
+
+    Ok(AppPage {
+        rows: apps,
+        next_page: None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{"apps": [
+                {"name": "Fennec F-Droid", "summary": "Browse the web",
+                 "icon": "https://f-droid.org/repo/org.mozilla.fennec_fdroid/en-US/icon.png",
+                 "url": "https://f-droid.org/en/packages/org.mozilla.fennec_fdroid/"},
+                {"name": "Broken", "summary": "", "icon": "", "url": "not a url"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 1);
+        let fennec = &page.rows[0];
+        assert_eq!(fennec.app_id, "org.mozilla.fennec_fdroid");
+        assert_eq!(
+            fennec.install_url.as_deref(),
+            Some("market://details?id=org.mozilla.fennec_fdroid")
+        );
+        assert_eq!(fennec.store, "F-Droid");
+        assert_eq!(page.next_page, None);
+    }
+}
@@ -0,0 +1,136 @@
+use async_trait::async_trait;
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+
+use crate::{
+    cache::AppRow,
+    engines::{AppPage, EngineError, EngineInfo, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://flathub.org/api/v2/search";
+const HITS_PER_PAGE: u32 = 20;
+
+/// Linux desktop apps on Flathub, through its search API. Each app links
+/// the `.flatpakref` software centers install it from.
+#[derive(Clone)]
+pub struct Flathub;
+
+impl EngineInfo for Flathub {
+    fn name(&self) -> &'static str {
+        "Flathub"
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    #[serde(default)]
+    hits: Vec<Hit>,
+    #[serde(default)]
+    total_pages: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct Hit {
+    app_id: String,
+    name: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    icon: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<AppRow> for Flathub {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<AppPage, EngineError> {
+        // Flathub pages count from 1
+        let page: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+        let request = serde_json::json!({
+            "query": query,
+            "filters": [],
+            "hits_per_page": HITS_PER_PAGE,
+            "page": page,
+        });
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .post(SEARCH_URL)
+            .header(CONTENT_TYPE, "application/json")
+            .body(request.to_string())
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, page)
+    }
+}
+
+fn parse_response(body: &str, page: u32) -> Result<AppPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+
+    let apps = response
+        .hits
+        .into_iter()
+        .map(|hit| AppRow {
+            url: format!("https://flathub.org/apps/{}", hit.app_id),
+            name: hit.name,
+            store: "Flathub".to_string(),
+            summary: hit.summary.filter(|s| !s.trim().is_empty()),
+            icon: hit.icon,
+            install_url: Some(format!(
+                "https://dl.flathub.org/repo/appstream/{}.flatpakref",
+                hit.app_id
+            )),
+            app_id: hit.app_id,
+        })
+        .collect();
+
+    Ok(AppPage {
+        rows: apps,
+        next_page: response
+            .total_pages
+            .filter(|total| page < *total)
+            .map(|_| (page + 1).to_string()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "hits": [
+                    {"id": "org_mozilla_firefox", "app_id": "org.mozilla.firefox", "name": "Firefox",
+                     "summary": "Fast, Private & Safe Web Browser",
+                     "icon": "https://dl.flathub.org/media/org/mozilla/firefox/icon.png",
+                     "main_categories": ["network"], "installs_last_month": 200000}
+                ],
+                "query": "firefox", "processingTimeMs": 1, "hitsPerPage": 20,
+                "page": 1, "totalPages": 3, "totalHits": 45
+            }"#,
+            1,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 1);
+        let firefox = &page.rows[0];
+        assert_eq!(firefox.url, "https://flathub.org/apps/org.mozilla.firefox");
+        assert_eq!(firefox.app_id, "org.mozilla.firefox");
+        assert_eq!(
+            firefox.install_url.as_deref(),
+            Some("https://dl.flathub.org/repo/appstream/org.mozilla.firefox.flatpakref")
+        );
+        assert_eq!(page.next_page.as_deref(), Some("2"));
+
+        let last = parse_response(r#"{"hits": [], "page": 3, "totalPages": 3}"#, 3).unwrap();
+        assert_eq!(last.next_page, None);
+    }
+}
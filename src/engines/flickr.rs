@@ -0,0 +1,278 @@
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{
+    cache::ImagesRow,
+    config::SafeSearch,
+    engines::{
+        EngineError, EngineInfo, ImageEngine, ImageLicense, ImageOptions, ImagePage,
+        new_rand_client, parse_json,
+    },
+};
+
+const API_URL: &str = "https://www.flickr.com/services/rest/";
+const PER_PAGE: u32 = 50;
+/// Square 150px, then 240px on the longest side
+const THUMBNAIL_SIZES: &[&str] = &["q", "m"];
+/// Original first, then 2048, 1600, 1024, 640 and 500px on the longest side
+const FULL_SIZES: &[&str] = &["o", "k", "h", "b", "z", "m"];
+
+/// Photos on Flickr, through its REST API with the key of a Flickr account.
+#[derive(Debug, Clone)]
+pub struct Flickr {
+    api_key: String,
+}
+
+impl Flickr {
+    pub const NAME: &'static str = "Flickr";
+
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+impl EngineInfo for Flickr {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+/// Errors, e.g. an invalid key, come back with a 200 and a `stat` of "fail".
+#[derive(Deserialize)]
+struct FlickrResponse {
+    stat: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    photos: Option<FlickrPhotos>,
+}
+
+#[derive(Deserialize)]
+struct FlickrPhotos {
+    page: u32,
+    pages: u32,
+    #[serde(default)]
+    photo: Vec<FlickrPhoto>,
+}
+
+#[derive(Deserialize)]
+struct FlickrPhoto {
+    id: String,
+    owner: String,
+    #[serde(default)]
+    title: String,
+    /// Id of the license, see [`license_name`]
+    #[serde(default)]
+    license: Option<String>,
+    #[serde(default)]
+    ownername: Option<String>,
+    /// `url_<size>`, `width_<size>` and `height_<size>` of each size asked for
+    #[serde(flatten)]
+    sizes: HashMap<String, serde_json::Value>,
+}
+
+impl FlickrPhoto {
+    /// The first of `sizes` the photo is available in, with its dimensions.
+    fn size(&self, sizes: &[&str]) -> Option<(String, Option<u32>, Option<u32>)> {
+        sizes.iter().find_map(|size| {
+            let url = self.sizes.get(&format!("url_{size}"))?.as_str()?;
+            let dimension = |key: &str| {
+                // numbers in most responses, strings in some older ones
+                match self.sizes.get(&format!("{key}_{size}"))? {
+                    serde_json::Value::Number(n) => n.as_u64().map(|n| n as u32),
+                    serde_json::Value::String(s) => s.parse().ok(),
+                    _ => None,
+                }
+            };
+            Some((url.to_string(), dimension("width"), dimension("height")))
+        })
+    }
+}
+
+#[async_trait]
+impl ImageEngine for Flickr {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        let extras = THUMBNAIL_SIZES
+            .iter()
+            .chain(FULL_SIZES)
+            .map(|size| format!("url_{size}"))
+            .chain(["license".to_string(), "owner_name".to_string()])
+            .collect::<Vec<_>>()
+            .join(",");
+        let safe_search = match options.safe_search.unwrap_or(SafeSearch::Moderate) {
+            SafeSearch::Strict => "1",
+            SafeSearch::Moderate => "2",
+            SafeSearch::Off => "3",
+        };
+
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(API_URL)
+            .query(&[
+                ("method", "flickr.photos.search"),
+                ("api_key", &self.api_key),
+                ("format", "json"),
+                ("nojsoncallback", "1"),
+                ("text", query),
+                ("sort", "relevance"),
+                // photos only, no screenshots or illustrations
+                ("content_types", "0"),
+                ("safe_search", safe_search),
+                ("extras", &extras),
+                ("per_page", &PER_PAGE.to_string()),
+                ("page", page.unwrap_or("1")),
+            ]);
+        if let Some(license) = options.license {
+            let ids = match license {
+                ImageLicense::CreativeCommons => "1,2,3,4,5,6,9,10,11,12,13,14,15,16",
+                ImageLicense::PublicDomain => "9,10",
+            };
+            request = request.query(&[("license", ids)]);
+        }
+
+        let resp = request.send().await.map_err(EngineError::ReqwestError)?;
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(EngineError::QuotaExceeded(
+                "Flickr rate limited the api key".to_string(),
+            ));
+        }
+        let body = resp
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_image_response(&body)
+    }
+}
+
+fn parse_image_response(body: &str) -> Result<ImagePage, EngineError> {
+    let response: FlickrResponse = parse_json(body)?;
+    let photos = match response.photos {
+        Some(photos) if response.stat == "ok" => photos,
+        _ => {
+            return Err(EngineError::ParseError(format!(
+                "Flickr returned an error: {}",
+                response.message
+            )));
+        }
+    };
+
+    let images = photos
+        .photo
+        .iter()
+        .filter_map(|photo| {
+            let (url, width, height) = photo.size(FULL_SIZES)?;
+            Some(ImagesRow {
+                url,
+                title: photo.title.clone(),
+                width,
+                height,
+                thumbnail: photo.size(THUMBNAIL_SIZES).map(|(url, _, _)| url),
+                source_url: Some(format!(
+                    "https://www.flickr.com/photos/{}/{}",
+                    photo.owner, photo.id
+                )),
+                license: photo.license.as_deref().and_then(license_name),
+                attribution: photo.ownername.clone().filter(|name| !name.is_empty()),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    Ok(ImagePage {
+        images,
+        next_page: (photos.page < photos.pages).then(|| (photos.page + 1).to_string()),
+        ..Default::default()
+    })
+}
+
+/// Name of a license id, as listed by `flickr.photos.licenses.getInfo`.
+fn license_name(id: &str) -> Option<String> {
+    let name = match id {
+        "0" => "All Rights Reserved",
+        "1" => "CC BY-NC-SA 2.0",
+        "2" => "CC BY-NC 2.0",
+        "3" => "CC BY-NC-ND 2.0",
+        "4" => "CC BY 2.0",
+        "5" => "CC BY-SA 2.0",
+        "6" => "CC BY-ND 2.0",
+        "7" => "No known copyright restrictions",
+        "8" => "United States Government Work",
+        "9" => "CC0 1.0",
+        "10" => "Public Domain Mark 1.0",
+        "11" => "CC BY 4.0",
+        "12" => "CC BY-SA 4.0",
+        "13" => "CC BY-ND 4.0",
+        "14" => "CC BY-NC 4.0",
+        "15" => "CC BY-NC-SA 4.0",
+        "16" => "CC BY-NC-ND 4.0",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_image_response;
+    use crate::engines::EngineError;
+
+    #[test]
+    fn test_parse_image_response() {
+        let page = parse_image_response(
+            r#"{
+                "photos": {"page": 1, "pages": 40, "perpage": 50, "total": 1987, "photo": [
+                    {"id": "53210", "owner": "12345@N00", "secret": "abc", "server": "65535", "farm": 66,
+                     "title": "Ferris at RustConf", "ispublic": 1, "license": "4", "ownername": "Jane Doe",
+                     "url_q": "https://live.staticflickr.com/65535/53210_abc_q.jpg", "height_q": 150, "width_q": 150,
+                     "url_b": "https://live.staticflickr.com/65535/53210_abc_b.jpg", "height_b": "683", "width_b": "1024"},
+                    {"id": "53211", "owner": "12345@N00", "title": "", "license": "0", "ownername": "",
+                     "url_o": "https://live.staticflickr.com/65535/53211_def_o.jpg", "height_o": 3000, "width_o": 4000},
+                    {"id": "53212", "owner": "12345@N00", "title": "no sizes", "license": "9"}
+                ]},
+                "stat": "ok"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.images.len(), 2);
+        let ferris = &page.images[0];
+        assert_eq!(
+            ferris.url,
+            "https://live.staticflickr.com/65535/53210_abc_b.jpg"
+        );
+        assert_eq!((ferris.width, ferris.height), (Some(1024), Some(683)));
+        assert_eq!(
+            ferris.thumbnail.as_deref(),
+            Some("https://live.staticflickr.com/65535/53210_abc_q.jpg")
+        );
+        assert_eq!(
+            ferris.source_url.as_deref(),
+            Some("https://www.flickr.com/photos/12345@N00/53210")
+        );
+        assert_eq!(ferris.license.as_deref(), Some("CC BY 2.0"));
+        assert_eq!(ferris.attribution.as_deref(), Some("Jane Doe"));
+
+        let original = &page.images[1];
+        assert_eq!((original.width, original.height), (Some(4000), Some(3000)));
+        assert_eq!(original.thumbnail, None);
+        assert_eq!(original.license.as_deref(), Some("All Rights Reserved"));
+        assert_eq!(original.attribution, None);
+        assert_eq!(page.next_page.as_deref(), Some("2"));
+
+        let error = parse_image_response(
+            r#"{"stat": "fail", "code": 100, "message": "Invalid API Key (Key has invalid format)"}"#,
+        );
+        assert!(matches!(error, Err(EngineError::ParseError(_))));
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::DateTime;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineInfo, JsonEngine, instance_id},
+};
+
+/// A 4get instance, queried through its `/api/v1/web` JSON API. 4get scrapes
+/// whichever backend the instance defaults to.
+#[derive(Debug, Clone)]
+pub struct FourGet {
+    base_url: String,
+}
+
+impl FourGet {
+    pub const NAME: &'static str = "4get";
+
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl EngineInfo for FourGet {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn id(&self) -> Cow<'static, str> {
+        instance_id(Self::NAME, &self.base_url)
+    }
+}
+
+/// Failures only come back as a `status` other than "ok".
+#[derive(Deserialize)]
+pub struct FourGetResponse {
+    #[serde(default)]
+    status: String,
+    /// Opaque token the instance resumes the search from
+    #[serde(default)]
+    npt: Option<String>,
+    #[serde(default)]
+    web: Vec<FourGetHit>,
+}
+
+#[derive(Deserialize)]
+struct FourGetHit {
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    /// Unix timestamp
+    #[serde(default)]
+    date: Option<i64>,
+}
+
+impl JsonEngine for FourGet {
+    type Response = FourGetResponse;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        let url = format!("{}/api/v1/web", self.base_url);
+        match page {
+            // the token already carries the query
+            Some(npt) => client.get(url).query(&[("npt", npt)]),
+            None => client.get(url).query(&[("s", query)]),
+        }
+    }
+
+    fn next_page(&self, response: &FourGetResponse, _page: Option<&str>) -> Option<String> {
+        response.npt.clone().filter(|npt| !npt.is_empty())
+    }
+
+    fn results(&self, response: FourGetResponse) -> Vec<ResultRow> {
+        if response.status != "ok" {
+            return Vec::new();
+        }
+        response
+            .web
+            .into_iter()
+            .map(|hit| ResultRow {
+                url: hit.url,
+                title: hit.title,
+                description: hit.description.unwrap_or_default(),
+                published_at: hit
+                    .date
+                    .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                    .map(|date| date.to_rfc3339()),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FourGet, FourGetResponse};
+    use crate::engines::{JsonEngine, parse_json};
+
+    #[test]
+    fn test_results() {
+        let fourget = FourGet::new("https://4get.example.org/");
+        let response: FourGetResponse = parse_json(
+            r#"{
+                "status": "ok", "spelling": {"type": "no_correction"}, "npt": "ddg1._rJabc",
+                "answer": [],
+                "web": [
+                    {"title": "Rust", "description": "A language empowering everyone.", "url": "https://www.rust-lang.org/",
+                     "date": 1700000000, "type": "web", "thumb": {"url": null, "ratio": null}, "sublink": [], "table": []},
+                    {"title": "The Book", "description": null, "url": "https://doc.rust-lang.org/book/", "date": null, "type": "web"}
+                ],
+                "image": [], "video": [], "news": [], "related": []
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fourget.next_page(&response, None).as_deref(),
+            Some("ddg1._rJabc")
+        );
+        let results = fourget.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].published_at.as_deref(),
+            Some("2023-11-14T22:13:20+00:00")
+        );
+        assert_eq!(results[1].description, "");
+
+        let error: FourGetResponse =
+            parse_json(r#"{"status": "Your IP has been rate limited"}"#).unwrap();
+        assert_eq!(fourget.next_page(&error, None), None);
+        assert!(fourget.results(error).is_empty());
+    }
+}
@@ -0,0 +1,389 @@
+use async_trait::async_trait;
+use regex::Regex;
+use reqwest::{Url, header::COOKIE};
+use serde_json::Value;
+use std::{collections::HashSet, sync::LazyLock};
+
+use crate::{
+    cache::ImagesRow,
+    config::SafeSearch,
+    engines::{
+        AspectRatio, EngineError, EngineInfo, ImageEngine, ImageLicense, ImageOptions, ImagePage,
+        ImageSize, SearchEngine, SerpPage, SerpSchema, SuggestEngine, new_rand_client,
+        parse_blocking, parse_opensearch_suggestions, parse_page_blocking,
+    },
+};
+
+const SEARCH_URL: &str = "https://www.google.com/search";
+const SUGGEST_URL: &str = "https://www.google.com/complete/search";
+/// Answers the cookie consent interstitial EU visitors are redirected to
+/// before any results are shown.
+const CONSENT_COOKIE: &str = "CONSENT=YES+; SOCS=CAESHAgBEhIaAB";
+
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new("#search div.g, #main div.Gx5Zad", "h3", "a[href]")
+        .container("#search, #main")
+        .description("div[data-sncf], div.VwiC3b, div.BNeawe.s3v9rd")
+        .total_results("#result-stats")
+        .next_page("a#pnnext, a[aria-label='Next page']")
+});
+
+#[derive(Clone)]
+pub struct Google;
+
+impl EngineInfo for Google {
+    fn name(&self) -> &'static str {
+        "Google"
+    }
+}
+
+#[async_trait]
+impl SearchEngine for Google {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let resp = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[("q", query), ("hl", "en"), ("start", page.unwrap_or("0"))])
+            .header(COOKIE, CONSENT_COOKIE)
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        // rate limited clients are sent to /sorry/ for a captcha
+        let blocked = resp.url().path().starts_with("/sorry");
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        if blocked || is_captcha(&html) {
+            return Err(EngineError::Blocked("Google served a captcha".to_string()));
+        }
+
+        parse_page_blocking(html, parse_response).await
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_response(body)
+    }
+}
+
+fn is_captcha(html: &str) -> bool {
+    html.contains("id=\"captcha-form\"") || html.contains("/sorry/index")
+}
+
+pub fn parse_response(html: &str) -> Result<SerpPage, EngineError> {
+    let mut page = SEARCH_SCHEMA.parse_results(html)?;
+    page.results = page
+        .results
+        .into_iter()
+        .filter_map(|mut r| {
+            r.url = unwrap_url(&r.url)?;
+            Some(r)
+        })
+        .collect();
+    // only the start offset is needed to fetch the next page
+    page.next_page = page.next_page.as_deref().and_then(start_param);
+
+    Ok(page)
+}
+
+/// Target of a `/url?q=` redirect link, other absolute links as they are.
+/// Links back into Google, e.g. to its own verticals, are dropped.
+pub(super) fn unwrap_url(href: &str) -> Option<String> {
+    let url = Url::parse("https://www.google.com").ok()?.join(href).ok()?;
+    if url.path() == "/url" {
+        return url
+            .query_pairs()
+            .find(|(k, _)| k == "q" || k == "url")
+            .map(|(_, v)| v.into_owned())
+            .filter(|v| v.starts_with("http"));
+    }
+    if url.host_str().is_some_and(|h| h.ends_with("google.com")) {
+        return None;
+    }
+    Some(url.into())
+}
+
+/// `start` query parameter of the next page link.
+pub(super) fn start_param(href: &str) -> Option<String> {
+    let url = Url::parse("https://www.google.com").ok()?.join(href).ok()?;
+    url.query_pairs()
+        .find(|(k, _)| k == "start")
+        .map(|(_, v)| v.into_owned())
+}
+
+#[async_trait]
+impl ImageEngine for Google {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        let ijn: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("q", query),
+                ("tbm", "isch"),
+                ("hl", "en"),
+                ("ijn", &ijn.to_string()),
+                ("tbs", &image_filters(options)),
+            ])
+            .header(COOKIE, CONSENT_COOKIE);
+        match options.safe_search {
+            Some(SafeSearch::Strict) => request = request.query(&[("safe", "active")]),
+            Some(SafeSearch::Off) => request = request.query(&[("safe", "off")]),
+            _ => {}
+        }
+
+        let resp = request.send().await.map_err(EngineError::ReqwestError)?;
+        let blocked = resp.url().path().starts_with("/sorry");
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        if blocked || is_captcha(&html) {
+            return Err(EngineError::Blocked("Google served a captcha".to_string()));
+        }
+
+        let mut images = parse_blocking(html, parse_image_response).await?;
+        // Google filtered to Creative Commons already, results don't say which
+        if options.license == Some(ImageLicense::CreativeCommons) {
+            for image in &mut images {
+                image.license = Some(ImageLicense::CreativeCommons.label().to_string());
+            }
+        }
+
+        Ok(ImagePage {
+            next_page: (!images.is_empty()).then(|| (ijn + 1).to_string()),
+            images,
+            ..Default::default()
+        })
+    }
+}
+
+/// The `tbs` parameter, comma separated filters.
+fn image_filters(options: &ImageOptions) -> String {
+    let mut filters = Vec::new();
+    match options.size {
+        Some(ImageSize::Small) => filters.push("isz:i"),
+        Some(ImageSize::Medium) => filters.push("isz:m"),
+        Some(ImageSize::Large) => filters.push("isz:l"),
+        Some(ImageSize::Wallpaper) => filters.extend(["isz:lt", "islt:2mp"]),
+        None => {}
+    }
+    match options.aspect {
+        Some(AspectRatio::Square) => filters.push("iar:s"),
+        Some(AspectRatio::Tall) => filters.push("iar:t"),
+        Some(AspectRatio::Wide) => filters.push("iar:w"),
+        None => {}
+    }
+    // there is no public domain filter, Creative Commons is the closest
+    if options.license.is_some() {
+        filters.push("sur:cl");
+    }
+    let format = options.format.map(|f| format!("ift:{}", f.as_str()));
+    filters
+        .into_iter()
+        .map(str::to_string)
+        .chain(format)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The result grid only has base64 thumbnails, the full size urls are in
+/// the JSON passed to `AF_initDataCallback` in the page's scripts.
+pub fn parse_image_response(html: &str) -> Result<Vec<ImagesRow>, EngineError> {
+    static DATA_CALLBACK: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?s)AF_initDataCallback\(\{key: '[^']*', hash: '[^']*', data:(.*?), sideChannel: \{\}\}\);")
+            .unwrap()
+    });
+
+    let mut images = Vec::new();
+    for captures in DATA_CALLBACK.captures_iter(html) {
+        if let Ok(data) = serde_json::from_str::<Value>(&captures[1]) {
+            collect_images(&data, &mut images);
+        }
+    }
+
+    let mut seen = HashSet::new();
+    images.retain(|image: &ImagesRow| seen.insert(image.url.clone()));
+    Ok(images)
+}
+
+/// Walks the data for image entries, which look like
+/// `[_, _, [thumbnail, h, w], [url, h, w], ..., {"2003": [_, _, page, title]}]`.
+fn collect_images(value: &Value, images: &mut Vec<ImagesRow>) {
+    let Value::Array(items) = value else {
+        return;
+    };
+    if let Some(image) = image_entry(items) {
+        images.push(image);
+        return;
+    }
+    for item in items {
+        collect_images(item, images);
+    }
+}
+
+fn image_entry(items: &[Value]) -> Option<ImagesRow> {
+    let (thumbnail, _, _) = sized_url(items.get(2)?)?;
+    let (url, height, width) = sized_url(items.get(3)?)?;
+    if !thumbnail.contains("gstatic.com") || url.contains("gstatic.com") {
+        return None;
+    }
+    let meta = items
+        .iter()
+        .find_map(|item| item.get("2003"))
+        .and_then(Value::as_array);
+    let meta_str = |i: usize| {
+        meta.and_then(|m| m.get(i))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+
+    Some(ImagesRow {
+        url,
+        title: meta_str(3).unwrap_or_default(),
+        width,
+        height,
+        thumbnail: Some(thumbnail),
+        source_url: meta_str(2),
+        ..Default::default()
+    })
+}
+
+/// `[url, height, width]`
+fn sized_url(value: &Value) -> Option<(String, Option<u32>, Option<u32>)> {
+    let [url, height, width] = value.as_array()?.as_slice() else {
+        return None;
+    };
+    let url = url.as_str().filter(|u| u.starts_with("http"))?;
+    let dimension = |v: &Value| v.as_u64().map(|n| n as u32);
+    Some((url.to_string(), dimension(height), dimension(width)))
+}
+
+#[async_trait]
+impl SuggestEngine for Google {
+    async fn suggest(&self, query: &str) -> Result<Vec<String>, EngineError> {
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SUGGEST_URL)
+            // the firefox client gets OpenSearch JSON, in Latin-1 unless asked otherwise
+            .query(&[
+                ("client", "firefox"),
+                ("q", query),
+                ("hl", "en"),
+                ("ie", "utf-8"),
+                ("oe", "utf-8"),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_opensearch_suggestions(&body)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{image_filters, is_captcha, parse_image_response, parse_response, unwrap_url};
+    use crate::engines::{ImageFormat, ImageLicense, ImageOptions, ImageSize};
+
+    #[test]
+    fn test_unwrap_url() {
+        assert_eq!(
+            unwrap_url("/url?q=https://www.rust-lang.org/&sa=U&ved=abc").as_deref(),
+            Some("https://www.rust-lang.org/")
+        );
+        assert_eq!(
+            unwrap_url("https://doc.rust-lang.org/book/").as_deref(),
+            Some("https://doc.rust-lang.org/book/")
+        );
+        assert_eq!(unwrap_url("/search?q=rust&tbm=isch"), None);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let html = r#"
+            <div id="result-stats">About 1,230,000 results</div>
+            <div id="search">
+              <div class="g">
+                <a href="/url?q=https://www.rust-lang.org/&amp;sa=U"><h3>Rust</h3></a>
+                <div class="VwiC3b">A language empowering everyone.</div>
+              </div>
+              <div class="g">
+                <a href="https://doc.rust-lang.org/book/"><h3>The Book</h3></a>
+              </div>
+            </div>
+            <a id="pnnext" href="/search?q=rust&amp;start=10">Next</a>
+        "#;
+
+        let page = parse_response(html).unwrap();
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(page.results[0].title, "Rust");
+        assert_eq!(
+            page.results[0].description,
+            "A language empowering everyone."
+        );
+        assert_eq!(page.results[1].url, "https://doc.rust-lang.org/book/");
+        assert_eq!(page.total_results, Some(1_230_000));
+        assert_eq!(page.next_page.as_deref(), Some("10"));
+    }
+
+    #[test]
+    fn test_is_captcha() {
+        assert!(is_captcha(r#"<form id="captcha-form" action="index">"#));
+        assert!(!is_captcha(r#"<div id="search">"#));
+    }
+
+    #[test]
+    fn test_parse_image_response() {
+        let html = r#"
+            <div id="islrg"><img src="data:image/jpeg;base64,/9j/4AAQSkZJRg==" alt="Rust logo"></div>
+            <script nonce="x">AF_initDataCallback({key: 'ds:1', hash: '2', data:[null,[[1,[0,"kB7x",["https://encrypted-tbn0.gstatic.com/images?q\u003dtbn:ANd9Gc1",225,225],["https://www.rust-lang.org/logos/rust-logo-512x512.png",512,512],null,0,"rgb(0,0,0)",null,0,{"2003":[null,"id1","https://www.rust-lang.org/","Rust Programming Language",null,null,null,null,null,null,"rust-lang.org"]}]],[1,[0,"zP3q",["https://encrypted-tbn0.gstatic.com/images?q\u003dtbn:ANd9Gc2",194,259],["https://upload.wikimedia.org/ferris.png",1200,1600],null,0,"rgb(1,1,1)",null,0,{}]],[1,[0,"kB7x",["https://encrypted-tbn0.gstatic.com/images?q\u003dtbn:ANd9Gc1",225,225],["https://www.rust-lang.org/logos/rust-logo-512x512.png",512,512]]]]], sideChannel: {}});</script>
+            <script nonce="x">AF_initDataCallback({key: 'ds:0', hash: '1', data:[["not", "images"]], sideChannel: {}});</script>
+        "#;
+
+        let images = parse_image_response(html).unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(
+            images[0].url,
+            "https://www.rust-lang.org/logos/rust-logo-512x512.png"
+        );
+        assert_eq!(images[0].title, "Rust Programming Language");
+        assert_eq!(
+            images[0].thumbnail.as_deref(),
+            Some("https://encrypted-tbn0.gstatic.com/images?q=tbn:ANd9Gc1")
+        );
+        assert_eq!(
+            images[0].source_url.as_deref(),
+            Some("https://www.rust-lang.org/")
+        );
+        assert_eq!(
+            (images[1].width, images[1].height),
+            (Some(1600), Some(1200))
+        );
+        assert_eq!(images[1].title, "");
+        assert_eq!(images[1].source_url, None);
+    }
+
+    #[test]
+    fn test_image_filters() {
+        assert_eq!(image_filters(&ImageOptions::default()), "");
+        let options = ImageOptions {
+            size: Some(ImageSize::Wallpaper),
+            license: Some(ImageLicense::PublicDomain),
+            format: Some(ImageFormat::Png),
+            ..Default::default()
+        };
+        assert_eq!(image_filters(&options), "isz:lt,islt:2mp,sur:cl,ift:png");
+    }
+}
@@ -0,0 +1,213 @@
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::Deserialize;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineError, EngineInfo, JsonEngine, parse_json},
+};
+
+const API_URL: &str = "https://www.googleapis.com/customsearch/v1";
+const MAX_START: u32 = 91; // the api serves at most the first 100 results
+
+/// Error reasons Google uses once a key's quota or rate limit is used up.
+const QUOTA_REASONS: [&str; 4] = [
+    "rateLimitExceeded",
+    "dailyLimitExceeded",
+    "userRateLimitExceeded",
+    "quotaExceeded",
+];
+
+/// Google's Custom Search JSON API, for an api `key` and the `cx` id of a
+/// Programmable Search Engine set up to search the entire web.
+#[derive(Debug, Clone)]
+pub struct GoogleCse {
+    key: String,
+    cx: String,
+}
+
+impl GoogleCse {
+    pub const NAME: &'static str = "Google CSE";
+
+    pub fn new(key: &str, cx: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            cx: cx.to_string(),
+        }
+    }
+}
+
+impl EngineInfo for GoogleCse {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CseResponse {
+    #[serde(default)]
+    items: Vec<CseItem>,
+    #[serde(default)]
+    queries: Option<CseQueries>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CseQueries {
+    #[serde(default)]
+    next_page: Vec<CseQuery>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CseQuery {
+    start_index: u32,
+}
+
+#[derive(Deserialize)]
+struct CseItem {
+    link: String,
+    title: String,
+    #[serde(default)]
+    snippet: String,
+}
+
+#[derive(Deserialize)]
+struct CseErrorResponse {
+    error: CseError,
+}
+
+#[derive(Deserialize)]
+struct CseError {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    errors: Vec<CseErrorDetail>,
+}
+
+#[derive(Deserialize)]
+struct CseErrorDetail {
+    #[serde(default)]
+    reason: String,
+}
+
+impl JsonEngine for GoogleCse {
+    type Response = CseResponse;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        client.get(API_URL).query(&[
+            ("key", self.key.as_str()),
+            ("cx", self.cx.as_str()),
+            ("q", query),
+            ("start", page.unwrap_or("1")),
+        ])
+    }
+
+    fn next_page(&self, response: &CseResponse, _page: Option<&str>) -> Option<String> {
+        let queries = response.queries.as_ref()?;
+        let start = queries.next_page.first()?.start_index;
+        (start <= MAX_START).then(|| start.to_string())
+    }
+
+    fn check(&self, status: StatusCode, body: &str) -> Result<(), EngineError> {
+        if status.is_success() {
+            return Ok(());
+        }
+        let error = parse_json::<CseErrorResponse>(body)
+            .map(|r| r.error)
+            .map_err(|_| EngineError::ParseError(format!("Google CSE returned {status}")))?;
+
+        let quota = status == StatusCode::TOO_MANY_REQUESTS
+            || error
+                .errors
+                .iter()
+                .any(|e| QUOTA_REASONS.contains(&e.reason.as_str()));
+        if quota {
+            Err(EngineError::QuotaExceeded(error.message))
+        } else {
+            Err(EngineError::ParseError(format!(
+                "Google CSE returned {status}: {}",
+                error.message
+            )))
+        }
+    }
+
+    fn results(&self, response: CseResponse) -> Vec<ResultRow> {
+        response
+            .items
+            .into_iter()
+            .map(|item| ResultRow {
+                url: item.link,
+                title: item.title,
+                description: item.snippet,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CseResponse, GoogleCse};
+    use crate::engines::{EngineError, JsonEngine, parse_json};
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_results() {
+        let cse = GoogleCse::new("key", "cx");
+        let response: CseResponse = parse_json(
+            r#"{
+                "kind": "customsearch#search",
+                "queries": {"request": [{"startIndex": 1}], "nextPage": [{"startIndex": 11}]},
+                "items": [
+                    {"kind": "customsearch#result", "title": "Rust", "link": "https://www.rust-lang.org/", "snippet": "A language empowering everyone."},
+                    {"kind": "customsearch#result", "title": "The Book", "link": "https://doc.rust-lang.org/book/"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(cse.next_page(&response, None).as_deref(), Some("11"));
+        let results = cse.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(results[1].description, "");
+
+        // no items at all when nothing matched
+        let empty: CseResponse = parse_json(r#"{"queries": {"request": []}}"#).unwrap();
+        assert_eq!(cse.next_page(&empty, None), None);
+        assert!(cse.results(empty).is_empty());
+    }
+
+    #[test]
+    fn test_check() {
+        let cse = GoogleCse::new("key", "cx");
+        assert!(cse.check(StatusCode::OK, "{}").is_ok());
+
+        let quota = r#"{"error": {"code": 429, "message": "Quota exceeded for quota metric 'Queries'",
+            "errors": [{"message": "Quota exceeded", "domain": "global", "reason": "rateLimitExceeded"}],
+            "status": "RESOURCE_EXHAUSTED"}}"#;
+        assert!(matches!(
+            cse.check(StatusCode::TOO_MANY_REQUESTS, quota),
+            Err(EngineError::QuotaExceeded(_))
+        ));
+
+        let daily = r#"{"error": {"code": 403, "message": "Daily Limit Exceeded",
+            "errors": [{"reason": "dailyLimitExceeded"}]}}"#;
+        assert!(matches!(
+            cse.check(StatusCode::FORBIDDEN, daily),
+            Err(EngineError::QuotaExceeded(_))
+        ));
+
+        let bad_key = r#"{"error": {"code": 400, "message": "API key not valid.",
+            "errors": [{"reason": "badRequest"}]}}"#;
+        assert!(matches!(
+            cse.check(StatusCode::BAD_REQUEST, bad_key),
+            Err(EngineError::ParseError(_))
+        ));
+        assert!(matches!(
+            cse.check(StatusCode::BAD_GATEWAY, "<html>"),
+            Err(EngineError::ParseError(_))
+        ));
+    }
+}
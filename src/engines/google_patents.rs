@@ -0,0 +1,204 @@
+use async_trait::async_trait;
+use scraper::Html;
+use serde::Deserialize;
+
+use crate::{
+    cache::PatentRow,
+    engines::{EngineError, EngineInfo, PatentPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const QUERY_URL: &str = "https://patents.google.com/xhr/query";
+const BASE_URL: &str = "https://patents.google.com";
+const PDF_URL: &str = "https://patentimages.storage.googleapis.com";
+const NUM: u32 = 20;
+
+/// Patents from the offices Google Patents indexes, through the JSON
+/// endpoint behind its search page.
+#[derive(Clone)]
+pub struct GooglePatents;
+
+impl EngineInfo for GooglePatents {
+    fn name(&self) -> &'static str {
+        "Google Patents"
+    }
+}
+
+#[derive(Deserialize)]
+struct QueryResponse {
+    results: Results,
+}
+
+#[derive(Deserialize)]
+struct Results {
+    #[serde(default)]
+    total_num_pages: u32,
+    /// Results grouped by the search page, usually a single cluster
+    #[serde(default)]
+    cluster: Vec<Cluster>,
+}
+
+#[derive(Deserialize)]
+struct Cluster {
+    #[serde(default)]
+    result: Vec<Hit>,
+}
+
+#[derive(Deserialize)]
+struct Hit {
+    /// Path of the patent's page, e.g. "patent/US9123456B2/en"
+    id: String,
+    patent: Patent,
+}
+
+#[derive(Deserialize)]
+struct Patent {
+    publication_number: String,
+    /// HTML escaped
+    title: String,
+    /// HTML, with the matched terms in bold
+    #[serde(default)]
+    snippet: Option<String>,
+    #[serde(default)]
+    assignee: Option<String>,
+    #[serde(default)]
+    inventor: Option<String>,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    filing_date: Option<String>,
+    /// Path on the patent image storage
+    #[serde(default)]
+    pdf: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<PatentRow> for GooglePatents {
+    async fn search_page(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<PatentPage, EngineError> {
+        let page: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+        // the search page's own query string, passed along as a single value
+        let search = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("q", query)
+            .append_pair("num", &NUM.to_string())
+            .append_pair("page", &page.to_string())
+            .finish();
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(QUERY_URL)
+            .query(&[("url", search.as_str()), ("exp", "")])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, page)
+    }
+}
+
+fn parse_response(body: &str, page: u32) -> Result<PatentPage, EngineError> {
+    let response: QueryResponse = parse_json(body)?;
+    let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+
+    let patents = response
+        .results
+        .cluster
+        .into_iter()
+        .flat_map(|cluster| cluster.result)
+        .filter_map(|hit| {
+            let patent = hit.patent;
+            Some(PatentRow {
+                url: format!("{BASE_URL}/{}", hit.id.trim_start_matches('/')),
+                number: patent.publication_number,
+                title: html_text(&patent.title)?,
+                assignee: patent.assignee.as_deref().and_then(html_text),
+                inventor: patent.inventor.as_deref().and_then(html_text),
+                filing_date: non_empty(patent.filing_date),
+                snippet: patent.snippet.as_deref().and_then(html_text),
+                pdf: non_empty(patent.pdf).map(|pdf| format!("{PDF_URL}/{pdf}")),
+            })
+        })
+        .collect();
+
+    Ok(PatentPage {
+        rows: patents,
+        next_page: (page + 1 < response.results.total_num_pages).then(|| (page + 1).to_string()),
+    })
+}
+
+/// Text of an HTML fragment, with its entities decoded and whitespace
+/// collapsed.
+fn html_text(html: &str) -> Option<String> {
+    let text: String = Html::parse_fragment(html).root_element().text().collect();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "results": {"total_num_results": 45, "total_num_pages": 3, "num_page": 0,
+                    "cluster": [{"result": [
+                        {"id": "patent/US9123456B2/en", "rank": 0,
+                         "patent": {"title": "Memory safe compiler &amp; runtime\n",
+                                    "snippet": "A <b>compiler</b> that checks ownership &hellip;",
+                                    "priority_date": "2012-06-01", "filing_date": "2013-01-02",
+                                    "grant_date": "2015-09-08", "publication_date": "2015-09-08",
+                                    "inventor": "Jane Doe", "assignee": "Acme Corp",
+                                    "publication_number": "US9123456B2", "language": "en",
+                                    "pdf": "0f/2a/b3/c4d5e6f7a8b9c0/US9123456.pdf"}},
+                        {"id": "patent/EP1234567A1/de",
+                         "patent": {"title": "", "publication_number": "EP1234567A1"}},
+                        {"id": "patent/WO2020123456A1/en",
+                         "patent": {"title": "Borrow checker", "publication_number": "WO2020123456A1",
+                                    "assignee": "", "pdf": ""}}
+                    ]}]
+                }
+            }"#,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let compiler = &page.rows[0];
+        assert_eq!(
+            compiler.url,
+            "https://patents.google.com/patent/US9123456B2/en"
+        );
+        assert_eq!(compiler.number, "US9123456B2");
+        assert_eq!(compiler.title, "Memory safe compiler & runtime");
+        assert_eq!(
+            compiler.snippet.as_deref(),
+            Some("A compiler that checks ownership …")
+        );
+        assert_eq!(compiler.assignee.as_deref(), Some("Acme Corp"));
+        assert_eq!(compiler.inventor.as_deref(), Some("Jane Doe"));
+        assert_eq!(compiler.filing_date.as_deref(), Some("2013-01-02"));
+        assert_eq!(
+            compiler.pdf.as_deref(),
+            Some(
+                "https://patentimages.storage.googleapis.com/0f/2a/b3/c4d5e6f7a8b9c0/US9123456.pdf"
+            )
+        );
+
+        let bare = &page.rows[1];
+        assert_eq!(bare.assignee, None);
+        assert_eq!(bare.pdf, None);
+        assert_eq!(page.next_page.as_deref(), Some("1"));
+
+        let last =
+            parse_response(r#"{"results": {"total_num_pages": 3, "cluster": []}}"#, 2).unwrap();
+        assert_eq!(last.next_page, None);
+    }
+}
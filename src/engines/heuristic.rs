@@ -0,0 +1,118 @@
+use scraper::{ElementRef, Html, Selector};
+use std::{collections::HashSet, sync::LazyLock};
+
+use crate::cache::ResultRow;
+
+const MAX_DESCRIPTION_LEN: usize = 300;
+
+/// Markup agnostic fallback for when an engine's schema stops matching.
+///
+/// Treats every heading linked to an absolute url as a result, using the
+/// first text following the heading as the description.
+pub fn heuristic_results(html: &Html) -> Vec<ResultRow> {
+    static HEADINGS: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("h1, h2, h3, h4").unwrap());
+    static ANCHOR: LazyLock<Selector> = LazyLock::new(|| Selector::parse("a[href]").unwrap());
+
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+
+    for heading in html.select(&HEADINGS) {
+        // the anchor either wraps the heading or sits inside of it
+        let (link, block) = match heading
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .find(|e| e.value().name() == "a" && e.value().attr("href").is_some())
+        {
+            Some(link) => (link, link),
+            None => match heading.select(&ANCHOR).next() {
+                Some(link) => (link, heading),
+                None => continue,
+            },
+        };
+
+        let url = link.value().attr("href").unwrap_or_default();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            continue;
+        }
+        if !seen.insert(url.to_string()) {
+            continue;
+        }
+
+        let title = collapse_whitespace(&heading.text().collect::<String>());
+        if title.is_empty() {
+            continue;
+        }
+
+        results.push(ResultRow {
+            url: url.to_string(),
+            title,
+            description: following_text(block),
+            ..Default::default()
+        });
+    }
+
+    results
+}
+
+fn following_text(element: ElementRef) -> String {
+    let mut node = element;
+
+    // climb until a sibling with text is found, stopping at the result boundary
+    for _ in 0..3 {
+        let text = node
+            .next_siblings()
+            .filter_map(ElementRef::wrap)
+            .map(|e| collapse_whitespace(&e.text().collect::<String>()))
+            .find(|t| !t.is_empty());
+
+        if let Some(text) = text {
+            return text.chars().take(MAX_DESCRIPTION_LEN).collect();
+        }
+
+        match node.parent().and_then(ElementRef::wrap) {
+            Some(parent) => node = parent,
+            None => break,
+        }
+    }
+
+    String::new()
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::heuristic_results;
+    use scraper::Html;
+
+    #[test]
+    fn test_heuristic_results() {
+        let html = Html::parse_document(
+            r#"
+            <nav><h2>Menu</h2></nav>
+            <div class="new-result-markup">
+                <a href="https://a.com"><h3>First  result</h3></a>
+                <span>About the first result</span>
+            </div>
+            <div class="new-result-markup">
+                <h3><a href="https://b.com">Second result</a></h3>
+                <div><p>About the second result</p></div>
+            </div>
+            <div><h3><a href="/settings">Settings</a></h3></div>
+            <div><h3><a href="https://a.com">Duplicate</a></h3></div>
+            "#,
+        );
+
+        let results = heuristic_results(&html);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://a.com");
+        assert_eq!(results[0].title, "First result");
+        assert_eq!(results[0].description, "About the first result");
+        assert_eq!(results[1].url, "https://b.com");
+        assert_eq!(results[1].description, "About the second result");
+    }
+}
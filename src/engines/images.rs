@@ -0,0 +1,449 @@
+use reqwest::Url;
+use std::{collections::HashSet, str::FromStr};
+
+use crate::{cache::ImagesRow, config::SafeSearch};
+
+/// Filters for an image search. Engines translate what they can into their
+/// own query parameters, the rest is checked against each result's metadata
+/// with [`ImageOptions::matches`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageOptions {
+    pub size: Option<ImageSize>,
+    pub min_width: Option<u32>,
+    pub min_height: Option<u32>,
+    pub aspect: Option<AspectRatio>,
+    /// `None` allows any license
+    pub license: Option<ImageLicense>,
+    pub format: Option<ImageFormat>,
+    /// `None` uses the configured level
+    pub safe_search: Option<SafeSearch>,
+    /// Zero based page of results, not a filter so it isn't part of the cache key
+    pub page: usize,
+    /// Read the cache but never write to it
+    pub private: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageSize {
+    /// Longest side under 500px
+    Small,
+    /// Longest side between 500 and 1500px
+    Medium,
+    /// Longest side of 1500px and up
+    Large,
+    /// At least 1920x1080
+    Wallpaper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AspectRatio {
+    Square,
+    Tall,
+    Wide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageLicense {
+    /// Any Creative Commons license, public domain dedications included
+    CreativeCommons,
+    /// CC0 or the public domain mark
+    PublicDomain,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Jpg,
+    Png,
+    Gif,
+    Svg,
+    Webp,
+}
+
+impl ImageSize {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageSize::Small => "small",
+            ImageSize::Medium => "medium",
+            ImageSize::Large => "large",
+            ImageSize::Wallpaper => "wallpaper",
+        }
+    }
+
+    fn matches(&self, width: u32, height: u32) -> bool {
+        let longest = width.max(height);
+        match self {
+            ImageSize::Small => longest < 500,
+            ImageSize::Medium => (500..1500).contains(&longest),
+            ImageSize::Large => longest >= 1500,
+            ImageSize::Wallpaper => width >= 1920 && height >= 1080,
+        }
+    }
+}
+
+impl FromStr for ImageSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "small" => Ok(Self::Small),
+            "medium" => Ok(Self::Medium),
+            "large" => Ok(Self::Large),
+            "wallpaper" => Ok(Self::Wallpaper),
+            _ => Err(format!("Unknown image size: {s}")),
+        }
+    }
+}
+
+impl AspectRatio {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AspectRatio::Square => "square",
+            AspectRatio::Tall => "tall",
+            AspectRatio::Wide => "wide",
+        }
+    }
+
+    fn matches(&self, width: u32, height: u32) -> bool {
+        let ratio = width as f64 / height.max(1) as f64;
+        match self {
+            AspectRatio::Square => (0.8..=1.25).contains(&ratio),
+            AspectRatio::Tall => ratio < 0.8,
+            AspectRatio::Wide => ratio > 1.25,
+        }
+    }
+}
+
+impl FromStr for AspectRatio {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "square" => Ok(Self::Square),
+            "tall" => Ok(Self::Tall),
+            "wide" => Ok(Self::Wide),
+            _ => Err(format!("Unknown aspect ratio: {s}")),
+        }
+    }
+}
+
+impl ImageLicense {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageLicense::CreativeCommons => "creative-commons",
+            ImageLicense::PublicDomain => "public-domain",
+        }
+    }
+
+    /// License shown for images an engine filtered by this license without
+    /// saying which one exactly each image has.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImageLicense::CreativeCommons => "Creative Commons",
+            ImageLicense::PublicDomain => "Public Domain",
+        }
+    }
+
+    /// Whether a license as shown by an engine, e.g. "CC BY-SA 4.0", qualifies.
+    fn matches(&self, license: &str) -> bool {
+        let license = license.to_lowercase();
+        let public_domain = license.starts_with("cc0")
+            || license.contains("public domain")
+            || license.contains("publicdomain")
+            || license == "pdm";
+
+        match self {
+            ImageLicense::PublicDomain => public_domain,
+            ImageLicense::CreativeCommons => {
+                public_domain || license.starts_with("cc") || license.contains("creative commons")
+            }
+        }
+    }
+}
+
+impl FromStr for ImageLicense {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "creative-commons" | "cc" => Ok(Self::CreativeCommons),
+            "public-domain" => Ok(Self::PublicDomain),
+            _ => Err(format!("Unknown image license: {s}")),
+        }
+    }
+}
+
+impl ImageFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpg => "jpg",
+            ImageFormat::Png => "png",
+            ImageFormat::Gif => "gif",
+            ImageFormat::Svg => "svg",
+            ImageFormat::Webp => "webp",
+        }
+    }
+
+    /// Guesses the format from the url's file extension, or a `format`/`fm`
+    /// query parameter as used by image CDNs.
+    pub fn from_url(url: &str) -> Option<Self> {
+        let url = Url::parse(url).ok()?;
+
+        let param = url
+            .query_pairs()
+            .find(|(k, _)| k == "format" || k == "fm")
+            .and_then(|(_, v)| v.parse().ok());
+
+        param.or_else(|| {
+            let file = url.path_segments()?.next_back()?;
+            file.rsplit_once('.')?.1.parse().ok()
+        })
+    }
+
+    /// Format of an `image/*` content type, parameters ignored.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_lowercase();
+        mime.strip_prefix("image/")?
+            .trim_start_matches("x-")
+            .trim_end_matches("+xml")
+            .parse()
+            .ok()
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "jpg" | "jpeg" | "pjpeg" => Ok(Self::Jpg),
+            "png" => Ok(Self::Png),
+            "gif" => Ok(Self::Gif),
+            "svg" => Ok(Self::Svg),
+            "webp" => Ok(Self::Webp),
+            _ => Err(format!("Unknown image format: {s}")),
+        }
+    }
+}
+
+impl ImageOptions {
+    /// Whether an image passes the filters. Images without dimensions or a
+    /// recognizable format are kept, the engine's own filter is all there is
+    /// to go on for them.
+    ///
+    /// A license filter is strict though, images with no known license are
+    /// dropped rather than risk showing one that can't be reused.
+    pub fn matches(&self, image: &ImagesRow) -> bool {
+        if let Some(license) = self.license
+            && !image.license.as_deref().is_some_and(|l| license.matches(l))
+        {
+            return false;
+        }
+
+        if let Some(format) = self.format
+            && ImageFormat::from_url(&image.url).is_some_and(|f| f != format)
+        {
+            return false;
+        }
+
+        let (Some(width), Some(height)) = (image.width, image.height) else {
+            return true;
+        };
+
+        self.size.is_none_or(|size| size.matches(width, height))
+            && self.min_width.is_none_or(|min| width >= min)
+            && self.min_height.is_none_or(|min| height >= min)
+            && self
+                .aspect
+                .is_none_or(|aspect| aspect.matches(width, height))
+    }
+
+    /// Stable text form of the set filters, empty when there are none.
+    /// Filtered searches are cached apart from unfiltered ones.
+    pub fn cache_suffix(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(size) = self.size {
+            parts.push(format!("size={}", size.as_str()));
+        }
+        if let Some(min) = self.min_width {
+            parts.push(format!("min_width={min}"));
+        }
+        if let Some(min) = self.min_height {
+            parts.push(format!("min_height={min}"));
+        }
+        if let Some(aspect) = self.aspect {
+            parts.push(format!("aspect={}", aspect.as_str()));
+        }
+        if let Some(license) = self.license {
+            parts.push(format!("license={}", license.as_str()));
+        }
+        if let Some(format) = self.format {
+            parts.push(format!("format={}", format.as_str()));
+        }
+        if let Some(safe_search) = self.safe_search.filter(|s| *s != SafeSearch::Moderate) {
+            parts.push(format!("safe_search={}", safe_search.as_str()));
+        }
+
+        parts.join("&")
+    }
+}
+
+/// Whether the image or the page it's on is hosted on one of `domains` or
+/// their subdomains.
+pub fn is_blocked_host(image: &ImagesRow, domains: &HashSet<String>) -> bool {
+    if domains.is_empty() {
+        return false;
+    }
+
+    [Some(&image.url), image.source_url.as_ref()]
+        .into_iter()
+        .flatten()
+        .filter_map(|url| Url::parse(url).ok())
+        .filter_map(|url| url.host_str().map(str::to_lowercase))
+        .any(|host| {
+            let mut host = host.as_str();
+            loop {
+                if domains.contains(host) {
+                    return true;
+                }
+                match host.split_once('.') {
+                    Some((_, parent)) => host = parent,
+                    None => return false,
+                }
+            }
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AspectRatio, ImageFormat, ImageLicense, ImageOptions, ImageSize, is_blocked_host};
+    use crate::cache::ImagesRow;
+    use std::collections::HashSet;
+
+    fn image(width: u32, height: u32) -> ImagesRow {
+        ImagesRow {
+            width: Some(width),
+            height: Some(height),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let options = ImageOptions {
+            size: Some(ImageSize::Wallpaper),
+            aspect: Some(AspectRatio::Wide),
+            ..Default::default()
+        };
+        assert!(options.matches(&image(2560, 1440)));
+        assert!(!options.matches(&image(1080, 1920)));
+        assert!(!options.matches(&image(800, 450)));
+        // unknown dimensions can't be ruled out
+        assert!(options.matches(&ImagesRow::default()));
+
+        let options = ImageOptions {
+            min_width: Some(1000),
+            ..Default::default()
+        };
+        assert!(options.matches(&image(1000, 10)));
+        assert!(!options.matches(&image(999, 2000)));
+
+        assert!(ImageOptions::default().matches(&image(1, 1)));
+    }
+
+    #[test]
+    fn test_license() {
+        let licensed = |license: &str| ImagesRow {
+            license: Some(license.to_string()),
+            ..Default::default()
+        };
+
+        let options = ImageOptions {
+            license: Some(ImageLicense::CreativeCommons),
+            ..Default::default()
+        };
+        assert!(options.matches(&licensed("CC BY-SA 4.0")));
+        assert!(options.matches(&licensed("CC0 1.0")));
+        assert!(!options.matches(&licensed("All rights reserved")));
+        assert!(!options.matches(&ImagesRow::default()));
+
+        let options = ImageOptions {
+            license: Some(ImageLicense::PublicDomain),
+            ..Default::default()
+        };
+        assert!(options.matches(&licensed("Public Domain Mark 1.0")));
+        assert!(!options.matches(&licensed("CC BY 4.0")));
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            ImageFormat::from_url("https://a.com/photos/cat.JPEG"),
+            Some(ImageFormat::Jpg)
+        );
+        assert_eq!(
+            ImageFormat::from_url("https://cdn.com/img/123?w=800&fm=webp"),
+            Some(ImageFormat::Webp)
+        );
+        assert_eq!(ImageFormat::from_url("https://a.com/image/123"), None);
+        assert_eq!(
+            ImageFormat::from_content_type("image/svg+xml; charset=utf-8"),
+            Some(ImageFormat::Svg)
+        );
+        assert_eq!(ImageFormat::from_content_type("text/html"), None);
+
+        let options = ImageOptions {
+            format: Some(ImageFormat::Png),
+            ..Default::default()
+        };
+        let at = |url: &str| ImagesRow {
+            url: url.to_string(),
+            ..Default::default()
+        };
+        assert!(options.matches(&at("https://a.com/a.png")));
+        assert!(!options.matches(&at("https://a.com/a.gif")));
+        assert!(options.matches(&at("https://a.com/image/123")));
+    }
+
+    #[test]
+    fn test_blocked_host() {
+        let domains: HashSet<String> = ["adult.example".to_string()].into();
+        let image = |url: &str, source: Option<&str>| ImagesRow {
+            url: url.to_string(),
+            source_url: source.map(str::to_string),
+            ..Default::default()
+        };
+
+        assert!(is_blocked_host(
+            &image("https://adult.example/a.jpg", None),
+            &domains
+        ));
+        assert!(is_blocked_host(
+            &image(
+                "https://cdn.com/a.jpg",
+                Some("https://www.Adult.example/gallery")
+            ),
+            &domains
+        ));
+        assert!(!is_blocked_host(
+            &image("https://notadult.example/a.jpg", None),
+            &domains
+        ));
+        assert!(!is_blocked_host(
+            &image("https://adult.example/a.jpg", None),
+            &HashSet::new()
+        ));
+    }
+
+    #[test]
+    fn test_cache_suffix() {
+        assert_eq!(ImageOptions::default().cache_suffix(), "");
+
+        let options = ImageOptions {
+            size: Some("Large".parse().unwrap()),
+            aspect: Some("square".parse().unwrap()),
+            private: true,
+            ..Default::default()
+        };
+        assert_eq!(options.cache_suffix(), "size=large&aspect=square");
+    }
+}
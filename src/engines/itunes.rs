@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    cache::PodcastRow,
+    engines::{EngineError, EngineInfo, PodcastPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://itunes.apple.com/search";
+const LIMIT: u32 = 50;
+
+/// Shows in the Apple Podcasts directory, through the keyless iTunes Search
+/// API. It has no paging, the best matches come on a single page.
+#[derive(Clone)]
+pub struct Itunes;
+
+impl EngineInfo for Itunes {
+    fn name(&self) -> &'static str {
+        "iTunes"
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    results: Vec<Collection>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Collection {
+    #[serde(default)]
+    collection_name: Option<String>,
+    /// The show's page on Apple Podcasts
+    #[serde(default)]
+    collection_view_url: Option<String>,
+    /// Missing for shows Apple hosts itself
+    #[serde(default)]
+    feed_url: Option<String>,
+    #[serde(default)]
+    artist_name: Option<String>,
+    #[serde(default)]
+    artwork_url600: Option<String>,
+    #[serde(default)]
+    artwork_url100: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<PodcastRow> for Itunes {
+    async fn search_page(
+        &self,
+        query: &str,
+        _page: Option<&str>,
+    ) -> Result<PodcastPage, EngineError> {
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("term", query),
+                ("media", "podcast"),
+                ("entity", "podcast"),
+                ("limit", &LIMIT.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body)
+    }
+}
+
+fn parse_response(body: &str) -> Result<PodcastPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+
+    let podcasts = response
+        .results
+        .into_iter()
+        .filter_map(|show| {
+            let feed_url = show.feed_url?;
+            Some(PodcastRow {
+                url: show.collection_view_url.unwrap_or_else(|| feed_url.clone()),
+                title: show.collection_name?,
+                feed_url,
+                author: show.artist_name,
+                artwork: show.artwork_url600.or(show.artwork_url100),
+                description: None,
+            })
+        })
+        .collect();
+
+    Ok(PodcastPage {
+        rows: podcasts,
+        next_page: None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "resultCount": 3,
+                "results": [
+                    {"wrapperType": "track", "kind": "podcast", "collectionId": 1504415185,
+                     "artistName": "Rustacean Station", "collectionName": "Rustacean Station",
+                     "collectionViewUrl": "https://podcasts.apple.com/us/podcast/rustacean-station/id1504415185?uo=4",
+                     "feedUrl": "https://rustacean-station.org/podcast.rss",
+                     "artworkUrl100": "https://is1-ssl.mzstatic.com/image/100x100bb.jpg",
+                     "artworkUrl600": "https://is1-ssl.mzstatic.com/image/600x600bb.jpg",
+                     "primaryGenreName": "Technology"},
+                    {"wrapperType": "track", "kind": "podcast", "collectionName": "Apple Original",
+                     "collectionViewUrl": "https://podcasts.apple.com/us/podcast/id1"},
+                    {"wrapperType": "track", "kind": "podcast", "collectionName": "Feed only",
+                     "feedUrl": "https://example.com/feed.xml",
+                     "artworkUrl100": "https://example.com/100.jpg"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let station = &page.rows[0];
+        assert_eq!(
+            station.url,
+            "https://podcasts.apple.com/us/podcast/rustacean-station/id1504415185?uo=4"
+        );
+        assert_eq!(
+            station.feed_url,
+            "https://rustacean-station.org/podcast.rss"
+        );
+        assert_eq!(station.author.as_deref(), Some("Rustacean Station"));
+        assert_eq!(
+            station.artwork.as_deref(),
+            Some("https://is1-ssl.mzstatic.com/image/600x600bb.jpg")
+        );
+        let feed_only = &page.rows[1];
+        assert_eq!(feed_only.url, "https://example.com/feed.xml");
+        assert_eq!(
+            feed_only.artwork.as_deref(),
+            Some("https://example.com/100.jpg")
+        );
+        assert_eq!(page.next_page, None);
+    }
+}
@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+
+use crate::{
+    cache::ResultRow,
+    config,
+    engines::{EngineError, EngineInfo, FileType, SearchEngine, SerpPage, new_rand_client},
+};
+
+/// An engine backed by a JSON API rather than a scraped result page.
+///
+/// Implementors describe the request and map the typed response into rows,
+/// [`SearchEngine`] is then implemented for them.
+pub trait JsonEngine: EngineInfo + Sync {
+    type Response: DeserializeOwned + Send;
+
+    /// `page` is a token previously returned by [`JsonEngine::next_page`].
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder;
+
+    /// Token for the page after `response`, if the API has one.
+    fn next_page(&self, _response: &Self::Response, _page: Option<&str>) -> Option<String> {
+        None
+    }
+
+    /// Turns an error response into the matching [`EngineError`], before the
+    /// body is parsed as a [`JsonEngine::Response`].
+    fn check(&self, _status: StatusCode, _body: &str) -> Result<(), EngineError> {
+        Ok(())
+    }
+
+    fn results(&self, response: Self::Response) -> Vec<ResultRow>;
+
+    /// Same as [`SearchEngine::file_type_query`].
+    fn file_type_query(&self, query: &str, file_type: FileType) -> Option<String> {
+        Some(format!("{query} filetype:{}", file_type.extension()))
+    }
+}
+
+#[async_trait]
+impl<E: JsonEngine> SearchEngine for E {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let client = new_rand_client().map_err(EngineError::ReqwestError)?;
+        let resp = self
+            .request(&client, query, page)
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        let status = resp.status();
+        let body = resp.text().await.map_err(EngineError::ReqwestError)?;
+        self.check(status, &body)?;
+        let response = parse_json(&body)?;
+        let next_page = self.next_page(&response, page);
+
+        let mut serp: SerpPage = self.results(response).into();
+        serp.has_next_page = next_page.is_some();
+        serp.next_page = next_page;
+        if config::get().archive_responses {
+            serp.raw = Some(body);
+        }
+        Ok(serp)
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        let response = parse_json(body)?;
+        // the token a page was fetched with isn't archived
+        let next_page = self.next_page(&response, None);
+
+        let mut serp: SerpPage = self.results(response).into();
+        serp.has_next_page = next_page.is_some();
+        serp.next_page = next_page;
+        Ok(serp)
+    }
+
+    fn file_type_query(&self, query: &str, file_type: FileType) -> Option<String> {
+        JsonEngine::file_type_query(self, query, file_type)
+    }
+}
+
+pub fn parse_json<T: DeserializeOwned>(body: &str) -> Result<T, EngineError> {
+    serde_json::from_str(body).map_err(EngineError::JsonError)
+}
+
+/// Completions from an OpenSearch suggestions response, `["query", [..]]`,
+/// which most engines' suggest endpoints answer with. Some add descriptions
+/// and other arrays after the completions, those are ignored.
+pub fn parse_opensearch_suggestions(body: &str) -> Result<Vec<String>, EngineError> {
+    let response: Vec<serde_json::Value> = parse_json(body)?;
+    let completions = response
+        .get(1)
+        .and_then(|completions| completions.as_array())
+        .ok_or_else(|| EngineError::ParseError("No suggestions array".to_string()))?;
+
+    Ok(completions
+        .iter()
+        .filter_map(|completion| completion.as_str())
+        .filter(|completion| !completion.trim().is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{JsonEngine, parse_json, parse_opensearch_suggestions};
+    use crate::{cache::ResultRow, engines::EngineInfo};
+    use reqwest::{Client, RequestBuilder};
+    use serde::Deserialize;
+
+    #[derive(Clone)]
+    struct Fake;
+
+    #[derive(Deserialize)]
+    struct FakeResponse {
+        hits: Vec<FakeHit>,
+    }
+
+    #[derive(Deserialize)]
+    struct FakeHit {
+        link: String,
+        name: String,
+        #[serde(default)]
+        summary: Option<String>,
+    }
+
+    impl EngineInfo for Fake {
+        fn name(&self) -> &'static str {
+            "Fake"
+        }
+    }
+
+    impl JsonEngine for Fake {
+        type Response = FakeResponse;
+
+        fn request(&self, client: &Client, query: &str, _page: Option<&str>) -> RequestBuilder {
+            client.get("https://example.com").query(&[("q", query)])
+        }
+
+        fn results(&self, response: FakeResponse) -> Vec<ResultRow> {
+            response
+                .hits
+                .into_iter()
+                .map(|h| ResultRow {
+                    url: h.link,
+                    title: h.name,
+                    description: h.summary.unwrap_or_default(),
+                    ..Default::default()
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_json_mapping() {
+        let response = parse_json(
+            r#"{"hits": [
+                {"link": "https://a.com", "name": "A", "summary": "about a"},
+                {"link": "https://b.com", "name": "B"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let results = Fake.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].description, "about a");
+        assert_eq!(results[1].url, "https://b.com");
+    }
+
+    #[test]
+    fn test_json_error() {
+        assert!(parse_json::<FakeResponse>("<html>blocked</html>").is_err());
+    }
+
+    #[test]
+    fn test_parse_opensearch_suggestions() {
+        assert_eq!(
+            parse_opensearch_suggestions(r#"["rust", ["rust lang", "", "rust game"]]"#).unwrap(),
+            ["rust lang", "rust game"]
+        );
+        // with the descriptions and urls arrays some engines add
+        assert_eq!(
+            parse_opensearch_suggestions(r#"["rust", ["rust lang"], [""], ["https://a.com"]]"#)
+                .unwrap(),
+            ["rust lang"]
+        );
+        assert!(parse_opensearch_suggestions(r#"{"suggestions": []}"#).is_err());
+    }
+}
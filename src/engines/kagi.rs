@@ -0,0 +1,128 @@
+use reqwest::{Client, RequestBuilder, header::AUTHORIZATION};
+use serde::Deserialize;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineInfo, JsonEngine},
+};
+
+const API_URL: &str = "https://kagi.com/api/v0/search";
+
+/// Kagi's Search API, billed per query to the account behind the token.
+#[derive(Debug, Clone)]
+pub struct Kagi {
+    api_key: String,
+}
+
+impl Kagi {
+    pub const NAME: &'static str = "Kagi";
+
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+impl EngineInfo for Kagi {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+/// Failed requests, e.g. with an exhausted balance, come back with an
+/// `error` list instead of `data`.
+#[derive(Deserialize)]
+pub struct KagiResponse {
+    #[serde(default)]
+    data: Option<Vec<KagiObject>>,
+}
+
+/// `t` is 0 for a search result and 1 for a list of related searches, which
+/// has none of the result fields.
+#[derive(Deserialize)]
+struct KagiObject {
+    t: u8,
+    #[serde(default)]
+    rank: Option<u32>,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    snippet: Option<String>,
+    #[serde(default)]
+    published: Option<String>,
+}
+
+impl JsonEngine for Kagi {
+    type Response = KagiResponse;
+
+    // the api has no paging, only a result limit
+    fn request(&self, client: &Client, query: &str, _page: Option<&str>) -> RequestBuilder {
+        client
+            .get(API_URL)
+            .header(AUTHORIZATION, format!("Bot {}", self.api_key))
+            .query(&[("q", query)])
+    }
+
+    fn results(&self, response: KagiResponse) -> Vec<ResultRow> {
+        let mut hits: Vec<KagiObject> = response
+            .data
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|obj| obj.t == 0 && !obj.url.is_empty())
+            .collect();
+        // stored in rank order, so the cache keeps Kagi's relevance ordering
+        hits.sort_by_key(|hit| hit.rank.unwrap_or(u32::MAX));
+
+        hits.into_iter()
+            .map(|hit| ResultRow {
+                url: hit.url,
+                title: hit.title,
+                description: hit.snippet.unwrap_or_default(),
+                published_at: hit.published,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Kagi, KagiResponse};
+    use crate::engines::{JsonEngine, parse_json};
+
+    #[test]
+    fn test_results() {
+        let kagi = Kagi::new("key");
+        let response: KagiResponse = parse_json(
+            r#"{
+                "meta": {"id": "120145a3", "node": "us-east", "ms": 412, "api_balance": 9.5},
+                "data": [
+                    {"t": 0, "rank": 2, "url": "https://doc.rust-lang.org/book/", "title": "The Book", "snippet": null},
+                    {"t": 0, "rank": 1, "url": "https://www.rust-lang.org/", "title": "Rust",
+                     "snippet": "A language empowering everyone.", "published": "2024-05-01T00:00:00Z"},
+                    {"t": 1, "list": ["rust book", "rust tutorial"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(kagi.next_page(&response, None), None);
+        let results = kagi.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(
+            results[0].published_at.as_deref(),
+            Some("2024-05-01T00:00:00Z")
+        );
+        assert_eq!(results[1].description, "");
+
+        let error: KagiResponse = parse_json(
+            r#"{"meta": {}, "data": null, "error": [{"code": 1, "msg": "Insufficient credit"}]}"#,
+        )
+        .unwrap();
+        assert!(kagi.results(error).is_empty());
+    }
+}
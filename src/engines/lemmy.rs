@@ -0,0 +1,234 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::{
+    cache::SocialRow,
+    engines::{
+        EngineError, EngineInfo, SocialPage, VerticalEngine, instance_id, new_rand_client,
+        parse_json,
+    },
+};
+
+const LIMIT: usize = 20;
+
+/// Posts and communities on a Lemmy instance, and those it federates with,
+/// through its search API.
+#[derive(Debug, Clone)]
+pub struct Lemmy {
+    base_url: String,
+}
+
+impl Lemmy {
+    pub const NAME: &'static str = "Lemmy";
+
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl EngineInfo for Lemmy {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn id(&self) -> Cow<'static, str> {
+        instance_id(Self::NAME, &self.base_url)
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    posts: Vec<PostView>,
+    #[serde(default)]
+    communities: Vec<CommunityView>,
+}
+
+#[derive(Deserialize)]
+struct PostView {
+    post: Post,
+    creator: Person,
+    community: Community,
+    #[serde(default)]
+    counts: Option<PostCounts>,
+}
+
+#[derive(Deserialize)]
+struct Post {
+    name: String,
+    /// Markdown
+    #[serde(default)]
+    body: Option<String>,
+    /// The post on its home instance
+    ap_id: String,
+    published: String,
+}
+
+#[derive(Deserialize)]
+struct Person {
+    name: String,
+    actor_id: String,
+}
+
+#[derive(Deserialize)]
+struct Community {
+    name: String,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    actor_id: String,
+    #[serde(default)]
+    published: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PostCounts {
+    score: i64,
+}
+
+#[derive(Deserialize)]
+struct CommunityView {
+    community: Community,
+}
+
+#[async_trait]
+impl VerticalEngine<SocialRow> for Lemmy {
+    async fn search_page(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SocialPage, EngineError> {
+        // Lemmy pages count from 1
+        let page: usize = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(format!("{}/api/v3/search", self.base_url))
+            .query(&[
+                ("q", query),
+                ("type_", "All"),
+                ("listing_type", "All"),
+                ("sort", "TopAll"),
+                ("page", &page.to_string()),
+                ("limit", &LIMIT.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, page)
+    }
+}
+
+fn parse_response(body: &str, page: usize) -> Result<SocialPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    // the limit applies to each kind of result
+    let full = response.posts.len() >= LIMIT || response.communities.len() >= LIMIT;
+
+    let posts = response.posts.into_iter().map(|view| SocialRow {
+        url: view.post.ap_id,
+        kind: "post".to_string(),
+        title: Some(view.post.name),
+        content: view.post.body.filter(|body| !body.trim().is_empty()),
+        author: handle('@', &view.creator.name, &view.creator.actor_id),
+        community: handle('!', &view.community.name, &view.community.actor_id),
+        score: view.counts.map(|counts| counts.score),
+        published_at: Some(timestamp(&view.post.published)),
+    });
+    let communities = response.communities.into_iter().map(|view| SocialRow {
+        kind: "community".to_string(),
+        title: Some(view.community.title),
+        content: view
+            .community
+            .description
+            .filter(|description| !description.trim().is_empty()),
+        community: handle('!', &view.community.name, &view.community.actor_id),
+        published_at: view.community.published.as_deref().map(timestamp),
+        url: view.community.actor_id,
+        ..Default::default()
+    });
+
+    Ok(SocialPage {
+        rows: communities.chain(posts).collect(),
+        next_page: full.then(|| (page + 1).to_string()),
+    })
+}
+
+/// `@name@host` for people and `!name@host` for communities, the host taken
+/// from their ActivityPub id.
+fn handle(sigil: char, name: &str, actor_id: &str) -> Option<String> {
+    let host = url::Url::parse(actor_id).ok()?.host_str()?.to_string();
+    Some(format!("{sigil}{name}@{host}"))
+}
+
+/// Older Lemmy versions leave the UTC offset out of their timestamps.
+fn timestamp(published: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(published)
+        .map(|date| date.to_rfc3339())
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(published, "%Y-%m-%dT%H:%M:%S%.f")
+                .map(|date| date.and_utc().to_rfc3339())
+        })
+        .unwrap_or_else(|_| published.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "type_": "All", "comments": [], "users": [],
+                "posts": [
+                    {"post": {"id": 1, "name": "Rust 1.80 is out", "url": "https://blog.rust-lang.org/2024/07/25/Rust-1.80.0.html",
+                              "body": "", "creator_id": 2, "community_id": 3,
+                              "published": "2024-07-25T14:00:00.123456", "ap_id": "https://lemmy.ml/post/1"},
+                     "creator": {"id": 2, "name": "ferris", "actor_id": "https://lemmy.ml/u/ferris"},
+                     "community": {"id": 3, "name": "rust", "title": "Rust Programming", "actor_id": "https://programming.dev/c/rust"},
+                     "counts": {"post_id": 1, "comments": 5, "score": 42, "upvotes": 44, "downvotes": 2}}
+                ],
+                "communities": [
+                    {"community": {"id": 3, "name": "rust", "title": "Rust Programming",
+                                   "description": "Discussion about the Rust language",
+                                   "actor_id": "https://programming.dev/c/rust", "published": "2023-06-01T00:00:00Z"},
+                     "counts": {"subscribers": 1000}}
+                ]
+            }"#,
+            1,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let community = &page.rows[0];
+        assert_eq!(community.url, "https://programming.dev/c/rust");
+        assert_eq!(community.kind, "community");
+        assert_eq!(
+            community.community.as_deref(),
+            Some("!rust@programming.dev")
+        );
+
+        let post = &page.rows[1];
+        assert_eq!(post.url, "https://lemmy.ml/post/1");
+        assert_eq!(post.kind, "post");
+        assert_eq!(post.title.as_deref(), Some("Rust 1.80 is out"));
+        assert_eq!(post.content, None);
+        assert_eq!(post.author.as_deref(), Some("@ferris@lemmy.ml"));
+        assert_eq!(post.community.as_deref(), Some("!rust@programming.dev"));
+        assert_eq!(post.score, Some(42));
+        assert_eq!(
+            post.published_at.as_deref(),
+            Some("2024-07-25T14:00:00.123456+00:00")
+        );
+        assert_eq!(page.next_page, None);
+    }
+}
@@ -0,0 +1,102 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineInfo, JsonEngine, instance_id},
+};
+
+/// A LibreY (or LibreX) instance, queried through its `api.php` JSON API.
+#[derive(Debug, Clone)]
+pub struct LibreY {
+    base_url: String,
+}
+
+impl LibreY {
+    pub const NAME: &'static str = "LibreY";
+
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl EngineInfo for LibreY {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn id(&self) -> Cow<'static, str> {
+        instance_id(Self::NAME, &self.base_url)
+    }
+}
+
+/// Instant answers come back as entries without a url.
+#[derive(Deserialize)]
+pub struct LibreyHit {
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: String,
+}
+
+impl JsonEngine for LibreY {
+    type Response = Vec<LibreyHit>;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        client.get(format!("{}/api.php", self.base_url)).query(&[
+            ("q", query),
+            ("t", "0"), // text results
+            ("p", page.unwrap_or("0")),
+        ])
+    }
+
+    fn next_page(&self, response: &Vec<LibreyHit>, page: Option<&str>) -> Option<String> {
+        let number: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+        (!response.is_empty()).then(|| (number + 1).to_string())
+    }
+
+    fn results(&self, response: Vec<LibreyHit>) -> Vec<ResultRow> {
+        response
+            .into_iter()
+            .filter(|hit| !hit.url.is_empty())
+            .map(|hit| ResultRow {
+                url: hit.url,
+                title: hit.title,
+                description: hit.description,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LibreY, LibreyHit};
+    use crate::engines::{JsonEngine, parse_json};
+
+    #[test]
+    fn test_results() {
+        let librey = LibreY::new("https://librey.example.org");
+        let response: Vec<LibreyHit> = parse_json(
+            r#"[
+                {"response": "Rust is a programming language", "source": "https://en.wikipedia.org/wiki/Rust"},
+                {"title": "Rust", "url": "https://www.rust-lang.org/", "base_url": "www.rust-lang.org", "description": "A language empowering everyone."},
+                {"title": "The Book", "url": "https://doc.rust-lang.org/book/", "base_url": "doc.rust-lang.org", "description": ""}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(librey.next_page(&response, Some("1")).as_deref(), Some("2"));
+        let results = librey.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(results[0].description, "A language empowering everyone.");
+
+        assert_eq!(librey.next_page(&Vec::new(), None), None);
+    }
+}
@@ -0,0 +1,221 @@
+use async_trait::async_trait;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+use std::{borrow::Cow, sync::LazyLock};
+
+use crate::{
+    cache::SocialRow,
+    engines::{
+        EngineError, EngineInfo, SocialPage, VerticalEngine, instance_id, new_rand_client,
+        parse_json,
+    },
+};
+
+const LIMIT: usize = 20;
+
+/// Posts and hashtags on a Mastodon instance, through its search API.
+/// Instances only search posts for signed in users, so without an access
+/// token mostly hashtags come back.
+#[derive(Debug, Clone)]
+pub struct Mastodon {
+    base_url: String,
+    token: Option<String>,
+}
+
+impl Mastodon {
+    pub const NAME: &'static str = "Mastodon";
+
+    pub fn new(base_url: &str, token: Option<&str>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: token.map(str::to_string),
+        }
+    }
+}
+
+impl EngineInfo for Mastodon {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn id(&self) -> Cow<'static, str> {
+        instance_id(Self::NAME, &self.base_url)
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    statuses: Vec<Status>,
+    #[serde(default)]
+    hashtags: Vec<Tag>,
+}
+
+#[derive(Deserialize)]
+struct Status {
+    /// The post's page, missing for some remote posts
+    #[serde(default)]
+    url: Option<String>,
+    /// The post's ActivityPub id
+    uri: String,
+    created_at: String,
+    /// HTML
+    content: String,
+    #[serde(default)]
+    spoiler_text: String,
+    account: Account,
+    #[serde(default)]
+    favourites_count: i64,
+    #[serde(default)]
+    reblogs_count: i64,
+}
+
+#[derive(Deserialize)]
+struct Account {
+    /// `name` for local accounts, `name@host` for remote ones
+    acct: String,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+    url: String,
+}
+
+#[async_trait]
+impl VerticalEngine<SocialRow> for Mastodon {
+    async fn search_page(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SocialPage, EngineError> {
+        let limit = LIMIT.to_string();
+        let mut params = vec![("q", query), ("limit", &limit)];
+        // only a single kind of result can be paged through
+        if let Some(offset) = page {
+            params.extend([("type", "statuses"), ("offset", offset)]);
+        }
+
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(format!("{}/api/v2/search", self.base_url))
+            .query(&params);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let body = request
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        let offset = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+        parse_response(&body, &self.base_url, offset)
+    }
+}
+
+fn parse_response(body: &str, base_url: &str, offset: usize) -> Result<SocialPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    let host = url::Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+    let next_page = (response.statuses.len() >= LIMIT).then(|| (offset + LIMIT).to_string());
+
+    let hashtags = response.hashtags.into_iter().map(|tag| SocialRow {
+        url: tag.url,
+        kind: "community".to_string(),
+        title: Some(format!("#{}", tag.name)),
+        ..Default::default()
+    });
+    let statuses = response.statuses.into_iter().map(|status| {
+        let author = match status.account.acct.contains('@') {
+            true => format!("@{}", status.account.acct),
+            false => format!("@{}@{host}", status.account.acct),
+        };
+        SocialRow {
+            url: status.url.unwrap_or(status.uri),
+            kind: "post".to_string(),
+            // content warnings hide the text behind a summary
+            title: Some(status.spoiler_text).filter(|spoiler| !spoiler.is_empty()),
+            content: html_text(&status.content),
+            author: Some(author),
+            community: None,
+            score: Some(status.favourites_count + status.reblogs_count),
+            published_at: Some(status.created_at),
+        }
+    });
+
+    Ok(SocialPage {
+        rows: statuses.chain(hashtags).collect(),
+        next_page,
+    })
+}
+
+/// Plain text of a post's HTML, one paragraph after the other.
+fn html_text(html: &str) -> Option<String> {
+    static PARAGRAPH: LazyLock<Selector> = LazyLock::new(|| Selector::parse("p").unwrap());
+
+    let fragment = Html::parse_fragment(html);
+    let paragraphs: Vec<String> = fragment
+        .select(&PARAGRAPH)
+        .map(|p| p.text().collect())
+        .collect();
+    let text = match paragraphs.is_empty() {
+        true => fragment.root_element().text().collect(),
+        false => paragraphs.join(" "),
+    };
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r##"{
+                "accounts": [],
+                "statuses": [
+                    {"id": "1", "created_at": "2024-07-25T14:00:00.000Z",
+                     "url": "https://mastodon.social/@ferris/1", "uri": "https://mastodon.social/users/ferris/statuses/1",
+                     "content": "<p>Rust 1.80 is out!</p><p><a href=\"https://mastodon.social/tags/rust\" class=\"mention hashtag\" rel=\"tag\">#<span>rust</span></a></p>",
+                     "spoiler_text": "", "account": {"id": "9", "username": "ferris", "acct": "ferris"},
+                     "favourites_count": 10, "reblogs_count": 5},
+                    {"id": "2", "created_at": "2024-07-26T08:00:00.000Z", "url": null,
+                     "uri": "https://fosstodon.org/users/crab/statuses/2",
+                     "content": "<p>Borrow checker thoughts</p>", "spoiler_text": "long post",
+                     "account": {"id": "10", "username": "crab", "acct": "crab@fosstodon.org"}}
+                ],
+                "hashtags": [{"name": "rust", "url": "https://mastodon.social/tags/rust", "history": []}]
+            }"##,
+            "https://mastodon.social",
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 3);
+        let first = &page.rows[0];
+        assert_eq!(first.url, "https://mastodon.social/@ferris/1");
+        assert_eq!(first.title, None);
+        assert_eq!(first.content.as_deref(), Some("Rust 1.80 is out! #rust"));
+        assert_eq!(first.author.as_deref(), Some("@ferris@mastodon.social"));
+        assert_eq!(first.score, Some(15));
+
+        let remote = &page.rows[1];
+        assert_eq!(remote.url, "https://fosstodon.org/users/crab/statuses/2");
+        assert_eq!(remote.title.as_deref(), Some("long post"));
+        assert_eq!(remote.author.as_deref(), Some("@crab@fosstodon.org"));
+
+        let tag = &page.rows[2];
+        assert_eq!(tag.kind, "community");
+        assert_eq!(tag.title.as_deref(), Some("#rust"));
+        assert_eq!(page.next_page, None);
+    }
+}
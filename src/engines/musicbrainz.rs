@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    cache::MusicRow,
+    engines::{EngineError, EngineInfo, MusicPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://musicbrainz.org/ws/2/release-group";
+const LIMIT: u32 = 20;
+
+/// Albums, singles and EPs from MusicBrainz, through its search API. Cover
+/// art links point at the Cover Art Archive, which has no image for some
+/// release groups.
+#[derive(Clone)]
+pub struct MusicBrainz;
+
+impl EngineInfo for MusicBrainz {
+    fn name(&self) -> &'static str {
+        "MusicBrainz"
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    count: u32,
+    #[serde(default, rename = "release-groups")]
+    release_groups: Vec<ReleaseGroup>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ReleaseGroup {
+    id: String,
+    title: String,
+    /// e.g. "Album", "Single" or "EP"
+    #[serde(default)]
+    primary_type: Option<String>,
+    /// `YYYY`, `YYYY-MM` or `YYYY-MM-DD`
+    #[serde(default)]
+    first_release_date: Option<String>,
+    #[serde(default)]
+    artist_credit: Vec<ArtistCredit>,
+}
+
+#[derive(Deserialize)]
+struct ArtistCredit {
+    /// The name as credited on this release
+    name: String,
+    /// Joins it to the next credit, e.g. " feat. "
+    #[serde(default)]
+    joinphrase: String,
+}
+
+#[async_trait]
+impl VerticalEngine<MusicRow> for MusicBrainz {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<MusicPage, EngineError> {
+        let offset: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("query", query),
+                ("fmt", "json"),
+                ("limit", &LIMIT.to_string()),
+                ("offset", &offset.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, offset)
+    }
+}
+
+fn parse_response(body: &str, offset: u32) -> Result<MusicPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    let next = offset + LIMIT;
+
+    let items = response
+        .release_groups
+        .into_iter()
+        .map(|group| {
+            let artist: String = group
+                .artist_credit
+                .iter()
+                .flat_map(|credit| [credit.name.as_str(), credit.joinphrase.as_str()])
+                .collect();
+            MusicRow {
+                url: format!("https://musicbrainz.org/release-group/{}", group.id),
+                title: group.title,
+                artist: (!artist.is_empty()).then_some(artist),
+                kind: group
+                    .primary_type
+                    .map(|kind| kind.to_lowercase())
+                    .unwrap_or_else(|| "release".to_string()),
+                year: group
+                    .first_release_date
+                    .and_then(|date| date.get(..4)?.parse().ok()),
+                cover: Some(format!(
+                    "https://coverartarchive.org/release-group/{}/front-250",
+                    group.id
+                )),
+            }
+        })
+        .collect();
+
+    Ok(MusicPage {
+        rows: items,
+        next_page: (next < response.count).then(|| next.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "created": "2024-10-01T10:00:00.000Z", "count": 45, "offset": 0,
+                "release-groups": [
+                    {"id": "b1392450-e666-3926-a536-22c65f834433", "score": 100,
+                     "title": "OK Computer", "primary-type": "Album",
+                     "first-release-date": "1997-05-21",
+                     "artist-credit": [{"name": "Radiohead",
+                                        "artist": {"id": "a74b1b7f-71a5-4011-9441-d0b5e4122711", "name": "Radiohead"}}]},
+                    {"id": "0f8ef3a1-0000-4000-8000-000000000000", "score": 80,
+                     "title": "Collab", "first-release-date": "",
+                     "artist-credit": [{"name": "A", "joinphrase": " feat. "}, {"name": "B"}]}
+                ]
+            }"#,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let ok = &page.rows[0];
+        assert_eq!(
+            ok.url,
+            "https://musicbrainz.org/release-group/b1392450-e666-3926-a536-22c65f834433"
+        );
+        assert_eq!(ok.artist.as_deref(), Some("Radiohead"));
+        assert_eq!(ok.kind, "album");
+        assert_eq!(ok.year, Some(1997));
+        assert_eq!(
+            ok.cover.as_deref(),
+            Some(
+                "https://coverartarchive.org/release-group/b1392450-e666-3926-a536-22c65f834433/front-250"
+            )
+        );
+
+        let collab = &page.rows[1];
+        assert_eq!(collab.artist.as_deref(), Some("A feat. B"));
+        assert_eq!(collab.kind, "release");
+        assert_eq!(collab.year, None);
+        assert_eq!(page.next_page.as_deref(), Some("20"));
+    }
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use reqwest::Url;
+use std::sync::LazyLock;
+
+use crate::engines::{
+    EngineError, EngineInfo, SearchEngine, SerpPage, SerpSchema, new_rand_client,
+    parse_page_blocking,
+};
+
+const SEARCH_URL: &str = "https://search.naver.com/search.naver";
+
+/// Naver's web tab, which leaves out its blog, cafe and shopping blocks.
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new(
+        "ul.lst_total > li.bx",
+        "a.link_tit, a.total_tit",
+        "a.link_tit, a.total_tit",
+    )
+    .container("#main_pack")
+    .description(".total_dsc, .dsc_txt, .api_txt_lines")
+    .next_page("a.btn_next:not([aria-disabled='true'])")
+});
+
+#[derive(Clone)]
+pub struct Naver;
+
+impl EngineInfo for Naver {
+    fn name(&self) -> &'static str {
+        "Naver"
+    }
+}
+
+#[async_trait]
+impl SearchEngine for Naver {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let html = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("where", "web"),
+                ("query", query),
+                ("start", page.unwrap_or("1")),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_page_blocking(html, parse_response).await
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_response(body)
+    }
+}
+
+pub fn parse_response(html: &str) -> Result<SerpPage, EngineError> {
+    let mut page = SEARCH_SCHEMA.parse_results(html)?;
+    // only the start index is needed to fetch the next page
+    page.next_page = page.next_page.as_deref().and_then(start_param);
+    page.has_next_page = page.next_page.is_some();
+
+    Ok(page)
+}
+
+/// `start` query parameter of the next page link.
+fn start_param(href: &str) -> Option<String> {
+    let url = Url::parse(SEARCH_URL).ok()?.join(href).ok()?;
+    url.query_pairs()
+        .find(|(k, _)| k == "start")
+        .map(|(_, v)| v.into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let html = r##"
+            <ul class="lst_total">
+              <li class="bx">
+                <div class="total_wrap">
+                  <a class="link_tit" href="https://www.rust-lang.org/ko/">Rust 프로그래밍 언어</a>
+                  <div class="total_dsc_wrap"><a class="total_dsc">모두가 안정적이고 효율적인 소프트웨어를 만들 수 있게 해주는 언어</a></div>
+                </div>
+              </li>
+              <li class="bx">
+                <div class="total_wrap">
+                  <a class="link_tit" href="https://doc.rust-lang.org/book/">The Rust Programming Language</a>
+                </div>
+              </li>
+            </ul>
+            <div class="sc_page">
+              <a class="btn_prev" aria-disabled="true" href="#">이전페이지</a>
+              <a class="btn_next" aria-disabled="false" href="?where=web&amp;query=rust&amp;start=16">다음페이지</a>
+            </div>
+        "##;
+
+        let page = parse_response(html).unwrap();
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/ko/");
+        assert_eq!(page.results[0].title, "Rust 프로그래밍 언어");
+        assert_eq!(
+            page.results[0].description,
+            "모두가 안정적이고 효율적인 소프트웨어를 만들 수 있게 해주는 언어"
+        );
+        assert_eq!(page.next_page.as_deref(), Some("16"));
+
+        let last = parse_response(
+            r##"<ul class="lst_total"></ul><a class="btn_next" aria-disabled="true" href="#">다음페이지</a>"##,
+        )
+        .unwrap();
+        assert!(!last.has_next_page);
+    }
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    cache::PlaceRow,
+    engines::{EngineError, EngineInfo, VerticalEngine, VerticalPage, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://nominatim.openstreetmap.org/search";
+const LIMIT: u32 = 10;
+
+/// OpenStreetMap's own geocoder. Its usage policy allows one request per
+/// second and asks for results to be cached, which the places cache does.
+#[derive(Clone)]
+pub struct Nominatim;
+
+impl EngineInfo for Nominatim {
+    fn name(&self) -> &'static str {
+        "Nominatim"
+    }
+}
+
+#[derive(Deserialize)]
+struct NominatimPlace {
+    /// "node", "way" or "relation"
+    osm_type: String,
+    osm_id: u64,
+    /// Coordinates come as strings
+    lat: String,
+    lon: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    display_name: Option<String>,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<PlaceRow> for Nominatim {
+    async fn search_page(
+        &self,
+        query: &str,
+        _page: Option<&str>,
+    ) -> Result<VerticalPage<PlaceRow>, EngineError> {
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("q", query),
+                ("format", "jsonv2"),
+                ("limit", &LIMIT.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body).map(VerticalPage::from)
+    }
+}
+
+fn parse_response(body: &str) -> Result<Vec<PlaceRow>, EngineError> {
+    let places: Vec<NominatimPlace> = parse_json(body)?;
+
+    Ok(places
+        .into_iter()
+        .filter_map(|place| {
+            let address = place.display_name.filter(|a| !a.is_empty());
+            // unnamed places, e.g. a house number, go by their address
+            let name = match place.name.is_empty() {
+                true => address.as_deref()?.split(',').next()?.trim().to_string(),
+                false => place.name,
+            };
+
+            Some(PlaceRow {
+                name,
+                lat: place.lat.parse().ok()?,
+                lon: place.lon.parse().ok()?,
+                address,
+                osm_url: osm_url(&place.osm_type, place.osm_id)?,
+                kind: place.kind,
+            })
+        })
+        .collect())
+}
+
+/// Page of an OpenStreetMap object, its type given as a word ("node") or
+/// its initial ("N").
+pub(super) fn osm_url(osm_type: &str, osm_id: u64) -> Option<String> {
+    let osm_type = match osm_type.to_lowercase().as_str() {
+        "n" | "node" => "node",
+        "w" | "way" => "way",
+        "r" | "relation" => "relation",
+        _ => return None,
+    };
+    Some(format!("https://www.openstreetmap.org/{osm_type}/{osm_id}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{osm_url, parse_response};
+
+    #[test]
+    fn test_osm_url() {
+        assert_eq!(
+            osm_url("relation", 62422).as_deref(),
+            Some("https://www.openstreetmap.org/relation/62422")
+        );
+        assert_eq!(
+            osm_url("N", 1).as_deref(),
+            Some("https://www.openstreetmap.org/node/1")
+        );
+        assert_eq!(osm_url("area", 1), None);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let places = parse_response(
+            r#"[
+                {"place_id": 1, "licence": "Data © OpenStreetMap contributors, ODbL 1.0.", "osm_type": "relation", "osm_id": 62422,
+                 "lat": "52.5170365", "lon": "13.3888599", "category": "boundary", "type": "administrative", "place_rank": 8,
+                 "importance": 0.85, "addresstype": "city", "name": "Berlin", "display_name": "Berlin, Deutschland",
+                 "boundingbox": ["52.3382448", "52.6755087", "13.0883450", "13.7611609"]},
+                {"place_id": 2, "osm_type": "way", "osm_id": 4321, "lat": "52.5", "lon": "13.4", "type": "house",
+                 "name": "", "display_name": "12, Unter den Linden, Mitte, Berlin, 10117, Deutschland"},
+                {"place_id": 3, "osm_type": "node", "osm_id": 1, "lat": "not a number", "lon": "13.4", "name": "Broken"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(places.len(), 2);
+        assert_eq!(places[0].name, "Berlin");
+        assert_eq!((places[0].lat, places[0].lon), (52.5170365, 13.3888599));
+        assert_eq!(places[0].address.as_deref(), Some("Berlin, Deutschland"));
+        assert_eq!(
+            places[0].osm_url,
+            "https://www.openstreetmap.org/relation/62422"
+        );
+        assert_eq!(places[0].kind.as_deref(), Some("administrative"));
+        assert_eq!(places[1].name, "12");
+    }
+}
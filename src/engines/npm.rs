@@ -0,0 +1,161 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    cache::PackageRow,
+    engines::{EngineError, EngineInfo, PackagePage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://registry.npmjs.org/-/v1/search";
+const SIZE: usize = 20;
+
+/// JavaScript packages, through the npm registry's search API.
+#[derive(Clone)]
+pub struct Npm;
+
+impl EngineInfo for Npm {
+    fn name(&self) -> &'static str {
+        "npm"
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    #[serde(default)]
+    objects: Vec<SearchObject>,
+    #[serde(default)]
+    total: usize,
+}
+
+#[derive(Deserialize)]
+struct SearchObject {
+    package: Package,
+    /// Only sent by newer versions of the registry
+    #[serde(default)]
+    downloads: Option<Downloads>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    name: String,
+    version: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    links: Links,
+}
+
+#[derive(Deserialize, Default)]
+struct Links {
+    #[serde(default)]
+    npm: Option<String>,
+    #[serde(default)]
+    repository: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Downloads {
+    monthly: i64,
+}
+
+#[async_trait]
+impl VerticalEngine<PackageRow> for Npm {
+    async fn search_page(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<PackagePage, EngineError> {
+        let from: usize = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("text", query),
+                ("size", &SIZE.to_string()),
+                ("from", &from.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, from)
+    }
+}
+
+fn parse_response(body: &str, from: usize) -> Result<PackagePage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    let next = from + SIZE;
+
+    let packages = response
+        .objects
+        .into_iter()
+        .map(|object| {
+            let package = object.package;
+            PackageRow {
+                url: package
+                    .links
+                    .npm
+                    .unwrap_or_else(|| format!("https://www.npmjs.com/package/{}", package.name)),
+                name: package.name,
+                registry: "npm".to_string(),
+                version: Some(package.version),
+                description: package.description.filter(|d| !d.trim().is_empty()),
+                downloads: object.downloads.map(|downloads| downloads.monthly),
+                repository: package.links.repository,
+            }
+        })
+        .collect();
+
+    Ok(PackagePage {
+        rows: packages,
+        next_page: (next < response.total).then(|| next.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "objects": [
+                    {"package": {"name": "express", "version": "4.21.0",
+                                 "description": "Fast, unopinionated, minimalist web framework",
+                                 "links": {"npm": "https://www.npmjs.com/package/express",
+                                           "repository": "https://github.com/expressjs/express"}},
+                     "downloads": {"monthly": 130000000, "weekly": 32000000},
+                     "score": {"final": 0.9}, "searchScore": 100},
+                    {"package": {"name": "@scope/tool", "version": "0.0.1", "links": {}},
+                     "score": {"final": 0.1}, "searchScore": 1}
+                ],
+                "total": 45,
+                "time": "Mon Sep 30 2024 10:00:00 GMT+0000"
+            }"#,
+            20,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let express = &page.rows[0];
+        assert_eq!(express.url, "https://www.npmjs.com/package/express");
+        assert_eq!(express.version.as_deref(), Some("4.21.0"));
+        assert_eq!(express.downloads, Some(130_000_000));
+        assert_eq!(
+            express.repository.as_deref(),
+            Some("https://github.com/expressjs/express")
+        );
+
+        let scoped = &page.rows[1];
+        assert_eq!(scoped.url, "https://www.npmjs.com/package/@scope/tool");
+        assert_eq!(scoped.downloads, None);
+        assert_eq!(page.next_page.as_deref(), Some("40"));
+    }
+}
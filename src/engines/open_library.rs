@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    cache::BookRow,
+    engines::{BookPage, EngineError, EngineInfo, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://openlibrary.org/search.json";
+const FIELDS: &str = "key,title,author_name,first_publish_year,cover_i,isbn";
+const LIMIT: u32 = 20;
+
+/// Books on Open Library, the Internet Archive's catalog, through its
+/// search API. Results are works, grouping a book's editions.
+#[derive(Clone)]
+pub struct OpenLibrary;
+
+impl EngineInfo for OpenLibrary {
+    fn name(&self) -> &'static str {
+        "Open Library"
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    #[serde(default)]
+    num_found: u32,
+    #[serde(default)]
+    docs: Vec<Doc>,
+}
+
+#[derive(Deserialize)]
+struct Doc {
+    /// Path of the work, e.g. `/works/OL45804W`
+    key: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    author_name: Vec<String>,
+    #[serde(default)]
+    first_publish_year: Option<i32>,
+    /// Id of the cover on covers.openlibrary.org
+    #[serde(default)]
+    cover_i: Option<i64>,
+    /// ISBNs of all editions, 10 and 13 digit mixed
+    #[serde(default)]
+    isbn: Vec<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<BookRow> for OpenLibrary {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<BookPage, EngineError> {
+        let offset: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("q", query),
+                ("fields", FIELDS),
+                ("limit", &LIMIT.to_string()),
+                ("offset", &offset.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, offset)
+    }
+}
+
+fn parse_response(body: &str, offset: u32) -> Result<BookPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+
+    let next = offset + LIMIT;
+    let next_page = (next < response.num_found).then(|| next.to_string());
+
+    let books = response
+        .docs
+        .into_iter()
+        .filter_map(|doc| {
+            let isbn = doc
+                .isbn
+                .iter()
+                .find(|isbn| isbn.len() == 13)
+                .or_else(|| doc.isbn.first())
+                .cloned();
+            Some(BookRow {
+                url: format!("https://openlibrary.org{}", doc.key),
+                title: doc.title?,
+                authors: doc.author_name,
+                year: doc.first_publish_year,
+                cover: doc
+                    .cover_i
+                    .map(|id| format!("https://covers.openlibrary.org/b/id/{id}-M.jpg")),
+                isbn,
+            })
+        })
+        .collect();
+
+    Ok(BookPage {
+        rows: books,
+        next_page,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "numFound": 52, "start": 20, "numFoundExact": true,
+                "docs": [
+                    {"key": "/works/OL17930368W", "title": "The Rust Programming Language",
+                     "author_name": ["Steve Klabnik", "Carol Nichols"], "first_publish_year": 2018,
+                     "cover_i": 8546231, "isbn": ["1593278284", "9781593278281", "9781718500440"]},
+                    {"key": "/works/OL1W", "title": "Untitled edition", "isbn": ["0123456789"]},
+                    {"key": "/works/OL2W"}
+                ],
+                "q": "rust", "offset": 20
+            }"#,
+            20,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let book = &page.rows[0];
+        assert_eq!(book.url, "https://openlibrary.org/works/OL17930368W");
+        assert_eq!(book.authors, ["Steve Klabnik", "Carol Nichols"]);
+        assert_eq!(book.year, Some(2018));
+        assert_eq!(
+            book.cover.as_deref(),
+            Some("https://covers.openlibrary.org/b/id/8546231-M.jpg")
+        );
+        assert_eq!(book.isbn.as_deref(), Some("9781593278281"));
+        assert_eq!(page.rows[1].isbn.as_deref(), Some("0123456789"));
+        assert_eq!(page.rows[1].cover, None);
+        assert_eq!(page.next_page.as_deref(), Some("40"));
+
+        let last = parse_response(r#"{"numFound": 3, "docs": []}"#, 0).unwrap();
+        assert_eq!(last.next_page, None);
+    }
+}
@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    cache::ImagesRow,
+    config::SafeSearch,
+    engines::{
+        EngineError, EngineInfo, ImageEngine, ImageLicense, ImageOptions, ImagePage, ImageSize,
+        new_rand_client, parse_json,
+    },
+};
+
+const IMAGES_URL: &str = "https://api.openverse.org/v1/images/";
+const PAGE_SIZE: u32 = 20;
+
+/// Openly licensed images indexed by Openverse, each with its license.
+#[derive(Clone)]
+pub struct Openverse;
+
+impl EngineInfo for Openverse {
+    fn name(&self) -> &'static str {
+        "Openverse"
+    }
+}
+
+#[derive(Deserialize)]
+struct OpenverseResponse {
+    #[serde(default)]
+    page: u32,
+    #[serde(default)]
+    page_count: u32,
+    #[serde(default)]
+    results: Vec<OpenverseImage>,
+}
+
+#[derive(Deserialize)]
+struct OpenverseImage {
+    url: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    creator: Option<String>,
+    #[serde(default)]
+    thumbnail: Option<String>,
+    /// Page the image was found on
+    #[serde(default)]
+    foreign_landing_url: Option<String>,
+    /// e.g. "by-sa", "cc0" or "pdm"
+    license: String,
+    #[serde(default)]
+    license_version: Option<String>,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+#[async_trait]
+impl ImageEngine for Openverse {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(IMAGES_URL)
+            .query(&[
+                ("q", query),
+                ("page", page.unwrap_or("1")),
+                ("page_size", &PAGE_SIZE.to_string()),
+            ]);
+        if let Some(size) = options.size {
+            // no wallpaper size, large is the closest
+            let size = match size {
+                ImageSize::Wallpaper => ImageSize::Large,
+                size => size,
+            };
+            request = request.query(&[("size", size.as_str())]);
+        }
+        if let Some(aspect) = options.aspect {
+            request = request.query(&[("aspect_ratio", aspect.as_str())]);
+        }
+        if options.license == Some(ImageLicense::PublicDomain) {
+            request = request.query(&[("license", "cc0,pdm")]);
+        }
+        if let Some(format) = options.format {
+            request = request.query(&[("extension", format.as_str())]);
+        }
+        if options.safe_search == Some(SafeSearch::Off) {
+            request = request.query(&[("mature", "true")]);
+        }
+
+        let body = request
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_image_response(&body)
+    }
+}
+
+fn parse_image_response(body: &str) -> Result<ImagePage, EngineError> {
+    let response: OpenverseResponse = parse_json(body)?;
+
+    Ok(ImagePage {
+        next_page: (response.page < response.page_count).then(|| (response.page + 1).to_string()),
+        images: response
+            .results
+            .into_iter()
+            .map(|image| ImagesRow {
+                license: Some(license_name(
+                    &image.license,
+                    image.license_version.as_deref(),
+                )),
+                attribution: image.creator,
+                url: image.url,
+                title: image.title.unwrap_or_default(),
+                width: image.width,
+                height: image.height,
+                thumbnail: image.thumbnail,
+                source_url: image.foreign_landing_url,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+/// The license as it's usually written, e.g. "CC BY-SA 4.0".
+fn license_name(license: &str, version: Option<&str>) -> String {
+    let name = match license.to_lowercase().as_str() {
+        "cc0" => "CC0".to_string(),
+        "pdm" => "Public Domain Mark".to_string(),
+        other => format!("CC {}", other.to_uppercase()),
+    };
+    match version {
+        Some(version) if !version.is_empty() => format!("{name} {version}"),
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{license_name, parse_image_response};
+
+    #[test]
+    fn test_license_name() {
+        assert_eq!(license_name("by-sa", Some("4.0")), "CC BY-SA 4.0");
+        assert_eq!(license_name("cc0", Some("1.0")), "CC0 1.0");
+        assert_eq!(license_name("pdm", None), "Public Domain Mark");
+    }
+
+    #[test]
+    fn test_parse_image_response() {
+        let page = parse_image_response(
+            r#"{
+                "result_count": 240, "page_count": 12, "page_size": 20, "page": 1,
+                "results": [
+                    {"id": "4bc43a04", "title": "Ferris", "url": "https://live.staticflickr.com/1/ferris.jpg",
+                     "creator": "rustacean", "license": "by", "license_version": "2.0",
+                     "thumbnail": "https://api.openverse.org/v1/images/4bc43a04/thumb/",
+                     "foreign_landing_url": "https://www.flickr.com/photos/rustacean/1", "width": 1024, "height": 768},
+                    {"id": "9f2e", "title": null, "url": "https://upload.wikimedia.org/a.png", "license": "cc0", "license_version": "1.0"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.images.len(), 2);
+        assert_eq!(page.images[0].license.as_deref(), Some("CC BY 2.0"));
+        assert_eq!(page.images[0].attribution.as_deref(), Some("rustacean"));
+        assert_eq!(
+            page.images[0].source_url.as_deref(),
+            Some("https://www.flickr.com/photos/rustacean/1")
+        );
+        assert_eq!(page.images[1].title, "");
+        assert_eq!(page.images[1].license.as_deref(), Some("CC0 1.0"));
+        assert_eq!(page.next_page.as_deref(), Some("2"));
+
+        let last = parse_image_response(r#"{"page": 3, "page_count": 3, "results": []}"#).unwrap();
+        assert_eq!(last.next_page, None);
+    }
+}
@@ -0,0 +1,183 @@
+use async_trait::async_trait;
+use reqwest::{StatusCode, header::AUTHORIZATION};
+use serde::Deserialize;
+
+use crate::{
+    cache::ImagesRow,
+    engines::{
+        AspectRatio, EngineError, EngineInfo, ImageEngine, ImageOptions, ImagePage,
+        new_rand_client, parse_json,
+    },
+};
+
+const API_URL: &str = "https://api.pexels.com/v1/search";
+const PER_PAGE: u32 = 40;
+const LICENSE: &str = "Pexels License";
+
+/// Pexels' stock photos, through its API with an account's key.
+#[derive(Debug, Clone)]
+pub struct Pexels {
+    api_key: String,
+}
+
+impl Pexels {
+    pub const NAME: &'static str = "Pexels";
+
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+impl EngineInfo for Pexels {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+#[derive(Deserialize)]
+struct PexelsResponse {
+    page: u32,
+    /// Url of the next page, missing on the last one
+    #[serde(default)]
+    next_page: Option<String>,
+    #[serde(default)]
+    photos: Vec<PexelsPhoto>,
+}
+
+#[derive(Deserialize)]
+struct PexelsPhoto {
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+    /// The photo's page on Pexels
+    url: String,
+    #[serde(default)]
+    photographer: Option<String>,
+    #[serde(default)]
+    alt: String,
+    src: PexelsSources,
+}
+
+#[derive(Deserialize)]
+struct PexelsSources {
+    original: String,
+    /// 350px high
+    #[serde(default)]
+    medium: Option<String>,
+}
+
+#[async_trait]
+impl ImageEngine for Pexels {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(API_URL)
+            .header(AUTHORIZATION, &self.api_key)
+            .query(&[
+                ("query", query),
+                ("page", page.unwrap_or("1")),
+                ("per_page", &PER_PAGE.to_string()),
+            ]);
+        if let Some(aspect) = options.aspect {
+            let orientation = match aspect {
+                AspectRatio::Square => "square",
+                AspectRatio::Tall => "portrait",
+                AspectRatio::Wide => "landscape",
+            };
+            request = request.query(&[("orientation", orientation)]);
+        }
+
+        let resp = request.send().await.map_err(EngineError::ReqwestError)?;
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(EngineError::QuotaExceeded(
+                "Pexels rate limited the api key".to_string(),
+            ));
+        }
+        let body = resp
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_image_response(&body)
+    }
+}
+
+fn parse_image_response(body: &str) -> Result<ImagePage, EngineError> {
+    let response: PexelsResponse = parse_json(body)?;
+
+    Ok(ImagePage {
+        next_page: response
+            .next_page
+            .is_some()
+            .then(|| (response.page + 1).to_string()),
+        images: response
+            .photos
+            .into_iter()
+            .map(|photo| ImagesRow {
+                url: photo.src.original,
+                title: photo.alt,
+                width: photo.width,
+                height: photo.height,
+                thumbnail: photo.src.medium,
+                source_url: Some(photo.url),
+                license: Some(LICENSE.to_string()),
+                attribution: photo.photographer,
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_image_response;
+
+    #[test]
+    fn test_parse_image_response() {
+        let page = parse_image_response(
+            r##"{
+                "total_results": 1000, "page": 1, "per_page": 40,
+                "photos": [
+                    {"id": 2014422, "width": 3024, "height": 3024, "url": "https://www.pexels.com/photo/brown-crab-2014422/",
+                     "photographer": "Joey Farina", "photographer_url": "https://www.pexels.com/@joey",
+                     "avg_color": "#978E82", "alt": "Brown crab on the beach", "liked": false,
+                     "src": {"original": "https://images.pexels.com/photos/2014422/pexels-photo-2014422.jpeg",
+                             "large": "https://images.pexels.com/photos/2014422/pexels-photo-2014422.jpeg?h=650",
+                             "medium": "https://images.pexels.com/photos/2014422/pexels-photo-2014422.jpeg?h=350",
+                             "tiny": "https://images.pexels.com/photos/2014422/pexels-photo-2014422.jpeg?h=200"}}
+                ],
+                "next_page": "https://api.pexels.com/v1/search/?page=2&per_page=40&query=crab"
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(page.images.len(), 1);
+        let image = &page.images[0];
+        assert_eq!(
+            image.url,
+            "https://images.pexels.com/photos/2014422/pexels-photo-2014422.jpeg"
+        );
+        assert_eq!(image.title, "Brown crab on the beach");
+        assert_eq!(
+            image.thumbnail.as_deref(),
+            Some("https://images.pexels.com/photos/2014422/pexels-photo-2014422.jpeg?h=350")
+        );
+        assert_eq!(image.license.as_deref(), Some("Pexels License"));
+        assert_eq!(image.attribution.as_deref(), Some("Joey Farina"));
+        assert_eq!(page.next_page.as_deref(), Some("2"));
+
+        let last = parse_image_response(r#"{"page": 25, "photos": []}"#).unwrap();
+        assert_eq!(last.next_page, None);
+    }
+}
@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use crate::{
+    cache::PlaceRow,
+    engines::{
+        EngineError, EngineInfo, VerticalEngine, VerticalPage, new_rand_client, nominatim::osm_url,
+        parse_json,
+    },
+};
+
+const API_URL: &str = "https://photon.komoot.io/api/";
+const LIMIT: u32 = 10;
+
+/// Komoot's geocoder over OpenStreetMap data, built for search as you type
+/// and more lenient with rate limits than Nominatim.
+#[derive(Clone)]
+pub struct Photon;
+
+impl EngineInfo for Photon {
+    fn name(&self) -> &'static str {
+        "Photon"
+    }
+}
+
+/// A GeoJSON feature collection
+#[derive(Deserialize)]
+struct PhotonResponse {
+    #[serde(default)]
+    features: Vec<PhotonFeature>,
+}
+
+#[derive(Deserialize)]
+struct PhotonFeature {
+    geometry: PhotonGeometry,
+    properties: PhotonProperties,
+}
+
+#[derive(Deserialize)]
+struct PhotonGeometry {
+    /// Longitude first
+    coordinates: (f64, f64),
+}
+
+#[derive(Deserialize)]
+struct PhotonProperties {
+    /// "N", "W" or "R"
+    osm_type: String,
+    osm_id: u64,
+    #[serde(default)]
+    osm_value: Option<String>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    housenumber: Option<String>,
+    #[serde(default)]
+    street: Option<String>,
+    #[serde(default)]
+    postcode: Option<String>,
+    #[serde(default)]
+    city: Option<String>,
+    #[serde(default)]
+    state: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
+}
+
+impl PhotonProperties {
+    /// e.g. "Unter den Linden 12, 10117 Berlin, Germany"
+    fn address(&self) -> Option<String> {
+        let street = match (&self.street, &self.housenumber) {
+            (Some(street), Some(number)) => Some(format!("{street} {number}")),
+            (street, _) => street.clone(),
+        };
+        let city = match (&self.postcode, &self.city) {
+            (Some(postcode), Some(city)) => Some(format!("{postcode} {city}")),
+            (postcode, city) => city.clone().or(postcode.clone()),
+        };
+
+        let parts = [street, city, self.state.clone(), self.country.clone()]
+            .into_iter()
+            .flatten()
+            // a city's own result would repeat its name
+            .filter(|part| self.name.as_ref() != Some(part))
+            .collect::<Vec<_>>();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+}
+
+#[async_trait]
+impl VerticalEngine<PlaceRow> for Photon {
+    async fn search_page(
+        &self,
+        query: &str,
+        _page: Option<&str>,
+    ) -> Result<VerticalPage<PlaceRow>, EngineError> {
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(API_URL)
+            .query(&[("q", query), ("limit", &LIMIT.to_string())])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body).map(VerticalPage::from)
+    }
+}
+
+fn parse_response(body: &str) -> Result<Vec<PlaceRow>, EngineError> {
+    let response: PhotonResponse = parse_json(body)?;
+
+    Ok(response
+        .features
+        .into_iter()
+        .filter_map(|feature| {
+            let properties = feature.properties;
+            let address = properties.address();
+            // unnamed places, e.g. a house number, go by their address
+            let name = properties.name.clone().or(address.clone())?;
+            let (lon, lat) = feature.geometry.coordinates;
+
+            Some(PlaceRow {
+                name,
+                lat,
+                lon,
+                address,
+                osm_url: osm_url(&properties.osm_type, properties.osm_id)?,
+                kind: properties.osm_value,
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let places = parse_response(
+            r#"{"type": "FeatureCollection", "features": [
+                {"type": "Feature", "geometry": {"type": "Point", "coordinates": [13.3888599, 52.5170365]},
+                 "properties": {"osm_type": "R", "osm_id": 62422, "osm_key": "place", "osm_value": "city",
+                                "name": "Berlin", "country": "Germany", "city": "Berlin", "countrycode": "DE", "type": "city"}},
+                {"type": "Feature", "geometry": {"type": "Point", "coordinates": [13.39, 52.51]},
+                 "properties": {"osm_type": "N", "osm_id": 42, "osm_value": "house", "housenumber": "12",
+                                "street": "Unter den Linden", "postcode": "10117", "city": "Berlin", "country": "Germany"}},
+                {"type": "Feature", "geometry": {"type": "Point", "coordinates": [0.0, 0.0]},
+                 "properties": {"osm_type": "X", "osm_id": 1, "name": "Unknown type"}}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(places.len(), 2);
+        let berlin = &places[0];
+        assert_eq!(berlin.name, "Berlin");
+        assert_eq!((berlin.lat, berlin.lon), (52.5170365, 13.3888599));
+        assert_eq!(berlin.address.as_deref(), Some("Germany"));
+        assert_eq!(
+            berlin.osm_url,
+            "https://www.openstreetmap.org/relation/62422"
+        );
+        assert_eq!(berlin.kind.as_deref(), Some("city"));
+
+        let house = &places[1];
+        assert_eq!(house.name, "Unter den Linden 12, 10117 Berlin, Germany");
+        assert_eq!(house.address, Some(house.name.clone()));
+        assert_eq!(house.osm_url, "https://www.openstreetmap.org/node/42");
+    }
+}
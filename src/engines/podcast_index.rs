@@ -0,0 +1,189 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+
+use crate::{
+    cache::PodcastRow,
+    engines::{EngineError, EngineInfo, PodcastPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://api.podcastindex.org/api/1.0/search/byterm";
+const MAX: u32 = 50;
+
+/// The open Podcast Index, through its API with a free key and secret from
+/// api.podcastindex.org. Every request is signed with them.
+#[derive(Debug, Clone)]
+pub struct PodcastIndex {
+    api_key: String,
+    api_secret: String,
+}
+
+impl PodcastIndex {
+    pub const NAME: &'static str = "Podcast Index";
+
+    pub fn new(api_key: &str, api_secret: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            api_secret: api_secret.to_string(),
+        }
+    }
+
+    /// The `Authorization` header, a sha1 of the key, secret and the
+    /// `X-Auth-Date` sent along.
+    fn signature(&self, date: i64) -> String {
+        let signed = format!("{}{}{date}", self.api_key, self.api_secret);
+        format!("{:x}", Sha1::digest(signed.as_bytes()))
+    }
+}
+
+impl EngineInfo for PodcastIndex {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    /// `"true"` or `"false"`, as a string
+    status: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    feeds: Vec<Feed>,
+}
+
+#[derive(Deserialize)]
+struct Feed {
+    #[serde(default)]
+    title: Option<String>,
+    /// The RSS feed
+    url: String,
+    /// The show's website
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    artwork: Option<String>,
+    /// Older artwork, used when `artwork` is empty
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<PodcastRow> for PodcastIndex {
+    async fn search_page(
+        &self,
+        query: &str,
+        _page: Option<&str>,
+    ) -> Result<PodcastPage, EngineError> {
+        let date = chrono::Utc::now().timestamp();
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .header("X-Auth-Key", &self.api_key)
+            .header("X-Auth-Date", date.to_string())
+            .header("Authorization", self.signature(date))
+            .query(&[("q", query), ("max", &MAX.to_string())])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body)
+    }
+}
+
+fn parse_response(body: &str) -> Result<PodcastPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+    if response.status != "true" {
+        return Err(EngineError::ParseError(format!(
+            "Podcast Index returned an error: {}",
+            response.description.unwrap_or_default()
+        )));
+    }
+
+    let non_empty = |s: Option<String>| s.filter(|s| !s.trim().is_empty());
+    let podcasts = response
+        .feeds
+        .into_iter()
+        .filter_map(|feed| {
+            Some(PodcastRow {
+                url: non_empty(feed.link).unwrap_or_else(|| feed.url.clone()),
+                title: non_empty(feed.title)?,
+                feed_url: feed.url,
+                author: non_empty(feed.author),
+                artwork: non_empty(feed.artwork).or_else(|| non_empty(feed.image)),
+                description: non_empty(feed.description),
+            })
+        })
+        .collect();
+
+    // byterm has no offset, `max` results come on a single page
+    Ok(PodcastPage {
+        rows: podcasts,
+        next_page: None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PodcastIndex, parse_response};
+    use crate::engines::EngineError;
+
+    #[test]
+    fn test_signature() {
+        let engine = PodcastIndex::new("KEY", "SECRET");
+        // sha1 of "KEYSECRET1700000000"
+        assert_eq!(
+            engine.signature(1_700_000_000),
+            "5f8983664e541a83aaae7f3a47f1957fbca48aec"
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "status": "true", "count": 2, "query": "rust", "description": "Found matching feeds",
+                "feeds": [
+                    {"id": 920666, "title": "Rustacean Station", "url": "https://rustacean-station.org/podcast.rss",
+                     "link": "https://rustacean-station.org/", "author": "Rustacean Station",
+                     "description": "Come journey with us into the weird, wonderful, and wily world of Rust.",
+                     "image": "https://rustacean-station.org/old.png", "artwork": "",
+                     "episodeCount": 150},
+                    {"id": 1, "title": "", "url": "https://example.com/untitled.xml"},
+                    {"id": 2, "title": "No link", "url": "https://example.com/feed.xml", "link": ""}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let station = &page.rows[0];
+        assert_eq!(station.url, "https://rustacean-station.org/");
+        assert_eq!(
+            station.feed_url,
+            "https://rustacean-station.org/podcast.rss"
+        );
+        assert_eq!(
+            station.artwork.as_deref(),
+            Some("https://rustacean-station.org/old.png")
+        );
+        assert!(station.description.is_some());
+        assert_eq!(page.rows[1].url, "https://example.com/feed.xml");
+        assert_eq!(page.next_page, None);
+
+        let error = parse_response(r#"{"status": "false", "description": "Authorization failed"}"#);
+        assert!(
+            matches!(error, Err(EngineError::ParseError(e)) if e.contains("Authorization failed"))
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+use crate::{
+    cache::ResultRow,
+    engines::{
+        EngineError, EngineInfo, SearchEngine, SerpPage, new_rand_client, parse_json,
+        parse_page_blocking,
+    },
+};
+
+const SEARCH_URL: &str = "https://presearch.com/search";
+const RESULTS_URL: &str = "https://presearch.com/results";
+
+/// Presearch renders results client side: the search page only carries an id
+/// the results are then fetched as JSON with.
+#[derive(Clone)]
+pub struct Presearch;
+
+impl EngineInfo for Presearch {
+    fn name(&self) -> &'static str {
+        "Presearch"
+    }
+}
+
+#[derive(Deserialize)]
+struct PresearchResponse {
+    results: PresearchResults,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PresearchResults {
+    #[serde(default)]
+    standard_results: Vec<PresearchHit>,
+    #[serde(default)]
+    pagination: Option<PresearchPagination>,
+}
+
+#[derive(Deserialize)]
+struct PresearchHit {
+    link: String,
+    title: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PresearchPagination {
+    #[serde(default)]
+    current_page: Option<u32>,
+    #[serde(default)]
+    has_next: bool,
+}
+
+#[async_trait]
+impl SearchEngine for Presearch {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let client = new_rand_client().map_err(EngineError::ReqwestError)?;
+
+        let html = client
+            .get(SEARCH_URL)
+            .query(&[("q", query), ("page", page.unwrap_or("1"))])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+        let Some(id) = parse_search_id(&html) else {
+            // served instead of the app when the request looks automated
+            return Err(EngineError::Blocked(
+                "Presearch page had no search id".to_string(),
+            ));
+        };
+
+        let body = client
+            .get(RESULTS_URL)
+            .query(&[("id", id.as_str())])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_page_blocking(body, parse_response).await
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_response(body)
+    }
+}
+
+fn parse_search_id(html: &str) -> Option<String> {
+    static SEARCH_ID: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"window\.searchId\s*=\s*["']([^"']+)["']"#).unwrap());
+
+    SEARCH_ID.captures(html).map(|c| c[1].to_string())
+}
+
+pub fn parse_response(body: &str) -> Result<SerpPage, EngineError> {
+    let response: PresearchResponse = parse_json(body)?;
+    let pagination = response.results.pagination;
+
+    let mut page = SerpPage::from(
+        response
+            .results
+            .standard_results
+            .into_iter()
+            .map(|hit| ResultRow {
+                url: hit.link,
+                title: hit.title,
+                description: hit.description,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>(),
+    );
+    if let Some(pagination) = pagination.filter(|p| p.has_next) {
+        page.has_next_page = true;
+        page.next_page = Some((pagination.current_page.unwrap_or(1) + 1).to_string());
+    }
+
+    Ok(page)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_response, parse_search_id};
+
+    #[test]
+    fn test_parse_search_id() {
+        assert_eq!(
+            parse_search_id(r#"<script>window.searchId = "d8f1c2a0-77";</script>"#).as_deref(),
+            Some("d8f1c2a0-77")
+        );
+        assert_eq!(parse_search_id("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{"results": {
+                "standardResults": [
+                    {"title": "Rust", "link": "https://www.rust-lang.org/", "description": "A language empowering everyone."},
+                    {"title": "The Book", "link": "https://doc.rust-lang.org/book/"}
+                ],
+                "pagination": {"currentPage": 1, "hasNext": true}
+            }}"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(page.results[1].description, "");
+        assert_eq!(page.next_page.as_deref(), Some("2"));
+
+        let page = parse_response(r#"{"results": {}}"#).unwrap();
+        assert!(page.results.is_empty());
+        assert!(!page.has_next_page);
+    }
+}
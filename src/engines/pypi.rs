@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
+use std::sync::LazyLock;
+
+use crate::{
+    cache::PackageRow,
+    engines::{EngineError, EngineInfo, PackagePage, VerticalEngine, new_rand_client},
+};
+
+const SEARCH_URL: &str = "https://pypi.org/search/";
+const BASE_URL: &str = "https://pypi.org";
+const PER_PAGE: usize = 20;
+
+static SNIPPET: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("a.package-snippet").unwrap());
+static NAME: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse(".package-snippet__name").unwrap());
+static VERSION: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse(".package-snippet__version").unwrap());
+static DESCRIPTION: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse(".package-snippet__description").unwrap());
+
+/// Python packages, from PyPI's search page. PyPI has no search API, and
+/// its pages carry no download counts or repository links.
+#[derive(Clone)]
+pub struct Pypi;
+
+impl EngineInfo for Pypi {
+    fn name(&self) -> &'static str {
+        "PyPI"
+    }
+}
+
+#[async_trait]
+impl VerticalEngine<PackageRow> for Pypi {
+    async fn search_page(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<PackagePage, EngineError> {
+        // PyPI pages count from 1
+        let page: usize = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+
+        let html = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[("q", query), ("page", &page.to_string())])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        Ok(parse_response(&html, page))
+    }
+}
+
+fn parse_response(html: &str, page: usize) -> PackagePage {
+    let document = Html::parse_document(html);
+    let text = |snippet: &ElementRef, selector: &Selector| {
+        snippet
+            .select(selector)
+            .next()
+            .map(|el| el.text().collect::<String>().trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+
+    let packages: Vec<PackageRow> = document
+        .select(&SNIPPET)
+        .filter_map(|snippet| {
+            let href = snippet.value().attr("href")?;
+            Some(PackageRow {
+                url: format!("{BASE_URL}{href}"),
+                name: text(&snippet, &NAME)?,
+                registry: "PyPI".to_string(),
+                version: text(&snippet, &VERSION),
+                description: text(&snippet, &DESCRIPTION),
+                downloads: None,
+                repository: None,
+            })
+        })
+        .collect();
+
+    // a full page is the only hint that another one follows
+    let next_page = (packages.len() >= PER_PAGE).then(|| (page + 1).to_string());
+    PackagePage {
+        rows: packages,
+        next_page,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"<html><body><ul class="unstyled" aria-label="Search results">
+                <li><a class="package-snippet" href="/project/requests/">
+                    <h3 class="package-snippet__title">
+                        <span class="package-snippet__name">requests</span>
+                        <span class="package-snippet__version">2.32.3</span>
+                        <span class="package-snippet__created"><time datetime="2024-05-29T15:37:47+0000">May 29, 2024</time></span>
+                    </h3>
+                    <p class="package-snippet__description">Python HTTP for Humans.</p>
+                </a></li>
+                <li><a class="package-snippet" href="/project/requests-mock/">
+                    <h3 class="package-snippet__title">
+                        <span class="package-snippet__name">requests-mock</span>
+                        <span class="package-snippet__version">1.12.1</span>
+                    </h3>
+                    <p class="package-snippet__description"></p>
+                </a></li>
+            </ul></body></html>"#,
+            1,
+        );
+
+        assert_eq!(page.rows.len(), 2);
+        let requests = &page.rows[0];
+        assert_eq!(requests.url, "https://pypi.org/project/requests/");
+        assert_eq!(requests.name, "requests");
+        assert_eq!(requests.version.as_deref(), Some("2.32.3"));
+        assert_eq!(
+            requests.description.as_deref(),
+            Some("Python HTTP for Humans.")
+        );
+        assert_eq!(page.rows[1].description, None);
+        assert_eq!(page.next_page, None);
+    }
+}
@@ -0,0 +1,286 @@
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use crate::{
+    cache::{ImagesRow, ResultRow},
+    config::SafeSearch,
+    engines::{
+        EngineError, EngineInfo, ImageEngine, ImageOptions, ImagePage, JsonEngine, new_rand_client,
+        parse_json,
+    },
+};
+
+const API_URL: &str = "https://api.qwant.com/v3/search/web";
+const IMAGES_URL: &str = "https://api.qwant.com/v3/search/images";
+const COUNT: u32 = 10;
+const IMAGES_COUNT: u32 = 50;
+const MAX_OFFSET: u32 = 40; // the api rejects anything past this
+const MAX_IMAGES_OFFSET: u32 = 200;
+
+#[derive(Clone)]
+pub struct Qwant;
+
+impl EngineInfo for Qwant {
+    fn name(&self) -> &'static str {
+        "Qwant"
+    }
+}
+
+/// Errors, e.g. when a captcha is wanted, come back with `status: "error"`
+/// and no result data.
+#[derive(Deserialize)]
+pub struct QwantResponse {
+    #[serde(default)]
+    data: Option<QwantData>,
+}
+
+#[derive(Deserialize)]
+struct QwantData {
+    #[serde(default)]
+    result: Option<QwantResult>,
+}
+
+#[derive(Deserialize)]
+struct QwantResult {
+    items: QwantItems,
+}
+
+#[derive(Deserialize)]
+struct QwantItems {
+    #[serde(default)]
+    mainline: Vec<QwantSection>,
+}
+
+/// A block of the result page, only `web` ones hold organic results.
+#[derive(Deserialize)]
+struct QwantSection {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    items: Vec<QwantItem>,
+}
+
+#[derive(Deserialize)]
+struct QwantItem {
+    url: String,
+    title: String,
+    #[serde(default)]
+    desc: Option<String>,
+}
+
+impl JsonEngine for Qwant {
+    type Response = QwantResponse;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        client.get(API_URL).query(&[
+            ("q", query),
+            ("count", &COUNT.to_string()),
+            ("offset", page.unwrap_or("0")),
+            ("locale", "en_US"),
+            ("device", "desktop"),
+        ])
+    }
+
+    fn next_page(&self, response: &QwantResponse, page: Option<&str>) -> Option<String> {
+        let offset: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0) + COUNT;
+        (offset <= MAX_OFFSET && web_items(response).next().is_some()).then(|| offset.to_string())
+    }
+
+    fn results(&self, response: QwantResponse) -> Vec<ResultRow> {
+        web_items(&response)
+            .map(|item| ResultRow {
+                url: item.url.clone(),
+                title: item.title.clone(),
+                description: item.desc.clone().unwrap_or_default(),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+fn web_items(response: &QwantResponse) -> impl Iterator<Item = &QwantItem> {
+    response
+        .data
+        .iter()
+        .flat_map(|data| &data.result)
+        .flat_map(|result| &result.items.mainline)
+        .filter(|section| section.kind == "web")
+        .flat_map(|section| &section.items)
+}
+
+#[derive(Deserialize)]
+struct QwantImagesResponse {
+    #[serde(default)]
+    data: Option<QwantImagesData>,
+}
+
+#[derive(Deserialize)]
+struct QwantImagesData {
+    #[serde(default)]
+    result: Option<QwantImagesResult>,
+}
+
+#[derive(Deserialize)]
+struct QwantImagesResult {
+    #[serde(default)]
+    items: Vec<QwantImage>,
+}
+
+#[derive(Deserialize)]
+struct QwantImage {
+    /// Full resolution image
+    media: String,
+    #[serde(default)]
+    title: String,
+    /// Page the image was found on
+    #[serde(default)]
+    url: Option<String>,
+    /// Resized copy served by Qwant, often without a scheme
+    #[serde(default)]
+    thumbnail: Option<String>,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+#[async_trait]
+impl ImageEngine for Qwant {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        let offset: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+        let safe_search = match options.safe_search {
+            Some(SafeSearch::Off) => "0",
+            Some(SafeSearch::Strict) => "2",
+            _ => "1",
+        };
+
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(IMAGES_URL)
+            .query(&[
+                ("q", query),
+                ("count", &IMAGES_COUNT.to_string()),
+                ("offset", &offset.to_string()),
+                ("locale", "en_US"),
+                ("safesearch", safe_search),
+            ]);
+        if let Some(size) = options.size {
+            request = request.query(&[("size", size.as_str())]);
+        }
+
+        let body = request
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+        let images = parse_image_response(&body)?;
+
+        let next = offset + IMAGES_COUNT;
+        Ok(ImagePage {
+            next_page: (!images.is_empty() && next <= MAX_IMAGES_OFFSET).then(|| next.to_string()),
+            images,
+            ..Default::default()
+        })
+    }
+}
+
+fn parse_image_response(body: &str) -> Result<Vec<ImagesRow>, EngineError> {
+    let response: QwantImagesResponse = parse_json(body)?;
+
+    Ok(response
+        .data
+        .and_then(|data| data.result)
+        .map(|result| result.items)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|image| ImagesRow {
+            url: image.media,
+            title: image.title,
+            width: image.width,
+            height: image.height,
+            thumbnail: image.thumbnail.map(|t| match t.strip_prefix("//") {
+                Some(rest) => format!("https://{rest}"),
+                None => t,
+            }),
+            source_url: image.url,
+            ..Default::default()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Qwant, QwantResponse, parse_image_response};
+    use crate::engines::{JsonEngine, parse_json};
+
+    const RESPONSE: &str = r#"{
+        "status": "success",
+        "data": {"result": {"items": {"mainline": [
+            {"type": "ads", "items": [{"url": "https://ad.com", "title": "Ad"}]},
+            {"type": "web", "items": [
+                {"url": "https://www.rust-lang.org/", "title": "Rust", "desc": "A language empowering everyone."},
+                {"url": "https://doc.rust-lang.org/book/", "title": "The Book"}
+            ]}
+        ]}}}
+    }"#;
+
+    #[test]
+    fn test_results() {
+        let response: QwantResponse = parse_json(RESPONSE).unwrap();
+        assert_eq!(Qwant.next_page(&response, None).as_deref(), Some("10"));
+        assert_eq!(Qwant.next_page(&response, Some("40")), None);
+
+        let results = Qwant.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(results[0].description, "A language empowering everyone.");
+        assert_eq!(results[1].description, "");
+    }
+
+    #[test]
+    fn test_error_response() {
+        let response: QwantResponse =
+            parse_json(r#"{"status": "error", "data": {"error_code": 24}}"#).unwrap();
+        assert_eq!(Qwant.next_page(&response, None), None);
+        assert!(Qwant.results(response).is_empty());
+    }
+
+    #[test]
+    fn test_parse_image_response() {
+        let images = parse_image_response(
+            r#"{"status": "success", "data": {"result": {"total": 2, "items": [
+                {"title": "Rust logo", "media": "https://www.rust-lang.org/logos/rust-logo.png",
+                 "thumbnail": "//s2.qwant.com/thumbr/0x380/a/b/rust-logo.png", "url": "https://www.rust-lang.org/",
+                 "width": 1024, "height": 1024, "thumb_width": 380, "thumb_height": 380},
+                {"title": "Ferris", "media": "https://rustacean.net/assets/rustacean-flat-happy.png"}
+            ]}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(
+            images[0].url,
+            "https://www.rust-lang.org/logos/rust-logo.png"
+        );
+        assert_eq!(
+            images[0].thumbnail.as_deref(),
+            Some("https://s2.qwant.com/thumbr/0x380/a/b/rust-logo.png")
+        );
+        assert_eq!(
+            images[0].source_url.as_deref(),
+            Some("https://www.rust-lang.org/")
+        );
+        assert_eq!(images[1].thumbnail, None);
+
+        let error = parse_image_response(r#"{"status": "error", "data": {"error_code": 24}}"#);
+        assert!(error.unwrap().is_empty());
+    }
+}
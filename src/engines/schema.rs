@@ -0,0 +1,571 @@
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+use std::{
+    borrow::Cow,
+    sync::{LazyLock, OnceLock},
+};
+
+use crate::{
+    cache::{ImagesRow, ResultRow},
+    engines::{EngineError, ImagePage, ParseDiagnostics, SerpPage, heuristic::heuristic_results},
+};
+
+/// Declarative description of where results live in an engine's result page.
+///
+/// `results` selects one element per result, every other selector is matched
+/// inside of it, except for the page level `container`, `total_results` and
+/// `next_page`.
+/// Selectors can be static strings or loaded at runtime, e.g. deserialized
+/// from a config file. They are compiled once on first use.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SerpSchema {
+    #[serde(default)]
+    container: Option<Cow<'static, str>>,
+    results: Cow<'static, str>,
+    title: Cow<'static, str>,
+    href: Cow<'static, str>,
+    #[serde(default = "default_href_attr")]
+    href_attr: Cow<'static, str>,
+    #[serde(default)]
+    description: Option<Cow<'static, str>>,
+    #[serde(default)]
+    thumbnail: Option<Cow<'static, str>>,
+    #[serde(default = "default_thumbnail_attr")]
+    thumbnail_attr: Cow<'static, str>,
+    #[serde(default)]
+    source_url: Option<Cow<'static, str>>,
+    #[serde(default)]
+    dimensions: Option<Cow<'static, str>>,
+    #[serde(default)]
+    license: Option<Cow<'static, str>>,
+    #[serde(default)]
+    total_results: Option<Cow<'static, str>>,
+    #[serde(default)]
+    next_page: Option<Cow<'static, str>>,
+    #[serde(skip)]
+    compiled: OnceLock<Result<CompiledSchema, String>>,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledSchema {
+    container: Option<Selector>,
+    results: Selector,
+    title: Selector,
+    href: Selector,
+    description: Option<Selector>,
+    thumbnail: Option<Selector>,
+    source_url: Option<Selector>,
+    dimensions: Option<Selector>,
+    license: Option<Selector>,
+    total_results: Option<Selector>,
+    next_page: Option<Selector>,
+}
+
+impl CompiledSchema {
+    fn new(schema: &SerpSchema) -> Result<Self, String> {
+        Ok(Self {
+            container: schema.container.as_deref().map(compile).transpose()?,
+            results: compile(&schema.results)?,
+            title: compile(&schema.title)?,
+            href: compile(&schema.href)?,
+            description: schema.description.as_deref().map(compile).transpose()?,
+            thumbnail: schema.thumbnail.as_deref().map(compile).transpose()?,
+            source_url: schema.source_url.as_deref().map(compile).transpose()?,
+            dimensions: schema.dimensions.as_deref().map(compile).transpose()?,
+            license: schema.license.as_deref().map(compile).transpose()?,
+            total_results: schema.total_results.as_deref().map(compile).transpose()?,
+            next_page: schema.next_page.as_deref().map(compile).transpose()?,
+        })
+    }
+}
+
+fn compile(selector: &str) -> Result<Selector, String> {
+    Selector::parse(selector).map_err(|e| format!("Invalid selector {selector:?}: {e}"))
+}
+
+fn default_href_attr() -> Cow<'static, str> {
+    Cow::Borrowed("href")
+}
+
+fn default_thumbnail_attr() -> Cow<'static, str> {
+    Cow::Borrowed("src")
+}
+
+impl SerpSchema {
+    pub fn new(
+        results: impl Into<Cow<'static, str>>,
+        title: impl Into<Cow<'static, str>>,
+        href: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            container: None,
+            results: results.into(),
+            title: title.into(),
+            href: href.into(),
+            href_attr: default_href_attr(),
+            description: None,
+            thumbnail: None,
+            thumbnail_attr: default_thumbnail_attr(),
+            source_url: None,
+            dimensions: None,
+            license: None,
+            total_results: None,
+            next_page: None,
+            compiled: OnceLock::new(),
+        }
+    }
+
+    pub fn href_attr(mut self, attr: impl Into<Cow<'static, str>>) -> Self {
+        self.href_attr = attr.into();
+        self
+    }
+
+    /// Element wrapping the results, present on a result page even when it
+    /// has none. With it, the heuristic parse is only used when it is missing
+    /// too, so a page without results stays empty.
+    pub fn container(mut self, selector: impl Into<Cow<'static, str>>) -> Self {
+        self.container = Some(selector.into());
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    pub fn description(mut self, selector: impl Into<Cow<'static, str>>) -> Self {
+        self.description = Some(selector.into());
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    pub fn thumbnail(
+        mut self,
+        selector: impl Into<Cow<'static, str>>,
+        attr: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        self.thumbnail = Some(selector.into());
+        self.thumbnail_attr = attr.into();
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    /// Link to the page an image result was found on.
+    pub fn source_url(mut self, selector: impl Into<Cow<'static, str>>) -> Self {
+        self.source_url = Some(selector.into());
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    /// Element whose text holds an image's size, e.g. "1920 × 1080".
+    pub fn dimensions(mut self, selector: impl Into<Cow<'static, str>>) -> Self {
+        self.dimensions = Some(selector.into());
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    /// Element whose text names an image's license.
+    pub fn license(mut self, selector: impl Into<Cow<'static, str>>) -> Self {
+        self.license = Some(selector.into());
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    /// Element whose text holds the "About N results" count.
+    pub fn total_results(mut self, selector: impl Into<Cow<'static, str>>) -> Self {
+        self.total_results = Some(selector.into());
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    /// Element only present when there is a further page of results. The
+    /// last match's `href`, if any, becomes the page's next page token.
+    pub fn next_page(mut self, selector: impl Into<Cow<'static, str>>) -> Self {
+        self.next_page = Some(selector.into());
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    fn compiled(&self) -> Result<&CompiledSchema, EngineError> {
+        self.compiled
+            .get_or_init(|| CompiledSchema::new(self))
+            .as_ref()
+            .map_err(|e| EngineError::ParseError(e.clone()))
+    }
+
+    /// Parses web results, recording every selector that came up empty.
+    /// Results without a link are dropped since they can't be merged or cached.
+    pub fn parse_results(&self, html: &str) -> Result<SerpPage, EngineError> {
+        self.parse_document(&Html::parse_document(html))
+    }
+
+    /// Same as [`SerpSchema::parse_results`] for an already parsed page, so
+    /// engines can extract more from the same document.
+    pub fn parse_document(&self, html: &Html) -> Result<SerpPage, EngineError> {
+        let selectors = self.compiled()?;
+
+        let mut page = SerpPage::default();
+
+        for result in html.select(&selectors.results) {
+            page.diagnostics.matched += 1;
+
+            let Some(url) = select_attr(result, &selectors.href, &self.href_attr) else {
+                page.diagnostics.miss("href", &self.href);
+                continue;
+            };
+
+            let title = select_text(result, &selectors.title).unwrap_or_else(|| {
+                page.diagnostics.miss("title", &self.title);
+                String::new()
+            });
+
+            let description = match (&selectors.description, &self.description) {
+                (Some(selector), Some(raw)) => select_text(result, selector).unwrap_or_else(|| {
+                    page.diagnostics.miss("description", raw);
+                    String::new()
+                }),
+                _ => String::new(),
+            };
+
+            page.results.push(ResultRow {
+                url,
+                title,
+                description,
+                ..Default::default()
+            })
+        }
+
+        page.total_results = selectors
+            .total_results
+            .as_ref()
+            .and_then(|s| html.select(s).next())
+            .and_then(|e| parse_count(&e.text().collect::<String>()));
+        if let Some(next) = selectors
+            .next_page
+            .as_ref()
+            .and_then(|s| html.select(s).next_back())
+        {
+            page.has_next_page = true;
+            page.next_page = next.value().attr("href").map(str::to_string);
+        }
+
+        let no_container = selectors
+            .container
+            .as_ref()
+            .is_none_or(|s| html.select(s).next().is_none());
+        if page.diagnostics.matched == 0 && no_container {
+            // markup likely changed, degrade to a best effort parse
+            page.results = heuristic_results(html);
+            page.diagnostics.heuristic_fallback = true;
+        }
+
+        Ok(page)
+    }
+
+    /// Parses image results into a page without a next page token, recording
+    /// every selector that came up empty. Images without a url are dropped.
+    pub fn parse_images(&self, html: &str) -> Result<ImagePage, EngineError> {
+        let selectors = self.compiled()?;
+        let html = Html::parse_document(html);
+
+        let mut images = Vec::new();
+        let mut diagnostics = ParseDiagnostics::default();
+
+        for result in html.select(&selectors.results) {
+            diagnostics.matched += 1;
+
+            let Some(url) = select_attr(result, &selectors.href, &self.href_attr) else {
+                diagnostics.miss("href", &self.href);
+                continue;
+            };
+
+            let title = select_text(result, &selectors.title).unwrap_or_else(|| {
+                diagnostics.miss("title", &self.title);
+                String::new()
+            });
+
+            let (width, height) = selectors
+                .dimensions
+                .as_ref()
+                .and_then(|s| select_text(result, s))
+                .and_then(|t| parse_dimensions(&t))
+                .unzip();
+
+            images.push(ImagesRow {
+                url,
+                title,
+                width,
+                height,
+                thumbnail: selectors
+                    .thumbnail
+                    .as_ref()
+                    .and_then(|s| select_attr(result, s, &self.thumbnail_attr)),
+                source_url: selectors
+                    .source_url
+                    .as_ref()
+                    .and_then(|s| select_attr(result, s, "href")),
+                license: selectors
+                    .license
+                    .as_ref()
+                    .and_then(|s| select_text(result, s))
+                    .map(|t| t.trim().to_string()),
+                attribution: None,
+                blurhash: None,
+                phash: None,
+            })
+        }
+
+        Ok(ImagePage {
+            images,
+            next_page: None,
+            diagnostics: Some(diagnostics),
+        })
+    }
+}
+
+pub(super) fn select_attr(element: ElementRef, selector: &Selector, attr: &str) -> Option<String> {
+    element
+        .select(selector)
+        .next()
+        .and_then(|e| e.value().attr(attr))
+        .filter(|a| !a.is_empty())
+        .map(str::to_string)
+}
+
+pub(super) fn select_text(element: ElementRef, selector: &Selector) -> Option<String> {
+    element
+        .select(selector)
+        .next()
+        .map(|e| e.text().collect::<String>())
+        .filter(|t| !t.trim().is_empty())
+}
+
+/// Largest number in a result count text, e.g. "Page 2 of about 1,230,000 results".
+fn parse_count(text: &str) -> Option<u64> {
+    static NUMBER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\d(?:[\d,.\s\u{a0}\u{202f}']*\d)?").unwrap());
+
+    NUMBER
+        .find_iter(text)
+        .filter_map(|m| {
+            m.as_str()
+                .chars()
+                .filter(char::is_ascii_digit)
+                .collect::<String>()
+                .parse()
+                .ok()
+        })
+        .max()
+}
+
+/// Width and height from text like "1920 × 1080" or "800x600".
+pub(super) fn parse_dimensions(text: &str) -> Option<(u32, u32)> {
+    static DIMENSIONS: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(\d+)\s*[x×X]\s*(\d+)").unwrap());
+
+    let captures = DIMENSIONS.captures(text)?;
+    Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SerpSchema, parse_count, parse_dimensions};
+    use crate::engines::EngineError;
+
+    const HTML: &str = r#"
+        <div class="result">
+            <a class="link" href="https://a.com">A title</a>
+            <p class="snippet">A description</p>
+            <img src="https://a.com/a.png" data-thumb="https://thumbs.com/a.jpg">
+            <span class="size">1920 × 1080</span>
+            <span class="license"> CC0 1.0 </span>
+        </div>
+        <div class="result">
+            <a class="link" href="https://b.com">B title</a>
+            <img src="https://b.com/b.png">
+        </div>
+    "#;
+
+    #[test]
+    fn test_parse_results() {
+        let schema = SerpSchema::new(".result", ".link", ".link").description(".snippet");
+        let page = schema.parse_results(HTML).unwrap();
+        let results = page.results;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://a.com");
+        assert_eq!(results[0].title, "A title");
+        assert_eq!(results[0].description, "A description");
+        assert_eq!(results[1].description, "");
+
+        assert_eq!(page.diagnostics.matched, 2);
+        assert_eq!(page.diagnostics.missing.len(), 1);
+        assert_eq!(page.diagnostics.missing[0].field, "description");
+        assert_eq!(page.diagnostics.missing[0].selector, ".snippet");
+        assert_eq!(page.diagnostics.missing[0].count, 1);
+    }
+
+    #[test]
+    fn test_page_metadata() {
+        let html = format!(
+            r#"{HTML}<div id="stats">Page 1 of about 1,230,000 results</div><a class="next" href="?p=2">Next</a>"#
+        );
+
+        let schema = SerpSchema::new(".result", ".link", ".link")
+            .total_results("#stats")
+            .next_page("a.next");
+        let page = schema.parse_results(&html).unwrap();
+        assert_eq!(page.total_results, Some(1_230_000));
+        assert!(page.has_next_page);
+        assert_eq!(page.next_page.as_deref(), Some("?p=2"));
+
+        let schema = SerpSchema::new(".result", ".link", ".link").next_page("a.prev");
+        let page = schema.parse_results(&html).unwrap();
+        assert_eq!(page.total_results, None);
+        assert!(!page.has_next_page);
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("About 4.560.000 résultats"), Some(4_560_000));
+        assert_eq!(parse_count("Environ 12 300 résultats"), Some(12_300));
+        assert_eq!(parse_count("7 results"), Some(7));
+        assert_eq!(parse_count("No results"), None);
+    }
+
+    #[test]
+    fn test_missing_href_dropped() {
+        let schema = SerpSchema::new(".result", ".link", "img").href_attr("data-src");
+        let page = schema.parse_results(HTML).unwrap();
+
+        assert!(page.results.is_empty());
+        assert!(!page.diagnostics.heuristic_fallback);
+        assert_eq!(page.diagnostics.missing[0].field, "href");
+        assert_eq!(page.diagnostics.missing[0].count, 2);
+    }
+
+    #[test]
+    fn test_heuristic_fallback() {
+        let schema = SerpSchema::new(".renamed-result", ".link", ".link");
+        let html = r#"<div><h3><a href="https://a.com">A title</a></h3><p>About a</p></div>"#;
+        let page = schema.parse_results(html).unwrap();
+        let results = page.results;
+
+        assert!(page.diagnostics.heuristic_fallback);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "A title");
+        assert_eq!(results[0].description, "About a");
+    }
+
+    #[test]
+    fn test_empty_results_page() {
+        let schema = SerpSchema::new(".result", ".link", ".link").container("#results");
+        let html = r#"
+            <nav><h3><a href="https://example.com/help">Help</a></h3></nav>
+            <div id="results"><p class="no-results">No results found for "qwzx"</p></div>
+        "#;
+        let page = schema.parse_results(html).unwrap();
+
+        assert!(page.results.is_empty());
+        assert!(!page.diagnostics.heuristic_fallback);
+
+        // without its container the page isn't a result page we know
+        let html = r#"<div><h3><a href="https://a.com">A title</a></h3></div>"#;
+        let page = schema.parse_results(html).unwrap();
+        assert!(page.diagnostics.heuristic_fallback);
+        assert_eq!(page.results.len(), 1);
+    }
+
+    #[test]
+    fn test_runtime_schema() {
+        let selector = String::from(".link");
+        let schema = SerpSchema::new(".result", selector.clone(), selector);
+        assert_eq!(
+            schema.parse_results(HTML).unwrap().results[1].url,
+            "https://b.com"
+        );
+
+        let schema: SerpSchema = toml::from_str(
+            r#"
+            results = ".result"
+            title = ".link"
+            href = "img"
+            href_attr = "src"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            schema.parse_images(HTML).unwrap().images[0].url,
+            "https://a.com/a.png"
+        );
+    }
+
+    #[test]
+    fn test_invalid_selector() {
+        let schema = SerpSchema::new(".result", "<<title", ".link");
+
+        assert!(matches!(
+            schema.parse_results(HTML),
+            Err(EngineError::ParseError(_))
+        ));
+        // the compile error is cached as well
+        assert!(schema.parse_images(HTML).is_err());
+    }
+
+    #[test]
+    fn test_parse_images() {
+        let schema = SerpSchema::new(".result", ".link", "img").href_attr("src");
+        let images = schema.parse_images(HTML).unwrap().images;
+
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[1].url, "https://b.com/b.png");
+        assert_eq!(images[1].title, "B title");
+        assert_eq!(images[1].thumbnail, None);
+
+        let schema = SerpSchema::new(".result", ".link", "img")
+            .href_attr("src")
+            .thumbnail("img", "data-thumb")
+            .source_url(".link")
+            .dimensions(".size")
+            .license(".license");
+        let images = schema.parse_images(HTML).unwrap().images;
+
+        assert_eq!(
+            images[0].thumbnail.as_deref(),
+            Some("https://thumbs.com/a.jpg")
+        );
+        assert_eq!(images[0].source_url.as_deref(), Some("https://a.com"));
+        assert_eq!(images[0].license.as_deref(), Some("CC0 1.0"));
+        assert_eq!(images[1].license, None);
+        assert_eq!(
+            (images[0].width, images[0].height),
+            (Some(1920), Some(1080))
+        );
+        assert_eq!((images[1].width, images[1].height), (None, None));
+    }
+
+    #[test]
+    fn test_image_diagnostics() {
+        let schema = SerpSchema::new(".result", ".snippet", "img").href_attr("data-thumb");
+        let page = schema.parse_images(HTML).unwrap();
+        let diagnostics = page.diagnostics.unwrap();
+
+        // the second image has no url and is dropped rather than cached empty
+        assert_eq!(page.images.len(), 1);
+        assert_eq!(page.images[0].url, "https://thumbs.com/a.jpg");
+        assert_eq!(page.images[0].title, "A description");
+        assert_eq!(diagnostics.matched, 2);
+        assert_eq!(diagnostics.missing.len(), 1);
+        assert_eq!(diagnostics.missing[0].field, "href");
+        assert_eq!(diagnostics.missing[0].count, 1);
+
+        let schema = SerpSchema::new(".result", ".caption", "img").href_attr("src");
+        let diagnostics = schema.parse_images(HTML).unwrap().diagnostics.unwrap();
+        assert_eq!(diagnostics.missing[0].field, "title");
+        assert_eq!(diagnostics.missing[0].count, 2);
+    }
+
+    #[test]
+    fn test_parse_dimensions() {
+        assert_eq!(parse_dimensions("1920 × 1080"), Some((1920, 1080)));
+        assert_eq!(parse_dimensions("800x600 · jpg"), Some((800, 600)));
+        assert_eq!(parse_dimensions("large"), None);
+    }
+}
@@ -0,0 +1,106 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineInfo, JsonEngine, instance_id},
+};
+
+/// A SearXNG instance, queried through its JSON API. The instance has to
+/// have `json` among its `search.formats`.
+#[derive(Debug, Clone)]
+pub struct SearxNG {
+    base_url: String,
+}
+
+impl SearxNG {
+    pub const NAME: &'static str = "SearXNG";
+
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl EngineInfo for SearxNG {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn id(&self) -> Cow<'static, str> {
+        instance_id(Self::NAME, &self.base_url)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SearxResponse {
+    #[serde(default)]
+    results: Vec<SearxHit>,
+}
+
+#[derive(Deserialize)]
+struct SearxHit {
+    url: String,
+    title: String,
+    #[serde(default)]
+    content: Option<String>,
+}
+
+impl JsonEngine for SearxNG {
+    type Response = SearxResponse;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        client.get(format!("{}/search", self.base_url)).query(&[
+            ("q", query),
+            ("format", "json"),
+            ("pageno", page.unwrap_or("1")),
+        ])
+    }
+
+    fn next_page(&self, response: &SearxResponse, page: Option<&str>) -> Option<String> {
+        let number: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+        (!response.results.is_empty()).then(|| (number + 1).to_string())
+    }
+
+    fn results(&self, response: SearxResponse) -> Vec<ResultRow> {
+        response
+            .results
+            .into_iter()
+            .map(|hit| ResultRow {
+                url: hit.url,
+                title: hit.title,
+                description: hit.content.unwrap_or_default(),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SearxNG, SearxResponse};
+    use crate::engines::{JsonEngine, parse_json};
+
+    #[test]
+    fn test_results() {
+        let searx = SearxNG::new("https://searx.example.org/");
+        let response: SearxResponse = parse_json(
+            r#"{"query": "rust", "number_of_results": 0, "results": [
+                {"url": "https://www.rust-lang.org/", "title": "Rust", "content": "A language empowering everyone.", "engines": ["brave"]},
+                {"url": "https://doc.rust-lang.org/book/", "title": "The Book", "content": null}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(searx.next_page(&response, Some("2")).as_deref(), Some("3"));
+        let results = searx.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].description, "A language empowering everyone.");
+        assert_eq!(results[1].description, "");
+
+        let empty: SearxResponse = parse_json(r#"{"results": []}"#).unwrap();
+        assert_eq!(searx.next_page(&empty, None), None);
+    }
+}
@@ -0,0 +1,156 @@
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::{
+    cache::PaperRow,
+    engines::{EngineError, EngineInfo, PaperPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const SEARCH_URL: &str = "https://api.semanticscholar.org/graph/v1/paper/search";
+const FIELDS: &str = "title,url,year,authors,abstract,externalIds";
+const LIMIT: u32 = 20;
+
+/// Semantic Scholar's Academic Graph API. Without a key requests share a
+/// pool with every other anonymous client, so rate limits are common.
+#[derive(Clone)]
+pub struct SemanticScholar;
+
+impl EngineInfo for SemanticScholar {
+    fn name(&self) -> &'static str {
+        "Semantic Scholar"
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    /// Offset of the next page, missing on the last one
+    #[serde(default)]
+    next: Option<u32>,
+    #[serde(default)]
+    data: Vec<Paper>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Paper {
+    #[serde(default)]
+    title: Option<String>,
+    /// The paper's page on semanticscholar.org
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    year: Option<i32>,
+    #[serde(default)]
+    authors: Vec<Author>,
+    #[serde(default, rename = "abstract")]
+    abstract_text: Option<String>,
+    #[serde(default)]
+    external_ids: Option<ExternalIds>,
+}
+
+#[derive(Deserialize)]
+struct Author {
+    #[serde(default)]
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ExternalIds {
+    #[serde(default, rename = "DOI")]
+    doi: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<PaperRow> for SemanticScholar {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<PaperPage, EngineError> {
+        let resp = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("query", query),
+                ("fields", FIELDS),
+                ("limit", &LIMIT.to_string()),
+                ("offset", page.unwrap_or("0")),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            return Err(EngineError::QuotaExceeded(
+                "Semantic Scholar rate limited the shared pool".to_string(),
+            ));
+        }
+        let body = resp
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body)
+    }
+}
+
+fn parse_response(body: &str) -> Result<PaperPage, EngineError> {
+    let response: SearchResponse = parse_json(body)?;
+
+    let papers = response
+        .data
+        .into_iter()
+        .filter_map(|paper| {
+            Some(PaperRow {
+                url: paper.url?,
+                title: paper.title?,
+                authors: paper.authors.into_iter().filter_map(|a| a.name).collect(),
+                year: paper.year,
+                doi: paper.external_ids.and_then(|ids| ids.doi),
+                abstract_text: paper.abstract_text.filter(|a| !a.trim().is_empty()),
+                kind: None,
+            })
+        })
+        .collect();
+
+    Ok(PaperPage {
+        rows: papers,
+        next_page: response.next.map(|offset| offset.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "total": 1234, "offset": 0, "next": 20,
+                "data": [
+                    {"paperId": "649def34f8be52c8b66281af98ae884c09aef38b",
+                     "url": "https://www.semanticscholar.org/paper/649def34f8be52c8b66281af98ae884c09aef38b",
+                     "title": "RustBelt: securing the foundations of the Rust programming language",
+                     "year": 2017, "abstract": "Rust is a new systems programming language...",
+                     "externalIds": {"DOI": "10.1145/3158154", "MAG": "2783542342", "CorpusId": 3587621},
+                     "authors": [{"authorId": "1", "name": "Ralf Jung"}, {"authorId": "2", "name": "Derek Dreyer"}]},
+                    {"paperId": "abc", "url": "https://www.semanticscholar.org/paper/abc",
+                     "title": "No metadata", "year": null, "abstract": null, "externalIds": null, "authors": []},
+                    {"paperId": "def", "url": null, "title": "Without a page"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let rustbelt = &page.rows[0];
+        assert_eq!(rustbelt.authors, ["Ralf Jung", "Derek Dreyer"]);
+        assert_eq!(rustbelt.year, Some(2017));
+        assert_eq!(rustbelt.doi.as_deref(), Some("10.1145/3158154"));
+        assert!(rustbelt.abstract_text.is_some());
+        assert_eq!(page.rows[1].doi, None);
+        assert_eq!(page.next_page.as_deref(), Some("20"));
+
+        let last = parse_response(r#"{"total": 3, "offset": 0, "data": []}"#).unwrap();
+        assert_eq!(last.next_page, None);
+    }
+}
@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use regex::Regex;
+use std::{
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::engines::{
+    EngineError, EngineInfo, SearchEngine, SerpPage, SerpSchema, new_rand_client,
+    parse_page_blocking,
+};
+
+const HOME_URL: &str = "https://www.startpage.com/";
+const SEARCH_URL: &str = "https://www.startpage.com/sp/search";
+const SC_TTL: Duration = Duration::from_secs(30 * 60);
+
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new(
+        ".w-gl .result",
+        ".wgl-title, .result-title",
+        "a.result-link",
+    )
+    .container(".w-gl, .layout-web")
+    .description("p.description")
+    .next_page(".pagination button.next, .pagination__next-prev-button.next")
+});
+
+/// Session code from the homepage's search form, searches without a recent
+/// one are answered with a captcha.
+static SC: LazyLock<Mutex<Option<(String, Instant)>>> = LazyLock::new(|| Mutex::new(None));
+
+#[derive(Clone)]
+pub struct Startpage;
+
+impl EngineInfo for Startpage {
+    fn name(&self) -> &'static str {
+        "Startpage"
+    }
+}
+
+#[async_trait]
+impl SearchEngine for Startpage {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        // startpage pages are numbered from 1
+        let number: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+        let sc = sc_token().await?;
+
+        let html = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[
+                ("query", query),
+                ("cat", "web"),
+                ("t", "device"),
+                ("sc", &sc),
+                ("page", &number.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        if is_captcha(&html) {
+            // the next search starts a new session
+            *SC.lock().unwrap() = None;
+            return Err(EngineError::Blocked(
+                "Startpage served a captcha".to_string(),
+            ));
+        }
+
+        let mut page = parse_page_blocking(html, parse_response).await?;
+        page.next_page = page.has_next_page.then(|| (number + 1).to_string());
+        Ok(page)
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_response(body)
+    }
+}
+
+/// Returns the cached session code, scraping a new one from the homepage
+/// when missing or expired.
+async fn sc_token() -> Result<String, EngineError> {
+    if let Some((sc, fetched)) = SC.lock().unwrap().as_ref()
+        && fetched.elapsed() < SC_TTL
+    {
+        return Ok(sc.clone());
+    }
+
+    let html = new_rand_client()
+        .map_err(EngineError::ReqwestError)?
+        .get(HOME_URL)
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)?;
+
+    let sc = parse_sc(&html)
+        .ok_or_else(|| EngineError::ParseError("No sc token in Startpage homepage".to_string()))?;
+    *SC.lock().unwrap() = Some((sc.clone(), Instant::now()));
+
+    Ok(sc)
+}
+
+fn parse_sc(html: &str) -> Option<String> {
+    static SC_INPUT: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"<input[^>]*name=["']sc["'][^>]*value=["']([^"']+)["']"#).unwrap()
+    });
+
+    SC_INPUT.captures(html).map(|c| c[1].to_string())
+}
+
+fn is_captcha(html: &str) -> bool {
+    html.contains("/sp/captcha") || html.contains("g-recaptcha")
+}
+
+pub fn parse_response(html: &str) -> Result<SerpPage, EngineError> {
+    SEARCH_SCHEMA.parse_results(html)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_captcha, parse_response, parse_sc};
+
+    #[test]
+    fn test_parse_sc() {
+        assert_eq!(
+            parse_sc(
+                r#"<form action="/sp/search"><input type="hidden" name="sc" value="Xy7abc20"></form>"#
+            ),
+            Some("Xy7abc20".to_string())
+        );
+        assert_eq!(parse_sc(r#"<input name="query">"#), None);
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let html = r#"
+            <div class="w-gl">
+              <div class="result">
+                <a class="result-link" href="https://www.rust-lang.org/"><h2 class="wgl-title">Rust</h2></a>
+                <p class="description">A language empowering everyone.</p>
+              </div>
+              <div class="result">
+                <a class="result-link" href="https://doc.rust-lang.org/book/"><h2 class="wgl-title">The Book</h2></a>
+              </div>
+            </div>
+            <div class="pagination"><button class="next">Next</button></div>
+        "#;
+
+        let page = parse_response(html).unwrap();
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(page.results[0].title, "Rust");
+        assert_eq!(
+            page.results[0].description,
+            "A language empowering everyone."
+        );
+        assert!(page.has_next_page);
+    }
+
+    #[test]
+    fn test_is_captcha() {
+        assert!(is_captcha(r#"<form action="/sp/captcha">"#));
+        assert!(!is_captcha(r#"<div class="w-gl">"#));
+    }
+}
@@ -0,0 +1,230 @@
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
+use std::{borrow::Cow, sync::LazyLock};
+
+use crate::{
+    cache::FileRow,
+    engines::{EngineError, EngineInfo, FilePage, VerticalEngine, instance_id, new_rand_client},
+};
+
+const LIMIT: usize = 100;
+
+/// An indexer speaking Torznab, the API Jackett and Prowlarr expose for
+/// their trackers. `url` is the feed's endpoint with the api key, e.g.
+/// `http://localhost:9117/api/v2.0/indexers/all/results/torznab?apikey=<key>`.
+#[derive(Debug, Clone)]
+pub struct Torznab {
+    url: String,
+}
+
+impl Torznab {
+    pub const NAME: &'static str = "Torznab";
+
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+        }
+    }
+}
+
+impl EngineInfo for Torznab {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn id(&self) -> Cow<'static, str> {
+        instance_id(Self::NAME, &self.url)
+    }
+}
+
+#[async_trait]
+impl VerticalEngine<FileRow> for Torznab {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<FilePage, EngineError> {
+        let offset: usize = page.and_then(|p| p.parse().ok()).unwrap_or(0);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(&self.url)
+            .query(&[
+                ("t", "search"),
+                ("q", query),
+                ("offset", &offset.to_string()),
+                ("limit", &LIMIT.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, offset)
+    }
+}
+
+/// The feed is RSS, which the HTML parser reads well enough: element names
+/// are lowercased and namespace prefixes stay part of them. `<link>` is void
+/// in HTML and loses its text, so the `<comments>` and `<enclosure>` are used.
+fn parse_response(body: &str, offset: usize) -> Result<FilePage, EngineError> {
+    static ERROR: LazyLock<Selector> = LazyLock::new(|| Selector::parse("error").unwrap());
+    static ITEM: LazyLock<Selector> = LazyLock::new(|| Selector::parse("item").unwrap());
+    static TITLE: LazyLock<Selector> = LazyLock::new(|| Selector::parse("title").unwrap());
+    static COMMENTS: LazyLock<Selector> = LazyLock::new(|| Selector::parse("comments").unwrap());
+    static GUID: LazyLock<Selector> = LazyLock::new(|| Selector::parse("guid").unwrap());
+    static PUB_DATE: LazyLock<Selector> = LazyLock::new(|| Selector::parse("pubdate").unwrap());
+    static SIZE: LazyLock<Selector> = LazyLock::new(|| Selector::parse("size").unwrap());
+    static ENCLOSURE: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse("enclosure[url]").unwrap());
+    static ATTR: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r"torznab\:attr[name][value]").unwrap());
+
+    let feed = Html::parse_document(body);
+    // e.g. a wrong api key, answered with a 200
+    if let Some(error) = feed.select(&ERROR).next() {
+        return Err(EngineError::ParseError(format!(
+            "Torznab returned error {}: {}",
+            error.attr("code").unwrap_or_default(),
+            error.attr("description").unwrap_or_default()
+        )));
+    }
+
+    let files: Vec<FileRow> = feed
+        .select(&ITEM)
+        .filter_map(|item| {
+            let first = |selector: &Selector| text(item.select(selector).next()?);
+            let attr = |name: &str| {
+                item.select(&ATTR)
+                    .find(|a| a.attr("name") == Some(name))?
+                    .attr("value")
+                    .map(str::to_string)
+            };
+            let count = |name: &str| attr(name)?.parse::<u32>().ok();
+
+            let enclosure = item
+                .select(&ENCLOSURE)
+                .next()
+                .and_then(|e| e.attr("url"))
+                .map(str::to_string);
+            // some indexers put the magnet link in the enclosure
+            let (magnet, download_url) = match enclosure {
+                Some(url) if url.starts_with("magnet:") => (Some(url), None),
+                enclosure => (attr("magneturl"), enclosure),
+            };
+            let url = first(&COMMENTS)
+                .or_else(|| first(&GUID).filter(|guid| guid.starts_with("http")))
+                .or_else(|| download_url.clone())
+                .or_else(|| magnet.clone())?;
+            let seeders = count("seeders");
+
+            Some(FileRow {
+                url,
+                title: first(&TITLE)?,
+                magnet,
+                download_url,
+                size: first(&SIZE).and_then(|size| size.parse().ok()),
+                // peers are seeders and leechers together
+                leechers: count("leechers")
+                    .or_else(|| Some(count("peers")?.saturating_sub(seeders?))),
+                seeders,
+                published_at: first(&PUB_DATE).map(|date| {
+                    chrono::DateTime::parse_from_rfc2822(&date)
+                        .map(|date| date.to_rfc3339())
+                        .unwrap_or(date)
+                }),
+            })
+        })
+        .collect();
+
+    // the total is optional in Torznab, a full page hints at another one
+    let next_page = (files.len() >= LIMIT).then(|| (offset + LIMIT).to_string());
+    Ok(FilePage {
+        rows: files,
+        next_page,
+    })
+}
+
+fn text(element: ElementRef) -> Option<String> {
+    let text = element.text().collect::<String>();
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+    use crate::engines::EngineError;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:torznab="http://torznab.com/schemas/2015/feed">
+  <channel>
+    <atom:link href="http://localhost:9117/api" rel="self" type="application/rss+xml" />
+    <title>AggregateSearch</title>
+    <item>
+      <title>debian-12.5.0-amd64-netinst.iso</title>
+      <guid>https://tracker.example/details/1</guid>
+      <jackettindexer id="example">Example</jackettindexer>
+      <comments>https://tracker.example/details/1</comments>
+      <pubDate>Sat, 10 Feb 2024 12:00:00 +0000</pubDate>
+      <size>659554304</size>
+      <link>http://localhost:9117/dl/example/?jackett_apikey=abc&amp;path=1</link>
+      <enclosure url="http://localhost:9117/dl/example/?jackett_apikey=abc&amp;path=1" length="659554304" type="application/x-bittorrent" />
+      <torznab:attr name="category" value="4000" />
+      <torznab:attr name="seeders" value="120" />
+      <torznab:attr name="peers" value="124" />
+      <torznab:attr name="magneturl" value="magnet:?xt=urn:btih:abc&amp;dn=debian" />
+    </item>
+    <item>
+      <title>ubuntu-24.04-desktop-amd64.iso</title>
+      <guid>2f1e5c</guid>
+      <pubDate>not a date</pubDate>
+      <enclosure url="magnet:?xt=urn:btih:def" type="application/x-bittorrent" />
+    </item>
+  </channel>
+</rss>"#,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let debian = &page.rows[0];
+        assert_eq!(debian.url, "https://tracker.example/details/1");
+        assert_eq!(debian.title, "debian-12.5.0-amd64-netinst.iso");
+        assert_eq!(
+            debian.magnet.as_deref(),
+            Some("magnet:?xt=urn:btih:abc&dn=debian")
+        );
+        assert_eq!(
+            debian.download_url.as_deref(),
+            Some("http://localhost:9117/dl/example/?jackett_apikey=abc&path=1")
+        );
+        assert_eq!(debian.size, Some(659_554_304));
+        assert_eq!((debian.seeders, debian.leechers), (Some(120), Some(4)));
+        assert_eq!(
+            debian.published_at.as_deref(),
+            Some("2024-02-10T12:00:00+00:00")
+        );
+
+        let ubuntu = &page.rows[1];
+        assert_eq!(ubuntu.url, "magnet:?xt=urn:btih:def");
+        assert_eq!(ubuntu.magnet.as_deref(), Some("magnet:?xt=urn:btih:def"));
+        assert_eq!(ubuntu.download_url, None);
+        assert_eq!((ubuntu.seeders, ubuntu.leechers), (None, None));
+        assert_eq!(ubuntu.published_at.as_deref(), Some("not a date"));
+        assert_eq!(page.next_page, None);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse_response(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<error code="100" description="Invalid API Key" />"#,
+            0,
+        );
+        assert!(matches!(error, Err(EngineError::ParseError(e)) if e.contains("Invalid API Key")));
+    }
+}
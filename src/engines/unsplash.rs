@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use reqwest::{StatusCode, header::AUTHORIZATION};
+use serde::Deserialize;
+
+use crate::{
+    cache::ImagesRow,
+    config::SafeSearch,
+    engines::{
+        AspectRatio, EngineError, EngineInfo, ImageEngine, ImageOptions, ImagePage,
+        new_rand_client, parse_json,
+    },
+};
+
+const API_URL: &str = "https://api.unsplash.com/search/photos";
+const PER_PAGE: u32 = 30;
+const LICENSE: &str = "Unsplash License";
+
+/// Unsplash's stock photos, through its API with an app's access key.
+#[derive(Debug, Clone)]
+pub struct Unsplash {
+    api_key: String,
+}
+
+impl Unsplash {
+    pub const NAME: &'static str = "Unsplash";
+
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+impl EngineInfo for Unsplash {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+}
+
+#[derive(Deserialize)]
+struct UnsplashResponse {
+    #[serde(default)]
+    total_pages: u32,
+    #[serde(default)]
+    results: Vec<UnsplashPhoto>,
+}
+
+#[derive(Deserialize)]
+struct UnsplashPhoto {
+    /// Of the original, which `urls.full` keeps
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    alt_description: Option<String>,
+    urls: UnsplashUrls,
+    links: UnsplashLinks,
+    #[serde(default)]
+    user: Option<UnsplashUser>,
+}
+
+#[derive(Deserialize)]
+struct UnsplashUrls {
+    full: String,
+    /// 400px wide
+    #[serde(default)]
+    small: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct UnsplashLinks {
+    /// The photo's page on Unsplash
+    html: String,
+}
+
+#[derive(Deserialize)]
+struct UnsplashUser {
+    name: String,
+}
+
+#[async_trait]
+impl ImageEngine for Unsplash {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        let content_filter = match options.safe_search {
+            Some(SafeSearch::Strict) => "high",
+            _ => "low",
+        };
+        let page: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(API_URL)
+            .header(AUTHORIZATION, format!("Client-ID {}", self.api_key))
+            .query(&[
+                ("query", query),
+                ("page", &page.to_string()),
+                ("per_page", &PER_PAGE.to_string()),
+                ("content_filter", content_filter),
+            ]);
+        if let Some(aspect) = options.aspect {
+            let orientation = match aspect {
+                AspectRatio::Square => "squarish",
+                AspectRatio::Tall => "portrait",
+                AspectRatio::Wide => "landscape",
+            };
+            request = request.query(&[("orientation", orientation)]);
+        }
+
+        let resp = request.send().await.map_err(EngineError::ReqwestError)?;
+        let status = resp.status();
+        let body = resp.text().await.map_err(EngineError::ReqwestError)?;
+        // the hourly limit is answered with a 403 and a plain text body
+        if status == StatusCode::FORBIDDEN && body.contains("Rate Limit Exceeded") {
+            return Err(EngineError::QuotaExceeded(body.trim().to_string()));
+        }
+        if !status.is_success() {
+            return Err(EngineError::ParseError(format!(
+                "Unsplash returned {status}: {}",
+                body.trim()
+            )));
+        }
+
+        parse_image_response(&body, page)
+    }
+}
+
+fn parse_image_response(body: &str, page: u32) -> Result<ImagePage, EngineError> {
+    let response: UnsplashResponse = parse_json(body)?;
+
+    Ok(ImagePage {
+        next_page: (page < response.total_pages).then(|| (page + 1).to_string()),
+        images: response
+            .results
+            .into_iter()
+            .map(|photo| ImagesRow {
+                url: photo.urls.full,
+                title: photo
+                    .description
+                    .or(photo.alt_description)
+                    .unwrap_or_default(),
+                width: photo.width,
+                height: photo.height,
+                thumbnail: photo.urls.small,
+                source_url: Some(photo.links.html),
+                license: Some(LICENSE.to_string()),
+                attribution: photo.user.map(|user| user.name),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_image_response;
+
+    #[test]
+    fn test_parse_image_response() {
+        let page = parse_image_response(
+            r##"{
+                "total": 133, "total_pages": 5,
+                "results": [
+                    {"id": "eOLpJytrbsQ", "width": 4000, "height": 3000, "color": "#A7A2A1",
+                     "description": null, "alt_description": "brown crab on sand",
+                     "urls": {"raw": "https://images.unsplash.com/photo-1?ixid=a", "full": "https://images.unsplash.com/photo-1?q=85&fm=jpg",
+                              "regular": "https://images.unsplash.com/photo-1?w=1080", "small": "https://images.unsplash.com/photo-1?w=400",
+                              "thumb": "https://images.unsplash.com/photo-1?w=200"},
+                     "links": {"self": "https://api.unsplash.com/photos/eOLpJytrbsQ", "html": "https://unsplash.com/photos/eOLpJytrbsQ"},
+                     "user": {"id": "Ul0QVz12Goo", "username": "ferris", "name": "Ferris Crab"}}
+                ]
+            }"##,
+            5,
+        )
+        .unwrap();
+
+        assert_eq!(page.images.len(), 1);
+        let image = &page.images[0];
+        assert_eq!(image.url, "https://images.unsplash.com/photo-1?q=85&fm=jpg");
+        assert_eq!(image.title, "brown crab on sand");
+        assert_eq!(
+            image.source_url.as_deref(),
+            Some("https://unsplash.com/photos/eOLpJytrbsQ")
+        );
+        assert_eq!(image.license.as_deref(), Some("Unsplash License"));
+        assert_eq!(image.attribution.as_deref(), Some("Ferris Crab"));
+        // last page
+        assert_eq!(page.next_page, None);
+    }
+}
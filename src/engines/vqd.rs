@@ -0,0 +1,93 @@
+//! DuckDuckGo's vqd tokens, required by its image, news and video JSON endpoints.
+//!
+//! Tokens are tied to the query they were issued for, so they are cached per
+//! query and refetched once they expire or get rejected.
+
+use regex::Regex;
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::engines::{EngineError, new_rand_client};
+
+const VQD_TTL: Duration = Duration::from_secs(15 * 60);
+
+static TOKENS: LazyLock<Mutex<HashMap<String, (String, Instant)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Returns a cached vqd token for the query, fetching a new one when missing or expired.
+pub async fn vqd_token(query: &str) -> Result<String, EngineError> {
+    if let Some((token, fetched)) = TOKENS.lock().unwrap().get(query)
+        && fetched.elapsed() < VQD_TTL
+    {
+        return Ok(token.clone());
+    }
+
+    let html = new_rand_client()
+        .map_err(EngineError::ReqwestError)?
+        .get("https://duckduckgo.com/")
+        .query(&[("q", query)])
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)?
+        .text()
+        .await
+        .map_err(EngineError::ReqwestError)?;
+
+    let token = parse_vqd(&html)
+        .ok_or_else(|| EngineError::ParseError("No vqd token in DuckDuckGo page".to_string()))?;
+
+    let mut tokens = TOKENS.lock().unwrap();
+    tokens.retain(|_, (_, fetched)| fetched.elapsed() < VQD_TTL);
+    tokens.insert(query.to_string(), (token.clone(), Instant::now()));
+
+    Ok(token)
+}
+
+/// Drops the cached token for the query, e.g. after an endpoint rejected it.
+pub fn invalidate(query: &str) {
+    TOKENS.lock().unwrap().remove(query);
+}
+
+fn parse_vqd(html: &str) -> Option<String> {
+    static VQD: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"vqd["']?\s*[=:]\s*["']?(\d+-[\d-]+)"#).unwrap());
+
+    VQD.captures(html).map(|c| c[1].to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TOKENS, invalidate, parse_vqd, vqd_token};
+    use std::time::Instant;
+
+    #[test]
+    fn test_parse_vqd() {
+        assert_eq!(
+            parse_vqd(
+                r#"<script>DDG.deep.initialize('/d.js?q=rust&vqd="4-1234567890123"&p=1');</script>"#
+            ),
+            Some("4-1234567890123".to_string())
+        );
+        assert_eq!(
+            parse_vqd(r#"{"vqd": "4-98765-4321"}"#),
+            Some("4-98765-4321".to_string())
+        );
+        assert_eq!(parse_vqd("<html></html>"), None);
+    }
+
+    #[tokio::test]
+    async fn test_cached_token() {
+        TOKENS
+            .lock()
+            .unwrap()
+            .insert("vqd cache test".into(), ("4-111".into(), Instant::now()));
+
+        assert_eq!(vqd_token("vqd cache test").await.unwrap(), "4-111");
+
+        invalidate("vqd cache test");
+        assert!(!TOKENS.lock().unwrap().contains_key("vqd cache test"));
+    }
+}
@@ -0,0 +1,125 @@
+use async_trait::async_trait;
+use std::{borrow::Cow, sync::LazyLock};
+
+use crate::engines::{
+    EngineError, EngineInfo, SearchEngine, SerpPage, SerpSchema,
+    google::{start_param, unwrap_url},
+    instance_id, new_rand_client, parse_page_blocking,
+};
+
+/// Whoogle serves Google's basic html results, which it already strips of
+/// tracking and most redirect links.
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new("#main div.ZINbbc, #s div.ZINbbc", "h3, .vvjwJb", "a[href]")
+        .container("#main, #s")
+        .description(".s3v9rd, .BNeawe.s3v9rd.AP7Wnd")
+        .next_page("footer a[href*='start='], a[aria-label='Next page']")
+});
+
+/// A self-hosted Whoogle instance, proxying Google.
+#[derive(Debug, Clone)]
+pub struct Whoogle {
+    base_url: String,
+}
+
+impl Whoogle {
+    pub const NAME: &'static str = "Whoogle";
+
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl EngineInfo for Whoogle {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn id(&self) -> Cow<'static, str> {
+        instance_id(Self::NAME, &self.base_url)
+    }
+}
+
+#[async_trait]
+impl SearchEngine for Whoogle {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(format!("{}/search", self.base_url))
+            .query(&[("q", query)]);
+        if let Some(start) = page {
+            request = request.query(&[("start", start)]);
+        }
+
+        let html = request
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_page_blocking(html, parse_response).await
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_response(body)
+    }
+}
+
+pub fn parse_response(html: &str) -> Result<SerpPage, EngineError> {
+    let mut page = SEARCH_SCHEMA.parse_results(html)?;
+    page.results = page
+        .results
+        .into_iter()
+        .filter_map(|mut r| {
+            r.url = unwrap_url(&r.url)?;
+            Some(r)
+        })
+        .collect();
+    page.next_page = page.next_page.as_deref().and_then(start_param);
+
+    Ok(page)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let html = r#"
+            <div id="main">
+              <div class="ZINbbc xpd">
+                <a href="https://www.rust-lang.org/"><h3 class="BNeawe vvjwJb">Rust</h3></a>
+                <div class="BNeawe s3v9rd AP7Wnd">A language empowering everyone.</div>
+              </div>
+              <div class="ZINbbc xpd">
+                <a href="/url?q=https://doc.rust-lang.org/book/&amp;sa=U"><h3 class="BNeawe vvjwJb">The Book</h3></a>
+              </div>
+            </div>
+            <footer>
+              <a href="search?q=rust&amp;start=0">Previous</a>
+              <a href="search?q=rust&amp;start=20">Next</a>
+            </footer>
+        "#;
+
+        let page = parse_response(html).unwrap();
+        assert_eq!(page.results.len(), 2);
+        assert_eq!(page.results[0].title, "Rust");
+        assert_eq!(
+            page.results[0].description,
+            "A language empowering everyone."
+        );
+        assert_eq!(page.results[1].url, "https://doc.rust-lang.org/book/");
+        assert_eq!(page.next_page.as_deref(), Some("20"));
+    }
+}
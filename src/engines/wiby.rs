@@ -0,0 +1,93 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineInfo, FileType, JsonEngine},
+};
+
+const API_URL: &str = "https://wiby.me/json/";
+
+/// Search engine for the small, hobbyist web.
+#[derive(Clone)]
+pub struct Wiby;
+
+impl EngineInfo for Wiby {
+    fn name(&self) -> &'static str {
+        "Wiby"
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WibyHit {
+    #[serde(rename = "URL")]
+    url: String,
+    title: String,
+    #[serde(default)]
+    snippet: String,
+    /// Only set on the last hit when there are more results
+    #[serde(default)]
+    next_offset: Option<String>,
+}
+
+impl JsonEngine for Wiby {
+    type Response = Vec<WibyHit>;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        let mut request = client.get(API_URL).query(&[("q", query)]);
+        if let Some(offset) = page {
+            request = request.query(&[("o", offset)]);
+        }
+        request
+    }
+
+    fn next_page(&self, response: &Vec<WibyHit>, _page: Option<&str>) -> Option<String> {
+        response
+            .last()
+            .and_then(|hit| hit.next_offset.clone())
+            .filter(|offset| !offset.is_empty())
+    }
+
+    fn results(&self, response: Vec<WibyHit>) -> Vec<ResultRow> {
+        response
+            .into_iter()
+            .map(|hit| ResultRow {
+                url: hit.url,
+                title: hit.title,
+                description: hit.snippet,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Wiby indexes hand made pages, not documents.
+    fn file_type_query(&self, _query: &str, _file_type: FileType) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Wiby, WibyHit};
+    use crate::engines::{JsonEngine, parse_json};
+
+    #[test]
+    fn test_results() {
+        let response: Vec<WibyHit> = parse_json(
+            r#"[
+                {"URL": "https://example.neocities.org/", "Title": "My homepage", "Snippet": "Under construction", "Date": "2023-01-01"},
+                {"URL": "http://old.example.net/", "Title": "Retro", "Snippet": "", "Date": "2021-05-03", "NextOffset": "12"}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(Wiby.next_page(&response, None).as_deref(), Some("12"));
+        let results = Wiby.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, "https://example.neocities.org/");
+        assert_eq!(results[0].description, "Under construction");
+
+        assert_eq!(Wiby.next_page(&Vec::new(), None), None);
+    }
+}
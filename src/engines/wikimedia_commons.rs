@@ -0,0 +1,257 @@
+use async_trait::async_trait;
+use scraper::Html;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{
+    cache::ImagesRow,
+    engines::{
+        EngineError, EngineInfo, ImageEngine, ImageFormat, ImageOptions, ImagePage,
+        new_rand_client, parse_json,
+    },
+};
+
+const API_URL: &str = "https://commons.wikimedia.org/w/api.php";
+const LIMIT: u32 = 30;
+const THUMB_WIDTH: u32 = 320;
+
+/// Files on Wikimedia Commons, found through the MediaWiki search API. Every
+/// file carries its license and author, so results are safe to reuse with
+/// the attribution shown.
+#[derive(Clone)]
+pub struct WikimediaCommons;
+
+impl EngineInfo for WikimediaCommons {
+    fn name(&self) -> &'static str {
+        "Wikimedia Commons"
+    }
+}
+
+#[derive(Deserialize)]
+struct CommonsResponse {
+    #[serde(default, rename = "continue")]
+    next: Option<CommonsContinue>,
+    /// Missing when nothing matched
+    #[serde(default)]
+    query: Option<CommonsQuery>,
+}
+
+#[derive(Deserialize)]
+struct CommonsContinue {
+    gsroffset: u32,
+}
+
+#[derive(Deserialize)]
+struct CommonsQuery {
+    /// Keyed by page id, `index` has the search order
+    pages: HashMap<String, CommonsPage>,
+}
+
+#[derive(Deserialize)]
+struct CommonsPage {
+    #[serde(default)]
+    index: u32,
+    /// e.g. "File:Rust logo.svg"
+    title: String,
+    #[serde(default)]
+    imageinfo: Vec<CommonsImageInfo>,
+}
+
+#[derive(Deserialize)]
+struct CommonsImageInfo {
+    url: String,
+    #[serde(default)]
+    thumburl: Option<String>,
+    /// The file's page on Commons
+    #[serde(default)]
+    descriptionurl: Option<String>,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+    #[serde(default)]
+    mime: String,
+    #[serde(default)]
+    extmetadata: HashMap<String, MetadataValue>,
+}
+
+#[derive(Deserialize)]
+struct MetadataValue {
+    /// Usually a string, sometimes HTML
+    value: serde_json::Value,
+}
+
+#[async_trait]
+impl ImageEngine for WikimediaCommons {
+    async fn search_images(
+        &self,
+        query: &str,
+        options: &ImageOptions,
+        page: Option<&str>,
+    ) -> Result<ImagePage, EngineError> {
+        // CirrusSearch keyword, narrowing the search to one file type
+        let search = match options.format {
+            Some(format) => format!("{query} filemime:{}", mime_type(format)),
+            None => query.to_string(),
+        };
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(API_URL)
+            .query(&[
+                ("action", "query"),
+                ("format", "json"),
+                ("generator", "search"),
+                ("gsrsearch", &search),
+                // the File: namespace
+                ("gsrnamespace", "6"),
+                ("gsrlimit", &LIMIT.to_string()),
+                ("gsroffset", page.unwrap_or("0")),
+                ("prop", "imageinfo"),
+                ("iiprop", "url|size|mime|extmetadata"),
+                ("iiextmetadatafilter", "ObjectName|LicenseShortName|Artist"),
+                ("iiurlwidth", &THUMB_WIDTH.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_image_response(&body)
+    }
+}
+
+fn mime_type(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Jpg => "image/jpeg",
+        ImageFormat::Png => "image/png",
+        ImageFormat::Gif => "image/gif",
+        ImageFormat::Svg => "image/svg+xml",
+        ImageFormat::Webp => "image/webp",
+    }
+}
+
+fn parse_image_response(body: &str) -> Result<ImagePage, EngineError> {
+    let response: CommonsResponse = parse_json(body)?;
+
+    let mut pages: Vec<CommonsPage> = response
+        .query
+        .map(|q| q.pages.into_values().collect())
+        .unwrap_or_default();
+    pages.sort_by_key(|page| page.index);
+
+    let images = pages
+        .into_iter()
+        .filter_map(|page| {
+            let mut info = page.imageinfo.into_iter().next()?;
+            // the namespace also holds pdfs, audio and video
+            if !info.mime.starts_with("image/") {
+                return None;
+            }
+            let title = metadata_text(&mut info.extmetadata, "ObjectName")
+                .unwrap_or_else(|| file_title(&page.title));
+
+            Some(ImagesRow {
+                license: metadata_text(&mut info.extmetadata, "LicenseShortName"),
+                attribution: metadata_text(&mut info.extmetadata, "Artist"),
+                url: info.url,
+                title,
+                width: info.width,
+                height: info.height,
+                thumbnail: info.thumburl,
+                source_url: info.descriptionurl,
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    Ok(ImagePage {
+        images,
+        next_page: response.next.map(|c| c.gsroffset.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Text of a metadata field, with the links and markup authors are often
+/// credited with stripped.
+fn metadata_text(metadata: &mut HashMap<String, MetadataValue>, key: &str) -> Option<String> {
+    let value = match metadata.remove(key)?.value {
+        serde_json::Value::String(value) => value,
+        serde_json::Value::Number(value) => value.to_string(),
+        _ => return None,
+    };
+    let text = Html::parse_fragment(&value)
+        .root_element()
+        .text()
+        .collect::<String>();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// "File:Rust logo.svg" as "Rust logo".
+fn file_title(title: &str) -> String {
+    let name = title.strip_prefix("File:").unwrap_or(title);
+    name.rsplit_once('.')
+        .map_or(name, |(stem, _)| stem)
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{file_title, parse_image_response};
+
+    #[test]
+    fn test_file_title() {
+        assert_eq!(file_title("File:Rust logo.svg"), "Rust logo");
+        assert_eq!(file_title("File:v1.2 ferris.png"), "v1.2 ferris");
+        assert_eq!(file_title("Ferris"), "Ferris");
+    }
+
+    #[test]
+    fn test_parse_image_response() {
+        let page = parse_image_response(
+            r#"{
+                "batchcomplete": "", "continue": {"gsroffset": 30, "continue": "gsroffset||"},
+                "query": {"pages": {
+                    "-1": {"pageid": 2, "ns": 6, "title": "File:Ferris.png", "index": 2,
+                        "imageinfo": [{"url": "https://upload.wikimedia.org/ferris.png", "mime": "image/png",
+                            "descriptionurl": "https://commons.wikimedia.org/wiki/File:Ferris.png",
+                            "extmetadata": {"LicenseShortName": {"value": "CC0"}}}]},
+                    "1": {"pageid": 1, "ns": 6, "title": "File:Rust programming language black logo.svg", "index": 1,
+                        "imageinfo": [{"url": "https://upload.wikimedia.org/rust.svg", "mime": "image/svg+xml",
+                            "thumburl": "https://upload.wikimedia.org/thumb/rust.svg/320px-rust.svg.png",
+                            "descriptionurl": "https://commons.wikimedia.org/wiki/File:Rust_programming_language_black_logo.svg",
+                            "width": 144, "height": 144,
+                            "extmetadata": {
+                                "ObjectName": {"value": "Rust logo", "source": "commons-desc-page"},
+                                "LicenseShortName": {"value": "CC BY 4.0", "source": "commons-desc-page"},
+                                "Artist": {"value": "<a href=\"https://www.rust-lang.org\" class=\"external text\">Rust Foundation</a>", "source": "commons-desc-page"}
+                            }}]},
+                    "3": {"pageid": 3, "ns": 6, "title": "File:Talk.pdf", "index": 3,
+                        "imageinfo": [{"url": "https://upload.wikimedia.org/talk.pdf", "mime": "application/pdf"}]}
+                }}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.images.len(), 2);
+        assert_eq!(page.images[0].title, "Rust logo");
+        assert_eq!(page.images[0].license.as_deref(), Some("CC BY 4.0"));
+        assert_eq!(
+            page.images[0].attribution.as_deref(),
+            Some("Rust Foundation")
+        );
+        assert_eq!(page.images[0].width, Some(144));
+        assert_eq!(page.images[1].title, "Ferris");
+        assert_eq!(page.images[1].attribution, None);
+        assert_eq!(page.next_page.as_deref(), Some("30"));
+
+        let empty = parse_image_response(r#"{"batchcomplete": ""}"#).unwrap();
+        assert!(empty.images.is_empty());
+        assert_eq!(empty.next_page, None);
+    }
+}
@@ -0,0 +1,187 @@
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::{
+    cache::ResultRow,
+    engines::{EngineError, EngineInfo, FileType, JsonEngine},
+};
+
+const LIMIT: u32 = 10;
+
+/// Wikipedia articles, through the MediaWiki search API of one language
+/// edition, each with the first sentences of its intro as the description.
+#[derive(Debug, Clone)]
+pub struct Wikipedia {
+    lang: String,
+}
+
+impl Wikipedia {
+    pub const NAME: &'static str = "Wikipedia";
+
+    /// `lang` is the edition's subdomain, e.g. `en` or `de`. Anything but
+    /// letters, digits and dashes is refused, it ends up in the host.
+    pub fn new(lang: &str) -> Result<Self, EngineError> {
+        let lang = lang.trim().to_lowercase();
+        if lang.is_empty() || !lang.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(EngineError::ParseError(format!(
+                "Invalid Wikipedia language: {lang}"
+            )));
+        }
+        Ok(Self { lang })
+    }
+
+    pub fn lang(&self) -> &str {
+        &self.lang
+    }
+}
+
+impl EngineInfo for Wikipedia {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Editions are cached apart, each has its own articles.
+    fn id(&self) -> Cow<'static, str> {
+        Cow::Owned(format!("{}:{}", Self::NAME, self.lang))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct WikipediaResponse {
+    #[serde(default, rename = "continue")]
+    next: Option<Continue>,
+    #[serde(default)]
+    query: Option<Query>,
+}
+
+#[derive(Deserialize)]
+struct Continue {
+    gsroffset: u32,
+}
+
+#[derive(Deserialize)]
+struct Query {
+    #[serde(default)]
+    pages: Vec<Page>,
+}
+
+#[derive(Deserialize)]
+struct Page {
+    title: String,
+    /// Rank in the search, pages come back in no particular order
+    #[serde(default)]
+    index: u32,
+    #[serde(default)]
+    extract: Option<String>,
+    fullurl: String,
+    /// Language of the page, e.g. `en`
+    #[serde(default)]
+    pagelanguage: Option<String>,
+}
+
+impl JsonEngine for Wikipedia {
+    type Response = WikipediaResponse;
+
+    fn request(&self, client: &Client, query: &str, page: Option<&str>) -> RequestBuilder {
+        client
+            .get(format!("https://{}.wikipedia.org/w/api.php", self.lang))
+            .query(&[
+                ("action", "query"),
+                ("format", "json"),
+                ("formatversion", "2"),
+                ("generator", "search"),
+                ("gsrsearch", query),
+                ("gsrlimit", &LIMIT.to_string()),
+                ("gsroffset", page.unwrap_or("0")),
+                ("prop", "extracts|info"),
+                ("exintro", "1"),
+                ("explaintext", "1"),
+                ("exsentences", "2"),
+                ("exlimit", "max"),
+                ("inprop", "url"),
+            ])
+    }
+
+    fn next_page(&self, response: &WikipediaResponse, _page: Option<&str>) -> Option<String> {
+        response
+            .next
+            .as_ref()
+            .map(|next| next.gsroffset.to_string())
+    }
+
+    fn results(&self, response: WikipediaResponse) -> Vec<ResultRow> {
+        let mut pages = response.query.map(|q| q.pages).unwrap_or_default();
+        pages.sort_by_key(|page| page.index);
+
+        pages
+            .into_iter()
+            .map(|page| ResultRow {
+                url: page.fullurl,
+                title: page.title,
+                description: page.extract.unwrap_or_default().trim().to_string(),
+                language: page.pagelanguage,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Only articles are searched, never files.
+    fn file_type_query(&self, _query: &str, _file_type: FileType) -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Wikipedia, WikipediaResponse};
+    use crate::engines::{JsonEngine, parse_json};
+
+    #[test]
+    fn test_results() {
+        let response: WikipediaResponse = parse_json(
+            r#"{
+                "batchcomplete": true,
+                "continue": {"gsroffset": 10, "continue": "gsroffset||"},
+                "query": {"pages": [
+                    {"pageid": 2, "ns": 0, "title": "Rust", "index": 2,
+                     "extract": "Rust is an iron oxide.", "pagelanguage": "en",
+                     "fullurl": "https://en.wikipedia.org/wiki/Rust"},
+                    {"pageid": 1, "ns": 0, "title": "Rust (programming language)", "index": 1,
+                     "extract": "Rust is a general-purpose programming language. It emphasizes performance.",
+                     "pagelanguage": "en",
+                     "fullurl": "https://en.wikipedia.org/wiki/Rust_(programming_language)"}
+                ]}
+            }"#,
+        )
+        .unwrap();
+
+        let wikipedia = Wikipedia::new("en").unwrap();
+        assert_eq!(wikipedia.next_page(&response, None).as_deref(), Some("10"));
+        let results = wikipedia.results(response);
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].url,
+            "https://en.wikipedia.org/wiki/Rust_(programming_language)"
+        );
+        assert!(
+            results[0]
+                .description
+                .starts_with("Rust is a general-purpose")
+        );
+        assert_eq!(results[0].language.as_deref(), Some("en"));
+        assert_eq!(results[1].title, "Rust");
+
+        // no matches come without a query object
+        let empty: WikipediaResponse = parse_json(r#"{"batchcomplete": true}"#).unwrap();
+        assert_eq!(wikipedia.next_page(&empty, None), None);
+        assert!(wikipedia.results(empty).is_empty());
+    }
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Wikipedia::new(" DE ").unwrap().lang(), "de");
+        assert!(Wikipedia::new("").is_err());
+        assert!(Wikipedia::new("evil.com/x").is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use async_trait::async_trait;
+use reqwest::{StatusCode, Url};
+use scraper::Html;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::{
+    cache::DefinitionRow,
+    engines::{EngineError, EngineInfo, VerticalEngine, VerticalPage, new_rand_client, parse_json},
+};
+
+const DEFINITION_URL: &str = "https://en.wiktionary.org/api/rest_v1/page/definition/";
+const PAGE_URL: &str = "https://en.wiktionary.org/wiki/";
+
+/// English senses from the English Wiktionary, through its REST API.
+#[derive(Clone)]
+pub struct Wiktionary;
+
+impl EngineInfo for Wiktionary {
+    fn name(&self) -> &'static str {
+        "Wiktionary"
+    }
+}
+
+/// Entries by language code, e.g. `en` or `fr`
+type DefinitionResponse = HashMap<String, Vec<Usage>>;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Usage {
+    /// Capitalized, e.g. "Noun"
+    part_of_speech: String,
+    #[serde(default)]
+    definitions: Vec<Sense>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sense {
+    /// HTML
+    definition: String,
+    /// HTML, left out by newer versions in favour of `parsed_examples`
+    #[serde(default)]
+    examples: Vec<String>,
+    #[serde(default)]
+    parsed_examples: Vec<ParsedExample>,
+}
+
+#[derive(Deserialize)]
+struct ParsedExample {
+    /// HTML
+    example: String,
+}
+
+#[async_trait]
+impl VerticalEngine<DefinitionRow> for Wiktionary {
+    async fn search_page(
+        &self,
+        word: &str,
+        _page: Option<&str>,
+    ) -> Result<VerticalPage<DefinitionRow>, EngineError> {
+        // page titles use underscores for spaces
+        let title = word.replace(' ', "_");
+        let mut url = Url::parse(DEFINITION_URL).unwrap();
+        url.path_segments_mut().unwrap().pop_if_empty().push(&title);
+
+        let resp = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(url)
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        // words without a page
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(VerticalPage::default());
+        }
+        let body = resp
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, word).map(VerticalPage::from)
+    }
+}
+
+fn parse_response(body: &str, word: &str) -> Result<Vec<DefinitionRow>, EngineError> {
+    let mut response: DefinitionResponse = parse_json(body)?;
+    let source_url = Url::parse(PAGE_URL)
+        .unwrap()
+        .join(&word.replace(' ', "_"))
+        .map(String::from)
+        .ok();
+
+    let mut rows = Vec::new();
+    for usage in response.remove("en").unwrap_or_default() {
+        let part_of_speech = usage.part_of_speech.to_lowercase();
+        for sense in usage.definitions {
+            let definition = html_text(&sense.definition);
+            // senses that only point at other entries, e.g. plurals, come
+            // with an empty definition
+            if definition.is_empty() {
+                continue;
+            }
+            let examples = match sense.parsed_examples.is_empty() {
+                true => sense.examples,
+                false => sense
+                    .parsed_examples
+                    .into_iter()
+                    .map(|e| e.example)
+                    .collect(),
+            };
+
+            rows.push(DefinitionRow {
+                word: word.to_string(),
+                part_of_speech: part_of_speech.clone(),
+                definition,
+                examples: examples
+                    .iter()
+                    .map(|example| html_text(example))
+                    .filter(|example| !example.is_empty())
+                    .collect(),
+                phonetic: None,
+                source_url: source_url.clone(),
+            });
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Plain text of a definition's HTML, with whitespace collapsed.
+fn html_text(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let text: String = fragment.root_element().text().collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let rows = parse_response(
+            r#"{
+                "en": [
+                    {"partOfSpeech": "Noun", "language": "English", "definitions": [
+                        {"definition": "<span class=\"usage-label-sense\">(uncountable)</span> An unsought, unintended, and unexpected <a rel=\"mw:WikiLink\" href=\"/wiki/discovery\">discovery</a>.",
+                         "parsedExamples": [{"example": "Finding the book was pure <b>serendipity</b>."}],
+                         "examples": ["Finding the book was pure <b>serendipity</b>."]},
+                        {"definition": ""}
+                    ]}
+                ],
+                "fr": [
+                    {"partOfSpeech": "Noun", "language": "French", "definitions": [
+                        {"definition": "serendipity"}
+                    ]}
+                ]
+            }"#,
+            "serendipity",
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].part_of_speech, "noun");
+        assert_eq!(
+            rows[0].definition,
+            "(uncountable) An unsought, unintended, and unexpected discovery."
+        );
+        assert_eq!(rows[0].examples, ["Finding the book was pure serendipity."]);
+        assert_eq!(
+            rows[0].source_url.as_deref(),
+            Some("https://en.wiktionary.org/wiki/serendipity")
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use reqwest::Url;
+use std::sync::LazyLock;
+
+use crate::engines::{
+    EngineError, EngineInfo, SearchEngine, SerpPage, SerpSchema, new_rand_client,
+    parse_page_blocking,
+};
+
+const SEARCH_URL: &str = "https://search.yahoo.com/search";
+
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new("#web ol > li div.algo", "h3.title", "h3.title a")
+        .container("#web")
+        .description(".compText p, .compText")
+        .next_page("a.next")
+});
+
+#[derive(Clone)]
+pub struct Yahoo;
+
+impl EngineInfo for Yahoo {
+    fn name(&self) -> &'static str {
+        "Yahoo"
+    }
+}
+
+#[async_trait]
+impl SearchEngine for Yahoo {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let mut request = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[("p", query)]);
+        if let Some(start) = page {
+            request = request.query(&[("b", start)]);
+        }
+
+        let html = request
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_page_blocking(html, parse_response).await
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_response(body)
+    }
+}
+
+pub fn parse_response(html: &str) -> Result<SerpPage, EngineError> {
+    let mut page = SEARCH_SCHEMA.parse_results(html)?;
+    page.results = page
+        .results
+        .into_iter()
+        .filter_map(|mut r| {
+            r.url = unwrap_url(&r.url)?;
+            Some(r)
+        })
+        .collect();
+    // only the start index is needed to fetch the next page
+    page.next_page = page.next_page.as_deref().and_then(start_param);
+
+    Ok(page)
+}
+
+/// Result links go through `r.search.yahoo.com`, with the target url
+/// percent encoded in the `RU=` path segment. Ads and other links back into
+/// Yahoo are dropped.
+fn unwrap_url(href: &str) -> Option<String> {
+    let url = Url::parse(SEARCH_URL).ok()?.join(href).ok()?;
+    let host = url.host_str()?;
+    if host == "r.search.yahoo.com" {
+        let target = url.path().split('/').find_map(|s| s.strip_prefix("RU="))?;
+        return url::form_urlencoded::parse(format!("u={target}").as_bytes())
+            .next()
+            .map(|(_, v)| v.into_owned())
+            .filter(|v| v.starts_with("http"));
+    }
+    if host.ends_with("yahoo.com") {
+        return None;
+    }
+    Some(url.into())
+}
+
+/// `b` query parameter of the next page link.
+fn start_param(href: &str) -> Option<String> {
+    let url = Url::parse(SEARCH_URL).ok()?.join(href).ok()?;
+    url.query_pairs()
+        .find(|(k, _)| k == "b")
+        .map(|(_, v)| v.into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_response, unwrap_url};
+
+    #[test]
+    fn test_unwrap_url() {
+        assert_eq!(
+            unwrap_url(
+                "https://r.search.yahoo.com/_ylt=AwrFG/RV=2/RE=1700000000/RO=10/RU=https%3a%2f%2fwww.rust-lang.org%2flearn%3fx%3d1/RK=2/RS=abc-"
+            )
+            .as_deref(),
+            Some("https://www.rust-lang.org/learn?x=1")
+        );
+        assert_eq!(
+            unwrap_url("https://doc.rust-lang.org/book/").as_deref(),
+            Some("https://doc.rust-lang.org/book/")
+        );
+        assert_eq!(
+            unwrap_url("https://r.search.yahoo.com/cbclk2/dWU9/RS=1"),
+            None
+        );
+        assert_eq!(
+            unwrap_url("https://news.search.yahoo.com/search?p=rust"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let html = r#"
+            <div id="web"><ol class="searchCenterMiddle">
+              <li><div class="dd algo algo-sr">
+                <div class="compTitle"><h3 class="title"><a href="https://r.search.yahoo.com/_ylt=A/RV=2/RE=1/RO=10/RU=https%3a%2f%2fwww.rust-lang.org%2f/RK=2/RS=x-">Rust</a></h3></div>
+                <div class="compText"><p>A language empowering everyone.</p></div>
+              </div></li>
+              <li><div class="dd algo algo-sr">
+                <div class="compTitle"><h3 class="title"><a href="https://r.search.yahoo.com/cbclk2/ad/RS=1">Sponsored</a></h3></div>
+              </div></li>
+            </ol></div>
+            <div class="compPagination"><a class="next" href="https://search.yahoo.com/search?p=rust&amp;b=8&amp;pz=7">Next</a></div>
+        "#;
+
+        let page = parse_response(html).unwrap();
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(page.results[0].title, "Rust");
+        assert_eq!(
+            page.results[0].description,
+            "A language empowering everyone."
+        );
+        assert_eq!(page.next_page.as_deref(), Some("8"));
+    }
+}
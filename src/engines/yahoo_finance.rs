@@ -0,0 +1,164 @@
+use async_trait::async_trait;
+use reqwest::{StatusCode, Url};
+use serde::Deserialize;
+
+use crate::{
+    cache::QuoteRow,
+    engines::{EngineError, EngineInfo, VerticalEngine, VerticalPage, new_rand_client, parse_json},
+};
+
+const CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart/";
+
+/// Stocks, funds and crypto pairs such as `BTC-USD`, through the keyless
+/// chart API behind Yahoo Finance. Prices of stocks are delayed by up to 15
+/// minutes, depending on the exchange.
+#[derive(Clone)]
+pub struct YahooFinance;
+
+impl EngineInfo for YahooFinance {
+    fn name(&self) -> &'static str {
+        "Yahoo Finance"
+    }
+}
+
+#[derive(Deserialize)]
+struct ChartResponse {
+    chart: Chart,
+}
+
+#[derive(Deserialize)]
+struct Chart {
+    /// `null` for unknown symbols
+    #[serde(default)]
+    result: Option<Vec<ChartResult>>,
+}
+
+#[derive(Deserialize)]
+struct ChartResult {
+    meta: Meta,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Meta {
+    symbol: String,
+    #[serde(default)]
+    currency: Option<String>,
+    #[serde(default)]
+    regular_market_price: Option<f64>,
+    /// Unix timestamp of the last trade
+    #[serde(default)]
+    regular_market_time: Option<i64>,
+    #[serde(default)]
+    previous_close: Option<f64>,
+    /// Close before the charted range, the previous close for a 1d range
+    #[serde(default)]
+    chart_previous_close: Option<f64>,
+    #[serde(default)]
+    long_name: Option<String>,
+    #[serde(default)]
+    short_name: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<QuoteRow> for YahooFinance {
+    async fn search_page(
+        &self,
+        symbol: &str,
+        _page: Option<&str>,
+    ) -> Result<VerticalPage<QuoteRow>, EngineError> {
+        let mut url = Url::parse(CHART_URL).unwrap();
+        url.path_segments_mut().unwrap().pop_if_empty().push(symbol);
+
+        let resp = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(url)
+            .query(&[("range", "1d"), ("interval", "1d")])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        // unknown symbols are a 404 with a "No data found" error
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(VerticalPage::default());
+        }
+        let body = resp
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body).map(|quote| Vec::from_iter(quote).into())
+    }
+}
+
+fn parse_response(body: &str) -> Result<Option<QuoteRow>, EngineError> {
+    let response: ChartResponse = parse_json(body)?;
+    let Some(meta) = response
+        .chart
+        .result
+        .and_then(|results| results.into_iter().next())
+        .map(|result| result.meta)
+    else {
+        return Ok(None);
+    };
+    let Some(price) = meta.regular_market_price else {
+        return Ok(None);
+    };
+
+    let previous = meta.previous_close.or(meta.chart_previous_close);
+    let change = previous.map(|previous| price - previous);
+    let change_percent = previous
+        .filter(|previous| *previous != 0.0)
+        .map(|previous| (price - previous) / previous * 100.0);
+
+    Ok(Some(QuoteRow {
+        symbol: meta.symbol,
+        name: meta.long_name.or(meta.short_name),
+        price,
+        change,
+        change_percent,
+        currency: meta.currency,
+        market_time: meta
+            .regular_market_time
+            .and_then(|time| chrono::DateTime::from_timestamp(time, 0))
+            .map(|time| time.to_rfc3339()),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let quote = parse_response(
+            r#"{"chart": {"result": [{
+                "meta": {"currency": "USD", "symbol": "AAPL", "exchangeName": "NMS",
+                         "regularMarketPrice": 192.0, "regularMarketTime": 1700000000,
+                         "chartPreviousClose": 190.0, "longName": "Apple Inc.", "shortName": "Apple Inc."},
+                "timestamp": [1700000000], "indicators": {"quote": [{}]}
+            }], "error": null}}"#,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(quote.symbol, "AAPL");
+        assert_eq!(quote.name.as_deref(), Some("Apple Inc."));
+        assert_eq!(quote.price, 192.0);
+        assert_eq!(quote.change, Some(2.0));
+        assert!((quote.change_percent.unwrap() - 1.0526).abs() < 0.001);
+        assert_eq!(quote.currency.as_deref(), Some("USD"));
+        assert_eq!(
+            quote.market_time.as_deref(),
+            Some("2023-11-14T22:13:20+00:00")
+        );
+
+        let unknown = parse_response(
+            r#"{"chart": {"result": null, "error": {"code": "Not Found", "description": "No data found, symbol may be delisted"}}}"#,
+        )
+        .unwrap();
+        assert!(unknown.is_none());
+    }
+}
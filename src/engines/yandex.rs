@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use reqwest::Url;
+use std::sync::LazyLock;
+
+use crate::engines::{
+    EngineError, EngineInfo, FileType, SearchEngine, SerpPage, SerpSchema, new_rand_client,
+    parse_page_blocking,
+};
+
+const SEARCH_URL: &str = "https://yandex.com/search/";
+
+static SEARCH_SCHEMA: LazyLock<SerpSchema> = LazyLock::new(|| {
+    SerpSchema::new(
+        "#search-result > li.serp-item:not([data-fast-name])",
+        ".OrganicTitle-LinkText, h2",
+        "a.OrganicTitle-Link, a.Link",
+    )
+    .container(".main__content, #search-result")
+    .description(".OrganicTextContentSpan, .TextContainer, .text-container")
+    .next_page(".Pager-Item_type_next, a.pager__item_kind_next")
+});
+
+#[derive(Clone)]
+pub struct Yandex;
+
+impl EngineInfo for Yandex {
+    fn name(&self) -> &'static str {
+        "Yandex"
+    }
+}
+
+#[async_trait]
+impl SearchEngine for Yandex {
+    async fn search_results(
+        &self,
+        query: &str,
+        page: Option<&str>,
+    ) -> Result<SerpPage, EngineError> {
+        let resp = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(SEARCH_URL)
+            .query(&[("text", query), ("p", page.unwrap_or("0"))])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        // suspected bots are redirected to /showcaptcha
+        let redirected = resp.url().path().contains("captcha");
+        let html = resp.text().await.map_err(EngineError::ReqwestError)?;
+        if redirected || is_captcha(&html) {
+            return Err(EngineError::Blocked("Yandex served a captcha".to_string()));
+        }
+
+        parse_page_blocking(html, parse_response).await
+    }
+
+    fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        parse_response(body)
+    }
+
+    fn file_type_query(&self, query: &str, file_type: FileType) -> Option<String> {
+        Some(format!("{query} mime:{}", file_type.extension()))
+    }
+}
+
+/// Yandex's SmartCaptcha and older checkbox captcha pages
+fn is_captcha(html: &str) -> bool {
+    html.contains("showcaptcha")
+        || html.contains("CheckboxCaptcha")
+        || html.contains("smartcaptcha")
+}
+
+pub fn parse_response(html: &str) -> Result<SerpPage, EngineError> {
+    let mut page = SEARCH_SCHEMA.parse_results(html)?;
+    // ads are served through yabs.yandex redirects
+    page.results.retain(|r| !r.url.contains("yabs.yandex"));
+    // only the page number is needed to fetch the next page
+    page.next_page = page.next_page.as_deref().and_then(page_param);
+
+    Ok(page)
+}
+
+/// `p` query parameter of a pagination link.
+fn page_param(href: &str) -> Option<String> {
+    let url = Url::parse(SEARCH_URL).ok()?.join(href).ok()?;
+    url.query_pairs()
+        .find(|(k, _)| k == "p")
+        .map(|(_, v)| v.into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_captcha, parse_response};
+
+    #[test]
+    fn test_parse_response() {
+        let html = r#"
+            <ul id="search-result">
+              <li class="serp-item">
+                <a class="OrganicTitle-Link" href="https://www.rust-lang.org/"><span class="OrganicTitle-LinkText">Rust</span></a>
+                <span class="OrganicTextContentSpan">A language empowering everyone.</span>
+              </li>
+              <li class="serp-item" data-fast-name="images"><a class="Link" href="https://yandex.com/images/">Images</a></li>
+              <li class="serp-item">
+                <a class="OrganicTitle-Link" href="https://yabs.yandex.com/count/abc"><span class="OrganicTitle-LinkText">Ad</span></a>
+              </li>
+            </ul>
+            <a class="Pager-Item Pager-Item_type_next" href="/search/?text=rust&amp;p=1">next</a>
+        "#;
+
+        let page = parse_response(html).unwrap();
+        assert_eq!(page.results.len(), 1);
+        assert_eq!(page.results[0].url, "https://www.rust-lang.org/");
+        assert_eq!(page.results[0].title, "Rust");
+        assert_eq!(
+            page.results[0].description,
+            "A language empowering everyone."
+        );
+        assert_eq!(page.next_page.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_is_captcha() {
+        assert!(is_captcha(
+            r#"<form action="/checkcaptcha"><div class="CheckboxCaptcha">"#
+        ));
+        assert!(!is_captcha(r#"<ul id="search-result">"#));
+    }
+}
@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+use scraper::Html;
+use serde::Deserialize;
+
+use crate::{
+    cache::PaperRow,
+    engines::{EngineError, EngineInfo, PaperPage, VerticalEngine, new_rand_client, parse_json},
+};
+
+const RECORDS_URL: &str = "https://zenodo.org/api/records";
+const SIZE: u32 = 20;
+
+/// Research outputs deposited on Zenodo, through its records API. Besides
+/// papers and preprints these include datasets and software releases.
+#[derive(Clone)]
+pub struct Zenodo;
+
+impl EngineInfo for Zenodo {
+    fn name(&self) -> &'static str {
+        "Zenodo"
+    }
+}
+
+#[derive(Deserialize)]
+struct RecordsResponse {
+    hits: Hits,
+}
+
+#[derive(Deserialize)]
+struct Hits {
+    #[serde(default)]
+    hits: Vec<Record>,
+    #[serde(default)]
+    total: u32,
+}
+
+#[derive(Deserialize)]
+struct Record {
+    #[serde(default)]
+    doi: Option<String>,
+    links: RecordLinks,
+    metadata: Metadata,
+}
+
+#[derive(Deserialize)]
+struct RecordLinks {
+    self_html: String,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    title: String,
+    #[serde(default)]
+    creators: Vec<Creator>,
+    /// `YYYY-MM-DD`
+    #[serde(default)]
+    publication_date: Option<String>,
+    /// HTML
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    resource_type: Option<ResourceType>,
+}
+
+#[derive(Deserialize)]
+struct Creator {
+    /// "Family, Given" for people
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct ResourceType {
+    /// e.g. "dataset", "software" or "publication"
+    #[serde(rename = "type")]
+    kind: String,
+    /// Narrows publications down, e.g. "preprint" or "article"
+    #[serde(default)]
+    subtype: Option<String>,
+}
+
+#[async_trait]
+impl VerticalEngine<PaperRow> for Zenodo {
+    async fn search_page(&self, query: &str, page: Option<&str>) -> Result<PaperPage, EngineError> {
+        // Zenodo pages count from 1
+        let page: u32 = page.and_then(|p| p.parse().ok()).unwrap_or(1);
+
+        let body = new_rand_client()
+            .map_err(EngineError::ReqwestError)?
+            .get(RECORDS_URL)
+            .query(&[
+                ("q", query),
+                ("page", &page.to_string()),
+                ("size", &SIZE.to_string()),
+            ])
+            .send()
+            .await
+            .map_err(EngineError::ReqwestError)?
+            .error_for_status()
+            .map_err(EngineError::ReqwestError)?
+            .text()
+            .await
+            .map_err(EngineError::ReqwestError)?;
+
+        parse_response(&body, page)
+    }
+}
+
+fn parse_response(body: &str, page: u32) -> Result<PaperPage, EngineError> {
+    let response: RecordsResponse = parse_json(body)?;
+
+    let papers = response
+        .hits
+        .hits
+        .into_iter()
+        .map(|record| {
+            let metadata = record.metadata;
+            PaperRow {
+                url: record.links.self_html,
+                title: metadata.title.trim().to_string(),
+                authors: metadata
+                    .creators
+                    .into_iter()
+                    .map(|creator| match creator.name.split_once(", ") {
+                        Some((family, given)) => format!("{given} {family}"),
+                        None => creator.name,
+                    })
+                    .collect(),
+                year: metadata
+                    .publication_date
+                    .and_then(|date| date.get(..4)?.parse().ok()),
+                doi: record.doi.filter(|doi| !doi.is_empty()),
+                abstract_text: metadata.description.and_then(|d| html_text(&d)),
+                kind: metadata
+                    .resource_type
+                    .map(|resource| resource.subtype.unwrap_or(resource.kind)),
+            }
+        })
+        .collect();
+
+    Ok(PaperPage {
+        rows: papers,
+        next_page: (page * SIZE < response.hits.total).then(|| (page + 1).to_string()),
+    })
+}
+
+/// Text of an HTML fragment, with its whitespace collapsed.
+fn html_text(html: &str) -> Option<String> {
+    let text: String = Html::parse_fragment(html).root_element().text().collect();
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_response;
+
+    #[test]
+    fn test_parse_response() {
+        let page = parse_response(
+            r#"{
+                "hits": {"hits": [
+                    {"id": 1234567, "doi": "10.5281/zenodo.1234567",
+                     "links": {"self": "https://zenodo.org/api/records/1234567",
+                               "self_html": "https://zenodo.org/records/1234567"},
+                     "metadata": {"title": "Global river discharge dataset ",
+                                  "creators": [{"name": "Doe, Jane", "affiliation": "ETH"},
+                                               {"name": "Hydrology Lab"}],
+                                  "publication_date": "2022-03-14",
+                                  "description": "<p>Daily discharge for <strong>3000</strong> rivers.</p>",
+                                  "resource_type": {"title": "Dataset", "type": "dataset"}}},
+                    {"id": 7654321, "doi": "10.5281/zenodo.7654321",
+                     "links": {"self_html": "https://zenodo.org/records/7654321"},
+                     "metadata": {"title": "River models", "publication_date": "2023-01-01",
+                                  "resource_type": {"type": "publication", "subtype": "preprint"}}}
+                ], "total": 45},
+                "links": {"self": "https://zenodo.org/api/records?page=2&size=20&q=river"}
+            }"#,
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(page.rows.len(), 2);
+        let dataset = &page.rows[0];
+        assert_eq!(dataset.url, "https://zenodo.org/records/1234567");
+        assert_eq!(dataset.title, "Global river discharge dataset");
+        assert_eq!(dataset.authors, ["Jane Doe", "Hydrology Lab"]);
+        assert_eq!(dataset.year, Some(2022));
+        assert_eq!(dataset.doi.as_deref(), Some("10.5281/zenodo.1234567"));
+        assert_eq!(
+            dataset.abstract_text.as_deref(),
+            Some("Daily discharge for 3000 rivers.")
+        );
+        assert_eq!(dataset.kind.as_deref(), Some("dataset"));
+        assert_eq!(page.rows[1].kind.as_deref(), Some("preprint"));
+        // 40 of 45 records seen
+        assert_eq!(page.next_page.as_deref(), Some("3"));
+    }
+}
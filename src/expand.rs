@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use crate::{SearchResult, config};
+
+/// Groups of interchangeable words used on top of the configured ones.
+const SYNONYMS: &[&[&str]] = &[
+    &["car", "automobile", "auto"],
+    &["movie", "film"],
+    &["picture", "photo", "image"],
+    &["buy", "purchase"],
+    &["cheap", "inexpensive", "affordable"],
+    &["fix", "repair"],
+    &["tutorial", "guide", "howto"],
+    &["laptop", "notebook"],
+    &["error", "exception"],
+];
+
+/// Crude suffix stripping so "running", "runs" and "run" compare equal.
+pub fn stem(word: &str) -> String {
+    let word = word.to_lowercase();
+    for (suffix, replacement) in [
+        ("ies", "y"),
+        ("sses", "ss"),
+        ("ing", ""),
+        ("ed", ""),
+        ("es", ""),
+        ("s", ""),
+        ("ly", ""),
+    ] {
+        if let Some(stem) = word.strip_suffix(suffix)
+            && stem.chars().count() >= 3
+            && !(suffix == "s" && stem.ends_with('s'))
+            // boxes -> box but images -> image
+            && (suffix != "es" || stem.ends_with(['s', 'x', 'z', 'h']))
+        {
+            let mut stem = format!("{stem}{replacement}");
+            // running -> runn -> run
+            let mut chars = stem.chars().rev();
+            if matches!(suffix, "ing" | "ed")
+                && let (Some(a), Some(b)) = (chars.next(), chars.next())
+                && a == b
+                && !matches!(a, 'l' | 's' | 'z')
+            {
+                stem.pop();
+            }
+            return stem;
+        }
+    }
+
+    word
+}
+
+/// Stems of every word of `text`.
+fn stems(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(stem)
+        .collect()
+}
+
+/// Each query word as the set of stems that count as a match for it: its
+/// own and those of its synonyms.
+pub fn expand(query: &str, synonyms: &[Vec<String>]) -> Vec<HashSet<String>> {
+    let builtin = SYNONYMS
+        .iter()
+        .map(|group| group.iter().map(|w| w.to_string()).collect::<Vec<_>>());
+    let groups: Vec<Vec<String>> = builtin.chain(synonyms.iter().cloned()).collect();
+
+    query
+        .split_whitespace()
+        .map(|word| {
+            let word = stem(word);
+            let mut terms: HashSet<String> = groups
+                .iter()
+                .filter(|group| group.iter().any(|w| stem(w) == word))
+                .flatten()
+                .map(|w| stem(w))
+                .collect();
+            terms.insert(word);
+            terms
+        })
+        .collect()
+}
+
+/// Moves results whose title and description cover more of the expanded
+/// query words up, keeping the existing order among equals.
+pub(crate) fn rerank(mut results: Vec<SearchResult>, query: &str) -> Vec<SearchResult> {
+    let terms = expand(query, &config::get().synonyms);
+
+    results.sort_by_cached_key(|r| {
+        let words = stems(&format!("{} {}", r.title, r.description));
+        std::cmp::Reverse(
+            terms
+                .iter()
+                .filter(|term| !term.is_disjoint(&words))
+                .count(),
+        )
+    });
+    results
+}
+
+#[cfg(test)]
+mod test {
+    use super::{expand, stem};
+
+    #[test]
+    fn test_stem() {
+        assert_eq!(stem("Running"), "run");
+        assert_eq!(stem("runs"), "run");
+        assert_eq!(stem("libraries"), "library");
+        assert_eq!(stem("classes"), "class");
+        assert_eq!(stem("boxes"), "box");
+        assert_eq!(stem("images"), "image");
+        assert_eq!(stem("glass"), "glass");
+        assert_eq!(stem("filled"), "fill");
+        assert_eq!(stem("is"), "is");
+    }
+
+    #[test]
+    fn test_expand() {
+        let custom = vec![vec!["rust".to_string(), "rustlang".to_string()]];
+        let terms = expand("cheap films rust", &custom);
+
+        assert_eq!(terms.len(), 3);
+        assert!(terms[0].contains("affordable"));
+        assert!(terms[1].contains("movie"));
+        assert!(terms[1].contains("film"));
+        assert!(terms[2].contains("rustlang"));
+    }
+}
@@ -1,18 +1,90 @@
 #![allow(async_fn_in_trait)]
 
 use serde::Serialize;
+use sha2::{Digest, Sha256};
 use sqlx::SqlitePool;
-use std::{cmp::Ordering, collections::BTreeMap, pin::Pin, time::Duration};
-use tokio::{sync::OnceCell, task::JoinSet, time::timeout};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    pin::Pin,
+    str::FromStr,
+    sync::Mutex,
+};
+use tokio::{
+    sync::OnceCell,
+    task::JoinSet,
+    time::{self, timeout},
+};
 
-use crate::engines::{Brave, DuckDuckGo, EngineError, EngineInfo, ImageEngine, SearchEngine};
+use crate::{
+    config::{Config, Ranking, SafeSearch},
+    engines::{
+        Arxiv, Baidu, Bandcamp, Bing, BingApi, Biorxiv, Brave, BraveApi, CoinGecko, CratesIo,
+        Crossref, DictionaryApi, DuckDuckGo, Ebay, EngineError, EngineInfo, FDroid, FileType,
+        Flathub, Flickr, FourGet, Google, GoogleCse, GooglePatents, ImageEngine, ImageOptions,
+        ImagePage, Itunes, Kagi, Lemmy, LibreY, Mastodon, MusicBrainz, Naver, Nominatim, Npm,
+        OpenLibrary, Openverse, Pexels, Photon, PodcastIndex, Presearch, Pypi, Qwant, SearchEngine,
+        SearxNG, SemanticScholar, SerpPage, Startpage, Torznab, Unsplash, VerticalEngine,
+        VerticalPage, VerticalRow, Whoogle, Wiby, WikimediaCommons, Wikipedia, Wiktionary, Yahoo,
+        YahooFinance, Yandex, Zenodo, current_circuit, is_blocked_host, new_circuit, with_circuit,
+    },
+};
 
+pub mod alerts;
+pub mod answers;
+mod budget;
 mod cache;
+pub mod config;
 pub mod engines;
+pub mod expand;
+#[cfg(feature = "memory-cache")]
+pub mod memory_cache;
+pub mod metrics;
+mod obfuscation;
+pub mod proxy;
+mod quotes;
+mod recipes;
+pub mod rewrite;
+#[cfg(feature = "screenshot")]
+pub mod screenshot;
+mod selection;
+pub mod suggest;
+pub mod summary;
+#[cfg(any(feature = "blurhash", feature = "phash"))]
+pub mod thumbnail;
 
-const ENGINE_TIMEOUT: u64 = 3; // seconds
+pub use cache::{
+    CacheBackend, CacheError, CacheStats, CachedRow, EngineStatsRow, Purge, QueryRow, ResultRow,
+    ResultRuleRow, Rows, SqliteCache, set_backend,
+};
+
+const RRF_K: f64 = 60.0; // reciprocal rank fusion damping constant
+const RESULTS_PER_PAGE: usize = 10;
+const IMAGES_PER_PAGE: usize = 50;
+const NEWS_PER_PAGE: usize = 30;
+const VIDEOS_PER_PAGE: usize = 30;
+const PRODUCTS_PER_PAGE: usize = 50;
+const PAPERS_PER_PAGE: usize = 20;
+const FILES_PER_PAGE: usize = 50;
+const BOOKS_PER_PAGE: usize = 20;
+const PODCASTS_PER_PAGE: usize = 50;
+const SOCIAL_PER_PAGE: usize = 20;
+const PACKAGES_PER_PAGE: usize = 20;
+const APPS_PER_PAGE: usize = 20;
+const PATENTS_PER_PAGE: usize = 20;
+const MUSIC_PER_PAGE: usize = 20;
+const RECIPE_PAGES: usize = 10; // top web results read for a recipe
+/// Engine name recipes are cached under, they come from the result pages
+const RECIPE_SOURCE: &str = "schema.org";
+const MAX_DEFINITION_WORDS: usize = 3; // longer "define" queries are phrases, not words
+const MAX_PAGES_PER_FETCH: usize = 3; // engine pages fetched to fill one window
+/// Max bits two images' perceptual hashes may differ by to be the same picture
+const PHASH_THRESHOLD: u32 = 6;
 
 static SQLPOOL: OnceCell<SqlitePool> = OnceCell::const_new();
+/// Cached queries being refetched in the background
+static REFRESHING: Mutex<BTreeSet<i64>> = Mutex::new(BTreeSet::new());
 
 async fn get_db() -> &'static SqlitePool {
     SQLPOOL
@@ -20,6 +92,28 @@ async fn get_db() -> &'static SqlitePool {
         .await
 }
 
+/// Index of the first item of the zero based `page`, saturating so a page far
+/// past the end is just empty.
+fn page_start(page: usize, per_page: usize) -> usize {
+    page.saturating_mul(per_page)
+}
+
+/// The query as stored in the cache, hashed when `hash_queries` is enabled.
+fn cache_key(query: &str) -> Cow<'_, str> {
+    if config::get().hash_queries {
+        Cow::Owned(format!("{:x}", Sha256::digest(query.as_bytes())))
+    } else {
+        Cow::Borrowed(query)
+    }
+}
+
+/// Cache key of a vertical's search of `query`, the query as [`cache_key`]
+/// stores it then `suffix`. The suffix stays out of the hash so purging the
+/// query finds every vertical.
+fn vertical_key(query: &str, suffix: &str) -> String {
+    format!("{}\u{1f}{suffix}", cache_key(query))
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SearchResult {
     url: String,
@@ -27,6 +121,43 @@ pub struct SearchResult {
     description: String,
     engines: Vec<String>,
     cached: bool,
+    /// Data url of a jpeg preview of the page (`screenshot` feature)
+    screenshot: Option<String>,
+    published_at: Option<String>,
+    language: Option<String>,
+    extra_snippets: Vec<String>,
+}
+
+impl SearchResult {
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn engines(&self) -> &[String] {
+        &self.engines
+    }
+
+    /// When the page was published, as shown by the engine
+    pub fn published_at(&self) -> Option<&str> {
+        self.published_at.as_deref()
+    }
+
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Further excerpts of the page, for engines that provide them
+    pub fn extra_snippets(&self) -> &[String] {
+        &self.extra_snippets
+    }
 }
 
 impl PartialEq for SearchResult {
@@ -42,260 +173,894 @@ impl PartialOrd for SearchResult {
 }
 
 #[derive(Debug, Clone, Serialize)]
-pub struct ImageResult {
+pub struct NewsResult {
     url: String,
     title: String,
+    source: String,
+    published_at: Option<String>,
     engines: Vec<String>,
     cached: bool,
 }
 
-impl PartialEq for ImageResult {
-    fn eq(&self, other: &Self) -> bool {
-        self.url == other.url
+impl NewsResult {
+    fn from_row(row: cache::NewsRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            source: row.source,
+            published_at: row.published_at,
+            engines: vec![engine.to_string()],
+            cached,
+        }
     }
 }
 
-impl PartialOrd for ImageResult {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.url.cmp(&other.url))
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoResult {
+    url: String,
+    title: String,
+    thumbnail: Option<String>,
+    duration: Option<String>,
+    channel: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
+
+impl VideoResult {
+    fn from_row(row: cache::VideoRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            thumbnail: row.thumbnail,
+            duration: row.duration,
+            channel: row.channel,
+            engines: vec![engine.to_string()],
+            cached,
+        }
     }
 }
 
-#[derive(Debug)]
-pub enum FetchError {
-    Sqlx(sqlx::Error),
-    Engine(EngineError),
-    AllEnginesFailed,
-    Timeouts,
+#[derive(Debug, Clone, Serialize)]
+pub struct MusicResult {
+    url: String,
+    title: String,
+    artist: Option<String>,
+    kind: String,
+    year: Option<i32>,
+    cover: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
 }
 
-#[derive(Clone)]
-pub enum SearchEngines {
-    Brave,
-    DuckDuckGo,
+impl MusicResult {
+    fn from_row(row: cache::MusicRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            artist: row.artist,
+            kind: row.kind,
+            year: row.year,
+            cover: row.cover,
+            engines: vec![engine.to_string()],
+            cached,
+        }
+    }
 }
 
-pub async fn search_engine_results(
-    query: String,
-    engines: Vec<SearchEngines>,
-) -> Result<Vec<SearchResult>, FetchError> {
-    let timeout_duration = Duration::from_secs(ENGINE_TIMEOUT);
+#[derive(Debug, Clone, Serialize)]
+pub struct PatentResult {
+    url: String,
+    number: String,
+    title: String,
+    assignee: Option<String>,
+    inventor: Option<String>,
+    filing_date: Option<String>,
+    snippet: Option<String>,
+    pdf: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-    let mut set = JoinSet::new();
+impl PatentResult {
+    fn from_row(row: cache::PatentRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            number: row.number,
+            title: row.title,
+            assignee: row.assignee,
+            inventor: row.inventor,
+            filing_date: row.filing_date,
+            snippet: row.snippet,
+            pdf: row.pdf,
+            engines: vec![engine.to_string()],
+            cached,
+        }
+    }
+}
 
-    for engine in engines {
-        let query = query.clone();
-        let engine = engine.clone();
-
-        // Box the future to unify types
-        let fut: Pin<Box<dyn Future<Output = Result<Vec<SearchResult>, FetchError>> + Send>> =
-            match engine {
-                SearchEngines::Brave => Box::pin(fetch_or_cache_result(Brave, query, 0, 10)),
-                SearchEngines::DuckDuckGo => {
-                    Box::pin(fetch_or_cache_result(DuckDuckGo, query, 0, 10))
-                }
-            };
+#[derive(Debug, Clone, Serialize)]
+pub struct AppResult {
+    url: String,
+    name: String,
+    store: String,
+    app_id: String,
+    summary: Option<String>,
+    icon: Option<String>,
+    install_url: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-        // Spawn the boxed future
-        set.spawn(timeout(timeout_duration, fut));
+impl AppResult {
+    fn from_row(row: cache::AppRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            name: row.name,
+            store: row.store,
+            app_id: row.app_id,
+            summary: row.summary,
+            icon: row.icon,
+            install_url: row.install_url,
+            engines: vec![engine.to_string()],
+            cached,
+        }
     }
+}
 
-    let combined = timeout(timeout_duration, set.join_all()).await;
+#[derive(Debug, Clone, Serialize)]
+pub struct PackageResult {
+    url: String,
+    name: String,
+    registry: String,
+    version: Option<String>,
+    description: Option<String>,
+    downloads: Option<i64>,
+    repository: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-    let per_engine = match combined {
-        Ok(res) => res,
-        Err(_) => {
-            return Err(FetchError::Timeouts);
+impl PackageResult {
+    fn from_row(row: cache::PackageRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            name: row.name,
+            registry: row.registry,
+            version: row.version,
+            description: row.description,
+            downloads: row.downloads,
+            repository: row.repository,
+            engines: vec![engine.to_string()],
+            cached,
         }
-    };
+    }
+}
 
-    let mut flat: Vec<SearchResult> = Vec::new();
-    let mut any_success = false;
+#[derive(Debug, Clone, Serialize)]
+pub struct QuoteResult {
+    symbol: String,
+    name: Option<String>,
+    price: f64,
+    change: Option<f64>,
+    change_percent: Option<f64>,
+    currency: Option<String>,
+    market_time: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-    for engine_result in per_engine {
-        match engine_result {
-            Ok(rows) => {
-                any_success = true;
-                flat.append(&mut rows.unwrap());
-            }
-            Err(e) => {
-                eprintln!("Engine failed: {:?}", e);
-            }
+impl QuoteResult {
+    fn from_row(row: cache::QuoteRow, engine: &str, cached: bool) -> Self {
+        Self {
+            symbol: row.symbol,
+            name: row.name,
+            price: row.price,
+            change: row.change,
+            change_percent: row.change_percent,
+            currency: row.currency,
+            market_time: row.market_time,
+            engines: vec![engine.to_string()],
+            cached,
         }
     }
+}
 
-    if !any_success {
-        return Err(FetchError::AllEnginesFailed);
-    }
+#[derive(Debug, Clone, Serialize)]
+pub struct DefinitionResult {
+    word: String,
+    part_of_speech: String,
+    definition: String,
+    examples: Vec<String>,
+    phonetic: Option<String>,
+    source_url: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-    let merged = merge_results(flat);
-    let sorted = sort_results(merged, &query);
-    Ok(sorted)
+impl DefinitionResult {
+    fn from_row(row: cache::DefinitionRow, engine: &str, cached: bool) -> Self {
+        Self {
+            word: row.word,
+            part_of_speech: row.part_of_speech,
+            definition: row.definition,
+            examples: row.examples,
+            phonetic: row.phonetic,
+            source_url: row.source_url,
+            engines: vec![engine.to_string()],
+            cached,
+        }
+    }
 }
 
-fn merge_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
-    let mut map: BTreeMap<String, SearchResult> = BTreeMap::new();
+#[derive(Debug, Clone, Serialize)]
+pub struct SocialResult {
+    url: String,
+    kind: String,
+    title: Option<String>,
+    content: Option<String>,
+    author: Option<String>,
+    community: Option<String>,
+    score: Option<i64>,
+    published_at: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-    for row in results {
-        let key = row.url.clone();
+impl SocialResult {
+    fn from_row(row: cache::SocialRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            kind: row.kind,
+            title: row.title,
+            content: row.content,
+            author: row.author,
+            community: row.community,
+            score: row.score,
+            published_at: row.published_at,
+            engines: vec![engine.to_string()],
+            cached,
+        }
+    }
+}
 
-        map.entry(key)
-            .and_modify(|existing| {
-                existing.engines.extend(row.engines.clone());
+#[derive(Debug, Clone, Serialize)]
+pub struct RecipeResult {
+    url: String,
+    title: String,
+    image: Option<String>,
+    ingredients: Vec<String>,
+    /// Times in minutes
+    prep_time: Option<u32>,
+    cook_time: Option<u32>,
+    total_time: Option<u32>,
+    yields: Option<String>,
+    rating: Option<f64>,
+    rating_count: Option<u32>,
+    cached: bool,
+}
 
-                if existing.description.is_empty() {
-                    existing.description = row.description.clone();
-                }
-                if existing.title.is_empty() {
-                    existing.title = row.title.clone();
-                }
-            })
-            .or_insert(row);
+impl RecipeResult {
+    fn from_row(row: cache::RecipeRow, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            image: row.image,
+            ingredients: row.ingredients,
+            prep_time: row.prep_time,
+            cook_time: row.cook_time,
+            total_time: row.total_time,
+            yields: row.yields,
+            rating: row.rating,
+            rating_count: row.rating_count,
+            cached,
+        }
     }
+}
 
-    map.into_values().collect()
+#[derive(Debug, Clone, Serialize)]
+pub struct PodcastResult {
+    url: String,
+    title: String,
+    feed_url: String,
+    author: Option<String>,
+    artwork: Option<String>,
+    description: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
 }
 
-pub fn sort_results(mut results: Vec<SearchResult>, query: &str) -> Vec<SearchResult> {
-    let stop = ["the", "and", "or", "of", "for", "in", "on", "at"];
-    let words: Vec<String> = query
-        .split_whitespace()
-        .filter(|w| !stop.contains(&w.to_lowercase().as_str()))
-        .map(str::to_lowercase)
-        .collect();
+impl PodcastResult {
+    fn from_row(row: cache::PodcastRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            feed_url: row.feed_url,
+            author: row.author,
+            artwork: row.artwork,
+            description: row.description,
+            engines: vec![engine.to_string()],
+            cached,
+        }
+    }
+}
 
-    fn score(url: &str, words: &[String]) -> u8 {
-        let dom = url
-            .trim_start_matches("http://")
-            .trim_start_matches("https://")
-            .split('/')
-            .next()
-            .unwrap_or("")
-            .to_lowercase();
+#[derive(Debug, Clone, Serialize)]
+pub struct BookResult {
+    url: String,
+    title: String,
+    authors: Vec<String>,
+    year: Option<i32>,
+    cover: Option<String>,
+    isbn: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-        let hits = words.iter().filter(|w| dom.contains(*w)).count();
-        if hits == words.len() {
-            2
-        }
-        // all words
-        else if hits > 0 {
-            1
-        }
-        // some words
-        else {
-            0
+impl BookResult {
+    fn from_row(row: cache::BookRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            authors: row.authors,
+            year: row.year,
+            cover: row.cover,
+            isbn: row.isbn,
+            engines: vec![engine.to_string()],
+            cached,
         }
     }
-
-    results.sort_by_cached_key(|r| std::cmp::Reverse(score(&r.url, &words)));
-    results
 }
 
-/// Checks the cache first; if miss, fetches from the engine and caches results.
-pub async fn fetch_or_cache_result<E>(
-    engine: E,
-    query: String,
-    start: usize,
-    count: usize,
-) -> Result<Vec<SearchResult>, FetchError>
-where
-    E: SearchEngine + EngineInfo + Send,
-{
-    let pool = get_db().await;
-    let mut search_results = Vec::new();
+#[derive(Debug, Clone, Serialize)]
+pub struct FileResult {
+    url: String,
+    title: String,
+    magnet: Option<String>,
+    download_url: Option<String>,
+    /// In bytes
+    size: Option<i64>,
+    seeders: Option<u32>,
+    leechers: Option<u32>,
+    published_at: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-    let engine_enum = engine.name();
-    let engine_id = cache::get_engine_id(pool, engine_enum)
-        .await
-        .map_err(FetchError::Sqlx)?;
+impl FileResult {
+    fn from_row(row: cache::FileRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            magnet: row.magnet,
+            download_url: row.download_url,
+            size: row.size,
+            seeders: row.seeders,
+            leechers: row.leechers,
+            published_at: row.published_at,
+            engines: vec![engine.to_string()],
+            cached,
+        }
+    }
+}
 
-    // Fetch cached results
-    let cached_rows = if let Some(query_row) = cache::get_query(pool, &query, engine_id)
-        .await
-        .map_err(FetchError::Sqlx)?
-    {
-        cache::get_results_for_query(pool, query_row.id)
-            .await
-            .map_err(FetchError::Sqlx)?
-    } else {
-        Vec::new()
-    };
+#[derive(Debug, Clone, Serialize)]
+pub struct PaperResult {
+    url: String,
+    title: String,
+    authors: Vec<String>,
+    year: Option<i32>,
+    doi: Option<String>,
+    #[serde(rename = "abstract")]
+    abstract_text: Option<String>,
+    /// e.g. "preprint" or "dataset"
+    kind: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-    let cached_count = cached_rows.len();
-    let needed_end = start + count;
+impl PaperResult {
+    fn from_row(row: cache::PaperRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            authors: row.authors,
+            year: row.year,
+            doi: row.doi,
+            abstract_text: row.abstract_text,
+            kind: row.kind,
+            engines: vec![engine.to_string()],
+            cached,
+        }
+    }
+}
 
-    let start = start.min(cached_count);
-    let end = cached_count.min(needed_end);
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductResult {
+    url: String,
+    title: String,
+    /// Decimal as written by the engine, e.g. "12.50"
+    price: Option<String>,
+    currency: Option<String>,
+    merchant: Option<String>,
+    image: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-    for cr in &cached_rows[start..end] {
-        search_results.push(SearchResult {
-            url: cr.url.clone(),
-            title: cr.title.clone(),
-            description: cr.description.clone(),
-            engines: vec![engine.name().to_string()],
-            cached: true,
-        });
+impl ProductResult {
+    fn from_row(row: cache::ProductRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            price: row.price,
+            currency: row.currency,
+            merchant: row.merchant,
+            image: row.image,
+            engines: vec![engine.to_string()],
+            cached,
+        }
     }
+}
 
-    if cached_count < needed_end {
-        let engine_results = engine
-            .search_results(&query)
-            .await
-            .map_err(FetchError::Engine)?;
-
-        let fetched_at = chrono::Utc::now().naive_utc();
-        let _query_id = cache::upsert_query_with_results(
-            pool,
-            engine_enum,
-            &query,
-            engine_results.clone(),
-            fetched_at,
-        )
-        .await
-        .map_err(FetchError::Sqlx)?;
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaceResult {
+    name: String,
+    lat: f64,
+    lon: f64,
+    address: Option<String>,
+    /// The place's page on openstreetmap.org
+    osm_url: String,
+    /// e.g. "city" or "restaurant"
+    kind: Option<String>,
+    engines: Vec<String>,
+    cached: bool,
+}
 
-        for cr in &engine_results {
-            search_results.push(SearchResult {
-                url: cr.url.clone(),
-                title: cr.title.clone(),
-                description: cr.description.clone(),
-                engines: vec![engine.name().to_string()],
-                cached: false,
-            });
+impl PlaceResult {
+    fn from_row(row: cache::PlaceRow, engine: &str, cached: bool) -> Self {
+        Self {
+            name: row.name,
+            lat: row.lat,
+            lon: row.lon,
+            address: row.address,
+            osm_url: row.osm_url,
+            kind: row.kind,
+            engines: vec![engine.to_string()],
+            cached,
         }
     }
-
-    Ok(search_results)
 }
 
-#[derive(Clone)]
-pub enum ImageEngines {
-    Brave,
+/// Merged web results plus anything worth surfacing about how they were fetched.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchResponse {
+    pub results: Vec<SearchResult>,
+    /// Largest result count estimate reported by any engine
+    pub total_results: Option<u64>,
+    /// Whether any engine has a further page of results
+    pub has_next_page: bool,
+    /// News cards embedded in the engines' web results
+    pub news: Vec<NewsResult>,
+    /// Video cards embedded in the engines' web results
+    pub videos: Vec<VideoResult>,
+    /// Failed engines and partially broken result parsing
+    pub warnings: Vec<String>,
+    /// Answer summary from the registered [`summary::Summarizer`]
+    pub summary: Option<String>,
+    /// Instant answers from the registered [`answers::AnswerProvider`]s, only
+    /// on the first page
+    pub answers: Vec<answers::Answer>,
 }
 
-pub async fn search_engine_images(
-    query: String,
-    engines: Vec<ImageEngines>,
-) -> Result<Vec<ImageResult>, FetchError> {
-    let timeout_duration = Duration::from_secs(ENGINE_TIMEOUT);
+/// Results of a vertical search plus anything worth surfacing about how they
+/// were fetched, the [`SearchResponse`] of images, news and the rest.
+#[derive(Debug, Clone, Serialize)]
+pub struct VerticalResponse<T> {
+    pub results: Vec<T>,
+    /// Failed engines and exhausted request budgets
+    pub warnings: Vec<String>,
+}
 
-    let mut set = JoinSet::new();
+// derived, it would ask for `T: Default`
+impl<T> Default for VerticalResponse<T> {
+    fn default() -> Self {
+        Self {
+            results: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
 
-    for engine in engines {
-        let query = query.clone();
-        let engine = engine.clone();
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageResult {
+    url: String,
+    title: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    thumbnail: Option<String>,
+    source_url: Option<String>,
+    license: Option<String>,
+    attribution: Option<String>,
+    blurhash: Option<String>,
+    #[serde(skip)]
+    phash: Option<u64>,
+    engines: Vec<String>,
+    cached: bool,
+}
+
+impl ImageResult {
+    fn from_row(row: cache::ImagesRow, engine: &str, cached: bool) -> Self {
+        Self {
+            url: row.url,
+            title: row.title,
+            width: row.width,
+            height: row.height,
+            thumbnail: row.thumbnail,
+            source_url: row.source_url,
+            license: row.license,
+            attribution: row.attribution,
+            blurhash: row.blurhash,
+            phash: row.phash.map(|h| h as u64),
+            engines: vec![engine.to_string()],
+            cached,
+        }
+    }
+
+    /// Folds in another result for the same image, keeping existing values.
+    fn merge(&mut self, other: ImageResult) {
+        for engine in other.engines {
+            if !self.engines.contains(&engine) {
+                self.engines.push(engine);
+            }
+        }
+
+        if self.title.is_empty() {
+            self.title = other.title;
+        }
+        self.width = self.width.or(other.width);
+        self.height = self.height.or(other.height);
+        self.thumbnail = self.thumbnail.take().or(other.thumbnail);
+        self.source_url = self.source_url.take().or(other.source_url);
+        self.license = self.license.take().or(other.license);
+        self.attribution = self.attribution.take().or(other.attribution);
+        self.blurhash = self.blurhash.take().or(other.blurhash);
+        self.phash = self.phash.or(other.phash);
+    }
+}
+
+impl PartialEq for ImageResult {
+    fn eq(&self, other: &Self) -> bool {
+        self.url == other.url
+    }
+}
+
+impl PartialOrd for ImageResult {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.url.cmp(&other.url))
+    }
+}
+
+#[derive(Debug)]
+pub enum FetchError {
+    Cache(cache::CacheError),
+    Config(config::ConfigError),
+    Engine(EngineError),
+    AllEnginesFailed,
+    Timeouts,
+    /// The engine's request budget is spent for now
+    BudgetExhausted,
+}
+
+#[derive(Debug, Clone)]
+pub enum SearchEngines {
+    Brave,
+    DuckDuckGo,
+    Startpage,
+    Qwant,
+    Google,
+    Yandex,
+    Wiby,
+    Presearch,
+    Yahoo,
+    Baidu,
+    Naver,
+    /// Wikipedia's article search, written `wikipedia` for the English
+    /// edition or `wikipedia:<language>`, e.g. `wikipedia:de`
+    Wikipedia {
+        lang: String,
+    },
+    /// A SearXNG instance, written `searxng:<base url>`
+    SearxNG {
+        base_url: String,
+    },
+    /// A Whoogle instance, written `whoogle:<base url>`
+    Whoogle {
+        base_url: String,
+    },
+    /// A LibreY or LibreX instance, written `librey:<base url>`
+    LibreY {
+        base_url: String,
+    },
+    /// A 4get instance, written `4get:<base url>`
+    FourGet {
+        base_url: String,
+    },
+    /// Brave's Search API, written `brave-api:<subscription token>`
+    BraveApi {
+        api_key: String,
+    },
+    /// Kagi's Search API, written `kagi:<api token>`
+    Kagi {
+        api_key: String,
+    },
+    /// Bing Web Search API, written `bing-api:<subscription key>`
+    BingApi {
+        api_key: String,
+    },
+    /// Google's Custom Search JSON API, written `google-cse:<api key>:<cx>`
+    GoogleCse {
+        key: String,
+        cx: String,
+    },
+}
+
+/// One engine's web search, boxed so engines of different types run side by
+/// side
+type SearchFuture = Pin<Box<dyn Future<Output = Result<SearchResponse, FetchError>> + Send>>;
+
+impl SearchEngines {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchEngines::Brave => Brave.name(),
+            SearchEngines::DuckDuckGo => DuckDuckGo.name(),
+            SearchEngines::Startpage => Startpage.name(),
+            SearchEngines::Qwant => Qwant.name(),
+            SearchEngines::Google => Google.name(),
+            SearchEngines::Yandex => Yandex.name(),
+            SearchEngines::Wiby => Wiby.name(),
+            SearchEngines::Presearch => Presearch.name(),
+            SearchEngines::Yahoo => Yahoo.name(),
+            SearchEngines::Baidu => Baidu.name(),
+            SearchEngines::Naver => Naver.name(),
+            SearchEngines::Wikipedia { .. } => Wikipedia::NAME,
+            SearchEngines::SearxNG { .. } => SearxNG::NAME,
+            SearchEngines::Whoogle { .. } => Whoogle::NAME,
+            SearchEngines::LibreY { .. } => LibreY::NAME,
+            SearchEngines::FourGet { .. } => FourGet::NAME,
+            SearchEngines::BraveApi { .. } => BraveApi::NAME,
+            SearchEngines::Kagi { .. } => Kagi::NAME,
+            SearchEngines::BingApi { .. } => BingApi::NAME,
+            SearchEngines::GoogleCse { .. } => GoogleCse::NAME,
+        }
+    }
+
+    /// [`EngineInfo::id`] of the engine.
+    pub fn id(&self) -> Cow<'static, str> {
+        match self {
+            SearchEngines::Wikipedia { lang } => match Wikipedia::new(lang) {
+                Ok(wikipedia) => wikipedia.id(),
+                Err(_) => Cow::Borrowed(Wikipedia::NAME),
+            },
+            SearchEngines::SearxNG { base_url } => SearxNG::new(base_url).id(),
+            SearchEngines::Whoogle { base_url } => Whoogle::new(base_url).id(),
+            SearchEngines::LibreY { base_url } => LibreY::new(base_url).id(),
+            SearchEngines::FourGet { base_url } => FourGet::new(base_url).id(),
+            _ => Cow::Borrowed(self.name()),
+        }
+    }
+
+    /// [`fetch_or_cache_result`] with the engine, from the `start`th result.
+    fn search(self, query: String, start: usize, options: SearchOptions) -> SearchFuture {
+        macro_rules! search {
+            ($engine:expr) => {
+                Box::pin(fetch_or_cache_result(
+                    $engine,
+                    query,
+                    start,
+                    RESULTS_PER_PAGE,
+                    options,
+                ))
+            };
+        }
+
+        match self {
+            SearchEngines::Brave => search!(Brave),
+            SearchEngines::DuckDuckGo => search!(DuckDuckGo),
+            SearchEngines::Startpage => search!(Startpage),
+            SearchEngines::Qwant => search!(Qwant),
+            SearchEngines::Google => search!(Google),
+            SearchEngines::Yandex => search!(Yandex),
+            SearchEngines::Wiby => search!(Wiby),
+            SearchEngines::Presearch => search!(Presearch),
+            SearchEngines::Yahoo => search!(Yahoo),
+            SearchEngines::Baidu => search!(Baidu),
+            SearchEngines::Naver => search!(Naver),
+            SearchEngines::Wikipedia { lang } => match Wikipedia::new(&lang) {
+                Ok(wikipedia) => search!(wikipedia),
+                Err(e) => Box::pin(async { Err(FetchError::Engine(e)) }),
+            },
+            SearchEngines::SearxNG { base_url } => search!(SearxNG::new(&base_url)),
+            SearchEngines::Whoogle { base_url } => search!(Whoogle::new(&base_url)),
+            SearchEngines::LibreY { base_url } => search!(LibreY::new(&base_url)),
+            SearchEngines::FourGet { base_url } => search!(FourGet::new(&base_url)),
+            SearchEngines::BraveApi { api_key } => search!(BraveApi::new(&api_key)),
+            SearchEngines::Kagi { api_key } => search!(Kagi::new(&api_key)),
+            SearchEngines::BingApi { api_key } => search!(BingApi::new(&api_key)),
+            SearchEngines::GoogleCse { key, cx } => search!(GoogleCse::new(&key, &cx)),
+        }
+    }
 
-        // Box the future to unify types
-        let fut: Pin<Box<dyn Future<Output = Result<Vec<ImageResult>, FetchError>> + Send>> =
-            match engine {
-                ImageEngines::Brave => Box::pin(fetch_or_cache_image(Brave, query, 0, 50)),
+    /// Parses a response archived with `archive_responses`.
+    pub(crate) fn parse_raw(&self, body: &str) -> Result<SerpPage, EngineError> {
+        match self {
+            SearchEngines::Brave => Brave.parse_raw(body),
+            SearchEngines::DuckDuckGo => DuckDuckGo.parse_raw(body),
+            SearchEngines::Startpage => Startpage.parse_raw(body),
+            SearchEngines::Qwant => Qwant.parse_raw(body),
+            SearchEngines::Google => Google.parse_raw(body),
+            SearchEngines::Yandex => Yandex.parse_raw(body),
+            SearchEngines::Wiby => Wiby.parse_raw(body),
+            SearchEngines::Presearch => Presearch.parse_raw(body),
+            SearchEngines::Yahoo => Yahoo.parse_raw(body),
+            SearchEngines::Baidu => Baidu.parse_raw(body),
+            SearchEngines::Naver => Naver.parse_raw(body),
+            SearchEngines::Wikipedia { lang } => Wikipedia::new(lang)?.parse_raw(body),
+            SearchEngines::SearxNG { base_url } => SearxNG::new(base_url).parse_raw(body),
+            SearchEngines::Whoogle { base_url } => Whoogle::new(base_url).parse_raw(body),
+            SearchEngines::LibreY { base_url } => LibreY::new(base_url).parse_raw(body),
+            SearchEngines::FourGet { base_url } => FourGet::new(base_url).parse_raw(body),
+            SearchEngines::BraveApi { api_key } => BraveApi::new(api_key).parse_raw(body),
+            SearchEngines::Kagi { api_key } => Kagi::new(api_key).parse_raw(body),
+            SearchEngines::BingApi { api_key } => BingApi::new(api_key).parse_raw(body),
+            SearchEngines::GoogleCse { key, cx } => GoogleCse::new(key, cx).parse_raw(body),
+        }
+    }
+}
+
+impl FromStr for SearchEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // engines backed by a user's own instance or account carry its url or key
+        if let Some((kind, arg)) = s.trim().split_once(':') {
+            return match kind.to_lowercase().as_str() {
+                "wikipedia" => match Wikipedia::new(arg) {
+                    Ok(wikipedia) => Ok(Self::Wikipedia {
+                        lang: wikipedia.lang().to_string(),
+                    }),
+                    Err(_) => Err(format!("Invalid Wikipedia language: {arg}")),
+                },
+                "searxng" => instance_url(arg).map(|base_url| Self::SearxNG { base_url }),
+                "whoogle" => instance_url(arg).map(|base_url| Self::Whoogle { base_url }),
+                "librey" | "librex" => instance_url(arg).map(|base_url| Self::LibreY { base_url }),
+                "brave-api" => api_key(arg).map(|api_key| Self::BraveApi { api_key }),
+                "kagi" => api_key(arg).map(|api_key| Self::Kagi { api_key }),
+                "bing-api" => api_key(arg).map(|api_key| Self::BingApi { api_key }),
+                "google-cse" => {
+                    let (key, cx) = arg.split_once(':').unwrap_or((arg, ""));
+                    Ok(Self::GoogleCse {
+                        key: api_key(key)?,
+                        cx: api_key(cx)?,
+                    })
+                }
+                "4get" => instance_url(arg).map(|base_url| Self::FourGet { base_url }),
+                _ => Err(format!("Unknown search engine: {s}")),
             };
+        }
+
+        match s.trim().to_lowercase().as_str() {
+            "brave" => Ok(Self::Brave),
+            "duckduckgo" | "ddg" => Ok(Self::DuckDuckGo),
+            "startpage" => Ok(Self::Startpage),
+            "qwant" => Ok(Self::Qwant),
+            "google" => Ok(Self::Google),
+            "yandex" => Ok(Self::Yandex),
+            "wiby" => Ok(Self::Wiby),
+            "presearch" => Ok(Self::Presearch),
+            "yahoo" => Ok(Self::Yahoo),
+            "baidu" => Ok(Self::Baidu),
+            "naver" => Ok(Self::Naver),
+            "wikipedia" | "wiki" => Ok(Self::Wikipedia {
+                lang: "en".to_string(),
+            }),
+            _ => Err(format!("Unknown search engine: {s}")),
+        }
+    }
+}
+
+/// Validates the base url of a self-hosted engine instance.
+fn instance_url(base_url: &str) -> Result<String, String> {
+    match url::Url::parse(base_url.trim()) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {
+            Ok(url.as_str().trim_end_matches('/').to_string())
+        }
+        _ => Err(format!("Invalid instance url: {base_url}")),
+    }
+}
 
-        // Spawn the boxed future
-        set.spawn(timeout(timeout_duration, fut));
+/// Validates the key of an engine used through its paid API.
+fn api_key(key: &str) -> Result<String, String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Missing api key".to_string());
     }
+    Ok(key.to_string())
+}
+
+/// Per search settings for [`search_engine_results_with`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SearchOptions {
+    /// Zero based page of results
+    pub page: usize,
+    /// Read the cache but never write to it, so the search leaves no trace
+    pub private: bool,
+    /// Ask the engines even if the query is cached, without reading or
+    /// writing the cache
+    pub fresh: bool,
+    /// Fill [`SearchResponse::summary`] with the registered summarizer
+    pub summarize: bool,
+    /// Only documents of this type, asked for with each engine's own
+    /// operator. Engines that can't filter by file type are skipped.
+    pub file_type: Option<FileType>,
+}
+
+/// Searches all given engines, or the configured default engines if `engines` is empty.
+pub async fn search_engine_results(
+    query: String,
+    engines: Vec<SearchEngines>,
+) -> Result<SearchResponse, FetchError> {
+    search_engine_results_with(query, engines, SearchOptions::default()).await
+}
+
+/// Same as [`search_engine_results`] for the zero based `page` of results.
+pub async fn search_engine_results_page(
+    query: String,
+    engines: Vec<SearchEngines>,
+    page: usize,
+) -> Result<SearchResponse, FetchError> {
+    let options = SearchOptions {
+        page,
+        ..Default::default()
+    };
+    search_engine_results_with(query, engines, options).await
+}
+
+/// Same as [`search_engine_results`] with per search settings.
+pub async fn search_engine_results_with(
+    query: String,
+    engines: Vec<SearchEngines>,
+    options: SearchOptions,
+) -> Result<SearchResponse, FetchError> {
+    search_engine_results_for(query, engines, options, answers::AnswerContext::default()).await
+}
 
-    let combined = timeout(timeout_duration, set.join_all()).await;
+/// Same as [`search_engine_results_with`] for a request whose details the
+/// instant answers can use, e.g. to answer "what is my ip".
+pub async fn search_engine_results_for(
+    query: String,
+    engines: Vec<SearchEngines>,
+    options: SearchOptions,
+    mut context: answers::AnswerContext,
+) -> Result<SearchResponse, FetchError> {
+    context.private |= options.private;
+    let config = config::init().map_err(FetchError::Config)?;
+    let timeout_duration = config.engine_timeout;
+    let engines = if engines.is_empty() {
+        selection::default_engines().await
+    } else {
+        engines
+    };
+
+    obfuscation::maybe_send_decoy(&engines);
+
+    let set = spawn_searches(&query, engines, options);
+    let answers = async {
+        match options.page {
+            0 => answers::search_answers_with(&query, &context).await,
+            _ => Ok(Vec::new()),
+        }
+    };
+    let (combined, answers) = tokio::join!(timeout(timeout_duration, set.join_all()), answers);
 
     let per_engine = match combined {
         Ok(res) => res,
@@ -303,18 +1068,37 @@ pub async fn search_engine_images(
             return Err(FetchError::Timeouts);
         }
     };
+    let answers = answers?;
 
-    let mut flat: Vec<ImageResult> = Vec::new();
+    let mut flat: Vec<SearchResult> = Vec::new();
+    let mut news: Vec<NewsResult> = Vec::new();
+    let mut videos: Vec<VideoResult> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+    let mut total_results: Option<u64> = None;
+    let mut has_next_page = false;
+    let mut fusion_scores: HashMap<String, f64> = HashMap::new();
     let mut any_success = false;
 
-    for engine_result in per_engine {
+    for (name, engine_result) in per_engine {
         match engine_result {
-            Ok(rows) => {
+            Ok(Ok(mut response)) => {
                 any_success = true;
-                flat.append(&mut rows.unwrap());
+                for (rank, row) in response.results.iter().enumerate() {
+                    *fusion_scores.entry(row.url.clone()).or_default() +=
+                        1.0 / (RRF_K + rank as f64 + 1.0);
+                }
+                flat.append(&mut response.results);
+                news.append(&mut response.news);
+                videos.append(&mut response.videos);
+                warnings.append(&mut response.warnings);
+                total_results = total_results.max(response.total_results);
+                has_next_page |= response.has_next_page;
             }
-            Err(e) => {
-                eprintln!("Engine failed: {:?}", e);
+            Ok(Err(e)) => {
+                warnings.push(format!("{name}: engine failed: {e:?}"));
+            }
+            Err(_) => {
+                warnings.push(format!("{name}: engine timed out"));
             }
         }
     }
@@ -323,22 +1107,184 @@ pub async fn search_engine_images(
         return Err(FetchError::AllEnginesFailed);
     }
 
-    Ok(merge_images(flat))
+    let merged = merge_results(flat);
+    let sorted = match config.ranking {
+        Ranking::Domain => sort_results(merged, &query),
+        Ranking::Fusion => sort_fused_results(merged, &fusion_scores),
+    };
+    let sorted = if config.query_expansion {
+        expand::rerank(sorted, &query)
+    } else {
+        sorted
+    };
+    let rules = cache::backend()
+        .await
+        .get_result_rules()
+        .await
+        .map_err(FetchError::Cache)?;
+    #[cfg_attr(not(feature = "screenshot"), allow(unused_mut))]
+    let mut sorted = apply_result_rules(sorted, &rules, &query);
+    #[cfg(feature = "screenshot")]
+    if config.screenshots > 0 {
+        match cache::sqlite().await {
+            Ok(pool) => screenshot::attach_screenshots(pool, &mut sorted, options.private).await,
+            Err(_) => warnings.push("screenshots need the SQLite cache".to_string()),
+        }
+    }
+    let summary = if options.summarize {
+        summary::summarize(&query, &sorted).await
+    } else {
+        None
+    };
+
+    Ok(SearchResponse {
+        results: sorted,
+        total_results,
+        has_next_page,
+        news: merge_by_url(news, |n| &n.url, |n| &mut n.engines),
+        videos: merge_by_url(videos, |v| &v.url, |v| &mut v.engines),
+        warnings,
+        summary,
+        answers,
+    })
 }
 
-fn merge_images(images: Vec<ImageResult>) -> Vec<ImageResult> {
-    let mut map: BTreeMap<String, ImageResult> = BTreeMap::new();
+type EngineOutcome = (
+    &'static str,
+    Result<Result<SearchResponse, FetchError>, time::error::Elapsed>,
+);
 
-    for row in images {
+/// Starts the search on every engine, all sharing one circuit and each
+/// limited to the engine timeout.
+fn spawn_searches(
+    query: &str,
+    engines: Vec<SearchEngines>,
+    options: SearchOptions,
+) -> JoinSet<EngineOutcome> {
+    let timeout_duration = config::get().engine_timeout;
+    let start = page_start(options.page, RESULTS_PER_PAGE);
+    let mut set = JoinSet::new();
+    let circuit = new_circuit();
+
+    for engine in engines {
+        let query = query.to_string();
+        let name = engine.name();
+
+        let fut = engine.search(query, start, options);
+
+        let fut = with_circuit(circuit.clone(), fut);
+        set.spawn(async move { (name, timeout(timeout_duration, fut).await) });
+    }
+
+    set
+}
+
+/// The single best result from whichever engines answer first, for "I'm
+/// feeling lucky" style redirects. Engines still running are left to finish
+/// in the background so their results get cached.
+pub async fn first_result(
+    query: String,
+    engines: Vec<SearchEngines>,
+) -> Result<Option<SearchResult>, FetchError> {
+    config::init().map_err(FetchError::Config)?;
+    let engines = if engines.is_empty() {
+        selection::default_engines().await
+    } else {
+        engines
+    };
+
+    let mut set = spawn_searches(&query, engines, SearchOptions::default());
+    let mut responses: Vec<SearchResponse> = Vec::new();
+    let mut any_success = false;
+
+    while responses.is_empty() {
+        match set.join_next().await {
+            Some(Ok((_, Ok(Ok(response))))) => {
+                any_success = true;
+                if !response.results.is_empty() {
+                    responses.push(response);
+                }
+            }
+            // failed, timed out or panicked, wait for the next engine
+            Some(_) => continue,
+            None if any_success => return Ok(None),
+            None => return Err(FetchError::AllEnginesFailed),
+        }
+    }
+    // engines that answered at the same time get a say in the pick
+    while let Some(joined) = set.try_join_next() {
+        if let Ok((_, Ok(Ok(response)))) = joined {
+            responses.push(response);
+        }
+    }
+    set.detach_all();
+
+    let mut flat: Vec<SearchResult> = Vec::new();
+    let mut fusion_scores: HashMap<String, f64> = HashMap::new();
+    for mut response in responses {
+        for (rank, row) in response.results.iter().enumerate() {
+            *fusion_scores.entry(row.url.clone()).or_default() += 1.0 / (RRF_K + rank as f64 + 1.0);
+        }
+        flat.append(&mut response.results);
+    }
+
+    let sorted = sort_fused_results(merge_results(flat), &fusion_scores);
+    let rules = cache::backend()
+        .await
+        .get_result_rules()
+        .await
+        .map_err(FetchError::Cache)?;
+
+    Ok(apply_result_rules(sorted, &rules, &query)
+        .into_iter()
+        .next())
+}
+
+/// Dedups results sharing a url, keeping the first and collecting every engine.
+fn merge_by_url<T>(
+    items: Vec<T>,
+    url: impl Fn(&T) -> &String,
+    engines: impl Fn(&mut T) -> &mut Vec<String>,
+) -> Vec<T> {
+    let mut map: BTreeMap<String, T> = BTreeMap::new();
+
+    for mut item in items {
+        match map.get_mut(url(&item)) {
+            Some(existing) => engines(existing).append(engines(&mut item)),
+            None => {
+                map.insert(url(&item).clone(), item);
+            }
+        }
+    }
+
+    map.into_values().collect()
+}
+
+fn merge_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut map: BTreeMap<String, SearchResult> = BTreeMap::new();
+
+    for row in results {
         let key = row.url.clone();
 
         map.entry(key)
             .and_modify(|existing| {
                 existing.engines.extend(row.engines.clone());
 
+                if existing.description.is_empty() {
+                    existing.description = row.description.clone();
+                }
                 if existing.title.is_empty() {
                     existing.title = row.title.clone();
                 }
+                if existing.published_at.is_none() {
+                    existing.published_at = row.published_at.clone();
+                }
+                if existing.language.is_none() {
+                    existing.language = row.language.clone();
+                }
+                if existing.extra_snippets.is_empty() {
+                    existing.extra_snippets = row.extra_snippets.clone();
+                }
             })
             .or_insert(row);
     }
@@ -346,77 +1292,3313 @@ fn merge_images(images: Vec<ImageResult>) -> Vec<ImageResult> {
     map.into_values().collect()
 }
 
-/// Checks the cache first; if miss, fetches from the engine and caches images.
-pub async fn fetch_or_cache_image<E>(
-    engine: E,
-    query: String,
-    start: usize,
-    count: usize,
-) -> Result<Vec<ImageResult>, FetchError>
-where
-    E: ImageEngine + EngineInfo,
-{
-    let pool = get_db().await;
-    let mut search_images = Vec::new();
+pub fn sort_results(mut results: Vec<SearchResult>, query: &str) -> Vec<SearchResult> {
+    let stop = ["the", "and", "or", "of", "for", "in", "on", "at"];
+    let words: Vec<String> = query
+        .split_whitespace()
+        .filter(|w| !stop.contains(&w.to_lowercase().as_str()))
+        .map(str::to_lowercase)
+        .collect();
 
-    let engine_enum = engine.name();
-    let engine_id = cache::get_engine_id(pool, engine_enum)
+    fn score(url: &str, words: &[String]) -> u8 {
+        let dom = url
+            .trim_start_matches("http://")
+            .trim_start_matches("https://")
+            .split('/')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+
+        let hits = words.iter().filter(|w| dom.contains(*w)).count();
+        if hits == words.len() {
+            2
+        }
+        // all words
+        else if hits > 0 {
+            1
+        }
+        // some words
+        else {
+            0
+        }
+    }
+
+    results.sort_by_cached_key(|r| std::cmp::Reverse(score(&r.url, &words)));
+    results
+}
+
+/// Orders results by their summed reciprocal rank across engines, best first.
+fn sort_fused_results(
+    mut results: Vec<SearchResult>,
+    scores: &HashMap<String, f64>,
+) -> Vec<SearchResult> {
+    let score = |r: &SearchResult| scores.get(&r.url).copied().unwrap_or_default();
+    results.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    results
+}
+
+/// Pins `target`, a url or a domain with its subdomains, to the top of the
+/// results of searches containing `query`, or of every search if `None`.
+pub async fn pin_result(target: &str, query: Option<&str>) -> Result<i64, FetchError> {
+    cache::backend()
+        .await
+        .add_result_rule(target, true, query)
         .await
-        .map_err(FetchError::Sqlx)?;
+        .map_err(FetchError::Cache)
+}
 
-    // Fetch cached images
-    let cached_rows = if let Some(query_row) = cache::get_query(pool, &query, engine_id)
+/// Hides `target`, a url or a domain with its subdomains, from the results of
+/// searches containing `query`, or of every search if `None`.
+pub async fn hide_result(target: &str, query: Option<&str>) -> Result<i64, FetchError> {
+    cache::backend()
         .await
-        .map_err(FetchError::Sqlx)?
-    {
-        cache::get_images_for_query(pool, query_row.id)
-            .await
-            .map_err(FetchError::Sqlx)?
-    } else {
-        Vec::new()
-    };
+        .add_result_rule(target, false, query)
+        .await
+        .map_err(FetchError::Cache)
+}
 
-    let cached_count = cached_rows.len();
-    let needed_end = start + count;
+/// Every pin and hide rule, oldest first.
+pub async fn result_rules() -> Result<Vec<cache::ResultRuleRow>, FetchError> {
+    cache::backend()
+        .await
+        .get_result_rules()
+        .await
+        .map_err(FetchError::Cache)
+}
 
-    let start = start.min(cached_count);
-    let end = cached_count.min(needed_end);
+pub async fn remove_result_rule(id: i64) -> Result<(), FetchError> {
+    cache::backend()
+        .await
+        .delete_result_rule(id)
+        .await
+        .map_err(FetchError::Cache)
+}
 
-    for cr in &cached_rows[start..end] {
-        search_images.push(ImageResult {
-            url: cr.url.clone(),
-            title: cr.title.clone(),
-            engines: vec![engine.name().to_string()],
-            cached: true,
-        });
+/// Drops everything cached for `query`, by every engine and in every
+/// vertical, and for its lowercase form, which suggestions and answers are
+/// cached under. Returns how many cached searches were removed.
+pub async fn purge_query(query: &str) -> Result<u64, FetchError> {
+    config::init().map_err(FetchError::Config)?;
+    let backend = cache::backend().await;
+    let lowercase = query.to_lowercase();
+    let mut keys = vec![cache_key(query)];
+    if lowercase != query {
+        keys.push(cache_key(&lowercase));
     }
 
-    if cached_count < needed_end {
-        let engine_images = engine
-            .search_images(&query)
+    let mut removed = 0;
+    for key in keys {
+        removed += backend
+            .purge(cache::Purge::Query(key.into_owned()))
             .await
-            .map_err(FetchError::Engine)?;
+            .map_err(FetchError::Cache)?;
+    }
+    Ok(removed)
+}
 
-        let fetched_at = chrono::Utc::now().naive_utc();
-        let _query_id = cache::upsert_query_with_images(
-            pool,
-            engine_enum,
-            &query,
-            engine_images.clone(),
-            fetched_at,
-        )
+/// Drops everything cached from `engine`, by its name, e.g. "DuckDuckGo",
+/// including every edition or instance of it.
+pub async fn purge_engine(engine: &str) -> Result<u64, FetchError> {
+    cache::backend()
         .await
-        .map_err(FetchError::Sqlx)?;
+        .purge(cache::Purge::Engine(engine.to_string()))
+        .await
+        .map_err(FetchError::Cache)
+}
 
-        for cr in &engine_images {
-            search_images.push(ImageResult {
-                url: cr.url.clone(),
-                title: cr.title.clone(),
-                engines: vec![engine.name().to_string()],
-                cached: false,
-            });
-        }
+/// Drops searches cached more than `age` ago.
+pub async fn purge_older_than(age: std::time::Duration) -> Result<u64, FetchError> {
+    let age = chrono::Duration::from_std(age).unwrap_or(chrono::Duration::MAX);
+    let Some(before) = chrono::Utc::now().naive_utc().checked_sub_signed(age) else {
+        return Ok(0);
+    };
+    cache::backend()
+        .await
+        .purge(cache::Purge::OlderThan(before))
+        .await
+        .map_err(FetchError::Cache)
+}
+
+/// Empties the search cache. Pins, hides, budgets and alerts are kept.
+pub async fn purge_all() -> Result<u64, FetchError> {
+    cache::backend()
+        .await
+        .purge(cache::Purge::All)
+        .await
+        .map_err(FetchError::Cache)
+}
+
+/// Parses the archived responses of `engine` again with its current parser,
+/// see [`config::Config::archive_responses`]. Returns how many queries got
+/// new results.
+pub async fn reparse_archived(engine: SearchEngines) -> Result<usize, FetchError> {
+    let pool = cache::sqlite().await.map_err(FetchError::Cache)?;
+    cache::reparse(pool, engine)
+        .await
+        .map_err(|e| FetchError::Cache(e.into()))
+}
+
+/// Number of cached searches, results and images.
+pub async fn cache_stats() -> Result<cache::CacheStats, FetchError> {
+    cache::backend()
+        .await
+        .stats()
+        .await
+        .map_err(FetchError::Cache)
+}
+
+/// Drops hidden results and moves pinned ones to the top, keeping their order.
+/// A result both pinned and hidden is hidden.
+fn apply_result_rules(
+    results: Vec<SearchResult>,
+    rules: &[cache::ResultRuleRow],
+    query: &str,
+) -> Vec<SearchResult> {
+    let query = query.to_lowercase();
+    let rules: Vec<&cache::ResultRuleRow> = rules
+        .iter()
+        .filter(|rule| {
+            rule.query
+                .as_ref()
+                .is_none_or(|q| query.contains(&q.to_lowercase()))
+        })
+        .collect();
+    if rules.is_empty() {
+        return results;
     }
 
-    Ok(search_images)
+    let matches = |pinned: bool, url: &str| {
+        rules
+            .iter()
+            .any(|rule| rule.pinned == pinned && rule_matches(&rule.target, url))
+    };
+
+    let (mut pinned, rest): (Vec<_>, Vec<_>) = results
+        .into_iter()
+        .filter(|r| !matches(false, &r.url))
+        .partition(|r| matches(true, &r.url));
+    pinned.extend(rest);
+    pinned
+}
+
+fn rule_matches(target: &str, url: &str) -> bool {
+    if target.contains("://") {
+        return url.trim_end_matches('/') == target.trim_end_matches('/');
+    }
+
+    let target = target.trim_start_matches("*.").to_lowercase();
+    url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_lowercase))
+        .is_some_and(|host| {
+            host == target
+                || host
+                    .strip_suffix(&target)
+                    .is_some_and(|sub| sub.ends_with('.'))
+        })
+}
+
+/// Checks the cache first; if miss, fetches from the engine and caches results.
+///
+/// Further pages are fetched with the engine's stored next page token, so
+/// asking for a later window never refetches the first page. Queries cached
+/// longer than [`config::Config::cache_ttl`] are a miss and fetched anew,
+/// ones older than [`config::Config::cache_refresh_after`] are served and
+/// refetched in the background.
+/// `private` searches still read the cache but don't write what they fetch,
+/// `fresh` ones don't touch it at all.
+pub async fn fetch_or_cache_result<E>(
+    engine: E,
+    query: String,
+    start: usize,
+    count: usize,
+    options: SearchOptions,
+) -> Result<SearchResponse, FetchError>
+where
+    E: SearchEngine + EngineInfo + Send + Sync + 'static,
+{
+    config::init().map_err(FetchError::Config)?;
+    let backend = cache::backend().await;
+    fetch_or_cache_result_in(backend, engine, query, start, count, options).await
+}
+
+/// [`fetch_or_cache_result`] against `backend`.
+async fn fetch_or_cache_result_in<E>(
+    backend: &dyn cache::CacheBackend,
+    engine: E,
+    query: String,
+    start: usize,
+    count: usize,
+    options: SearchOptions,
+) -> Result<SearchResponse, FetchError>
+where
+    E: SearchEngine + EngineInfo + Send + Sync + 'static,
+{
+    let persist = config::get().persist_cache && !options.private && !options.fresh;
+    let mut response = SearchResponse::default();
+
+    let engine_enum = engine.name();
+    let engine_id = engine.id();
+
+    // the narrowed query is what gets sent and cached
+    let query = match options.file_type {
+        Some(file_type) => match engine.file_type_query(&query, file_type) {
+            Some(query) => query,
+            None => {
+                response
+                    .warnings
+                    .push(format!("{engine_enum}: can't filter by file type"));
+                return Ok(response);
+            }
+        },
+        None => query,
+    };
+
+    // Fetch cached results
+    let key = cache_key(&query);
+    let query_row = if options.fresh {
+        None
+    } else {
+        backend
+            .get_query(&engine_id, &key, config::get().cache_ttl, persist)
+            .await
+            .map_err(FetchError::Cache)?
+    };
+
+    let mut rows = match &query_row {
+        Some(query_row) => {
+            response.total_results = query_row.total_results.map(|t| t as u64);
+            response.has_next_page = query_row.has_next_page;
+
+            // the cards came with the first page, show them along with it
+            if start == 0 {
+                let news: Vec<cache::NewsRow> = backend
+                    .rows(query_row.id)
+                    .await
+                    .map_err(FetchError::Cache)?;
+                let videos: Vec<cache::VideoRow> = backend
+                    .rows(query_row.id)
+                    .await
+                    .map_err(FetchError::Cache)?;
+                response.news = news
+                    .into_iter()
+                    .map(|n| NewsResult::from_row(n, engine_enum, true))
+                    .collect();
+                response.videos = videos
+                    .into_iter()
+                    .map(|v| VideoResult::from_row(v, engine_enum, true))
+                    .collect();
+            }
+
+            backend
+                .rows(query_row.id)
+                .await
+                .map_err(FetchError::Cache)?
+        }
+        None => Vec::new(),
+    };
+
+    let cached_count = rows.len();
+    let needed_end = start.saturating_add(count);
+
+    let refresh_after = config::get()
+        .cache_refresh_after
+        .and_then(|age| chrono::Duration::from_std(age).ok());
+    let stale = query_row
+        .as_ref()
+        .filter(|q| {
+            refresh_after.is_some_and(|age| chrono::Utc::now().naive_utc() - q.fetched_at > age)
+        })
+        .map(|q| q.id);
+
+    // `None` until a first page exists, then the token for the page after it
+    let mut next_page: Option<Option<String>> = query_row.map(|q| q.next_page);
+    let mut fetched_pages = 0;
+
+    while rows.len() < needed_end && fetched_pages < MAX_PAGES_PER_FETCH {
+        let token = match &next_page {
+            None => None,
+            Some(Some(token)) => Some(token.as_str()),
+            Some(None) => break, // no further pages
+        };
+
+        let total_results = response.total_results;
+        let fetched = backend
+            .fetch_and_store(
+                engine_enum,
+                &engine_id,
+                persist,
+                true,
+                async {
+                    let mut page = engine.search_results(&query, token).await?;
+                    rewrite::rewrite_page(&mut page);
+                    Ok(page)
+                },
+                async |page: &SerpPage| {
+                    // a heuristic parse may be nav links, it isn't kept past this search
+                    if page.diagnostics.heuristic_fallback {
+                        return Ok(());
+                    }
+                    let fetched_at = chrono::Utc::now().naive_utc();
+                    let query_id = backend
+                        .store(&engine_id, &key, page.results.clone(), fetched_at)
+                        .await?;
+                    if !page.news.is_empty() {
+                        backend
+                            .store(&engine_id, &key, page.news.clone(), fetched_at)
+                            .await?;
+                    }
+                    if !page.videos.is_empty() {
+                        backend
+                            .store(&engine_id, &key, page.videos.clone(), fetched_at)
+                            .await?;
+                    }
+
+                    backend
+                        .set_page_meta(
+                            query_id,
+                            page.total_results.or(total_results).map(|t| t as i64),
+                            page.has_next_page || page.next_page.is_some(),
+                            page.next_page.as_deref(),
+                        )
+                        .await?;
+
+                    if let Some(raw) = &page.raw {
+                        backend
+                            .archive_response(query_id, token, raw, fetched_at)
+                            .await?;
+                        let retention = chrono::Duration::from_std(config::get().archive_retention)
+                            .unwrap_or(chrono::Duration::MAX);
+                        if let Some(before) = fetched_at.checked_sub_signed(retention) {
+                            backend.prune_archived_responses(before).await?;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .await;
+        let page = match fetched {
+            // serve what's cached rather than failing the engine
+            Err(FetchError::BudgetExhausted) => {
+                response
+                    .warnings
+                    .push(format!("{engine_enum}: request budget exhausted"));
+                break;
+            }
+            fetched => fetched?,
+        };
+        fetched_pages += 1;
+
+        metrics::record_parse(engine_enum, &page.diagnostics);
+        response
+            .warnings
+            .extend(page.diagnostics.warnings(engine_enum));
+        response.total_results = page.total_results.or(response.total_results);
+        response.has_next_page = page.has_next_page || page.next_page.is_some();
+        let heuristic = page.diagnostics.heuristic_fallback;
+
+        response.news.extend(
+            page.news
+                .into_iter()
+                .map(|n| NewsResult::from_row(n, engine.name(), false)),
+        );
+        response.videos.extend(
+            page.videos
+                .into_iter()
+                .map(|v| VideoResult::from_row(v, engine.name(), false)),
+        );
+
+        let exhausted = page.results.is_empty();
+        rows.extend(page.results);
+        next_page = Some(page.next_page);
+
+        if exhausted || heuristic {
+            break;
+        }
+    }
+
+    // served from the cache alone, so the refetch can't race a page fetch
+    if persist
+        && fetched_pages == 0
+        && let Some(query_id) = stale
+    {
+        spawn_refresh(engine.clone(), query.clone(), query_id);
+    }
+
+    let end = rows.len().min(needed_end);
+    let start = start.min(end);
+    response.has_next_page |= rows.len() > end;
+
+    response.results = rows[start..end]
+        .iter()
+        .enumerate()
+        .map(|(i, cr)| SearchResult {
+            url: cr.url.clone(),
+            title: cr.title.clone(),
+            description: cr.description.clone(),
+            engines: vec![engine.name().to_string()],
+            cached: start + i < cached_count,
+            screenshot: None,
+            published_at: cr.published_at.clone(),
+            language: cr.language.clone(),
+            extra_snippets: cr.extra_snippets.clone(),
+        })
+        .collect();
+
+    Ok(response)
+}
+
+/// Refetches the first page of a cached query on a spawned task, unless it
+/// is already being refetched.
+fn spawn_refresh<E>(engine: E, query: String, query_id: i64)
+where
+    E: SearchEngine + EngineInfo + Send + Sync + 'static,
+{
+    if !REFRESHING.lock().unwrap().insert(query_id) {
+        return;
+    }
+    let refreshing = Refreshing(query_id);
+
+    // the refresh goes out over the circuit of the search that found it stale
+    tokio::spawn(with_circuit(current_circuit(), async move {
+        let _refreshing = refreshing;
+        if let Err(e) = refresh_results(&engine, &query, query_id).await {
+            eprintln!("Failed to refresh cached {} results: {e:?}", engine.name());
+        }
+    }));
+}
+
+/// Takes a query off [`REFRESHING`] when its refresh ends, panicked or not.
+struct Refreshing(i64);
+
+impl Drop for Refreshing {
+    fn drop(&mut self) {
+        REFRESHING.lock().unwrap().remove(&self.0);
+    }
+}
+
+/// Replaces the cached results of `query_id` with the engine's first page.
+async fn refresh_results<E>(engine: &E, query: &str, query_id: i64) -> Result<(), FetchError>
+where
+    E: SearchEngine + EngineInfo + Send + Sync,
+{
+    let backend = cache::backend().await;
+    backend
+        .fetch_and_store(
+            engine.name(),
+            &engine.id(),
+            true,
+            true,
+            async {
+                let mut page = engine.search_results(query, None).await?;
+                rewrite::rewrite_page(&mut page);
+                metrics::record_parse(engine.name(), &page.diagnostics);
+                Ok(page)
+            },
+            async |page: &SerpPage| {
+                // keep serving the old results over an empty or heuristically parsed page
+                if page.results.is_empty() || page.diagnostics.heuristic_fallback {
+                    return Ok(());
+                }
+                backend
+                    .replace_results(query_id, &page.results, chrono::Utc::now().naive_utc())
+                    .await?;
+                backend
+                    .set_page_meta(
+                        query_id,
+                        page.total_results.map(|t| t as i64),
+                        page.has_next_page || page.next_page.is_some(),
+                        page.next_page.as_deref(),
+                    )
+                    .await
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub enum ImageEngines {
+    Brave,
+    DuckDuckGo,
+    Bing,
+    Qwant,
+    Openverse,
+    WikimediaCommons,
+    Google,
+    /// Flickr's API, written `flickr:<api key>`
+    Flickr {
+        api_key: String,
+    },
+    /// Unsplash's API, written `unsplash:<access key>`
+    Unsplash {
+        api_key: String,
+    },
+    /// Pexels' API, written `pexels:<api key>`
+    Pexels {
+        api_key: String,
+    },
+}
+
+impl ImageEngines {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageEngines::Brave => Brave.name(),
+            ImageEngines::DuckDuckGo => DuckDuckGo.name(),
+            ImageEngines::Bing => Bing.name(),
+            ImageEngines::Qwant => Qwant.name(),
+            ImageEngines::Openverse => Openverse.name(),
+            ImageEngines::WikimediaCommons => WikimediaCommons.name(),
+            ImageEngines::Google => Google.name(),
+            ImageEngines::Flickr { .. } => Flickr::NAME,
+            ImageEngines::Unsplash { .. } => Unsplash::NAME,
+            ImageEngines::Pexels { .. } => Pexels::NAME,
+        }
+    }
+
+    /// [`fetch_or_cache_image`] with the engine, from the `start`th image.
+    fn search(
+        self,
+        query: String,
+        options: ImageOptions,
+        start: usize,
+    ) -> VerticalSearch<ImageResult> {
+        macro_rules! search {
+            ($engine:expr) => {
+                Box::pin(fetch_or_cache_image(
+                    $engine,
+                    query,
+                    options,
+                    start,
+                    IMAGES_PER_PAGE,
+                ))
+            };
+        }
+
+        match self {
+            ImageEngines::Brave => search!(Brave),
+            ImageEngines::DuckDuckGo => search!(DuckDuckGo),
+            ImageEngines::Bing => search!(Bing),
+            ImageEngines::Qwant => search!(Qwant),
+            ImageEngines::Openverse => search!(Openverse),
+            ImageEngines::WikimediaCommons => search!(WikimediaCommons),
+            ImageEngines::Google => search!(Google),
+            ImageEngines::Flickr { api_key } => search!(Flickr::new(&api_key)),
+            ImageEngines::Unsplash { api_key } => search!(Unsplash::new(&api_key)),
+            ImageEngines::Pexels { api_key } => search!(Pexels::new(&api_key)),
+        }
+    }
+}
+
+impl FromStr for ImageEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((kind, arg)) = s.trim().split_once(':') {
+            return match kind.to_lowercase().as_str() {
+                "flickr" => api_key(arg).map(|api_key| Self::Flickr { api_key }),
+                "unsplash" => api_key(arg).map(|api_key| Self::Unsplash { api_key }),
+                "pexels" => api_key(arg).map(|api_key| Self::Pexels { api_key }),
+                _ => Err(format!("Unknown image engine: {s}")),
+            };
+        }
+
+        match s.trim().to_lowercase().as_str() {
+            "brave" => Ok(Self::Brave),
+            "duckduckgo" | "ddg" => Ok(Self::DuckDuckGo),
+            "bing" => Ok(Self::Bing),
+            "qwant" => Ok(Self::Qwant),
+            "openverse" => Ok(Self::Openverse),
+            "commons" | "wikimedia-commons" => Ok(Self::WikimediaCommons),
+            "google" => Ok(Self::Google),
+            _ => Err(format!("Unknown image engine: {s}")),
+        }
+    }
+}
+
+/// Searches all given image engines, or the configured default engines if `engines` is empty.
+pub async fn search_engine_images(
+    query: String,
+    engines: Vec<ImageEngines>,
+) -> Result<VerticalResponse<ImageResult>, FetchError> {
+    search_engine_images_with(query, engines, ImageOptions::default()).await
+}
+
+/// Same as [`search_engine_images`] with filters and a per search safe search level.
+pub async fn search_engine_images_with(
+    query: String,
+    engines: Vec<ImageEngines>,
+    mut options: ImageOptions,
+) -> Result<VerticalResponse<ImageResult>, FetchError> {
+    let config = config::init().map_err(FetchError::Config)?;
+    options.safe_search.get_or_insert(config.safe_search);
+    let start = page_start(options.page, IMAGES_PER_PAGE);
+    let engines = if engines.is_empty() {
+        config.image_engines.clone()
+    } else {
+        engines
+    };
+
+    let mut searches = Vec::new();
+    for engine in engines {
+        let query = query.clone();
+        let options = options.clone();
+        let name = engine.name();
+        let search = engine.search(query, options, start);
+        searches.push((name, search));
+    }
+
+    #[cfg_attr(not(any(feature = "blurhash", feature = "phash")), allow(unused_mut))]
+    let VerticalResponse {
+        results: lists,
+        mut warnings,
+    } = search_verticals(searches).await?;
+    let flat: Vec<ImageResult> = lists.into_iter().flatten().collect();
+    let results = merge_images(flat);
+
+    #[cfg(any(feature = "blurhash", feature = "phash"))]
+    if config.thumbnail_cache
+        && config.persist_cache
+        && !options.private
+        && cache::sqlite().await.is_err()
+    {
+        warnings.push("image hashes need the SQLite cache".to_string());
+    }
+
+    Ok(VerticalResponse { results, warnings })
+}
+
+/// Merges images with the same url, then ones whose perceptual hashes match
+/// since the same picture is often hosted at several urls.
+fn merge_images(images: Vec<ImageResult>) -> Vec<ImageResult> {
+    let mut map: BTreeMap<String, ImageResult> = BTreeMap::new();
+
+    for row in images {
+        let key = row.url.clone();
+
+        map.entry(key)
+            .and_modify(|existing| existing.merge(row.clone()))
+            .or_insert(row);
+    }
+
+    let mut merged: Vec<ImageResult> = Vec::with_capacity(map.len());
+    for row in map.into_values() {
+        let duplicate = row.phash.and_then(|hash| {
+            merged.iter_mut().find(|existing| {
+                existing
+                    .phash
+                    .is_some_and(|other| (hash ^ other).count_ones() <= PHASH_THRESHOLD)
+            })
+        });
+
+        match duplicate {
+            Some(existing) => existing.merge(row),
+            None => merged.push(row),
+        }
+    }
+
+    merged
+}
+
+/// Post filters on top of the engine's own, adult hosts are only let through
+/// with safe search off.
+fn keep_image(options: &ImageOptions, image: &cache::ImagesRow) -> bool {
+    let config = config::get();
+    let safe_search = options.safe_search.unwrap_or(config.safe_search);
+
+    options.matches(image)
+        && (safe_search == SafeSearch::Off || !is_blocked_host(image, &config.adult_domains))
+}
+
+/// Checks the cache first; if miss, fetches from the engine and caches images.
+///
+/// Like [`fetch_or_cache_result`], later pages are fetched with the engine's
+/// stored next page token, and nothing is written for `options.private`.
+pub async fn fetch_or_cache_image<E>(
+    engine: E,
+    query: String,
+    options: ImageOptions,
+    start: usize,
+    count: usize,
+) -> Result<VerticalResponse<ImageResult>, FetchError>
+where
+    E: ImageEngine + EngineInfo,
+{
+    config::init().map_err(FetchError::Config)?;
+    let backend = cache::backend().await;
+    fetch_or_cache_image_in(backend, engine, query, options, start, count).await
+}
+
+/// [`fetch_or_cache_image`] against `backend`.
+async fn fetch_or_cache_image_in<E>(
+    backend: &dyn cache::CacheBackend,
+    engine: E,
+    query: String,
+    options: ImageOptions,
+    start: usize,
+    count: usize,
+) -> Result<VerticalResponse<ImageResult>, FetchError>
+where
+    E: ImageEngine + EngineInfo,
+{
+    let persist = config::get().persist_cache && !options.private;
+
+    let engine_enum = engine.name();
+    let engine_id = engine.id();
+
+    // Image searches are cached apart from web searches with the same engine,
+    // and filtered ones apart from unfiltered ones
+    let key = vertical_key(&query, &format!("images{}", options.cache_suffix()));
+    let query_row = backend
+        .get_query(&engine_id, &key, config::get().cache_ttl, persist)
+        .await
+        .map_err(FetchError::Cache)?;
+
+    let mut rows = match &query_row {
+        Some(query_row) => backend
+            .rows(query_row.id)
+            .await
+            .map_err(FetchError::Cache)?,
+        None => Vec::new(),
+    };
+
+    let cached_count = rows.len();
+    let needed_end = start.saturating_add(count);
+
+    // `None` until a first page exists, then the token for the page after it
+    let mut next_page: Option<Option<String>> = query_row.map(|q| q.next_page);
+    let mut fetched_pages = 0;
+    let mut warnings = Vec::new();
+
+    // The window is taken from the images the filters keep, so pages keep
+    // coming until it is full or the engine runs out
+    let mut kept = rows
+        .iter()
+        .filter(|image| keep_image(&options, image))
+        .count();
+
+    while kept < needed_end && fetched_pages < MAX_PAGES_PER_FETCH {
+        let token = match &next_page {
+            None => None,
+            Some(Some(token)) => Some(token.as_str()),
+            Some(None) => break, // no further pages
+        };
+
+        let fetched = backend
+            .fetch_and_store(
+                engine_enum,
+                &engine_id,
+                persist,
+                true,
+                engine.search_images(&query, &options, token),
+                async |page: &ImagePage| {
+                    let fetched_at = chrono::Utc::now().naive_utc();
+                    let query_id = backend
+                        .store(&engine_id, &key, page.images.clone(), fetched_at)
+                        .await?;
+
+                    backend
+                        .set_page_meta(
+                            query_id,
+                            None,
+                            page.next_page.is_some(),
+                            page.next_page.as_deref(),
+                        )
+                        .await?;
+
+                    #[cfg(any(feature = "blurhash", feature = "phash"))]
+                    if config::get().thumbnail_cache
+                        && let Ok(pool) = cache::sqlite().await
+                    {
+                        let images = page
+                            .images
+                            .iter()
+                            .map(|i| {
+                                let thumbnail = i.thumbnail.as_ref().unwrap_or(&i.url);
+                                (i.url.clone(), thumbnail.clone())
+                            })
+                            .collect();
+                        // hashes show up once the images are served from the cache
+                        tokio::spawn(thumbnail::cache_thumbnail_hashes(pool, images));
+                    }
+                    Ok(())
+                },
+            )
+            .await;
+        let page = match fetched {
+            Err(FetchError::BudgetExhausted) => {
+                warnings.push(format!("{engine_enum}: request budget exhausted"));
+                break;
+            }
+            fetched => fetched?,
+        };
+        fetched_pages += 1;
+
+        if let Some(diagnostics) = &page.diagnostics {
+            metrics::record_parse(engine_enum, diagnostics);
+            warnings.extend(diagnostics.warnings(engine_enum));
+        }
+
+        let exhausted = page.images.is_empty();
+        kept += page
+            .images
+            .iter()
+            .filter(|image| keep_image(&options, image))
+            .count();
+        rows.extend(page.images);
+        next_page = Some(page.next_page);
+
+        if exhausted {
+            break;
+        }
+    }
+
+    let results = rows
+        .into_iter()
+        .enumerate()
+        .filter(|(_, image)| keep_image(&options, image))
+        .skip(start)
+        .take(count)
+        .map(|(i, image)| ImageResult::from_row(image, engine.name(), i < cached_count))
+        .collect();
+
+    Ok(VerticalResponse { results, warnings })
+}
+
+/// One engine's search of a vertical, boxed so engines of different types
+/// run side by side
+pub type VerticalSearch<T> =
+    Pin<Box<dyn Future<Output = Result<VerticalResponse<T>, FetchError>> + Send>>;
+
+/// Runs the engines' searches at once under one circuit and the engine
+/// timeout. Returns what each engine that answered found, in the order the
+/// searches were given, and fails only if none answered.
+async fn search_verticals<T: Send + 'static>(
+    searches: Vec<(&'static str, VerticalSearch<T>)>,
+) -> Result<VerticalResponse<Vec<T>>, FetchError> {
+    let timeout_duration = config::get().engine_timeout;
+    let mut set = JoinSet::new();
+    let circuit = new_circuit();
+
+    for (order, (name, search)) in searches.into_iter().enumerate() {
+        let fut = with_circuit(circuit.clone(), search);
+        set.spawn(async move { (order, name, timeout(timeout_duration, fut).await) });
+    }
+
+    let mut per_engine = timeout(timeout_duration, set.join_all())
+        .await
+        .map_err(|_| FetchError::Timeouts)?;
+    per_engine.sort_by_key(|(order, _, _)| *order);
+
+    let mut lists = Vec::new();
+    let mut warnings = Vec::new();
+    for (_, name, engine_result) in per_engine {
+        match engine_result {
+            Ok(Ok(mut response)) => {
+                lists.push(response.results);
+                warnings.append(&mut response.warnings);
+            }
+            Ok(Err(e)) => warnings.push(format!("{name}: engine failed: {e:?}")),
+            Err(_) => warnings.push(format!("{name}: engine timed out")),
+        }
+    }
+
+    if lists.is_empty() {
+        return Err(FetchError::AllEnginesFailed);
+    }
+    Ok(VerticalResponse {
+        results: lists,
+        warnings,
+    })
+}
+
+/// The engines of a vertical like news or books, searched together by
+/// [`search_vertical`].
+pub trait Vertical: Clone + Send + 'static {
+    type Result: Send + 'static;
+
+    /// Results per page, `None` for verticals answering in one list
+    const PER_PAGE: Option<usize>;
+
+    fn name(&self) -> &'static str;
+
+    /// The engines searched when none are given
+    fn defaults(config: &Config) -> Vec<Self>;
+
+    /// What the engines are asked for `query`, `None` if the vertical has no
+    /// results for it.
+    fn query(query: &str) -> Option<String> {
+        Some(query.to_string())
+    }
+
+    /// This engine's `start..start + count` window of results, see
+    /// [`fetch_or_cache_vertical`].
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<Self::Result>;
+
+    /// Merges the lists of the engines that answered, in the order given.
+    fn merge(lists: Vec<Vec<Self::Result>>) -> Vec<Self::Result>;
+}
+
+/// Searches all given engines of a vertical, or the configured default
+/// engines if `engines` is empty, for page `options.page` of
+/// [`Vertical::PER_PAGE`] results. Honors `options.private` and
+/// `options.fresh` like the web search. Verticals answering in one list
+/// ignore the page; the summary and file type are always ignored.
+pub async fn search_vertical<V: Vertical>(
+    query: String,
+    engines: Vec<V>,
+    options: SearchOptions,
+) -> Result<VerticalResponse<V::Result>, FetchError> {
+    let config = config::init().map_err(FetchError::Config)?;
+    let Some(query) = V::query(&query) else {
+        return Ok(VerticalResponse::default());
+    };
+
+    let engines = if engines.is_empty() {
+        V::defaults(config)
+    } else {
+        engines
+    };
+    let (start, count) = match V::PER_PAGE {
+        Some(per_page) => (page_start(options.page, per_page), per_page),
+        None => (0, usize::MAX),
+    };
+
+    let searches = engines
+        .into_iter()
+        .map(|engine| {
+            let name = engine.name();
+            (name, engine.search(query.clone(), options, start, count))
+        })
+        .collect();
+    let VerticalResponse {
+        results: lists,
+        warnings,
+    } = search_verticals(searches).await?;
+
+    Ok(VerticalResponse {
+        results: V::merge(lists),
+        warnings,
+    })
+}
+
+/// Checks the cache first; if miss, fetches from the engine and caches the
+/// items, like [`fetch_or_cache_image`]. Serves every vertical, each cached
+/// apart from web searches with the same engine, and `from_row` turns the
+/// `start..start + count` window into results. Nothing is written for
+/// `options.private`, and `options.fresh` skips the cache altogether.
+pub async fn fetch_or_cache_vertical<E, Row, T>(
+    engine: E,
+    query: String,
+    options: SearchOptions,
+    start: usize,
+    count: usize,
+    from_row: fn(Row, &str, bool) -> T,
+) -> Result<VerticalResponse<T>, FetchError>
+where
+    E: VerticalEngine<Row>,
+    Row: VerticalRow,
+{
+    config::init().map_err(FetchError::Config)?;
+    let backend = cache::backend().await;
+    fetch_or_cache_vertical_in(backend, engine, query, options, start, count, from_row).await
+}
+
+/// [`fetch_or_cache_vertical`] against `backend`.
+async fn fetch_or_cache_vertical_in<E, Row, T>(
+    backend: &dyn cache::CacheBackend,
+    engine: E,
+    query: String,
+    options: SearchOptions,
+    start: usize,
+    count: usize,
+    from_row: fn(Row, &str, bool) -> T,
+) -> Result<VerticalResponse<T>, FetchError>
+where
+    E: VerticalEngine<Row>,
+    Row: VerticalRow,
+{
+    let persist = config::get().persist_cache && !options.private && !options.fresh;
+
+    let engine_enum = engine.name();
+    let engine_id = engine.id();
+
+    let key = vertical_key(&query, Row::KIND);
+    let query_row = if options.fresh {
+        None
+    } else {
+        backend
+            .get_query(&engine_id, &key, Row::max_age(), persist)
+            .await
+            .map_err(FetchError::Cache)?
+    };
+
+    let mut rows = match &query_row {
+        Some(query_row) => backend
+            .rows(query_row.id)
+            .await
+            .map_err(FetchError::Cache)?,
+        None => Vec::new(),
+    };
+
+    let cached_count = rows.len();
+    let needed_end = start.saturating_add(count);
+
+    // `None` until a first page exists, then the token for the page after it
+    let mut next_page: Option<Option<String>> = query_row.map(|q| q.next_page);
+    let mut fetched_pages = 0;
+    let mut warnings = Vec::new();
+
+    while rows.len() < needed_end && fetched_pages < MAX_PAGES_PER_FETCH {
+        let token = match &next_page {
+            None => None,
+            Some(Some(token)) => Some(token.as_str()),
+            Some(None) => break, // no further pages
+        };
+
+        let fetched = backend
+            .fetch_and_store(
+                engine_enum,
+                &engine_id,
+                persist,
+                true,
+                engine.search_page(&query, token),
+                async |page: &VerticalPage<Row>| {
+                    let fetched_at = chrono::Utc::now().naive_utc();
+                    let query_id = backend
+                        .store(&engine_id, &key, page.rows.clone(), fetched_at)
+                        .await?;
+
+                    backend
+                        .set_page_meta(
+                            query_id,
+                            None,
+                            page.next_page.is_some(),
+                            page.next_page.as_deref(),
+                        )
+                        .await
+                },
+            )
+            .await;
+        let page = match fetched {
+            // serve what's cached rather than failing the engine
+            Err(FetchError::BudgetExhausted) => {
+                warnings.push(format!("{engine_enum}: request budget exhausted"));
+                break;
+            }
+            fetched => fetched?,
+        };
+        fetched_pages += 1;
+
+        let exhausted = page.rows.is_empty();
+        rows.extend(page.rows);
+        next_page = Some(page.next_page);
+
+        if exhausted {
+            break;
+        }
+    }
+
+    let end = rows.len().min(needed_end);
+    let start = start.min(end);
+
+    let results = rows
+        .into_iter()
+        .enumerate()
+        .skip(start)
+        .take(end - start)
+        .map(|(i, row)| from_row(row, engine_enum, i < cached_count))
+        .collect();
+
+    Ok(VerticalResponse { results, warnings })
+}
+
+#[derive(Debug, Clone)]
+pub enum NewsEngines {
+    DuckDuckGo,
+}
+
+impl FromStr for NewsEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "duckduckgo" | "ddg" => Ok(Self::DuckDuckGo),
+            _ => Err(format!("Unknown news engine: {s}")),
+        }
+    }
+}
+
+/// News stories, newest first.
+impl Vertical for NewsEngines {
+    type Result = NewsResult;
+
+    const PER_PAGE: Option<usize> = Some(NEWS_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            NewsEngines::DuckDuckGo => DuckDuckGo.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.news_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<NewsResult> {
+        match self {
+            NewsEngines::DuckDuckGo => Box::pin(fetch_or_cache_vertical(
+                DuckDuckGo,
+                query,
+                options,
+                start,
+                count,
+                NewsResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<NewsResult>>) -> Vec<NewsResult> {
+        let flat: Vec<NewsResult> = lists.into_iter().flatten().collect();
+
+        sort_news(merge_by_url(flat, |n| &n.url, |n| &mut n.engines))
+    }
+}
+
+/// Newest first, stories without a parseable date last.
+fn sort_news(mut news: Vec<NewsResult>) -> Vec<NewsResult> {
+    news.sort_by_cached_key(|n| {
+        let published = n
+            .published_at
+            .as_deref()
+            .and_then(|date| chrono::DateTime::parse_from_rfc3339(date).ok());
+        std::cmp::Reverse(published)
+    });
+    news
+}
+
+#[derive(Debug, Clone)]
+pub enum VideoEngines {
+    DuckDuckGo,
+}
+
+impl FromStr for VideoEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "duckduckgo" | "ddg" => Ok(Self::DuckDuckGo),
+            _ => Err(format!("Unknown video engine: {s}")),
+        }
+    }
+}
+
+/// Videos.
+impl Vertical for VideoEngines {
+    type Result = VideoResult;
+
+    const PER_PAGE: Option<usize> = Some(VIDEOS_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            VideoEngines::DuckDuckGo => DuckDuckGo.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.video_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<VideoResult> {
+        match self {
+            VideoEngines::DuckDuckGo => Box::pin(fetch_or_cache_vertical(
+                DuckDuckGo,
+                query,
+                options,
+                start,
+                count,
+                VideoResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<VideoResult>>) -> Vec<VideoResult> {
+        let flat: Vec<VideoResult> = lists.into_iter().flatten().collect();
+
+        merge_by_url(flat, |v| &v.url, |v| &mut v.engines)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MusicEngines {
+    MusicBrainz,
+    Bandcamp,
+}
+
+impl FromStr for MusicEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "musicbrainz" | "mb" => Ok(Self::MusicBrainz),
+            "bandcamp" => Ok(Self::Bandcamp),
+            _ => Err(format!("Unknown music engine: {s}")),
+        }
+    }
+}
+
+/// Music catalogs.
+impl Vertical for MusicEngines {
+    type Result = MusicResult;
+
+    const PER_PAGE: Option<usize> = Some(MUSIC_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            MusicEngines::MusicBrainz => MusicBrainz.name(),
+            MusicEngines::Bandcamp => Bandcamp.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.music_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<MusicResult> {
+        match self {
+            MusicEngines::MusicBrainz => Box::pin(fetch_or_cache_vertical(
+                MusicBrainz,
+                query,
+                options,
+                start,
+                count,
+                MusicResult::from_row,
+            )),
+            MusicEngines::Bandcamp => Box::pin(fetch_or_cache_vertical(
+                Bandcamp,
+                query,
+                options,
+                start,
+                count,
+                MusicResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<MusicResult>>) -> Vec<MusicResult> {
+        // interleaved, so the best hit of every catalog shows up first
+        merge_by_rank(
+            lists,
+            |m| m.url.clone(),
+            |existing, mut item| existing.engines.append(&mut item.engines),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PatentEngines {
+    GooglePatents,
+}
+
+impl FromStr for PatentEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "google-patents" | "googlepatents" | "google" => Ok(Self::GooglePatents),
+            _ => Err(format!("Unknown patent engine: {s}")),
+        }
+    }
+}
+
+/// Patents. A patent found by several engines is shown once.
+impl Vertical for PatentEngines {
+    type Result = PatentResult;
+
+    const PER_PAGE: Option<usize> = Some(PATENTS_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            PatentEngines::GooglePatents => GooglePatents.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.patent_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<PatentResult> {
+        match self {
+            PatentEngines::GooglePatents => Box::pin(fetch_or_cache_vertical(
+                GooglePatents,
+                query,
+                options,
+                start,
+                count,
+                PatentResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<PatentResult>>) -> Vec<PatentResult> {
+        merge_by_rank(
+            lists,
+            |p| p.number.clone(),
+            |existing, mut patent| {
+                existing.engines.append(&mut patent.engines);
+                existing.assignee = existing.assignee.take().or(patent.assignee);
+                existing.filing_date = existing.filing_date.take().or(patent.filing_date);
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AppEngines {
+    FDroid,
+    Flathub,
+}
+
+impl FromStr for AppEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "fdroid" | "f-droid" => Ok(Self::FDroid),
+            "flathub" => Ok(Self::Flathub),
+            _ => Err(format!("Unknown app engine: {s}")),
+        }
+    }
+}
+
+/// Software catalogs.
+impl Vertical for AppEngines {
+    type Result = AppResult;
+
+    const PER_PAGE: Option<usize> = Some(APPS_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            AppEngines::FDroid => FDroid.name(),
+            AppEngines::Flathub => Flathub.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.app_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<AppResult> {
+        match self {
+            AppEngines::FDroid => Box::pin(fetch_or_cache_vertical(
+                FDroid,
+                query,
+                options,
+                start,
+                count,
+                AppResult::from_row,
+            )),
+            AppEngines::Flathub => Box::pin(fetch_or_cache_vertical(
+                Flathub,
+                query,
+                options,
+                start,
+                count,
+                AppResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<AppResult>>) -> Vec<AppResult> {
+        // interleaved, so the best hit of every catalog shows up first
+        merge_by_rank(
+            lists,
+            |a| a.url.clone(),
+            |existing, mut app| existing.engines.append(&mut app.engines),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PackageEngines {
+    CratesIo,
+    Npm,
+    Pypi,
+}
+
+impl FromStr for PackageEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "crates" | "crates.io" | "cratesio" => Ok(Self::CratesIo),
+            "npm" => Ok(Self::Npm),
+            "pypi" => Ok(Self::Pypi),
+            _ => Err(format!("Unknown package engine: {s}")),
+        }
+    }
+}
+
+/// Package registries.
+impl Vertical for PackageEngines {
+    type Result = PackageResult;
+
+    const PER_PAGE: Option<usize> = Some(PACKAGES_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            PackageEngines::CratesIo => CratesIo.name(),
+            PackageEngines::Npm => Npm.name(),
+            PackageEngines::Pypi => Pypi.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.package_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<PackageResult> {
+        match self {
+            PackageEngines::CratesIo => Box::pin(fetch_or_cache_vertical(
+                CratesIo,
+                query,
+                options,
+                start,
+                count,
+                PackageResult::from_row,
+            )),
+            PackageEngines::Npm => Box::pin(fetch_or_cache_vertical(
+                Npm,
+                query,
+                options,
+                start,
+                count,
+                PackageResult::from_row,
+            )),
+            PackageEngines::Pypi => Box::pin(fetch_or_cache_vertical(
+                Pypi,
+                query,
+                options,
+                start,
+                count,
+                PackageResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<PackageResult>>) -> Vec<PackageResult> {
+        // interleaved, so the best hit of every registry shows up first
+        merge_by_rank(
+            lists,
+            |p| p.url.clone(),
+            |existing, mut package| existing.engines.append(&mut package.engines),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum QuoteEngines {
+    YahooFinance,
+    CoinGecko,
+}
+
+impl FromStr for QuoteEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "yahoo-finance" | "yahoofinance" => Ok(Self::YahooFinance),
+            "coingecko" => Ok(Self::CoinGecko),
+            _ => Err(format!("Unknown quote engine: {s}")),
+        }
+    }
+}
+
+/// The price of the stock or crypto currency a ticker style query asks
+/// about, e.g. "$AAPL", "aapl stock" or "bitcoin price". Sources are tried
+/// in the order given and the first that lists the symbol is the only
+/// result. Any other query has none.
+impl Vertical for QuoteEngines {
+    type Result = QuoteResult;
+
+    const PER_PAGE: Option<usize> = None;
+
+    fn name(&self) -> &'static str {
+        match self {
+            QuoteEngines::YahooFinance => YahooFinance.name(),
+            QuoteEngines::CoinGecko => CoinGecko.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.quote_engines.clone()
+    }
+
+    fn query(query: &str) -> Option<String> {
+        quotes::quote_symbol(query)
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<QuoteResult> {
+        match self {
+            QuoteEngines::YahooFinance => Box::pin(fetch_or_cache_vertical(
+                YahooFinance,
+                query,
+                options,
+                start,
+                count,
+                QuoteResult::from_row,
+            )),
+            QuoteEngines::CoinGecko => Box::pin(fetch_or_cache_vertical(
+                CoinGecko,
+                query,
+                options,
+                start,
+                count,
+                QuoteResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<QuoteResult>>) -> Vec<QuoteResult> {
+        // lists come in the order given, the first source listing the symbol wins
+        lists.into_iter().flatten().take(1).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum DictionaryEngines {
+    DictionaryApi,
+    Wiktionary,
+}
+
+impl FromStr for DictionaryEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "dictionaryapi" | "dictionaryapi.dev" | "freedictionary" => Ok(Self::DictionaryApi),
+            "wiktionary" => Ok(Self::Wiktionary),
+            _ => Err(format!("Unknown dictionary engine: {s}")),
+        }
+    }
+}
+
+/// The word a "define X" style query asks about, lowercased, e.g.
+/// "serendipity" for "define serendipity", "meaning of serendipity",
+/// "serendipity definition" or "what does serendipity mean?". `None` for
+/// any other query.
+pub fn definition_word(query: &str) -> Option<String> {
+    const PREFIXES: [&str; 6] = [
+        "define:",
+        "define ",
+        "definition of ",
+        "meaning of ",
+        "what is the definition of ",
+        "what is the meaning of ",
+    ];
+    const SUFFIXES: [&str; 2] = [" definition", " meaning"];
+
+    let query = query.trim().trim_end_matches('?').to_lowercase();
+    let word = match query.strip_prefix("what does ") {
+        Some(rest) => rest.strip_suffix(" mean")?,
+        None => PREFIXES
+            .iter()
+            .find_map(|prefix| query.strip_prefix(prefix))
+            .or_else(|| {
+                SUFFIXES
+                    .iter()
+                    .find_map(|suffix| query.strip_suffix(suffix))
+            })?,
+    };
+
+    let words: Vec<&str> = word
+        .trim_matches(|c: char| c.is_whitespace() || c == '"' || c == '\'')
+        .split_whitespace()
+        .collect();
+    (1..=MAX_DEFINITION_WORDS)
+        .contains(&words.len())
+        .then(|| words.join(" "))
+}
+
+/// Definitions for a "define X" style query, see [`definition_word`]. Any
+/// other query has no definitions.
+impl Vertical for DictionaryEngines {
+    type Result = DefinitionResult;
+
+    const PER_PAGE: Option<usize> = None;
+
+    fn name(&self) -> &'static str {
+        match self {
+            DictionaryEngines::DictionaryApi => DictionaryApi.name(),
+            DictionaryEngines::Wiktionary => Wiktionary.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.dictionary_engines.clone()
+    }
+
+    fn query(query: &str) -> Option<String> {
+        definition_word(query)
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<DefinitionResult> {
+        match self {
+            DictionaryEngines::DictionaryApi => Box::pin(fetch_or_cache_vertical(
+                DictionaryApi,
+                query,
+                options,
+                start,
+                count,
+                DefinitionResult::from_row,
+            )),
+            DictionaryEngines::Wiktionary => Box::pin(fetch_or_cache_vertical(
+                Wiktionary,
+                query,
+                options,
+                start,
+                count,
+                DefinitionResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<DefinitionResult>>) -> Vec<DefinitionResult> {
+        // dictionaries copying Wiktionary word their senses alike
+        merge_by_rank(
+            lists,
+            |d| format!("{}\u{1f}{}", d.part_of_speech, d.definition.to_lowercase()),
+            |existing, mut definition| {
+                existing.engines.append(&mut definition.engines);
+                if existing.examples.is_empty() {
+                    existing.examples = definition.examples;
+                }
+                existing.phonetic = existing.phonetic.take().or(definition.phonetic);
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum BookEngines {
+    OpenLibrary,
+}
+
+impl FromStr for BookEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "openlibrary" | "open-library" => Ok(Self::OpenLibrary),
+            _ => Err(format!("Unknown book engine: {s}")),
+        }
+    }
+}
+
+/// Books.
+impl Vertical for BookEngines {
+    type Result = BookResult;
+
+    const PER_PAGE: Option<usize> = Some(BOOKS_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            BookEngines::OpenLibrary => OpenLibrary.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.book_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<BookResult> {
+        match self {
+            BookEngines::OpenLibrary => Box::pin(fetch_or_cache_vertical(
+                OpenLibrary,
+                query,
+                options,
+                start,
+                count,
+                BookResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<BookResult>>) -> Vec<BookResult> {
+        let flat: Vec<BookResult> = lists.into_iter().flatten().collect();
+
+        merge_by_url(flat, |b| &b.url, |b| &mut b.engines)
+    }
+}
+
+/// Recipes for `query`, read from the schema.org Recipe data of the top web
+/// results on the given engines, or the configured default engines if
+/// `engines` is empty. Results without a recipe are left out, in rank order.
+pub async fn search_engine_recipes(
+    query: String,
+    engines: Vec<SearchEngines>,
+) -> Result<VerticalResponse<RecipeResult>, FetchError> {
+    search_engine_recipes_with(query, engines, SearchOptions::default()).await
+}
+
+/// Same as [`search_engine_recipes`], honoring `options.private` and
+/// `options.fresh` like the web search. The page and file type are ignored.
+pub async fn search_engine_recipes_with(
+    query: String,
+    engines: Vec<SearchEngines>,
+    options: SearchOptions,
+) -> Result<VerticalResponse<RecipeResult>, FetchError> {
+    let config = config::init().map_err(FetchError::Config)?;
+    let backend = cache::backend().await;
+    let persist = config.persist_cache && !options.private && !options.fresh;
+    let engines = if engines.is_empty() {
+        selection::default_engines().await
+    } else {
+        engines
+    };
+
+    // recipes found through other engines are a different search
+    let mut names: Vec<&str> = engines.iter().map(SearchEngines::name).collect();
+    names.sort_unstable();
+    names.dedup();
+    let key = vertical_key(&query, &format!("recipes\u{1f}{}", names.join(",")));
+    if !options.fresh
+        && let Some(query_row) = backend
+            .get_query(RECIPE_SOURCE, &key, config.cache_ttl, persist)
+            .await
+            .map_err(FetchError::Cache)?
+    {
+        let rows: Vec<cache::RecipeRow> = backend
+            .rows(query_row.id)
+            .await
+            .map_err(FetchError::Cache)?;
+        let results = rows
+            .into_iter()
+            .map(|row| RecipeResult::from_row(row, true))
+            .collect();
+        return Ok(VerticalResponse {
+            results,
+            warnings: Vec::new(),
+        });
+    }
+
+    let web_query = if query.to_lowercase().contains("recipe") {
+        query.clone()
+    } else {
+        format!("{query} recipe")
+    };
+    let web_options = SearchOptions {
+        private: options.private,
+        fresh: options.fresh,
+        ..Default::default()
+    };
+    let response = search_engine_results_with(web_query, engines, web_options).await?;
+
+    let mut set = JoinSet::new();
+    let circuit = new_circuit();
+    for (rank, result) in response.results.into_iter().take(RECIPE_PAGES).enumerate() {
+        let fut = with_circuit(circuit.clone(), async move {
+            recipes::fetch_recipe(&result.url).await
+        });
+        set.spawn(async move { (rank, timeout(config.engine_timeout, fut).await) });
+    }
+
+    let mut found: Vec<(usize, cache::RecipeRow)> = Vec::new();
+    let mut failed = false;
+    for (rank, page) in set.join_all().await {
+        match page {
+            Ok(Ok(Some(recipe))) => found.push((rank, recipe)),
+            Ok(Ok(None)) => {}
+            Ok(Err(e)) => {
+                failed = true;
+                eprintln!("Recipe page failed: {:?}", e);
+            }
+            Err(e) => {
+                failed = true;
+                eprintln!("Recipe page failed: {:?}", e);
+            }
+        }
+    }
+    found.sort_by_key(|(rank, _)| *rank);
+    let rows: Vec<cache::RecipeRow> = found.into_iter().map(|(_, recipe)| recipe).collect();
+
+    // a partial or empty answer is retried next time rather than kept
+    if persist && !failed && !rows.is_empty() {
+        let fetched_at = chrono::Utc::now().naive_utc();
+        backend
+            .store(RECIPE_SOURCE, &key, rows.clone(), fetched_at)
+            .await
+            .map_err(FetchError::Cache)?;
+        cache::evict_past_max(backend)
+            .await
+            .map_err(FetchError::Cache)?;
+    }
+
+    let results = rows
+        .into_iter()
+        .map(|row| RecipeResult::from_row(row, false))
+        .collect();
+
+    Ok(VerticalResponse {
+        results,
+        warnings: response.warnings,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub enum PodcastEngines {
+    Itunes,
+    /// Podcast Index's API, written `podcastindex:<api key>:<api secret>`
+    PodcastIndex {
+        api_key: String,
+        api_secret: String,
+    },
+}
+
+impl FromStr for PodcastEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((kind, arg)) = s.trim().split_once(':') {
+            return match kind.to_lowercase().as_str() {
+                "podcastindex" | "podcast-index" => {
+                    let (key, secret) = arg.split_once(':').unwrap_or((arg, ""));
+                    Ok(Self::PodcastIndex {
+                        api_key: api_key(key)?,
+                        api_secret: api_key(secret)?,
+                    })
+                }
+                _ => Err(format!("Unknown podcast engine: {s}")),
+            };
+        }
+
+        match s.trim().to_lowercase().as_str() {
+            "itunes" | "apple" => Ok(Self::Itunes),
+            _ => Err(format!("Unknown podcast engine: {s}")),
+        }
+    }
+}
+
+/// Podcasts.
+impl Vertical for PodcastEngines {
+    type Result = PodcastResult;
+
+    const PER_PAGE: Option<usize> = Some(PODCASTS_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            PodcastEngines::Itunes => Itunes.name(),
+            PodcastEngines::PodcastIndex { .. } => PodcastIndex::NAME,
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.podcast_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<PodcastResult> {
+        match self {
+            PodcastEngines::Itunes => Box::pin(fetch_or_cache_vertical(
+                Itunes,
+                query,
+                options,
+                start,
+                count,
+                PodcastResult::from_row,
+            )),
+            PodcastEngines::PodcastIndex {
+                api_key,
+                api_secret,
+            } => Box::pin(fetch_or_cache_vertical(
+                PodcastIndex::new(&api_key, &api_secret),
+                query,
+                options,
+                start,
+                count,
+                PodcastResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<PodcastResult>>) -> Vec<PodcastResult> {
+        // the engines link a show's page on their own site, the feed is the same
+        merge_by_rank(
+            lists,
+            |p| p.feed_url.trim_end_matches('/').to_lowercase(),
+            |existing, mut podcast| {
+                existing.engines.append(&mut podcast.engines);
+                existing.author = existing.author.take().or(podcast.author);
+                existing.artwork = existing.artwork.take().or(podcast.artwork);
+                existing.description = existing.description.take().or(podcast.description);
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SocialEngines {
+    /// A Lemmy instance, written `lemmy:<url>`
+    Lemmy { base_url: String },
+    /// A Mastodon instance, written `mastodon:<url>`. Instances only search
+    /// posts for signed in users, `mastodon:<access token>:<url>` does so.
+    Mastodon {
+        base_url: String,
+        token: Option<String>,
+    },
+}
+
+impl FromStr for SocialEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((kind, arg)) = s.trim().split_once(':') {
+            return match kind.to_lowercase().as_str() {
+                "lemmy" => instance_url(arg).map(|base_url| Self::Lemmy { base_url }),
+                "mastodon" => {
+                    let (token, url) = match arg.split_once(':') {
+                        Some((token, url)) if !url.starts_with("//") => {
+                            (Some(api_key(token)?), url)
+                        }
+                        _ => (None, arg),
+                    };
+                    Ok(Self::Mastodon {
+                        base_url: instance_url(url)?,
+                        token,
+                    })
+                }
+                _ => Err(format!("Unknown social engine: {s}")),
+            };
+        }
+
+        Err(format!("Unknown social engine: {s}"))
+    }
+}
+
+/// Fediverse instances. Instances are the user's pick, so there are none by
+/// default.
+impl Vertical for SocialEngines {
+    type Result = SocialResult;
+
+    const PER_PAGE: Option<usize> = Some(SOCIAL_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            SocialEngines::Lemmy { .. } => Lemmy::NAME,
+            SocialEngines::Mastodon { .. } => Mastodon::NAME,
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.social_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<SocialResult> {
+        match self {
+            SocialEngines::Lemmy { base_url } => Box::pin(fetch_or_cache_vertical(
+                Lemmy::new(&base_url),
+                query,
+                options,
+                start,
+                count,
+                SocialResult::from_row,
+            )),
+            SocialEngines::Mastodon { base_url, token } => Box::pin(fetch_or_cache_vertical(
+                Mastodon::new(&base_url, token.as_deref()),
+                query,
+                options,
+                start,
+                count,
+                SocialResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<SocialResult>>) -> Vec<SocialResult> {
+        merge_social(lists)
+    }
+}
+
+/// Interleaves the instances' results by rank. Instances federate, so the
+/// same post can come from several, under the url of its home instance.
+fn merge_social(lists: Vec<Vec<SocialResult>>) -> Vec<SocialResult> {
+    merge_by_rank(
+        lists,
+        |r| r.url.clone(),
+        |existing, mut result| {
+            existing.engines.append(&mut result.engines);
+            // instances only count the votes they have seen
+            existing.score = existing.score.max(result.score);
+            existing.content = existing.content.take().or(result.content);
+        },
+    )
+}
+
+#[derive(Debug, Clone)]
+pub enum FilesEngines {
+    /// A Jackett or Prowlarr feed, written `torznab:<url with api key>`
+    Torznab { url: String },
+}
+
+impl FromStr for FilesEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((kind, arg)) = s.trim().split_once(':') {
+            return match kind.to_lowercase().as_str() {
+                "torznab" => instance_url(arg).map(|url| Self::Torznab { url }),
+                _ => Err(format!("Unknown files engine: {s}")),
+            };
+        }
+
+        Err(format!("Unknown files engine: {s}"))
+    }
+}
+
+/// Torrent indexers. Best seeded torrents come first.
+impl Vertical for FilesEngines {
+    type Result = FileResult;
+
+    const PER_PAGE: Option<usize> = Some(FILES_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            FilesEngines::Torznab { .. } => Torznab::NAME,
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.files_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<FileResult> {
+        match self {
+            FilesEngines::Torznab { url } => Box::pin(fetch_or_cache_vertical(
+                Torznab::new(&url),
+                query,
+                options,
+                start,
+                count,
+                FileResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<FileResult>>) -> Vec<FileResult> {
+        let flat: Vec<FileResult> = lists.into_iter().flatten().collect();
+
+        let mut files = merge_by_url(flat, |f| &f.url, |f| &mut f.engines);
+        files.sort_by_key(|f| std::cmp::Reverse(f.seeders));
+        files
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ScholarEngines {
+    SemanticScholar,
+    Arxiv,
+    Crossref,
+    Biorxiv,
+    Zenodo,
+}
+
+impl FromStr for ScholarEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "semantic-scholar" | "semanticscholar" | "s2" => Ok(Self::SemanticScholar),
+            "arxiv" => Ok(Self::Arxiv),
+            "crossref" => Ok(Self::Crossref),
+            "biorxiv" => Ok(Self::Biorxiv),
+            "zenodo" => Ok(Self::Zenodo),
+            _ => Err(format!("Unknown scholar engine: {s}")),
+        }
+    }
+}
+
+/// Scholarly papers. A paper found by several engines is shown once.
+impl Vertical for ScholarEngines {
+    type Result = PaperResult;
+
+    const PER_PAGE: Option<usize> = Some(PAPERS_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            ScholarEngines::SemanticScholar => SemanticScholar.name(),
+            ScholarEngines::Arxiv => Arxiv.name(),
+            ScholarEngines::Crossref => Crossref.name(),
+            ScholarEngines::Biorxiv => Biorxiv.name(),
+            ScholarEngines::Zenodo => Zenodo.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.scholar_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<PaperResult> {
+        match self {
+            ScholarEngines::SemanticScholar => Box::pin(fetch_or_cache_vertical(
+                SemanticScholar,
+                query,
+                options,
+                start,
+                count,
+                PaperResult::from_row,
+            )),
+            ScholarEngines::Arxiv => Box::pin(fetch_or_cache_vertical(
+                Arxiv,
+                query,
+                options,
+                start,
+                count,
+                PaperResult::from_row,
+            )),
+            ScholarEngines::Crossref => Box::pin(fetch_or_cache_vertical(
+                Crossref,
+                query,
+                options,
+                start,
+                count,
+                PaperResult::from_row,
+            )),
+            ScholarEngines::Biorxiv => Box::pin(fetch_or_cache_vertical(
+                Biorxiv,
+                query,
+                options,
+                start,
+                count,
+                PaperResult::from_row,
+            )),
+            ScholarEngines::Zenodo => Box::pin(fetch_or_cache_vertical(
+                Zenodo,
+                query,
+                options,
+                start,
+                count,
+                PaperResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<PaperResult>>) -> Vec<PaperResult> {
+        // arXiv and Crossref link the same paper at different urls, the doi
+        // tells them apart
+        merge_by_rank(
+            lists,
+            |p| match &p.doi {
+                Some(doi) => doi.to_lowercase(),
+                None => p.url.clone(),
+            },
+            |existing, mut paper| {
+                existing.engines.append(&mut paper.engines);
+                existing.year = existing.year.or(paper.year);
+                existing.doi = existing.doi.take().or(paper.doi);
+                existing.abstract_text = existing.abstract_text.take().or(paper.abstract_text);
+                existing.kind = existing.kind.take().or(paper.kind);
+            },
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ShoppingEngines {
+    /// eBay's Browse API, written `ebay:<client id>:<client secret>`
+    Ebay {
+        client_id: String,
+        client_secret: String,
+    },
+}
+
+impl FromStr for ShoppingEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((kind, arg)) = s.trim().split_once(':') {
+            return match kind.to_lowercase().as_str() {
+                "ebay" => {
+                    let (client_id, client_secret) = arg.split_once(':').unwrap_or((arg, ""));
+                    Ok(Self::Ebay {
+                        client_id: api_key(client_id)?,
+                        client_secret: api_key(client_secret)?,
+                    })
+                }
+                _ => Err(format!("Unknown shopping engine: {s}")),
+            };
+        }
+
+        Err(format!("Unknown shopping engine: {s}"))
+    }
+}
+
+/// Products. Every shopping engine needs credentials, so there are no
+/// default engines unless configured.
+impl Vertical for ShoppingEngines {
+    type Result = ProductResult;
+
+    const PER_PAGE: Option<usize> = Some(PRODUCTS_PER_PAGE);
+
+    fn name(&self) -> &'static str {
+        match self {
+            ShoppingEngines::Ebay { .. } => Ebay::NAME,
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.shopping_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<ProductResult> {
+        match self {
+            ShoppingEngines::Ebay {
+                client_id,
+                client_secret,
+            } => Box::pin(fetch_or_cache_vertical(
+                Ebay::new(&client_id, &client_secret),
+                query,
+                options,
+                start,
+                count,
+                ProductResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<ProductResult>>) -> Vec<ProductResult> {
+        let flat: Vec<ProductResult> = lists.into_iter().flatten().collect();
+
+        merge_by_url(flat, |p| &p.url, |p| &mut p.engines)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PlacesEngines {
+    Nominatim,
+    Photon,
+}
+
+impl FromStr for PlacesEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "nominatim" | "osm" => Ok(Self::Nominatim),
+            "photon" => Ok(Self::Photon),
+            _ => Err(format!("Unknown places engine: {s}")),
+        }
+    }
+}
+
+/// Geocoders. Best matches come first.
+impl Vertical for PlacesEngines {
+    type Result = PlaceResult;
+
+    const PER_PAGE: Option<usize> = None;
+
+    fn name(&self) -> &'static str {
+        match self {
+            PlacesEngines::Nominatim => Nominatim.name(),
+            PlacesEngines::Photon => Photon.name(),
+        }
+    }
+
+    fn defaults(config: &Config) -> Vec<Self> {
+        config.places_engines.clone()
+    }
+
+    fn search(
+        self,
+        query: String,
+        options: SearchOptions,
+        start: usize,
+        count: usize,
+    ) -> VerticalSearch<PlaceResult> {
+        match self {
+            PlacesEngines::Nominatim => Box::pin(fetch_or_cache_vertical(
+                Nominatim,
+                query,
+                options,
+                start,
+                count,
+                PlaceResult::from_row,
+            )),
+            PlacesEngines::Photon => Box::pin(fetch_or_cache_vertical(
+                Photon,
+                query,
+                options,
+                start,
+                count,
+                PlaceResult::from_row,
+            )),
+        }
+    }
+
+    fn merge(lists: Vec<Vec<PlaceResult>>) -> Vec<PlaceResult> {
+        // the same OpenStreetMap object found by several geocoders
+        merge_by_rank(
+            lists,
+            |p| p.osm_url.clone(),
+            |existing, mut place| existing.engines.append(&mut place.engines),
+        )
+    }
+}
+
+/// Interleaves the engines' lists by rank, so each engine's best match stays
+/// near the top, folding items with the same `key` into the first with `merge`.
+fn merge_by_rank<T>(
+    lists: Vec<Vec<T>>,
+    key: impl Fn(&T) -> String,
+    merge: impl Fn(&mut T, T),
+) -> Vec<T> {
+    let mut ranked: Vec<(usize, T)> = lists
+        .into_iter()
+        .flat_map(|items| items.into_iter().enumerate())
+        .collect();
+    ranked.sort_by_key(|(rank, _)| *rank);
+
+    let mut merged: Vec<T> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (_, item) in ranked {
+        match index.get(&key(&item)) {
+            Some(&i) => merge(&mut merged[i], item),
+            None => {
+                index.insert(key(&item), merged.len());
+                merged.push(item);
+            }
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        Config, FilesEngines, IMAGES_PER_PAGE, ImageEngines, ImageResult, NewsResult, PlaceResult,
+        PodcastEngines, QuoteResult, REFRESHING, RESULTS_PER_PAGE, Refreshing, SearchEngines,
+        SearchOptions, SearchResult, ShoppingEngines, SocialEngines, SocialResult, Vertical,
+        VerticalResponse, VerticalSearch, apply_result_rules,
+        cache::{self, CacheBackend, NewsRow, QuoteRow, ResultRow, ResultRuleRow, VideoRow},
+        definition_word,
+        engines::{
+            EngineError, EngineInfo, ImageEngine, ImageOptions, ImagePage, NewsPage, SearchEngine,
+            SerpPage, VerticalEngine, VerticalPage, Wikipedia,
+        },
+        fetch_or_cache_image_in, fetch_or_cache_result_in, fetch_or_cache_vertical_in,
+        merge_by_rank, merge_images, merge_social, page_start, search_vertical, sort_news,
+    };
+    use async_trait::async_trait;
+    use sqlx::SqlitePool;
+
+    #[derive(Clone)]
+    struct Headlines;
+
+    impl EngineInfo for Headlines {
+        fn name(&self) -> &'static str {
+            "Headlines"
+        }
+    }
+
+    #[async_trait]
+    impl VerticalEngine<NewsRow> for Headlines {
+        async fn search_page(
+            &self,
+            _query: &str,
+            _page: Option<&str>,
+        ) -> Result<NewsPage, EngineError> {
+            Ok(NewsPage {
+                rows: vec![NewsRow {
+                    url: "https://example.com/story".to_string(),
+                    title: "Story".to_string(),
+                    ..Default::default()
+                }],
+                next_page: None,
+            })
+        }
+    }
+
+    /// Pages of one wide and one narrow image, numbered from 0
+    #[derive(Clone)]
+    struct Gallery;
+
+    impl EngineInfo for Gallery {
+        fn name(&self) -> &'static str {
+            "Gallery"
+        }
+    }
+
+    #[async_trait]
+    impl ImageEngine for Gallery {
+        async fn search_images(
+            &self,
+            _query: &str,
+            _options: &ImageOptions,
+            page: Option<&str>,
+        ) -> Result<ImagePage, EngineError> {
+            let page: usize = page.map_or(0, |p| p.parse().unwrap());
+            let image = |name: &str, width| cache::ImagesRow {
+                url: format!("https://example.com/{name}{page}.png"),
+                width: Some(width),
+                height: Some(100),
+                ..Default::default()
+            };
+            Ok(ImagePage {
+                images: vec![image("wide", 2000), image("narrow", 100)],
+                next_page: Some((page + 1).to_string()),
+                diagnostics: None,
+            })
+        }
+    }
+
+    /// Quotes every symbol at the same price
+    #[derive(Clone)]
+    struct Ticker;
+
+    impl EngineInfo for Ticker {
+        fn name(&self) -> &'static str {
+            "Ticker"
+        }
+    }
+
+    #[async_trait]
+    impl VerticalEngine<QuoteRow> for Ticker {
+        async fn search_page(
+            &self,
+            symbol: &str,
+            _page: Option<&str>,
+        ) -> Result<VerticalPage<QuoteRow>, EngineError> {
+            Ok(vec![QuoteRow {
+                symbol: symbol.to_string(),
+                price: 1.0,
+                ..Default::default()
+            }]
+            .into())
+        }
+    }
+
+    /// Web results with a news and a video card
+    #[derive(Clone)]
+    struct Serp;
+
+    impl EngineInfo for Serp {
+        fn name(&self) -> &'static str {
+            "Serp"
+        }
+    }
+
+    #[async_trait]
+    impl SearchEngine for Serp {
+        async fn search_results(
+            &self,
+            _query: &str,
+            _page: Option<&str>,
+        ) -> Result<SerpPage, EngineError> {
+            Ok(SerpPage {
+                results: vec![ResultRow {
+                    url: "https://example.com".to_string(),
+                    title: "Example".to_string(),
+                    ..Default::default()
+                }],
+                news: vec![NewsRow {
+                    url: "https://example.com/story".to_string(),
+                    title: "Story".to_string(),
+                    ..Default::default()
+                }],
+                videos: vec![VideoRow {
+                    url: "https://example.com/video".to_string(),
+                    title: "Video".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })
+        }
+
+        fn parse_raw(&self, _body: &str) -> Result<SerpPage, EngineError> {
+            Ok(SerpPage::default())
+        }
+    }
+
+    /// A vertical answering with the window each search asked for
+    #[derive(Clone)]
+    struct Windows;
+
+    impl Vertical for Windows {
+        type Result = (usize, usize);
+
+        const PER_PAGE: Option<usize> = Some(10);
+
+        fn name(&self) -> &'static str {
+            "Windows"
+        }
+
+        fn defaults(_config: &Config) -> Vec<Self> {
+            vec![Windows]
+        }
+
+        fn search(
+            self,
+            _query: String,
+            _options: SearchOptions,
+            start: usize,
+            count: usize,
+        ) -> VerticalSearch<(usize, usize)> {
+            Box::pin(async move {
+                Ok(VerticalResponse {
+                    results: vec![(start, count)],
+                    warnings: Vec::new(),
+                })
+            })
+        }
+
+        fn merge(lists: Vec<Vec<(usize, usize)>>) -> Vec<(usize, usize)> {
+            lists.into_iter().flatten().collect()
+        }
+    }
+
+    fn image(url: &str, engine: &str, phash: Option<u64>) -> ImageResult {
+        ImageResult {
+            url: url.to_string(),
+            title: String::new(),
+            width: None,
+            height: None,
+            thumbnail: None,
+            source_url: None,
+            license: None,
+            attribution: None,
+            blurhash: None,
+            phash,
+            engines: vec![engine.to_string()],
+            cached: true,
+        }
+    }
+
+    #[test]
+    fn test_merge_by_rank() {
+        let place = |osm_url: &str, engine: &str| PlaceResult {
+            name: osm_url.to_string(),
+            lat: 0.0,
+            lon: 0.0,
+            address: None,
+            osm_url: osm_url.to_string(),
+            kind: None,
+            engines: vec![engine.to_string()],
+            cached: false,
+        };
+
+        let merged = merge_by_rank(
+            vec![
+                vec![place("node/1", "Nominatim"), place("way/2", "Nominatim")],
+                vec![
+                    place("relation/3", "Photon"),
+                    place("node/1", "Photon"),
+                    place("node/4", "Photon"),
+                ],
+            ],
+            |p| p.osm_url.clone(),
+            |existing, mut p| existing.engines.append(&mut p.engines),
+        );
+
+        let urls: Vec<&str> = merged.iter().map(|p| p.osm_url.as_str()).collect();
+        assert_eq!(urls, ["node/1", "relation/3", "way/2", "node/4"]);
+        assert_eq!(merged[0].engines, ["Nominatim", "Photon"]);
+    }
+
+    #[test]
+    fn test_merge_social() {
+        let post = |url: &str, score: i64, engine: &str| SocialResult {
+            url: url.to_string(),
+            kind: "post".to_string(),
+            title: None,
+            content: None,
+            author: None,
+            community: None,
+            score: Some(score),
+            published_at: None,
+            engines: vec![engine.to_string()],
+            cached: false,
+        };
+
+        let merged = merge_social(vec![
+            vec![
+                post("https://lemmy.ml/post/1", 40, "Lemmy"),
+                post("https://lemmy.ml/post/2", 3, "Lemmy"),
+            ],
+            vec![post("https://lemmy.ml/post/1", 42, "Lemmy")],
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].score, Some(42));
+        assert_eq!(merged[0].engines, ["Lemmy", "Lemmy"]);
+    }
+
+    #[test]
+    fn test_parse_social_engine() {
+        assert!(matches!(
+            "lemmy:https://lemmy.ml/".parse::<SocialEngines>(),
+            Ok(SocialEngines::Lemmy { ref base_url }) if base_url == "https://lemmy.ml"
+        ));
+        assert!(matches!(
+            "mastodon:https://mastodon.social".parse::<SocialEngines>(),
+            Ok(SocialEngines::Mastodon { ref base_url, token: None })
+                if base_url == "https://mastodon.social"
+        ));
+        assert!(matches!(
+            "mastodon:abc123:https://mastodon.social".parse::<SocialEngines>(),
+            Ok(SocialEngines::Mastodon { ref base_url, token: Some(ref token) })
+                if base_url == "https://mastodon.social" && token == "abc123"
+        ));
+        assert!("lemmy:lemmy.ml".parse::<SocialEngines>().is_err());
+        assert!("mastodon".parse::<SocialEngines>().is_err());
+    }
+
+    #[test]
+    fn test_definition_word() {
+        for query in [
+            "define serendipity",
+            "Define: Serendipity",
+            "definition of serendipity",
+            "meaning of serendipity",
+            "what is the meaning of serendipity?",
+            "serendipity meaning",
+            "serendipity definition",
+            "what does \"serendipity\" mean?",
+        ] {
+            assert_eq!(
+                definition_word(query).as_deref(),
+                Some("serendipity"),
+                "{query}"
+            );
+        }
+        assert_eq!(
+            definition_word("define  ad   hoc").as_deref(),
+            Some("ad hoc")
+        );
+        assert_eq!(definition_word("serendipity"), None);
+        assert_eq!(definition_word("define"), None);
+        assert_eq!(definition_word("what does it take to learn rust"), None);
+        assert_eq!(
+            definition_word("meaning of the song hotel california lyrics"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sort_news() {
+        let story = |url: &str, published_at: Option<&str>| NewsResult {
+            url: url.to_string(),
+            title: String::new(),
+            source: String::new(),
+            published_at: published_at.map(str::to_string),
+            engines: vec!["DuckDuckGo".to_string()],
+            cached: false,
+        };
+
+        let news = sort_news(vec![
+            story("https://a.com", Some("2 hours ago")),
+            story("https://b.com", Some("2024-05-01T10:00:00+00:00")),
+            story("https://c.com", Some("2024-05-01T12:00:00+02:00")),
+            story("https://d.com", Some("2024-05-02T08:00:00+00:00")),
+        ]);
+        let urls: Vec<_> = news.iter().map(|n| n.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://d.com",
+                "https://b.com",
+                "https://c.com",
+                "https://a.com"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_images_by_phash() {
+        let merged = merge_images(vec![
+            image(
+                "https://a.com/cat.jpg",
+                "Brave",
+                Some(0xf0f0_f0f0_0000_ffff),
+            ),
+            // same picture rehosted, a couple of bits off
+            image(
+                "https://b.com/cat.jpg",
+                "DuckDuckGo",
+                Some(0xf0f0_f0f0_0000_fffc),
+            ),
+            image(
+                "https://c.com/dog.jpg",
+                "Brave",
+                Some(0x0f0f_0f0f_ffff_0000),
+            ),
+            image("https://d.com/unhashed.jpg", "Brave", None),
+            image("https://a.com/cat.jpg", "DuckDuckGo", None),
+        ]);
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].url, "https://a.com/cat.jpg");
+        assert_eq!(merged[0].engines, vec!["Brave", "DuckDuckGo"]);
+        assert_eq!(merged[1].url, "https://c.com/dog.jpg");
+        assert_eq!(merged[2].url, "https://d.com/unhashed.jpg");
+    }
+
+    #[test]
+    fn test_apply_result_rules() {
+        let result = |url: &str| SearchResult {
+            url: url.to_string(),
+            title: String::new(),
+            description: String::new(),
+            engines: vec!["Brave".to_string()],
+            cached: false,
+            screenshot: None,
+            published_at: None,
+            language: None,
+            extra_snippets: Vec::new(),
+        };
+        let rule = |target: &str, pinned: bool, query: Option<&str>| ResultRuleRow {
+            id: 0,
+            target: target.to_string(),
+            pinned,
+            query: query.map(str::to_string),
+        };
+
+        let results = vec![
+            result("https://www.w3schools.com/rust"),
+            result("https://example.com/rust"),
+            result("https://doc.rust-lang.org/book"),
+            result("https://notrust-lang.org/"),
+        ];
+        let rules = [
+            rule("rust-lang.org", true, Some("Rust")),
+            rule("w3schools.com", false, None),
+            rule("https://example.com/rust/", false, Some("python")),
+        ];
+
+        let urls = |results: Vec<SearchResult>| -> Vec<String> {
+            results.into_iter().map(|r| r.url).collect()
+        };
+        assert_eq!(
+            urls(apply_result_rules(results.clone(), &rules, "rust book")),
+            [
+                "https://doc.rust-lang.org/book",
+                "https://example.com/rust",
+                "https://notrust-lang.org/"
+            ]
+        );
+        // the pin only applies to rust searches
+        assert_eq!(
+            urls(apply_result_rules(results, &rules, "python")),
+            [
+                "https://doc.rust-lang.org/book",
+                "https://notrust-lang.org/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_instance_engine() {
+        let engine: SearchEngines = " SearXNG:https://searx.example.org/Search/ "
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            engine,
+            SearchEngines::SearxNG { ref base_url } if base_url == "https://searx.example.org/Search"
+        ));
+        assert_eq!(engine.name(), "SearXNG");
+
+        assert!("searxng:".parse::<SearchEngines>().is_err());
+        assert!(
+            "searxng:ftp://searx.example.org"
+                .parse::<SearchEngines>()
+                .is_err()
+        );
+        assert!(
+            "brave:https://search.brave.com"
+                .parse::<SearchEngines>()
+                .is_err()
+        );
+
+        assert_eq!(
+            "4get:https://4get.example.org"
+                .parse::<SearchEngines>()
+                .unwrap()
+                .name(),
+            "4get"
+        );
+
+        let engine: SearchEngines = "brave-api:BSA123".parse().unwrap();
+        assert!(matches!(engine, SearchEngines::BraveApi { ref api_key } if api_key == "BSA123"));
+        assert!("brave-api: ".parse::<SearchEngines>().is_err());
+        assert_eq!("kagi:abc".parse::<SearchEngines>().unwrap().name(), "Kagi");
+        assert_eq!(
+            "bing-api:abc".parse::<SearchEngines>().unwrap().name(),
+            "Bing API"
+        );
+        assert!(matches!(
+            "google-cse:abc:0123".parse::<SearchEngines>().unwrap(),
+            SearchEngines::GoogleCse { ref key, ref cx } if key == "abc" && cx == "0123"
+        ));
+        assert!("google-cse:abc".parse::<SearchEngines>().is_err());
+
+        assert!(matches!(
+            "wikipedia".parse::<SearchEngines>().unwrap(),
+            SearchEngines::Wikipedia { ref lang } if lang == "en"
+        ));
+        assert!(matches!(
+            "wikipedia:DE".parse::<SearchEngines>().unwrap(),
+            SearchEngines::Wikipedia { ref lang } if lang == "de"
+        ));
+        assert!("wikipedia:de.evil.com/".parse::<SearchEngines>().is_err());
+    }
+
+    #[test]
+    fn test_parse_image_engine() {
+        assert_eq!("ddg".parse::<ImageEngines>().unwrap().name(), "DuckDuckGo");
+        let engine: ImageEngines = "flickr:0123abcd".parse().unwrap();
+        assert!(matches!(engine, ImageEngines::Flickr { ref api_key } if api_key == "0123abcd"));
+        assert!("flickr".parse::<ImageEngines>().is_err());
+        assert!("flickr:".parse::<ImageEngines>().is_err());
+        assert_eq!(
+            "pexels:abc".parse::<ImageEngines>().unwrap().name(),
+            "Pexels"
+        );
+    }
+
+    #[test]
+    fn test_parse_files_engine() {
+        let engine: FilesEngines =
+            "torznab:http://localhost:9117/api/v2.0/indexers/all/results/torznab?apikey=abc"
+                .parse()
+                .unwrap();
+        assert!(matches!(
+            engine,
+            FilesEngines::Torznab { ref url }
+                if url == "http://localhost:9117/api/v2.0/indexers/all/results/torznab?apikey=abc"
+        ));
+        assert!("torznab:localhost".parse::<FilesEngines>().is_err());
+    }
+
+    #[test]
+    fn test_parse_shopping_engine() {
+        let engine: ShoppingEngines = "ebay:App-1234:SBX-secret".parse().unwrap();
+        assert!(matches!(
+            engine,
+            ShoppingEngines::Ebay { ref client_id, ref client_secret }
+                if client_id == "App-1234" && client_secret == "SBX-secret"
+        ));
+        assert_eq!(engine.name(), "eBay");
+        assert!("ebay:App-1234".parse::<ShoppingEngines>().is_err());
+        assert!("ebay".parse::<ShoppingEngines>().is_err());
+    }
+
+    #[test]
+    fn test_parse_podcast_engine() {
+        assert!(matches!(
+            "iTunes".parse::<PodcastEngines>(),
+            Ok(PodcastEngines::Itunes)
+        ));
+        let engine: PodcastEngines = "podcastindex:KEY123:secret456".parse().unwrap();
+        assert!(matches!(
+            engine,
+            PodcastEngines::PodcastIndex { ref api_key, ref api_secret }
+                if api_key == "KEY123" && api_secret == "secret456"
+        ));
+        assert_eq!(engine.name(), "Podcast Index");
+        assert!("podcastindex:KEY123".parse::<PodcastEngines>().is_err());
+        assert!("podcastindex".parse::<PodcastEngines>().is_err());
+    }
+
+    #[sqlx::test]
+    async fn test_private_vertical_writes_nothing() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        cache::create_search_cache(&pool).await.unwrap();
+        let queries = async || -> i64 {
+            sqlx::query_scalar("SELECT COUNT(*) FROM queries")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        let backend = cache::SqliteCache::new(pool.clone());
+        let private = SearchOptions {
+            private: true,
+            ..Default::default()
+        };
+        let news = fetch_or_cache_vertical_in(
+            &backend,
+            Headlines,
+            "rust".to_string(),
+            private,
+            0,
+            10,
+            NewsResult::from_row,
+        )
+        .await
+        .unwrap();
+        assert_eq!(news.results.len(), 1);
+        assert_eq!(queries().await, 0);
+        let engines: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM engines")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(engines, 0);
+
+        fetch_or_cache_vertical_in(
+            &backend,
+            Headlines,
+            "rust".to_string(),
+            SearchOptions::default(),
+            0,
+            10,
+            NewsResult::from_row,
+        )
+        .await
+        .unwrap();
+        assert_eq!(queries().await, 1);
+
+        // an expired query is a miss, but is left for a normal search to drop
+        let quote = async |options| {
+            fetch_or_cache_vertical_in(
+                &backend,
+                Ticker,
+                "AAPL".to_string(),
+                options,
+                0,
+                usize::MAX,
+                QuoteResult::from_row,
+            )
+            .await
+            .unwrap()
+        };
+        quote(SearchOptions::default()).await;
+        let expired = chrono::Utc::now().naive_utc() - chrono::Duration::hours(1);
+        sqlx::query("UPDATE queries SET fetched_at = ?, last_used = ?")
+            .bind(expired)
+            .bind(expired)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let fresh = quote(private).await;
+        assert!(!fresh.results[0].cached);
+        assert_eq!(queries().await, 2);
+        let stale: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM queries WHERE fetched_at = ? AND last_used = ?",
+        )
+        .bind(expired)
+        .bind(expired)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(stale, 2);
+    }
+
+    #[sqlx::test]
+    async fn test_cached_search_keeps_cards() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        cache::create_search_cache(&pool).await.unwrap();
+        let backend = cache::SqliteCache::new(pool);
+
+        let search = async || {
+            fetch_or_cache_result_in(
+                &backend,
+                Serp,
+                "rust".to_string(),
+                0,
+                10,
+                SearchOptions::default(),
+            )
+            .await
+            .unwrap()
+        };
+        let fetched = search().await;
+        assert!(!fetched.results[0].cached);
+        assert!(!fetched.news[0].cached);
+
+        let cached = search().await;
+        assert!(cached.results[0].cached);
+        assert_eq!(cached.news.len(), 1);
+        assert!(cached.news[0].cached);
+        assert_eq!(cached.videos.len(), 1);
+        assert_eq!(cached.videos[0].url, "https://example.com/video");
+    }
+
+    /// Results found only by the heuristic parse
+    #[derive(Clone)]
+    struct Guessed;
+
+    impl EngineInfo for Guessed {
+        fn name(&self) -> &'static str {
+            "Guessed"
+        }
+    }
+
+    #[async_trait]
+    impl SearchEngine for Guessed {
+        async fn search_results(
+            &self,
+            _query: &str,
+            _page: Option<&str>,
+        ) -> Result<SerpPage, EngineError> {
+            let mut page = SerpPage {
+                results: vec![ResultRow {
+                    url: "https://example.com/help".to_string(),
+                    title: "Help".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            };
+            page.diagnostics.heuristic_fallback = true;
+            Ok(page)
+        }
+
+        fn parse_raw(&self, _body: &str) -> Result<SerpPage, EngineError> {
+            Ok(SerpPage::default())
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_heuristic_page_not_cached() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        cache::create_search_cache(&pool).await.unwrap();
+        let backend = cache::SqliteCache::new(pool.clone());
+
+        let response = fetch_or_cache_result_in(
+            &backend,
+            Guessed,
+            "rust".to_string(),
+            0,
+            10,
+            SearchOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.results.len(), 1);
+
+        let queries: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM queries")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queries, 0);
+    }
+
+    #[sqlx::test]
+    async fn test_wikipedia_editions_cached_apart() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        cache::create_search_cache(&pool).await.unwrap();
+        let sqlite = cache::SqliteCache::new(pool);
+        let backend: &dyn CacheBackend = &sqlite;
+        let article = |lang: &str| format!("https://{lang}.wikipedia.org/wiki/Rust");
+
+        let now = chrono::Utc::now().naive_utc();
+        for lang in ["en", "de"] {
+            let row = ResultRow {
+                url: article(lang),
+                title: "Rust".to_string(),
+                ..Default::default()
+            };
+            let id = Wikipedia::new(lang).unwrap().id();
+            backend.store(&id, "rust", vec![row], now).await.unwrap();
+        }
+
+        // both are served from the cache, a fetch would find no more pages
+        for lang in ["en", "de"] {
+            let response = fetch_or_cache_result_in(
+                backend,
+                Wikipedia::new(lang).unwrap(),
+                "rust".to_string(),
+                0,
+                10,
+                SearchOptions::default(),
+            )
+            .await
+            .unwrap();
+            let urls: Vec<_> = response.results.iter().map(|r| r.url.clone()).collect();
+            assert_eq!(urls, [article(lang)]);
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_page_far_past_the_end() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        cache::create_search_cache(&pool).await.unwrap();
+        let backend = cache::SqliteCache::new(pool);
+
+        let options = SearchOptions {
+            page: usize::MAX,
+            ..Default::default()
+        };
+        let start = page_start(options.page, RESULTS_PER_PAGE);
+        assert_eq!(start, usize::MAX);
+        assert_eq!(page_start(options.page, IMAGES_PER_PAGE), usize::MAX);
+
+        let response = fetch_or_cache_result_in(
+            &backend,
+            Serp,
+            "rust".to_string(),
+            start,
+            RESULTS_PER_PAGE,
+            options,
+        )
+        .await
+        .unwrap();
+        assert!(response.results.is_empty());
+    }
+    #[sqlx::test]
+    async fn test_vertical_page_far_past_the_end() {
+        let options = SearchOptions {
+            page: usize::MAX,
+            ..Default::default()
+        };
+        let response = search_vertical::<Windows>("rust".to_string(), Vec::new(), options)
+            .await
+            .unwrap();
+        assert_eq!(response.results, vec![(usize::MAX, 10)]);
+    }
+
+    #[sqlx::test]
+    async fn test_refreshing_cleared_on_panic() {
+        let query_id = -578;
+        assert!(REFRESHING.lock().unwrap().insert(query_id));
+        let refreshing = Refreshing(query_id);
+
+        let task = tokio::spawn(async move {
+            let _refreshing = refreshing;
+            panic!("refresh failed");
+        });
+        assert!(task.await.unwrap_err().is_panic());
+        assert!(!REFRESHING.lock().unwrap().contains(&query_id));
+    }
+
+    #[sqlx::test]
+    async fn test_image_window_taken_after_filtering() {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        cache::create_search_cache(&pool).await.unwrap();
+        let backend = cache::SqliteCache::new(pool);
+
+        let options = ImageOptions {
+            min_width: Some(1000),
+            ..Default::default()
+        };
+        let window = async |start| {
+            fetch_or_cache_image_in(
+                &backend,
+                Gallery,
+                "rust".to_string(),
+                options.clone(),
+                start,
+                2,
+            )
+            .await
+            .unwrap()
+            .results
+            .into_iter()
+            .map(|image| image.url)
+            .collect::<Vec<_>>()
+        };
+
+        // narrow images don't count towards the window, so more pages are fetched
+        assert_eq!(
+            window(0).await,
+            [
+                "https://example.com/wide0.png",
+                "https://example.com/wide1.png"
+            ]
+        );
+        assert_eq!(
+            window(1).await,
+            [
+                "https://example.com/wide1.png",
+                "https://example.com/wide2.png"
+            ]
+        );
+    }
 }
@@ -0,0 +1,569 @@
+use async_trait::async_trait;
+use moka::{future::Cache, notification::RemovalCause};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicI64, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::cache::{
+    CacheBackend, CacheError, CacheStats, CachedRow, EngineStatsRow, ImagesRow, Purge, QueryRow,
+    ResultRow, ResultRuleRow, Rows,
+};
+
+/// Engine name and cache key of a search
+type Key = (String, String);
+
+#[derive(Debug)]
+struct Search {
+    row: QueryRow,
+    /// What the search lists of each kind, by [`Rows::kind`]
+    rows: HashMap<&'static str, Rows>,
+}
+
+type Entry = Arc<Mutex<Search>>;
+
+/// A request recorded for engine stats
+#[derive(Debug)]
+struct EngineRequest {
+    engine: String,
+    requested_at: chrono::NaiveDateTime,
+    latency_ms: i64,
+    ok: bool,
+}
+
+/// Searches of every vertical kept in memory only, for tests and deployments
+/// that must not write to disk. Set it with
+/// [`cache::set_backend`](crate::cache::set_backend) before the first search.
+///
+/// Result rules, request budgets and engine stats are kept in memory too and
+/// are lost on restart. It never opens the SQLite database, so raw responses
+/// aren't archived and alerts, screenshots, thumbnail hashes and suggestions
+/// from past searches are turned off.
+pub struct MemoryCache {
+    searches: Cache<Key, Entry>,
+    /// Key of each cached query id, entries remove themselves when dropped
+    keys: Arc<Mutex<HashMap<i64, Key>>>,
+    next_id: AtomicI64,
+    rules: Mutex<Vec<ResultRuleRow>>,
+    next_rule_id: AtomicI64,
+    /// Requests by engine name and clock hour
+    usage: Mutex<HashMap<(String, chrono::NaiveDateTime), i64>>,
+    requests: Mutex<Vec<EngineRequest>>,
+}
+
+impl MemoryCache {
+    /// Keeps up to `max_queries` searches, dropping the least used past it.
+    pub fn new(max_queries: u64) -> Self {
+        let keys: Arc<Mutex<HashMap<i64, Key>>> = Arc::default();
+        let listener_keys = keys.clone();
+        let searches = Cache::builder()
+            .max_capacity(max_queries)
+            .eviction_listener(move |_key, entry: Entry, cause| {
+                if cause != RemovalCause::Replaced {
+                    let id = entry.lock().unwrap().row.id;
+                    listener_keys.lock().unwrap().remove(&id);
+                }
+            })
+            .build();
+
+        Self {
+            searches,
+            keys,
+            next_id: AtomicI64::new(1),
+            rules: Mutex::default(),
+            next_rule_id: AtomicI64::new(1),
+            usage: Mutex::default(),
+            requests: Mutex::default(),
+        }
+    }
+
+    async fn entry(&self, query_id: i64) -> Option<Entry> {
+        let key = self.keys.lock().unwrap().get(&query_id).cloned()?;
+        self.searches.get(&key).await
+    }
+
+    /// The search of `query` by `engine`, created empty if it isn't cached.
+    async fn entry_or_insert(
+        &self,
+        engine: &str,
+        query: &str,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Entry {
+        let key = (engine.to_string(), query.to_string());
+        self.searches
+            .get_with(key.clone(), async {
+                let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+                self.keys.lock().unwrap().insert(id, key);
+                Arc::new(Mutex::new(Search {
+                    row: QueryRow {
+                        id,
+                        query: query.to_string(),
+                        engine_id: 0,
+                        fetched_at,
+                        total_results: None,
+                        has_next_page: false,
+                        next_page: None,
+                    },
+                    rows: HashMap::new(),
+                }))
+            })
+            .await
+    }
+
+    /// Removes the searches `matches` picks, returns how many.
+    async fn remove_where(&self, matches: impl Fn(&Key, &Search) -> bool) -> u64 {
+        let keys: Vec<Key> = self
+            .searches
+            .iter()
+            .filter(|(key, entry)| matches(key, &entry.lock().unwrap()))
+            .map(|(key, _)| (*key).clone())
+            .collect();
+
+        for key in &keys {
+            self.searches.invalidate(key).await;
+        }
+        self.searches.run_pending_tasks().await;
+        keys.len() as u64
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    async fn get_query(
+        &self,
+        engine: &str,
+        query: &str,
+        max_age: Option<Duration>,
+        touch: bool,
+    ) -> Result<Option<QueryRow>, CacheError> {
+        let key = (engine.to_string(), query.to_string());
+        let entry = if touch {
+            self.searches.get(&key).await
+        } else {
+            // iterating doesn't count as a use, unlike `get`
+            self.searches
+                .iter()
+                .find(|(k, _)| **k == key)
+                .map(|(_, entry)| entry)
+        };
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+        let row = entry.lock().unwrap().row.clone();
+
+        let max_age = max_age.and_then(|age| chrono::Duration::from_std(age).ok());
+        if max_age.is_some_and(|max_age| chrono::Utc::now().naive_utc() - row.fetched_at > max_age)
+        {
+            if touch {
+                self.searches.invalidate(&key).await;
+            }
+            return Ok(None);
+        }
+        Ok(Some(row))
+    }
+
+    async fn get_rows(&self, query_id: i64, kind: &str) -> Result<Option<Rows>, CacheError> {
+        Ok(match self.entry(query_id).await {
+            Some(entry) => entry.lock().unwrap().rows.get(kind).cloned(),
+            None => None,
+        })
+    }
+
+    async fn store_rows(
+        &self,
+        engine: &str,
+        query: &str,
+        rows: Rows,
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<i64, CacheError> {
+        let entry = self.entry_or_insert(engine, query, fetched_at).await;
+        let mut search = entry.lock().unwrap();
+        if rows.replaces() {
+            search.row.fetched_at = fetched_at;
+            search.rows.insert(rows.kind(), rows);
+        } else if let Some(cached) = search.rows.get_mut(rows.kind()) {
+            cached.append(rows);
+        } else {
+            search.rows.insert(rows.kind(), rows);
+        }
+        Ok(search.row.id)
+    }
+
+    async fn replace_results(
+        &self,
+        query_id: i64,
+        entries: &[ResultRow],
+        fetched_at: chrono::NaiveDateTime,
+    ) -> Result<(), CacheError> {
+        if let Some(entry) = self.entry(query_id).await {
+            let mut search = entry.lock().unwrap();
+            let rows = Rows::Results(entries.to_vec());
+            search.rows.insert(rows.kind(), rows);
+            search.row.fetched_at = fetched_at;
+        }
+        Ok(())
+    }
+
+    async fn set_page_meta(
+        &self,
+        query_id: i64,
+        total_results: Option<i64>,
+        has_next_page: bool,
+        next_page: Option<&str>,
+    ) -> Result<(), CacheError> {
+        if let Some(entry) = self.entry(query_id).await {
+            let row = &mut entry.lock().unwrap().row;
+            row.total_results = total_results;
+            row.has_next_page = has_next_page;
+            row.next_page = next_page.map(str::to_string);
+        }
+        Ok(())
+    }
+
+    async fn archive_response(
+        &self,
+        _query_id: i64,
+        _page: Option<&str>,
+        _body: &str,
+        _fetched_at: chrono::NaiveDateTime,
+    ) -> Result<(), CacheError> {
+        // only archived on disk
+        Ok(())
+    }
+
+    async fn prune_archived_responses(
+        &self,
+        _before: chrono::NaiveDateTime,
+    ) -> Result<u64, CacheError> {
+        Ok(0)
+    }
+
+    async fn add_result_rule(
+        &self,
+        target: &str,
+        pinned: bool,
+        query: Option<&str>,
+    ) -> Result<i64, CacheError> {
+        let id = self.next_rule_id.fetch_add(1, Ordering::Relaxed);
+        self.rules.lock().unwrap().push(ResultRuleRow {
+            id,
+            target: target.to_string(),
+            pinned,
+            query: query.map(str::to_string),
+        });
+        Ok(id)
+    }
+
+    async fn get_result_rules(&self) -> Result<Vec<ResultRuleRow>, CacheError> {
+        Ok(self.rules.lock().unwrap().clone())
+    }
+
+    async fn delete_result_rule(&self, id: i64) -> Result<(), CacheError> {
+        self.rules.lock().unwrap().retain(|rule| rule.id != id);
+        Ok(())
+    }
+
+    async fn get_engine_usage(
+        &self,
+        engine: &str,
+        since: chrono::NaiveDateTime,
+    ) -> Result<i64, CacheError> {
+        Ok(self
+            .usage
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|((name, hour), _)| name == engine && *hour >= since)
+            .map(|(_, requests)| requests)
+            .sum())
+    }
+
+    async fn try_add_engine_usage(
+        &self,
+        engine: &str,
+        hour: chrono::NaiveDateTime,
+        hourly: Option<u32>,
+        daily: Option<u32>,
+    ) -> Result<bool, CacheError> {
+        let mut usage = self.usage.lock().unwrap();
+        let used = |since| -> i64 {
+            usage
+                .iter()
+                .filter(|((name, h), _)| name == engine && *h >= since)
+                .map(|(_, requests)| requests)
+                .sum()
+        };
+        let limits = [(hourly, hour), (daily, hour - chrono::Duration::hours(23))];
+        if limits
+            .into_iter()
+            .any(|(limit, since)| limit.is_some_and(|limit| used(since) >= limit as i64))
+        {
+            return Ok(false);
+        }
+        *usage.entry((engine.to_string(), hour)).or_default() += 1;
+        Ok(true)
+    }
+
+    async fn record_engine_request(
+        &self,
+        engine: &str,
+        requested_at: chrono::NaiveDateTime,
+        latency_ms: i64,
+        outcome: &str,
+    ) -> Result<(), CacheError> {
+        self.requests.lock().unwrap().push(EngineRequest {
+            engine: engine.to_string(),
+            requested_at,
+            latency_ms,
+            ok: outcome == "ok",
+        });
+        Ok(())
+    }
+
+    async fn prune_engine_requests(&self, before: chrono::NaiveDateTime) -> Result<(), CacheError> {
+        self.requests
+            .lock()
+            .unwrap()
+            .retain(|request| request.requested_at >= before);
+        Ok(())
+    }
+
+    async fn get_engine_stats(
+        &self,
+        engine: &str,
+        since: chrono::NaiveDateTime,
+    ) -> Result<EngineStatsRow, CacheError> {
+        let requests = self.requests.lock().unwrap();
+        let recent = requests
+            .iter()
+            .filter(|request| request.engine == engine && request.requested_at >= since);
+
+        let mut stats = EngineStatsRow::default();
+        let mut ok_latency = 0;
+        for request in recent {
+            stats.requests += 1;
+            if request.ok {
+                stats.successes += 1;
+                ok_latency += request.latency_ms;
+            }
+        }
+        if stats.successes > 0 {
+            stats.avg_latency_ms = Some(ok_latency as f64 / stats.successes as f64);
+        }
+        Ok(stats)
+    }
+
+    async fn evict(&self, _max: usize) -> Result<u64, CacheError> {
+        // already bounded by the capacity it was built with
+        Ok(0)
+    }
+
+    async fn purge(&self, purge: Purge) -> Result<u64, CacheError> {
+        Ok(match purge {
+            Purge::Query(query) => {
+                let prefix = format!("{query}\u{1f}");
+                self.remove_where(|(_, key), _| *key == query || key.starts_with(&prefix))
+                    .await
+            }
+            Purge::Engine(engine) => {
+                let prefix = format!("{engine}:");
+                self.remove_where(|(id, _), _| *id == engine || id.starts_with(&prefix))
+                    .await
+            }
+            Purge::OlderThan(before) => {
+                self.remove_where(|_, search| search.row.fetched_at < before)
+                    .await
+            }
+            Purge::All => self.remove_where(|_, _| true).await,
+        })
+    }
+
+    async fn stats(&self) -> Result<CacheStats, CacheError> {
+        self.searches.run_pending_tasks().await;
+        let mut stats = CacheStats::default();
+        for (_, entry) in self.searches.iter() {
+            let search = entry.lock().unwrap();
+            stats.queries += 1;
+            let len = |rows: Option<&Rows>| rows.map_or(0, |rows| rows.len() as u64);
+            stats.results += len(search.rows.get(ResultRow::LIST_TABLE));
+            stats.images += len(search.rows.get(ImagesRow::LIST_TABLE));
+        }
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MemoryCache;
+    use crate::cache::{CacheBackend, CacheStats, ImagesRow, Purge, ResultRow, Rows};
+    use chrono::Utc;
+    use std::time::Duration;
+
+    fn result(url: &str) -> ResultRow {
+        ResultRow {
+            url: url.to_string(),
+            title: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache() {
+        let cache = MemoryCache::new(100);
+        let now = Utc::now().naive_utc();
+
+        assert!(
+            cache
+                .get_query("Brave", "rust", None, true)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        let query_id = cache
+            .store_rows(
+                "Brave",
+                "rust",
+                Rows::Results(vec![result("https://rust-lang.org")]),
+                now,
+            )
+            .await
+            .unwrap();
+        cache
+            .store_rows(
+                "Brave",
+                "rust",
+                Rows::Results(vec![result("https://crates.io")]),
+                now,
+            )
+            .await
+            .unwrap();
+        cache
+            .set_page_meta(query_id, None, true, Some("offset=2"))
+            .await
+            .unwrap();
+        let image = ImagesRow {
+            url: "https://rust-lang.org/logo.png".to_string(),
+            ..Default::default()
+        };
+        cache
+            .store_rows("Brave", "rust\u{1f}images", Rows::Images(vec![image]), now)
+            .await
+            .unwrap();
+
+        let row = cache
+            .get_query("Brave", "rust", None, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.id, query_id);
+        assert_eq!(row.next_page.as_deref(), Some("offset=2"));
+        let results = cache.get_rows(query_id, "query_results").await.unwrap();
+        assert_eq!(results.map(|rows| rows.len()), Some(2));
+        assert_eq!(
+            cache.stats().await.unwrap(),
+            CacheStats {
+                queries: 2,
+                results: 2,
+                images: 1
+            }
+        );
+
+        // past its max age a search misses and is dropped
+        let old = now - chrono::Duration::hours(2);
+        cache
+            .store_rows(
+                "Google",
+                "rust",
+                Rows::Results(vec![result("https://rust-lang.org")]),
+                old,
+            )
+            .await
+            .unwrap();
+        let hour = Some(Duration::from_secs(60 * 60));
+        assert!(
+            cache
+                .get_query("Google", "rust", hour, true)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        assert_eq!(
+            cache.purge(Purge::Query("rust".to_string())).await.unwrap(),
+            2
+        );
+        assert!(
+            cache
+                .get_rows(query_id, "query_results")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(cache.stats().await.unwrap(), CacheStats::default());
+    }
+
+    #[tokio::test]
+    async fn test_memory_bookkeeping() {
+        let cache = MemoryCache::new(100);
+        let now = Utc::now().naive_utc();
+        assert!(cache.sqlite().is_none());
+
+        let pin = cache
+            .add_result_rule("rust-lang.org", true, None)
+            .await
+            .unwrap();
+        cache
+            .add_result_rule("w3schools.com", false, Some("html"))
+            .await
+            .unwrap();
+        cache.delete_result_rule(pin).await.unwrap();
+        let rules = cache.get_result_rules().await.unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].target, "w3schools.com");
+
+        let hour = now - chrono::Duration::hours(1);
+        for (engine, hour) in [("Brave", hour), ("Brave", now), ("Google", now)] {
+            assert!(
+                cache
+                    .try_add_engine_usage(engine, hour, None, None)
+                    .await
+                    .unwrap()
+            );
+        }
+        assert!(
+            !cache
+                .try_add_engine_usage("Brave", now, Some(1), None)
+                .await
+                .unwrap()
+        );
+        assert_eq!(cache.get_engine_usage("Brave", hour).await.unwrap(), 2);
+        assert_eq!(cache.get_engine_usage("Brave", now).await.unwrap(), 1);
+
+        for (latency_ms, outcome) in [(100, "ok"), (300, "ok"), (5000, "timeout")] {
+            cache
+                .record_engine_request("Brave", now, latency_ms, outcome)
+                .await
+                .unwrap();
+        }
+        let stats = cache.get_engine_stats("Brave", hour).await.unwrap();
+        assert_eq!((stats.requests, stats.successes), (3, 2));
+        assert_eq!(stats.avg_latency_ms, Some(200.0));
+
+        cache
+            .prune_engine_requests(now + chrono::Duration::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            cache
+                .get_engine_stats("Brave", hour)
+                .await
+                .unwrap()
+                .requests,
+            0
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use serde::Serialize;
+use std::{collections::BTreeMap, sync::Mutex};
+
+use crate::engines::ParseDiagnostics;
+
+static PARSE_STATS: Mutex<BTreeMap<String, ParseStats>> = Mutex::new(BTreeMap::new());
+
+/// Running parse counters for one engine since startup.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ParseStats {
+    pub pages: u64,
+    pub results: u64,
+    /// Times each field's selector matched nothing, keyed by field name
+    pub missing: BTreeMap<&'static str, u64>,
+    pub heuristic_fallbacks: u64,
+}
+
+pub fn record_parse(engine: &str, diagnostics: &ParseDiagnostics) {
+    let mut stats = PARSE_STATS.lock().unwrap();
+    let stats = stats.entry(engine.to_string()).or_default();
+
+    stats.pages += 1;
+    stats.results += diagnostics.matched as u64;
+    for missing in &diagnostics.missing {
+        *stats.missing.entry(missing.field).or_default() += missing.count as u64;
+    }
+    if diagnostics.heuristic_fallback {
+        stats.heuristic_fallbacks += 1;
+    }
+}
+
+/// Snapshot of the parse counters keyed by engine name.
+pub fn parse_stats() -> BTreeMap<String, ParseStats> {
+    PARSE_STATS.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_stats, record_parse};
+    use crate::engines::ParseDiagnostics;
+
+    #[test]
+    fn test_record_parse() {
+        let mut diagnostics = ParseDiagnostics {
+            matched: 10,
+            ..Default::default()
+        };
+        diagnostics.miss("description", ".snippet");
+        diagnostics.miss("description", ".snippet");
+
+        record_parse("MetricsTest", &diagnostics);
+        record_parse("MetricsTest", &diagnostics);
+
+        let stats = &parse_stats()["MetricsTest"];
+        assert_eq!(stats.pages, 2);
+        assert_eq!(stats.results, 20);
+        assert_eq!(stats.missing["description"], 4);
+        assert_eq!(stats.heuristic_fallbacks, 0);
+    }
+}
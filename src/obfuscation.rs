@@ -0,0 +1,107 @@
+use rand::{Rng, seq::IndexedRandom};
+use tokio::time::{sleep, timeout};
+
+use crate::{
+    SearchEngines, budget, config,
+    engines::{
+        Baidu, Brave, DuckDuckGo, Google, Naver, Presearch, Qwant, SearchEngine, Startpage, Wiby,
+        Yahoo, Yandex, new_circuit, with_circuit,
+    },
+};
+
+/// Sleeps for a random time up to the configured `max_jitter`, so engine
+/// requests can't be lined up exactly with the searches behind them.
+pub(crate) async fn jitter() {
+    if let Some(max) = config::get().max_jitter {
+        let delay = rand::rng().random_range(0..=max.as_millis() as u64);
+        sleep(std::time::Duration::from_millis(delay)).await;
+    }
+}
+
+/// With a `decoy_rate` chance, searches a random `decoy_queries` entry on one
+/// of the keyless public scrapers among `engines` in the background. Decoys
+/// skip the cache and get their own circuit, so to the engine they look like
+/// any other search. They never go to keyed APIs or self-hosted instances,
+/// and are counted against the engine's budget, skipped once it is spent.
+pub(crate) fn maybe_send_decoy(engines: &[SearchEngines]) {
+    let config = config::get();
+    let scrapers: Vec<&SearchEngines> = engines.iter().filter(|e| is_decoy_engine(e)).collect();
+    let (query, engine) = {
+        let mut rng = rand::rng();
+        if !rng.random_bool(config.decoy_rate) {
+            return;
+        }
+        match (
+            config.decoy_queries.choose(&mut rng),
+            scrapers.choose(&mut rng),
+        ) {
+            (Some(query), Some(engine)) => (query.clone(), (*engine).clone()),
+            _ => return,
+        }
+    };
+
+    let decoy = async move {
+        if !matches!(
+            budget::try_spend(engine.name(), &engine.id()).await,
+            Ok(true)
+        ) {
+            return;
+        }
+        jitter().await;
+        // the response is thrown away, only the traffic matters
+        let _ = match engine {
+            SearchEngines::Brave => Brave.search_results(&query, None).await,
+            SearchEngines::DuckDuckGo => DuckDuckGo.search_results(&query, None).await,
+            SearchEngines::Startpage => Startpage.search_results(&query, None).await,
+            SearchEngines::Qwant => Qwant.search_results(&query, None).await,
+            SearchEngines::Google => Google.search_results(&query, None).await,
+            SearchEngines::Yandex => Yandex.search_results(&query, None).await,
+            SearchEngines::Wiby => Wiby.search_results(&query, None).await,
+            SearchEngines::Presearch => Presearch.search_results(&query, None).await,
+            SearchEngines::Yahoo => Yahoo.search_results(&query, None).await,
+            SearchEngines::Baidu => Baidu.search_results(&query, None).await,
+            SearchEngines::Naver => Naver.search_results(&query, None).await,
+            _ => return,
+        };
+    };
+    tokio::spawn(timeout(
+        config.engine_timeout,
+        with_circuit(new_circuit(), decoy),
+    ));
+}
+
+/// Whether decoys may be sent to `engine`: only public engines needing no
+/// key, so decoys never spend paid quota or load someone's own instance.
+fn is_decoy_engine(engine: &SearchEngines) -> bool {
+    matches!(
+        engine,
+        SearchEngines::Brave
+            | SearchEngines::DuckDuckGo
+            | SearchEngines::Startpage
+            | SearchEngines::Qwant
+            | SearchEngines::Google
+            | SearchEngines::Yandex
+            | SearchEngines::Wiby
+            | SearchEngines::Presearch
+            | SearchEngines::Yahoo
+            | SearchEngines::Baidu
+            | SearchEngines::Naver
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_decoy_engine;
+    use crate::SearchEngines;
+
+    #[test]
+    fn test_decoy_engines() {
+        assert!(is_decoy_engine(&SearchEngines::DuckDuckGo));
+        assert!(!is_decoy_engine(&SearchEngines::Kagi {
+            api_key: "key".into()
+        }));
+        assert!(!is_decoy_engine(&SearchEngines::SearxNG {
+            base_url: "https://searx.example".into()
+        }));
+    }
+}
@@ -0,0 +1,336 @@
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use reqwest::{
+    ClientBuilder, StatusCode, Url,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::{CONTENT_LENGTH, CONTENT_TYPE},
+    redirect::Policy,
+};
+use std::{net::IpAddr, sync::Arc};
+
+use crate::{
+    config::{self, ConfigError},
+    engines::rand_client_builder,
+};
+
+const MAX_IMAGE_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_REDIRECTS: usize = 5;
+
+#[derive(Debug)]
+pub enum ProxyError {
+    InvalidUrl(String),
+    Forbidden(String), // non public host
+    NotAnImage(String),
+    TooLarge,
+    Upstream(StatusCode),
+    ReqwestError(reqwest::Error),
+    Config(ConfigError), // proxy settings couldn't be loaded
+}
+
+/// An image being fetched on behalf of a client.
+pub struct ProxiedImage {
+    pub content_type: String,
+    pub content_length: Option<u64>,
+    response: reqwest::Response,
+}
+
+impl ProxiedImage {
+    /// Image bytes as they arrive, cut off with [`ProxyError::TooLarge`]
+    /// once the upstream sends more than the size limit.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Bytes, ProxyError>> + Send {
+        self.response
+            .bytes_stream()
+            .scan((0u64, false), |(received, failed), chunk| {
+                if *failed {
+                    return futures_util::future::ready(None);
+                }
+
+                let chunk = chunk.map_err(ProxyError::ReqwestError).and_then(|chunk| {
+                    *received += chunk.len() as u64;
+                    if *received > MAX_IMAGE_BYTES {
+                        Err(ProxyError::TooLarge)
+                    } else {
+                        Ok(chunk)
+                    }
+                });
+                // yield the error, then end the stream
+                *failed = chunk.is_err();
+
+                futures_util::future::ready(Some(chunk))
+            })
+    }
+}
+
+/// Fetches an image server side with the crate's HTTP settings, so the end
+/// user's browser never contacts the image host.
+///
+/// Only public http(s) hosts are fetched, redirects included, and anything
+/// that isn't an image is refused so this can't be used as an open proxy.
+pub async fn proxy_image(url: &str) -> Result<ProxiedImage, ProxyError> {
+    config::init().map_err(ProxyError::Config)?;
+    let url = Url::parse(url).map_err(|e| ProxyError::InvalidUrl(e.to_string()))?;
+    check_public(&url)?;
+
+    let client = rand_client_builder()
+        .and_then(|builder| public_only(builder).build())
+        .map_err(ProxyError::ReqwestError)?;
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(ProxyError::ReqwestError)?;
+
+    if !response.status().is_success() {
+        return Err(ProxyError::Upstream(response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    if !is_image(&content_type) {
+        return Err(ProxyError::NotAnImage(content_type));
+    }
+
+    let content_length = response
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+    if content_length.is_some_and(|len| len > MAX_IMAGE_BYTES) {
+        return Err(ProxyError::TooLarge);
+    }
+
+    Ok(ProxiedImage {
+        content_type,
+        content_length,
+        response,
+    })
+}
+
+/// Rewrites an image url to go through a proxy endpoint, e.g.
+/// `proxied_url("/image", url)` for the route served by [`router`].
+pub fn proxied_url(endpoint: &str, url: &str) -> String {
+    format!(
+        "{endpoint}?url={}",
+        utf8_percent_encode(url, NON_ALPHANUMERIC)
+    )
+}
+
+/// Restricts a client to public hosts: redirects are checked with
+/// [`check_public`] and every resolved address must be public, so a domain
+/// pointing (or rebinding) at a private network is refused as well.
+pub(crate) fn public_only(builder: ClientBuilder) -> ClientBuilder {
+    builder
+        .dns_resolver(Arc::new(PublicResolver))
+        .redirect(Policy::custom(|attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error("too many redirects")
+            } else if check_public(attempt.url()).is_err() {
+                attempt.stop()
+            } else {
+                attempt.follow()
+            }
+        }))
+}
+
+/// System resolver that drops non public addresses, failing the lookup
+/// when none are left.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<_> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{host} does not resolve to a public address").into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+pub(crate) fn check_public(url: &Url) -> Result<(), ProxyError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ProxyError::InvalidUrl(format!(
+            "Unsupported scheme {}",
+            url.scheme()
+        )));
+    }
+
+    let public = match url.host() {
+        Some(url::Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain != "localhost"
+                && !domain.ends_with(".localhost")
+                && !domain.ends_with(".local")
+                && !domain.ends_with(".internal")
+        }
+        Some(url::Host::Ipv4(ip)) => is_public_ip(IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => is_public_ip(IpAddr::V6(ip)),
+        None => false,
+    };
+
+    if public {
+        Ok(())
+    } else {
+        Err(ProxyError::Forbidden(url.to_string()))
+    }
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                // carrier grade NAT, 100.64.0.0/10
+                || (ip.octets()[0] == 100 && ip.octets()[1] & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || first & 0xfe00 == 0xfc00 // unique local
+                    || first & 0xffc0 == 0xfe80) // link local
+            }
+        },
+    }
+}
+
+fn is_image(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|mime| mime.trim().to_ascii_lowercase().starts_with("image/"))
+}
+
+/// `GET /image?url=...` streaming the image back through [`proxy_image`].
+#[cfg(feature = "server")]
+pub fn router() -> axum::Router {
+    use axum::{
+        Router,
+        body::Body,
+        extract::Query,
+        http::{HeaderValue, header},
+        response::{IntoResponse, Response},
+        routing::get,
+    };
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct ImageQuery {
+        url: String,
+    }
+
+    async fn image(Query(query): Query<ImageQuery>) -> Response {
+        let image = match proxy_image(&query.url).await {
+            Ok(image) => image,
+            Err(e) => {
+                let status = match e {
+                    ProxyError::InvalidUrl(_) => StatusCode::BAD_REQUEST,
+                    ProxyError::Forbidden(_) => StatusCode::FORBIDDEN,
+                    ProxyError::NotAnImage(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    ProxyError::TooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+                    ProxyError::Config(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                    ProxyError::Upstream(_) | ProxyError::ReqwestError(_) => {
+                        StatusCode::BAD_GATEWAY
+                    }
+                };
+                return status.into_response();
+            }
+        };
+
+        let content_type = HeaderValue::from_str(&image.content_type).ok();
+        let stream = image
+            .into_stream()
+            .map(|chunk| chunk.map_err(|e| std::io::Error::other(format!("{e:?}"))));
+
+        let mut response = Response::new(Body::from_stream(stream));
+        let headers = response.headers_mut();
+        if let Some(content_type) = content_type {
+            headers.insert(header::CONTENT_TYPE, content_type);
+        }
+        // served from our origin, so images like svg must not run scripts
+        headers.insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static("default-src 'none'; style-src 'unsafe-inline'; sandbox"),
+        );
+        headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        );
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=86400"),
+        );
+        response
+    }
+
+    Router::new().route("/image", get(image))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PublicResolver, check_public, is_image, proxied_url};
+    use reqwest::{
+        Url,
+        dns::{Name, Resolve},
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn test_check_public() {
+        let public = |url: &str| check_public(&Url::parse(url).unwrap()).is_ok();
+
+        assert!(public("https://upload.wikimedia.org/a.png"));
+        assert!(public("http://93.184.216.34/a.png"));
+
+        assert!(!public("file:///etc/passwd"));
+        assert!(!public("http://localhost:8080/a.png"));
+        assert!(!public("http://127.0.0.1/a.png"));
+        assert!(!public("http://192.168.1.1/a.png"));
+        assert!(!public("http://169.254.169.254/latest/meta-data"));
+        assert!(!public("http://100.64.0.1/a.png"));
+        assert!(!public("http://[::1]/a.png"));
+        assert!(!public("http://[fd00::1]/a.png"));
+        assert!(!public("http://[::ffff:10.0.0.1]/a.png"));
+    }
+
+    #[tokio::test]
+    async fn test_public_resolver() {
+        // a host name the resolver maps to loopback
+        let name = Name::from_str("localhost").unwrap();
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+
+    #[test]
+    fn test_is_image() {
+        assert!(is_image("image/png"));
+        assert!(is_image("Image/JPEG; charset=binary"));
+        assert!(!is_image("text/html; charset=utf-8"));
+        assert!(!is_image(""));
+    }
+
+    #[test]
+    fn test_proxied_url() {
+        assert_eq!(
+            proxied_url("/image", "https://a.com/a.png?s=1"),
+            "/image?url=https%3A%2F%2Fa%2Ecom%2Fa%2Epng%3Fs%3D1"
+        );
+    }
+}
@@ -0,0 +1,116 @@
+/// Crypto currencies recognised by name or ticker, as in "bitcoin price" or
+/// "eth to eur". Other coins can still be asked for as a pair, e.g. `PEPE-USD`.
+const COINS: [(&str, &str); 16] = [
+    ("bitcoin", "BTC"),
+    ("ethereum", "ETH"),
+    ("tether", "USDT"),
+    ("solana", "SOL"),
+    ("ripple", "XRP"),
+    ("dogecoin", "DOGE"),
+    ("cardano", "ADA"),
+    ("tron", "TRX"),
+    ("polkadot", "DOT"),
+    ("litecoin", "LTC"),
+    ("monero", "XMR"),
+    ("chainlink", "LINK"),
+    ("avalanche", "AVAX"),
+    ("stellar", "XLM"),
+    ("toncoin", "TON"),
+    ("bitcoin cash", "BCH"),
+];
+
+/// Fiat currency crypto prices are quoted in unless the query names one
+const DEFAULT_CURRENCY: &str = "USD";
+
+/// The symbol a ticker style query asks the price of, uppercase: a stock
+/// ticker such as `AAPL` for "$aapl" or "aapl stock price", or a crypto pair
+/// such as `BTC-USD` for "bitcoin price", "btc to usd" or "btc-usd". `None`
+/// for any other query.
+pub(crate) fn quote_symbol(query: &str) -> Option<String> {
+    let query = query.trim().trim_end_matches('?').to_lowercase();
+
+    if let Some(ticker) = query.strip_prefix('$') {
+        return is_ticker(ticker).then(|| ticker.to_uppercase());
+    }
+    if let Some((coin, currency)) = query.split_once(['-', '/'])
+        && let Some(pair) = crypto_pair(coin, currency)
+    {
+        return Some(pair);
+    }
+
+    let words: Vec<&str> = query.split_whitespace().collect();
+    match words.as_slice() {
+        [ticker, "stock"]
+        | [ticker, "stock", "price" | "quote"]
+        | [ticker, "share" | "shares"]
+        | [ticker, "share", "price"]
+        | ["stock" | "shares", ticker] => is_ticker(ticker).then(|| ticker.to_uppercase()),
+        [coin @ .., "price"] | ["price", "of", coin @ ..] => {
+            crypto_pair(&coin.join(" "), DEFAULT_CURRENCY)
+        }
+        [coin @ .., "to" | "in", currency] => crypto_pair(&coin.join(" "), currency),
+        _ => None,
+    }
+}
+
+/// Exchange tickers are a few letters, with a class or market suffix on
+/// some exchanges, e.g. `BRK.B` or `VOD.L`.
+fn is_ticker(ticker: &str) -> bool {
+    let (base, suffix) = ticker.split_once('.').unwrap_or((ticker, ""));
+    (1..=5).contains(&base.len())
+        && base.chars().all(|c| c.is_ascii_alphabetic())
+        && suffix.len() <= 2
+        && suffix.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// `COIN-CURRENCY` when `coin` is a known crypto currency and `currency` a
+/// three letter code.
+fn crypto_pair(coin: &str, currency: &str) -> Option<String> {
+    let coin = coin.trim();
+    let currency = currency.trim();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let ticker = COINS
+        .iter()
+        .find(|(name, ticker)| *name == coin || ticker.eq_ignore_ascii_case(coin))
+        .map(|(_, ticker)| *ticker)?;
+    Some(format!("{ticker}-{}", currency.to_uppercase()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::quote_symbol;
+
+    #[test]
+    fn test_quote_symbol() {
+        for (query, symbol) in [
+            ("$aapl", "AAPL"),
+            ("$BRK.B", "BRK.B"),
+            ("AAPL stock", "AAPL"),
+            ("msft stock price?", "MSFT"),
+            ("tsla share price", "TSLA"),
+            ("stock nvda", "NVDA"),
+            ("bitcoin price", "BTC-USD"),
+            ("Bitcoin Cash price", "BCH-USD"),
+            ("price of eth", "ETH-USD"),
+            ("btc to eur", "BTC-EUR"),
+            ("BTC-USD", "BTC-USD"),
+            ("xmr/gbp", "XMR-GBP"),
+        ] {
+            assert_eq!(quote_symbol(query).as_deref(), Some(symbol), "{query}");
+        }
+
+        for query in [
+            "aapl",
+            "milk price",
+            "stock market news",
+            "$100 to eur",
+            "how to price a house",
+            "self-hosted search",
+        ] {
+            assert_eq!(quote_symbol(query), None, "{query}");
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use regex::Regex;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::{Map, Value};
+use std::sync::LazyLock;
+
+use crate::{
+    cache::RecipeRow,
+    engines::{EngineError, rand_client_builder},
+    proxy::{check_public, public_only},
+};
+
+const MAX_PAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Fetches a result page and reads its schema.org Recipe, `None` when the
+/// page has none. Only public hosts are fetched, as with the image proxy.
+pub(crate) async fn fetch_recipe(url: &str) -> Result<Option<RecipeRow>, EngineError> {
+    let Ok(parsed) = Url::parse(url) else {
+        return Ok(None);
+    };
+    if check_public(&parsed).is_err() {
+        return Ok(None);
+    }
+
+    let mut response = rand_client_builder()
+        .and_then(|builder| public_only(builder).build())
+        .map_err(EngineError::ReqwestError)?
+        .get(parsed)
+        .send()
+        .await
+        .map_err(EngineError::ReqwestError)?
+        .error_for_status()
+        .map_err(EngineError::ReqwestError)?;
+
+    if response
+        .content_length()
+        .is_some_and(|len| len > MAX_PAGE_BYTES)
+    {
+        return Err(EngineError::ParseError("Recipe page too large".to_string()));
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(EngineError::ReqwestError)? {
+        body.extend_from_slice(&chunk);
+        if body.len() as u64 > MAX_PAGE_BYTES {
+            return Err(EngineError::ParseError("Recipe page too large".to_string()));
+        }
+    }
+
+    Ok(extract_recipe(&String::from_utf8_lossy(&body), url))
+}
+
+/// The page's Recipe from its JSON-LD, which nearly every recipe site and
+/// plugin emits, else from microdata.
+pub(crate) fn extract_recipe(html: &str, url: &str) -> Option<RecipeRow> {
+    static LD_JSON: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"script[type="application/ld+json"]"#).unwrap());
+
+    let document = Html::parse_document(html);
+    document
+        .select(&LD_JSON)
+        .filter_map(|script| serde_json::from_str::<Value>(&script.text().collect::<String>()).ok())
+        .find_map(|json| from_json_ld(find_recipe(&json)?, url))
+        .or_else(|| from_microdata(&document, url))
+}
+
+/// The first object typed Recipe, looking through `@graph`s, arrays and
+/// nested entities like a WebPage's `mainEntity`.
+fn find_recipe(value: &Value) -> Option<&Map<String, Value>> {
+    match value {
+        Value::Object(object) if is_recipe(object.get("@type")) => Some(object),
+        Value::Object(object) => object.values().find_map(find_recipe),
+        Value::Array(values) => values.iter().find_map(find_recipe),
+        _ => None,
+    }
+}
+
+fn is_recipe(kind: Option<&Value>) -> bool {
+    match kind {
+        Some(Value::String(kind)) => kind == "Recipe",
+        Some(Value::Array(kinds)) => kinds.iter().any(|kind| kind == "Recipe"),
+        _ => false,
+    }
+}
+
+fn from_json_ld(recipe: &Map<String, Value>, url: &str) -> Option<RecipeRow> {
+    let string = |key: &str| match recipe.get(key)? {
+        Value::String(s) => clean(s),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Array(values) => values.first()?.as_str().and_then(clean),
+        _ => None,
+    };
+    let ingredients = match recipe
+        .get("recipeIngredient")
+        .or_else(|| recipe.get("ingredients"))
+    {
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(Value::as_str)
+            .filter_map(clean)
+            .collect(),
+        Some(Value::String(s)) => clean(s).into_iter().collect(),
+        _ => Vec::new(),
+    };
+    let rating = recipe.get("aggregateRating").and_then(Value::as_object);
+    let rating_field = |key: &str| match rating?.get(key)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().replace(',', ".").parse().ok(),
+        _ => None,
+    };
+
+    Some(fill_total_time(RecipeRow {
+        url: url.to_string(),
+        title: string("name")?,
+        image: recipe.get("image").and_then(image_url),
+        ingredients,
+        prep_time: string("prepTime").and_then(|d| duration_minutes(&d)),
+        cook_time: string("cookTime").and_then(|d| duration_minutes(&d)),
+        total_time: string("totalTime").and_then(|d| duration_minutes(&d)),
+        yields: string("recipeYield"),
+        rating: rating_field("ratingValue"),
+        rating_count: rating_field("ratingCount")
+            .or_else(|| rating_field("reviewCount"))
+            .map(|count| count as u32),
+    }))
+}
+
+/// `image` is a url, an ImageObject or a list of either.
+fn image_url(image: &Value) -> Option<String> {
+    match image {
+        Value::String(url) => Some(url.clone()),
+        Value::Object(object) => object.get("url").and_then(image_url),
+        Value::Array(images) => images.first().and_then(image_url),
+        _ => None,
+    }
+}
+
+fn from_microdata(document: &Html, url: &str) -> Option<RecipeRow> {
+    static RECIPE: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"[itemscope][itemtype$="schema.org/Recipe"]"#).unwrap());
+    static NAME: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"[itemprop="name"]"#).unwrap());
+    static IMAGE: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"[itemprop="image"]"#).unwrap());
+    static INGREDIENT: LazyLock<Selector> = LazyLock::new(|| {
+        Selector::parse(r#"[itemprop="recipeIngredient"], [itemprop="ingredients"]"#).unwrap()
+    });
+    static PREP_TIME: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"[itemprop="prepTime"]"#).unwrap());
+    static COOK_TIME: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"[itemprop="cookTime"]"#).unwrap());
+    static TOTAL_TIME: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"[itemprop="totalTime"]"#).unwrap());
+    static YIELD: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"[itemprop="recipeYield"]"#).unwrap());
+    static RATING: LazyLock<Selector> =
+        LazyLock::new(|| Selector::parse(r#"[itemprop="ratingValue"]"#).unwrap());
+    static RATING_COUNT: LazyLock<Selector> = LazyLock::new(|| {
+        Selector::parse(r#"[itemprop="ratingCount"], [itemprop="reviewCount"]"#).unwrap()
+    });
+
+    let recipe = document.select(&RECIPE).next()?;
+    let first = |selector: &Selector| property(recipe.select(selector).next()?);
+    let number =
+        |selector: &Selector| -> Option<f64> { first(selector)?.replace(',', ".").parse().ok() };
+
+    Some(fill_total_time(RecipeRow {
+        url: url.to_string(),
+        title: first(&NAME)?,
+        image: recipe.select(&IMAGE).next().and_then(|image| {
+            ["src", "content", "href"]
+                .into_iter()
+                .find_map(|attr| image.attr(attr))
+                .map(str::to_string)
+        }),
+        ingredients: recipe.select(&INGREDIENT).filter_map(property).collect(),
+        prep_time: first(&PREP_TIME).and_then(|d| duration_minutes(&d)),
+        cook_time: first(&COOK_TIME).and_then(|d| duration_minutes(&d)),
+        total_time: first(&TOTAL_TIME).and_then(|d| duration_minutes(&d)),
+        yields: first(&YIELD),
+        rating: number(&RATING),
+        rating_count: number(&RATING_COUNT).map(|count| count as u32),
+    }))
+}
+
+/// A microdata property's value, from `content` or `datetime` when the
+/// visible text is meant for people, e.g. "1 hour" for `PT1H`.
+fn property(element: ElementRef) -> Option<String> {
+    match element.attr("content").or_else(|| element.attr("datetime")) {
+        Some(value) => clean(value),
+        None => clean(&element.text().collect::<String>()),
+    }
+}
+
+/// Pages often leave out the total when there is a prep and cook time.
+fn fill_total_time(mut recipe: RecipeRow) -> RecipeRow {
+    if recipe.total_time.is_none()
+        && let (Some(prep), Some(cook)) = (recipe.prep_time, recipe.cook_time)
+    {
+        recipe.total_time = Some(prep + cook);
+    }
+    recipe
+}
+
+/// Minutes of an ISO 8601 duration like `PT1H30M` or `P0DT0H45M`, seconds
+/// rounded up.
+fn duration_minutes(duration: &str) -> Option<u32> {
+    static DURATION: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^P(?:(\d+)D)?(?:T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+)(?:\.\d+)?S)?)?$").unwrap()
+    });
+
+    let captures = DURATION.captures(duration.trim())?;
+    let part = |i: usize| -> u32 {
+        captures
+            .get(i)
+            .and_then(|m| m.as_str().parse().ok())
+            .unwrap_or(0)
+    };
+    let minutes = part(1) * 24 * 60 + part(2) * 60 + part(3) + part(4).div_ceil(60);
+    // a bare `P` or `PT` says nothing
+    captures
+        .iter()
+        .skip(1)
+        .any(|m| m.is_some())
+        .then_some(minutes)
+}
+
+/// Text with entities decoded, JSON-LD is often written with them, and the
+/// whitespace collapsed.
+fn clean(text: &str) -> Option<String> {
+    let decoded: String = match text.contains('&') || text.contains('<') {
+        true => Html::parse_fragment(text).root_element().text().collect(),
+        false => text.to_string(),
+    };
+    let text = decoded.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{duration_minutes, extract_recipe};
+
+    #[test]
+    fn test_duration_minutes() {
+        assert_eq!(duration_minutes("PT1H30M"), Some(90));
+        assert_eq!(duration_minutes("P0DT0H45M"), Some(45));
+        assert_eq!(duration_minutes("PT90S"), Some(2));
+        assert_eq!(duration_minutes("P1D"), Some(1440));
+        assert_eq!(duration_minutes("PT"), None);
+        assert_eq!(duration_minutes("45 minutes"), None);
+    }
+
+    #[test]
+    fn test_extract_json_ld() {
+        let recipe = extract_recipe(
+            r#"<html><head>
+<script type="application/ld+json">{"@context": "https://schema.org", "@type": "Organization", "name": "Crab Kitchen"}</script>
+<script type="application/ld+json">
+{"@context": "https://schema.org", "@graph": [
+  {"@type": "WebPage", "@id": "https://example.com/pancakes"},
+  {"@type": ["Recipe", "NewsArticle"], "name": "Fluffy pancakes",
+   "image": [{"@type": "ImageObject", "url": "https://example.com/pancakes.jpg"}],
+   "recipeIngredient": ["1 &frac12; cups flour", "  2 eggs ", ""],
+   "prepTime": "PT10M", "cookTime": "PT20M", "recipeYield": ["4", "4 servings"],
+   "aggregateRating": {"@type": "AggregateRating", "ratingValue": "4.8", "ratingCount": 1234}}
+]}
+</script></head><body></body></html>"#,
+            "https://example.com/pancakes",
+        )
+        .unwrap();
+
+        assert_eq!(recipe.url, "https://example.com/pancakes");
+        assert_eq!(recipe.title, "Fluffy pancakes");
+        assert_eq!(
+            recipe.image.as_deref(),
+            Some("https://example.com/pancakes.jpg")
+        );
+        assert_eq!(recipe.ingredients, ["1 ½ cups flour", "2 eggs"]);
+        assert_eq!((recipe.prep_time, recipe.cook_time), (Some(10), Some(20)));
+        assert_eq!(recipe.total_time, Some(30));
+        assert_eq!(recipe.yields.as_deref(), Some("4"));
+        assert_eq!(recipe.rating, Some(4.8));
+        assert_eq!(recipe.rating_count, Some(1234));
+    }
+
+    #[test]
+    fn test_extract_microdata() {
+        let recipe = extract_recipe(
+            r#"<div itemscope itemtype="http://schema.org/Recipe">
+  <h1 itemprop="name">Grandma's soup</h1>
+  <img itemprop="image" src="https://example.com/soup.jpg">
+  <time itemprop="totalTime" datetime="PT1H">1 hour</time>
+  <ul><li itemprop="recipeIngredient">3 carrots</li><li itemprop="recipeIngredient">1 onion</li></ul>
+  <div itemprop="aggregateRating" itemscope itemtype="http://schema.org/AggregateRating">
+    <span itemprop="ratingValue">4,5</span> from <span itemprop="reviewCount">12</span> reviews
+  </div>
+</div>"#,
+            "https://example.com/soup",
+        )
+        .unwrap();
+
+        assert_eq!(recipe.title, "Grandma's soup");
+        assert_eq!(
+            recipe.image.as_deref(),
+            Some("https://example.com/soup.jpg")
+        );
+        assert_eq!(recipe.ingredients, ["3 carrots", "1 onion"]);
+        assert_eq!(recipe.total_time, Some(60));
+        assert_eq!(recipe.rating, Some(4.5));
+        assert_eq!(recipe.rating_count, Some(12));
+
+        assert!(extract_recipe("<p>No recipe here</p>", "https://example.com").is_none());
+    }
+}
@@ -0,0 +1,142 @@
+use reqwest::Url;
+
+use crate::{
+    config::{self, Redirects, RewriteRule},
+    engines::SerpPage,
+};
+
+/// Link to `url` on the configured privacy frontend for its site, or `None`
+/// if the site has no frontend configured.
+pub fn redirect(url: &str, redirects: &Redirects) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let host = url.host_str()?.to_ascii_lowercase();
+    let host = ["www.", "m.", "mobile."]
+        .iter()
+        .find_map(|prefix| host.strip_prefix(prefix))
+        .unwrap_or(&host);
+
+    let (instance, path) = match host {
+        // short links carry the video id as their path
+        "youtu.be" => (
+            redirects.invidious.as_ref()?,
+            format!("/watch?v={}", url.path().trim_start_matches('/')),
+        ),
+        "youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+            (redirects.invidious.as_ref()?, path_and_query(&url))
+        }
+        "reddit.com" | "old.reddit.com" | "new.reddit.com" => {
+            (redirects.redlib.as_ref()?, path_and_query(&url))
+        }
+        "twitter.com" | "x.com" => (redirects.nitter.as_ref()?, path_and_query(&url)),
+        "medium.com" => (redirects.scribe.as_ref()?, path_and_query(&url)),
+        _ => return None,
+    };
+
+    Some(format!("{}{path}", instance.trim_end_matches('/')))
+}
+
+fn path_and_query(url: &Url) -> String {
+    match url.query() {
+        Some(query) => format!("{}?{query}", url.path()),
+        None => url.path().to_string(),
+    }
+}
+
+/// `url` with every matching user rule applied in order, or `None` if no
+/// rule matched.
+pub fn rewrite(url: &str, rules: &[RewriteRule]) -> Option<String> {
+    let mut rewritten: Option<String> = None;
+    for rule in rules {
+        let current = rewritten.as_deref().unwrap_or(url);
+        if rule.pattern.is_match(current) {
+            rewritten = Some(
+                rule.pattern
+                    .replace(current, rule.replacement.as_str())
+                    .into_owned(),
+            );
+        }
+    }
+
+    rewritten
+}
+
+/// Rewrites every link of a freshly fetched page, before it's cached.
+pub(crate) fn rewrite_page(page: &mut SerpPage) {
+    let config = config::get();
+    let urls = page
+        .results
+        .iter_mut()
+        .map(|r| &mut r.url)
+        .chain(page.news.iter_mut().map(|n| &mut n.url))
+        .chain(page.videos.iter_mut().map(|v| &mut v.url));
+
+    for url in urls {
+        if let Some(redirected) = redirect(url, &config.redirects) {
+            *url = redirected;
+        }
+        if let Some(rewritten) = rewrite(url, &config.rewrite_rules) {
+            *url = rewritten;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{redirect, rewrite};
+    use crate::config::{Redirects, RewriteRule};
+    use regex::Regex;
+
+    #[test]
+    fn test_redirect() {
+        let redirects = Redirects {
+            invidious: Some("https://yewtu.be/".to_string()),
+            redlib: Some("https://safereddit.com".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            redirect("https://www.youtube.com/watch?v=dQw4w9WgXcQ", &redirects).as_deref(),
+            Some("https://yewtu.be/watch?v=dQw4w9WgXcQ")
+        );
+        assert_eq!(
+            redirect("https://youtu.be/dQw4w9WgXcQ", &redirects).as_deref(),
+            Some("https://yewtu.be/watch?v=dQw4w9WgXcQ")
+        );
+        assert_eq!(
+            redirect("https://old.reddit.com/r/rust/comments/abc/", &redirects).as_deref(),
+            Some("https://safereddit.com/r/rust/comments/abc/")
+        );
+
+        // no nitter instance configured
+        assert!(redirect("https://x.com/rustlang", &redirects).is_none());
+        assert!(redirect("https://notyoutube.com/watch", &redirects).is_none());
+        assert!(redirect("not a url", &redirects).is_none());
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let rule = |pattern: &str, replacement: &str| RewriteRule {
+            pattern: Regex::new(pattern).unwrap(),
+            replacement: replacement.to_string(),
+        };
+        let rules = [
+            rule(
+                r"^https://(en\.)?m\.wikipedia\.org/",
+                "https://${1}wikipedia.org/",
+            ),
+            rule(r"^https://(www\.)?reddit\.com/", "https://old.reddit.com/"),
+            rule(r"\?utm_source=[^&]*$", ""),
+        ];
+
+        assert_eq!(
+            rewrite("https://en.m.wikipedia.org/wiki/Rust", &rules).as_deref(),
+            Some("https://en.wikipedia.org/wiki/Rust")
+        );
+        // later rules see earlier rewrites
+        assert_eq!(
+            rewrite("https://www.reddit.com/r/rust?utm_source=share", &rules).as_deref(),
+            Some("https://old.reddit.com/r/rust")
+        );
+        assert!(rewrite("https://example.com/", &rules).is_none());
+    }
+}
@@ -0,0 +1,191 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chromiumoxide::{
+    Browser, BrowserConfig, cdp::browser_protocol::page::CaptureScreenshotFormat, error::CdpError,
+    page::ScreenshotParams,
+};
+use futures_util::StreamExt;
+use image::codecs::jpeg::JpegEncoder;
+use reqwest::Url;
+use sqlx::SqlitePool;
+use std::time::Duration;
+use tokio::{task::JoinHandle, time::timeout};
+
+use crate::{
+    SearchResult, cache,
+    config::{self, ConfigError},
+    proxy::{ProxyError, check_public},
+};
+
+const VIEWPORT_WIDTH: u32 = 1280;
+const VIEWPORT_HEIGHT: u32 = 800;
+const PREVIEW_WIDTH: u32 = 320; // screenshots are downscaled to this
+const JPEG_QUALITY: u8 = 70;
+const PAGE_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug)]
+pub enum ScreenshotError {
+    Proxy(ProxyError), // invalid or non public url
+    Launch(String),
+    Browser(CdpError),
+    Image(image::ImageError),
+    Timeout,
+    Config(ConfigError), // proxy settings couldn't be loaded
+}
+
+/// A headless Chromium behind the configured proxy, kept open to take a
+/// batch of screenshots.
+pub struct Screenshotter {
+    browser: Browser,
+    handler: JoinHandle<()>,
+}
+
+impl Screenshotter {
+    /// Starts Chromium, which has to be installed where chromiumoxide looks
+    /// for it (or at `CHROME`).
+    pub async fn launch() -> Result<Self, ScreenshotError> {
+        let mut builder = BrowserConfig::builder().window_size(VIEWPORT_WIDTH, VIEWPORT_HEIGHT);
+        let proxy = &config::init().map_err(ScreenshotError::Config)?.proxy;
+        if let Some(proxy) = proxy {
+            // chromium resolves hosts through socks5 proxies already and
+            // doesn't know the socks5h scheme
+            let proxy = proxy.replacen("socks5h://", "socks5://", 1);
+            builder = builder.arg(format!("--proxy-server={proxy}"));
+        }
+        let config = builder.build().map_err(ScreenshotError::Launch)?;
+
+        let (browser, mut handler) = Browser::launch(config)
+            .await
+            .map_err(ScreenshotError::Browser)?;
+        let handler = tokio::spawn(async move {
+            while let Some(event) = handler.next().await {
+                if event.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self { browser, handler })
+    }
+
+    /// Jpeg preview of the top of the page at `url`.
+    pub async fn capture(&self, url: &str) -> Result<Vec<u8>, ScreenshotError> {
+        let parsed = Url::parse(url)
+            .map_err(|e| ScreenshotError::Proxy(ProxyError::InvalidUrl(e.to_string())))?;
+        check_public(&parsed).map_err(ScreenshotError::Proxy)?;
+
+        let png = timeout(PAGE_TIMEOUT, async {
+            let page = self.browser.new_page(url).await?;
+            page.wait_for_navigation().await?;
+            let png = page
+                .screenshot(
+                    ScreenshotParams::builder()
+                        .format(CaptureScreenshotFormat::Png)
+                        .build(),
+                )
+                .await;
+            page.close().await?;
+            png
+        })
+        .await
+        .map_err(|_| ScreenshotError::Timeout)?
+        .map_err(ScreenshotError::Browser)?;
+
+        tokio::task::spawn_blocking(move || preview(&png))
+            .await
+            .expect("screenshot task panicked")
+    }
+
+    pub async fn close(mut self) {
+        let _ = self.browser.close().await;
+        let _ = self.browser.wait().await;
+        self.handler.abort();
+    }
+}
+
+/// Downscales a png screenshot to a small jpeg.
+fn preview(png: &[u8]) -> Result<Vec<u8>, ScreenshotError> {
+    let image = image::load_from_memory(png).map_err(ScreenshotError::Image)?;
+    let height = image.height() * PREVIEW_WIDTH / image.width().max(1);
+    let image = image
+        .resize_exact(
+            PREVIEW_WIDTH,
+            height.max(1),
+            image::imageops::FilterType::Triangle,
+        )
+        .to_rgb8();
+
+    let mut jpeg = Vec::new();
+    image
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY))
+        .map_err(ScreenshotError::Image)?;
+
+    Ok(jpeg)
+}
+
+/// Fills in cached screenshots for the top `config.screenshots` results and
+/// takes the missing ones in the background, unless the search is private.
+pub(crate) async fn attach_screenshots(
+    pool: &'static SqlitePool,
+    results: &mut [SearchResult],
+    private: bool,
+) {
+    let config = config::get();
+    let mut missing = Vec::new();
+
+    for result in results.iter_mut().take(config.screenshots) {
+        match cache::get_screenshot(pool, &result.url).await {
+            Ok(Some(jpeg)) => {
+                result.screenshot =
+                    Some(format!("data:image/jpeg;base64,{}", STANDARD.encode(jpeg)));
+            }
+            Ok(None) => missing.push(result.url.clone()),
+            Err(e) => eprintln!("Failed to read screenshot for {}: {e:?}", result.url),
+        }
+    }
+
+    if !missing.is_empty() && !private && config.persist_cache {
+        // screenshots show up once the results are searched again
+        tokio::spawn(cache_screenshots(pool, missing));
+    }
+}
+
+async fn cache_screenshots(pool: &SqlitePool, urls: Vec<String>) {
+    let screenshotter = match Screenshotter::launch().await {
+        Ok(screenshotter) => screenshotter,
+        Err(e) => {
+            eprintln!("Failed to launch browser for screenshots: {e:?}");
+            return;
+        }
+    };
+
+    for url in urls {
+        let Ok(jpeg) = screenshotter.capture(&url).await else {
+            continue;
+        };
+        if let Err(e) = cache::set_screenshot(pool, &url, &jpeg).await {
+            eprintln!("Failed to cache screenshot for {url}: {e:?}");
+        }
+    }
+
+    screenshotter.close().await;
+}
+
+#[cfg(test)]
+mod test {
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_preview() {
+        let screenshot = RgbImage::from_pixel(1280, 800, Rgb([40, 80, 120]));
+        let mut png = Vec::new();
+        screenshot
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+
+        let jpeg = super::preview(&png).unwrap();
+        let preview = image::load_from_memory(&jpeg).unwrap();
+        assert_eq!((preview.width(), preview.height()), (320, 200));
+        assert_eq!(image::guess_format(&jpeg).unwrap(), ImageFormat::Jpeg);
+    }
+}
@@ -0,0 +1,207 @@
+use chrono::{TimeDelta, Utc};
+use std::{borrow::Cow, time::Instant};
+
+use crate::{SearchEngines, cache, config, engines::EngineError};
+
+const RECENT: TimeDelta = TimeDelta::hours(1); // window engines are judged over
+const RETENTION: TimeDelta = TimeDelta::days(1); // recorded requests older than this are dropped
+const MIN_REQUESTS: i64 = 3; // below this an engine gets the benefit of the doubt
+const MIN_SUCCESS_RATE: f64 = 0.5;
+const SLOW_FACTOR: f64 = 3.0; // times the fastest engine's latency an engine may take
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Ok,
+    Error,
+    Blocked,
+    Timeout,
+}
+
+impl Outcome {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Error => "error",
+            Outcome::Blocked => "blocked",
+            Outcome::Timeout => "timeout",
+        }
+    }
+}
+
+/// Times one engine request and records how it went once dropped, so a
+/// request cut off by the engine timeout is recorded as a timeout.
+pub(crate) struct RequestTimer {
+    engine: Cow<'static, str>,
+    started: Instant,
+    outcome: Outcome,
+    record: bool,
+}
+
+impl RequestTimer {
+    /// Records the request under `engine`'s [`id`](crate::engines::EngineInfo::id).
+    /// Nothing is recorded unless `record` is set.
+    pub(crate) fn start(engine: impl Into<Cow<'static, str>>, record: bool) -> Self {
+        Self {
+            engine: engine.into(),
+            started: Instant::now(),
+            outcome: Outcome::Timeout,
+            record,
+        }
+    }
+
+    pub(crate) fn finish<T>(mut self, result: &Result<T, EngineError>) {
+        self.outcome = match result {
+            Ok(_) => Outcome::Ok,
+            // either way the engine is unusable for a while
+            Err(EngineError::Blocked(_) | EngineError::QuotaExceeded(_)) => Outcome::Blocked,
+            Err(EngineError::Timeout) => Outcome::Timeout,
+            Err(_) => Outcome::Error,
+        };
+    }
+}
+
+impl Drop for RequestTimer {
+    fn drop(&mut self) {
+        if !self.record {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let engine = std::mem::take(&mut self.engine);
+        let outcome = self.outcome;
+        let latency_ms = self.started.elapsed().as_millis() as i64;
+        runtime.spawn(async move {
+            let backend = cache::backend().await;
+            let now = Utc::now().naive_utc();
+            let recorded = backend
+                .record_engine_request(&engine, now, latency_ms, outcome.as_str())
+                .await;
+            if let Err(e) = recorded.and(backend.prune_engine_requests(now - RETENTION).await) {
+                eprintln!("Failed to record {engine} request: {e:?}");
+            }
+        });
+    }
+}
+
+/// The configured engines worth using right now, fastest first.
+pub(crate) async fn auto_engines(
+    candidates: Vec<SearchEngines>,
+) -> Result<Vec<SearchEngines>, cache::CacheError> {
+    let backend = cache::backend().await;
+    let since = Utc::now().naive_utc() - RECENT;
+
+    let mut stats = Vec::with_capacity(candidates.len());
+    for engine in candidates {
+        let engine_stats = backend.get_engine_stats(&engine.id(), since).await?;
+        stats.push((engine, engine_stats));
+    }
+
+    Ok(select(stats))
+}
+
+/// Drops engines that have mostly been failing or are far slower than the
+/// fastest one. If every engine has been failing, the least bad one is kept
+/// so searches still go somewhere.
+fn select(stats: Vec<(SearchEngines, cache::EngineStatsRow)>) -> Vec<SearchEngines> {
+    let success_rate = |s: &cache::EngineStatsRow| {
+        if s.requests < MIN_REQUESTS {
+            1.0
+        } else {
+            s.successes as f64 / s.requests as f64
+        }
+    };
+
+    let (mut healthy, failing): (Vec<_>, Vec<_>) = stats
+        .into_iter()
+        .partition(|(_, s)| success_rate(s) >= MIN_SUCCESS_RATE);
+
+    if healthy.is_empty() {
+        return failing
+            .into_iter()
+            .max_by(|(_, a), (_, b)| success_rate(a).total_cmp(&success_rate(b)))
+            .map(|(engine, _)| vec![engine])
+            .unwrap_or_default();
+    }
+
+    // engines without timings yet go last
+    let latency = |s: &cache::EngineStatsRow| s.avg_latency_ms.unwrap_or(f64::INFINITY);
+    healthy.sort_by(|(_, a), (_, b)| latency(a).total_cmp(&latency(b)));
+    let fastest = latency(&healthy[0].1);
+
+    healthy
+        .into_iter()
+        .filter(|(_, s)| s.avg_latency_ms.is_none_or(|l| l <= fastest * SLOW_FACTOR))
+        .map(|(engine, _)| engine)
+        .collect()
+}
+
+/// Default engines for a search, narrowed down when `engine_selection` is auto.
+pub(crate) async fn default_engines() -> Vec<SearchEngines> {
+    let config = config::get();
+    match config.engine_selection {
+        config::EngineSelection::All => config.engines.clone(),
+        config::EngineSelection::Auto => match auto_engines(config.engines.clone()).await {
+            Ok(engines) => engines,
+            Err(e) => {
+                eprintln!("Failed to read engine stats: {e:?}");
+                config.engines.clone()
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::select;
+    use crate::{SearchEngines, cache::EngineStatsRow};
+
+    fn stats(requests: i64, successes: i64, latency: Option<f64>) -> EngineStatsRow {
+        EngineStatsRow {
+            requests,
+            successes,
+            avg_latency_ms: latency,
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let names = |engines: Vec<SearchEngines>| -> Vec<&str> {
+            engines.iter().map(|e| e.name()).collect()
+        };
+
+        // faster engine first
+        assert_eq!(
+            names(select(vec![
+                (SearchEngines::Brave, stats(10, 10, Some(900.0))),
+                (SearchEngines::DuckDuckGo, stats(10, 9, Some(400.0))),
+            ])),
+            ["DuckDuckGo", "Brave"]
+        );
+        // mostly blocked engines are dropped, untried ones kept
+        assert_eq!(
+            names(select(vec![
+                (SearchEngines::Brave, stats(10, 2, Some(300.0))),
+                (SearchEngines::DuckDuckGo, stats(1, 0, None)),
+            ])),
+            ["DuckDuckGo"]
+        );
+        // far slower engines are dropped
+        assert_eq!(
+            names(select(vec![
+                (SearchEngines::Brave, stats(10, 10, Some(200.0))),
+                (SearchEngines::DuckDuckGo, stats(10, 10, Some(1500.0))),
+            ])),
+            ["Brave"]
+        );
+        // all failing keeps the least bad
+        assert_eq!(
+            names(select(vec![
+                (SearchEngines::Brave, stats(10, 1, None)),
+                (SearchEngines::DuckDuckGo, stats(10, 4, Some(500.0))),
+            ])),
+            ["DuckDuckGo"]
+        );
+    }
+}
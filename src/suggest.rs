@@ -0,0 +1,347 @@
+use std::{
+    collections::{HashMap, HashSet},
+    pin::Pin,
+    str::FromStr,
+    time::Duration,
+};
+use tokio::{task::JoinSet, time::timeout};
+
+use crate::{
+    FetchError,
+    cache::{self, CacheError},
+    config,
+    engines::{Brave, DuckDuckGo, EngineInfo, Google, SuggestEngine, new_circuit, with_circuit},
+    vertical_key,
+};
+
+const MAX_SUGGESTIONS: usize = 8;
+/// Engine suggestions follow the news, so they're refetched after this
+const SUGGESTION_TTL: Duration = Duration::from_secs(60 * 60);
+/// Media type browsers expect [`opensearch_json`] to be served with
+pub const OPENSEARCH_CONTENT_TYPE: &str = "application/x-suggestions+json";
+const FUZZY_CANDIDATES: i64 = 500; // recent queries compared when prefixes run out
+const MIN_SIMILARITY: f64 = 0.3;
+
+/// Autocomplete candidates for `prefix` built only from the local cache, so
+/// keystrokes never reach an engine.
+///
+/// Cached queries starting with `prefix` come first, then result titles with
+/// a word starting with it, then cached queries similar to it by trigrams to
+/// get past typos. Queries cached with `hash_queries` can't be suggested.
+/// Fails with [`CacheError::Unsupported`] unless the cache is SQLite.
+pub async fn local_suggestions(prefix: &str) -> Result<Vec<String>, CacheError> {
+    let prefix = prefix.trim();
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let pool = cache::sqlite().await?;
+    let limit = MAX_SUGGESTIONS as i64;
+    let mut seen = HashSet::new();
+    let mut suggestions = Vec::new();
+    let mut add = |candidate: String| {
+        if suggestions.len() < MAX_SUGGESTIONS
+            && !is_hashed(&candidate)
+            && seen.insert(candidate.to_lowercase())
+        {
+            suggestions.push(candidate);
+        }
+    };
+
+    for query in cache::get_query_suggestions(pool, prefix, limit).await? {
+        add(query);
+    }
+    // the last word is the one being typed
+    let word = prefix.split_whitespace().last().unwrap_or(prefix);
+    for title in cache::get_title_suggestions(pool, word, limit).await? {
+        if title.to_lowercase().contains(&prefix.to_lowercase()) {
+            add(title);
+        }
+    }
+
+    let mut fuzzy: Vec<(f64, String)> = cache::get_recent_queries(pool, FUZZY_CANDIDATES)
+        .await?
+        .into_iter()
+        .map(|query| (trigram_similarity(prefix, &query), query))
+        .filter(|(similarity, _)| *similarity >= MIN_SIMILARITY)
+        .collect();
+    fuzzy.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (_, query) in fuzzy {
+        add(query);
+    }
+
+    Ok(suggestions)
+}
+
+type SuggestFuture = Pin<Box<dyn Future<Output = Result<Vec<String>, FetchError>> + Send>>;
+
+/// Engines asked for autocomplete suggestions.
+#[derive(Debug, Clone)]
+pub enum SuggestEngines {
+    DuckDuckGo,
+    Brave,
+    /// Widest coverage, but every keystroke goes to Google
+    Google,
+}
+
+impl SuggestEngines {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SuggestEngines::DuckDuckGo => DuckDuckGo.name(),
+            SuggestEngines::Brave => Brave.name(),
+            SuggestEngines::Google => Google.name(),
+        }
+    }
+
+    fn suggest(self, query: String) -> SuggestFuture {
+        match self {
+            SuggestEngines::DuckDuckGo => Box::pin(fetch_or_cache_suggestions(DuckDuckGo, query)),
+            SuggestEngines::Brave => Box::pin(fetch_or_cache_suggestions(Brave, query)),
+            SuggestEngines::Google => Box::pin(fetch_or_cache_suggestions(Google, query)),
+        }
+    }
+}
+
+impl FromStr for SuggestEngines {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "duckduckgo" | "ddg" => Ok(Self::DuckDuckGo),
+            "brave" => Ok(Self::Brave),
+            "google" => Ok(Self::Google),
+            _ => Err(format!("Unknown suggestion engine: {s}")),
+        }
+    }
+}
+
+/// Autocomplete suggestions for `query` from all given engines at once, or
+/// the configured default engines if `engines` is empty, ranked by
+/// [`rank_suggestions`].
+pub async fn search_suggestions(
+    query: &str,
+    engines: Vec<SuggestEngines>,
+) -> Result<Vec<String>, FetchError> {
+    let query = query.trim();
+    let config = config::init().map_err(FetchError::Config)?;
+    let timeout_duration = config.engine_timeout;
+    let engines = if engines.is_empty() {
+        config.suggest_engines.clone()
+    } else {
+        engines
+    };
+    if query.is_empty() || engines.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut set = JoinSet::new();
+    let circuit = new_circuit();
+
+    for engine in engines {
+        let fut = with_circuit(circuit.clone(), engine.suggest(query.to_string()));
+        set.spawn(timeout(timeout_duration, fut));
+    }
+
+    let per_engine = timeout(timeout_duration, set.join_all())
+        .await
+        .map_err(|_| FetchError::Timeouts)?;
+
+    let mut lists: Vec<Vec<String>> = Vec::new();
+
+    for engine_result in per_engine {
+        match engine_result {
+            Ok(Ok(suggestions)) => lists.push(suggestions),
+            Ok(Err(e)) => eprintln!("Engine failed: {:?}", e),
+            Err(e) => eprintln!("Engine failed: {:?}", e),
+        }
+    }
+
+    if lists.is_empty() {
+        return Err(FetchError::AllEnginesFailed);
+    }
+
+    let mut ranked = rank_suggestions(lists);
+    ranked.truncate(MAX_SUGGESTIONS);
+    Ok(ranked)
+}
+
+/// Merges the engines' suggestion lists, ignoring case. Suggestions more
+/// engines agree on come first, ties go to the one the engines put higher,
+/// scored by the sum of its reciprocal ranks.
+fn rank_suggestions(lists: Vec<Vec<String>>) -> Vec<String> {
+    struct Candidate {
+        text: String,
+        engines: usize,
+        score: f64,
+        first_seen: usize,
+    }
+
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for list in lists {
+        // an engine repeating itself doesn't count twice
+        let mut seen = HashSet::new();
+        for (rank, text) in list.into_iter().enumerate() {
+            let key = text.trim().to_lowercase();
+            if !seen.insert(key.clone()) {
+                continue;
+            }
+            let score = 1.0 / (rank + 1) as f64;
+            match index.get(&key) {
+                Some(&i) => {
+                    candidates[i].engines += 1;
+                    candidates[i].score += score;
+                }
+                None => {
+                    index.insert(key, candidates.len());
+                    candidates.push(Candidate {
+                        text: text.trim().to_string(),
+                        engines: 1,
+                        score,
+                        first_seen: candidates.len(),
+                    });
+                }
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| {
+        b.engines
+            .cmp(&a.engines)
+            .then(b.score.total_cmp(&a.score))
+            .then(a.first_seen.cmp(&b.first_seen))
+    });
+    candidates.into_iter().map(|c| c.text).collect()
+}
+
+/// Suggestions in the OpenSearch Suggestions format, `["query", [..]]`, so
+/// a browser can use them as its search bar's autocomplete.
+pub fn opensearch_json(query: &str, suggestions: &[String]) -> String {
+    serde_json::json!([query, suggestions]).to_string()
+}
+
+/// Checks the cache first; if the suggestions are missing or older than
+/// [`SUGGESTION_TTL`], fetches them from the engine and caches them.
+pub async fn fetch_or_cache_suggestions<E>(
+    engine: E,
+    query: String,
+) -> Result<Vec<String>, FetchError>
+where
+    E: SuggestEngine + EngineInfo,
+{
+    let config = config::init().map_err(FetchError::Config)?;
+    let backend = cache::backend().await;
+    let persist = config.persist_cache;
+
+    let engine_enum = engine.name();
+    let engine_id = engine.id();
+
+    // Suggestions are cached apart from web searches with the same engine
+    let key = vertical_key(&query.to_lowercase(), "suggest");
+    let query_row = backend
+        .get_query(&engine_id, &key, Some(SUGGESTION_TTL), persist)
+        .await
+        .map_err(FetchError::Cache)?;
+
+    if let Some(query_row) = query_row {
+        return backend.rows(query_row.id).await.map_err(FetchError::Cache);
+    }
+
+    // no jitter, suggestions are only useful while the user is typing
+    let suggestions = backend
+        .fetch_and_store(
+            engine_enum,
+            &engine_id,
+            persist,
+            false,
+            engine.suggest(&query),
+            async |suggestions: &Vec<String>| {
+                let fetched_at = chrono::Utc::now().naive_utc();
+                backend
+                    .store(&engine_id, &key, suggestions.clone(), fetched_at)
+                    .await?;
+                Ok(())
+            },
+        )
+        .await?;
+
+    Ok(suggestions)
+}
+
+/// Whether a cached query is a SHA-256 hash rather than the query itself.
+fn is_hashed(query: &str) -> bool {
+    query.len() == 64 && query.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    // padded so short words and word starts still make trigrams
+    let padded: Vec<char> = format!("  {} ", text.to_lowercase()).chars().collect();
+    padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// Share of trigrams the two strings have in common, from 0 to 1.
+fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SuggestEngines, is_hashed, opensearch_json, rank_suggestions, trigram_similarity};
+
+    #[test]
+    fn test_trigram_similarity() {
+        assert_eq!(trigram_similarity("rust", "rust"), 1.0);
+        assert!(trigram_similarity("rust traits", "rsut traits") > 0.3);
+        assert!(trigram_similarity("rust traits", "python lists") < 0.1);
+        assert_eq!(trigram_similarity("", ""), 1.0);
+    }
+
+    #[test]
+    fn test_is_hashed() {
+        assert!(is_hashed(&"ab12".repeat(16)));
+        assert!(!is_hashed("rust traits"));
+    }
+
+    #[test]
+    fn test_suggest_engines_from_str() {
+        assert!(matches!("ddg".parse(), Ok(SuggestEngines::DuckDuckGo)));
+        assert!(matches!(" Brave ".parse(), Ok(SuggestEngines::Brave)));
+        assert!(matches!("google".parse(), Ok(SuggestEngines::Google)));
+        assert!("bing".parse::<SuggestEngines>().is_err());
+    }
+
+    #[test]
+    fn test_rank_suggestions() {
+        let list = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let ranked = rank_suggestions(vec![
+            list(&["rust lang", "rust game", "rust book"]),
+            list(&["rust book", "Rust Lang", "rust traits"]),
+            list(&["rust traits", "rust book"]),
+        ]);
+        // in all three lists, then two lists with rank 0 and 1, then one list
+        assert_eq!(
+            ranked,
+            ["rust book", "rust lang", "rust traits", "rust game"]
+        );
+
+        // an engine listing a suggestion twice counts once
+        let ranked = rank_suggestions(vec![list(&["a", "b", "b"]), list(&["a"])]);
+        assert_eq!(ranked, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_opensearch_json() {
+        let suggestions = vec!["rust lang".to_string(), "rust \"book\"".to_string()];
+        assert_eq!(
+            opensearch_json("rust", &suggestions),
+            r#"["rust",["rust lang","rust \"book\""]]"#
+        );
+        assert_eq!(opensearch_json("", &[]), r#"["",[]]"#);
+    }
+}
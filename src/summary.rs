@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use std::sync::OnceLock;
+
+use crate::SearchResult;
+
+static SUMMARIZER: OnceLock<Box<dyn Summarizer>> = OnceLock::new();
+
+/// Writes the answer summary of a search from its merged results, e.g. with
+/// a local LLM or an extractive summarizer. The crate ships none itself.
+#[async_trait]
+pub trait Summarizer: Send + Sync {
+    /// `None` when the results don't support a summary.
+    async fn summarize(&self, query: &str, results: &[SearchResult]) -> Option<String>;
+}
+
+/// Sets the summarizer used by searches with
+/// [`SearchOptions::summarize`](crate::SearchOptions::summarize). Can only be
+/// set once, a second summarizer is handed back.
+pub fn set_summarizer(summarizer: impl Summarizer + 'static) -> Result<(), Box<dyn Summarizer>> {
+    SUMMARIZER.set(Box::new(summarizer))
+}
+
+/// Summary from the registered summarizer, `None` if there isn't one.
+pub(crate) async fn summarize(query: &str, results: &[SearchResult]) -> Option<String> {
+    SUMMARIZER.get()?.summarize(query, results).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Summarizer, set_summarizer, summarize};
+    use crate::SearchResult;
+    use async_trait::async_trait;
+
+    /// Extractive summary: the first result's description
+    struct FirstDescription;
+
+    #[async_trait]
+    impl Summarizer for FirstDescription {
+        async fn summarize(&self, _query: &str, results: &[SearchResult]) -> Option<String> {
+            results.first().map(|r| r.description().to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summarize() {
+        let result = SearchResult {
+            url: "https://www.rust-lang.org".to_string(),
+            title: "Rust".to_string(),
+            description: "A language empowering everyone".to_string(),
+            engines: vec!["Brave".to_string()],
+            cached: false,
+            screenshot: None,
+            published_at: None,
+            language: None,
+            extra_snippets: Vec::new(),
+        };
+
+        assert!(set_summarizer(FirstDescription).is_ok());
+        assert!(set_summarizer(FirstDescription).is_err());
+
+        assert_eq!(
+            summarize("rust", &[result]).await.as_deref(),
+            Some("A language empowering everyone")
+        );
+        assert!(summarize("rust", &[]).await.is_none());
+    }
+}
@@ -0,0 +1,162 @@
+use futures_util::StreamExt;
+use sqlx::SqlitePool;
+
+use crate::{
+    cache,
+    proxy::{ProxyError, proxy_image},
+};
+
+#[cfg(feature = "blurhash")]
+const COMPONENTS_X: u32 = 4;
+#[cfg(feature = "blurhash")]
+const COMPONENTS_Y: u32 = 3;
+#[cfg(feature = "blurhash")]
+const SAMPLE_SIZE: u32 = 32; // images are downscaled to this before encoding
+
+#[derive(Debug)]
+pub enum ThumbnailError {
+    Proxy(ProxyError),
+    Image(image::ImageError),
+    #[cfg(feature = "blurhash")]
+    Blurhash(blurhash::Error),
+}
+
+/// Downloads an image through the [`proxy`](crate::proxy).
+pub async fn fetch(url: &str) -> Result<Vec<u8>, ThumbnailError> {
+    let mut stream = Box::pin(
+        proxy_image(url)
+            .await
+            .map_err(ThumbnailError::Proxy)?
+            .into_stream(),
+    );
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        bytes.extend_from_slice(&chunk.map_err(ThumbnailError::Proxy)?);
+    }
+
+    Ok(bytes)
+}
+
+/// Blurhash of an encoded image (png, jpeg, gif or webp).
+#[cfg(feature = "blurhash")]
+pub fn blurhash(bytes: &[u8]) -> Result<String, ThumbnailError> {
+    let image = image::load_from_memory(bytes)
+        .map_err(ThumbnailError::Image)?
+        .thumbnail(SAMPLE_SIZE, SAMPLE_SIZE)
+        .to_rgba8();
+
+    blurhash::encode(
+        COMPONENTS_X,
+        COMPONENTS_Y,
+        image.width(),
+        image.height(),
+        image.as_raw(),
+    )
+    .map_err(ThumbnailError::Blurhash)
+}
+
+/// 64 bit difference hash: each bit is whether a pixel of the image scaled
+/// to 9x8 grayscale is brighter than its right neighbour. Resized or
+/// recompressed copies of an image hash within a few bits of each other.
+#[cfg(feature = "phash")]
+pub fn dhash(bytes: &[u8]) -> Result<u64, ThumbnailError> {
+    let image = image::load_from_memory(bytes)
+        .map_err(ThumbnailError::Image)?
+        .resize_exact(9, 8, image::imageops::FilterType::Triangle)
+        .to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let brighter = image.get_pixel(x, y)[0] > image.get_pixel(x + 1, y)[0];
+            hash = (hash << 1) | brighter as u64;
+        }
+    }
+
+    Ok(hash)
+}
+
+/// Downloads the thumbnails of freshly cached images, given as
+/// `(image url, thumbnail url)`, and stores what the enabled features derive
+/// from them. Images that fail to load are skipped.
+pub(crate) async fn cache_thumbnail_hashes(pool: &SqlitePool, images: Vec<(String, String)>) {
+    for (url, thumbnail) in images {
+        let Ok(bytes) = fetch(&thumbnail).await else {
+            continue;
+        };
+
+        let (blurhash, phash) = tokio::task::spawn_blocking(move || {
+            #[cfg(feature = "blurhash")]
+            let blurhash = blurhash(&bytes).ok();
+            #[cfg(not(feature = "blurhash"))]
+            let blurhash: Option<String> = None;
+
+            #[cfg(feature = "phash")]
+            let phash = dhash(&bytes).ok();
+            #[cfg(not(feature = "phash"))]
+            let phash: Option<u64> = None;
+
+            (blurhash, phash)
+        })
+        .await
+        .expect("thumbnail task panicked");
+
+        if let Some(blurhash) = blurhash
+            && let Err(e) = cache::set_image_blurhash(pool, &url, &blurhash).await
+        {
+            eprintln!("Failed to cache blurhash for {url}: {e:?}");
+        }
+        if let Some(phash) = phash
+            && let Err(e) = cache::set_image_phash(pool, &url, phash).await
+        {
+            eprintln!("Failed to cache perceptual hash for {url}: {e:?}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use image::{ImageFormat, Rgba, RgbaImage};
+    use std::io::Cursor;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbaImage::from_fn(width, height, |x, y| {
+            let shade = (255 * x / width) as u8 ^ (255 * y / height) as u8;
+            Rgba([shade, 0, 255 - shade, 255])
+        });
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[cfg(feature = "blurhash")]
+    #[test]
+    fn test_blurhash() {
+        let hash = super::blurhash(&png(64, 48)).unwrap();
+        // 4 x 3 components
+        assert_eq!(hash.len(), 4 + 2 * 4 * 3);
+        assert!(super::blurhash(b"<html>not an image</html>").is_err());
+    }
+
+    #[cfg(feature = "phash")]
+    #[test]
+    fn test_dhash() {
+        use super::dhash;
+        use crate::PHASH_THRESHOLD;
+
+        let original = dhash(&png(64, 48)).unwrap();
+        let resized = dhash(&png(640, 480)).unwrap();
+        assert!((original ^ resized).count_ones() <= PHASH_THRESHOLD);
+
+        let flipped = image::load_from_memory(&png(64, 48)).unwrap().fliph();
+        let mut bytes = Vec::new();
+        flipped
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        assert!((original ^ dhash(&bytes).unwrap()).count_ones() > PHASH_THRESHOLD);
+    }
+}